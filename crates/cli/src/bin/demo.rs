@@ -14,7 +14,7 @@ fn main() -> Result<()> {
         ("String Method", "let upper = \"hello\".toUpperCase();"),
         ("Array Method", "let len = [1, 2, 3].length;"),
         ("Dynamic Array", "let nums: number[] = [1, 2, 3];"),
-        ("Static Array", "let fixed: number[number, 5] = [1, 2, 3, 4, 5];"),
+        ("Static Array", "let fixed: number[5] = [1, 2, 3, 4, 5];"),
     ];
     
     for (description, code) in examples {
@@ -49,7 +49,7 @@ fn main() -> Result<()> {
         let alice = User { name: "Alice", age: 30, active: true };
         
         let nums: number[] = [1, 2, 3, 4, 5];
-        let fixed: number[number, 3] = [10, 20, 30];
+        let fixed: number[3] = [10, 20, 30];
         const LIMIT: number = 3;
         
         function processNumbers(values: number[]): void {
@@ -109,6 +109,59 @@ fn main() -> Result<()> {
         
         let mixed: any = "flexible type";
         print(mixed);
+        
+        struct Config {
+            retries: number = 3,
+            verbose: boolean = false,
+            label?: string
+        }
+        
+        let defaults = Config {};
+        let custom = Config { retries: 5, label: "primary" };
+        print(defaults.retries);
+        print(custom.retries);
+        
+        enum Shape {
+            Circle { radius: number },
+            Rect { w: number, h: number },
+            Empty
+        }
+        
+        function area(shape: Shape): number {
+            match (shape) {
+                Shape.Circle { radius } => {
+                    return 3 * radius * radius;
+                }
+                Shape.Rect { w, h: height } => return w * height;
+                _ => return 0;
+            }
+        }
+        
+        print(area(Shape.Circle { radius: 2 }));
+        print(area(Shape.Rect { w: 3, h: 4 }));
+        
+        function work(name: string): void {
+            print("start " + name);
+            defer {
+                print("cleanup " + name);
+            }
+            print("working");
+        }
+        
+        work("job");
+        
+        let [tx, rx] = channel<string>();
+        tx.send("hello");
+        let received = rx.recv();
+        print(received);
+        
+        let counter = shared<number>(0);
+        lock (counter) {
+            counter = counter + 5;
+        }
+        lock (counter) {
+            print(counter);
+        }
     "#;
     
     println!("Input:\n{}\n", complex);
@@ -117,12 +170,13 @@ fn main() -> Result<()> {
             println!("Output:\n{}\n", rust_code);
             println!("✅ Successfully transpiled complex program with ALL features!");
             println!("   • Type inference (x, y, upper, lower, sub)");
-            println!("   • Structs (User)");
-            println!("   • Enums (Role with variants)");
+            println!("   • Structs (User, Config with defaults and optional fields)");
+            println!("   • Enums (Role with variants, Shape with named fields and match)");
             println!("   • Array methods (length, indexing)");
             println!("   • String methods (toUpperCase, toLowerCase, substring)");
             println!("   • Functions, loops, conditionals");
             println!("   • Constants, variables, break/continue");
+            println!("   • defer, channels, shared state and lock");
         }
         Err(e) => {
             eprintln!("❌ Error: {}", e);
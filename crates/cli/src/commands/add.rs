@@ -0,0 +1,62 @@
+use anyhow::Result;
+use std::process::Command;
+use crate::commands::build;
+use crate::project::{self, ProjectConfig};
+
+pub fn handle(name: String, features: Vec<String>) -> Result<()> {
+    let root = project::project_root()?;
+    let mut config = ProjectConfig::from_path(&root)?;
+    
+    let (crate_name, version) = match name.split_once('@') {
+        Some((crate_name, version)) if !version.is_empty() => (crate_name.to_string(), version.to_string()),
+        Some((crate_name, _)) => anyhow::bail!("Missing version after '@'; use `jrust add {}@<version>`", crate_name),
+        None => {
+            let version = latest_version(&name)?;
+            (name, version)
+        }
+    };
+    
+    let dependency = if features.is_empty() {
+        toml::Value::String(version.clone())
+    } else {
+        let mut table = toml::value::Table::new();
+        table.insert("version".to_string(), toml::Value::String(version.clone()));
+        table.insert(
+            "features".to_string(),
+            toml::Value::Array(features.into_iter().map(toml::Value::String).collect()),
+        );
+        toml::Value::Table(table)
+    };
+    
+    let replaced = config.dependencies.insert(crate_name.clone(), dependency).is_some();
+    config.save(&root)?;
+    let runtime = build::project_runtime(&root)?;
+    build::generate_cargo_toml(&root, &config.out_dir(&root), build::is_library(&root, false)?, runtime.as_ref(), true)?;
+    
+    if replaced {
+        status!("📦 Updated dependency: {} = {}", crate_name, version);
+    } else {
+        status!("📦 Added dependency: {} = {}", crate_name, version);
+    }
+    Ok(())
+}
+
+fn latest_version(name: &str) -> Result<String> {
+    let output = Command::new("cargo").args(["search", "--limit", "10", name]).output();
+    let prefix = format!("{} = \"", name);
+    let version = output
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(|line| line.strip_prefix(&prefix)?.split('"').next().map(str::to_string))
+        });
+    version.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Couldn't find the latest version of '{}' on crates.io; pass one explicitly with `jrust add {}@<version>`",
+            name,
+            name
+        )
+    })
+}
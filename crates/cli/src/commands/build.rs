@@ -1,35 +1,316 @@
 use anyhow::{Result, Context};
-use jrust_transpiler_core::{Lexer, Parser, Codegen};
+use clap::ValueEnum;
+use jrust_transpiler_core::{ast_to_json, Cfg, CompileOptions, Compiler, Diagnostic, Fix, NamingPolicy, OutputKind, Severity, Stage};
+use jrust_transpiler_core::codegen::convert_name;
+use crate::diagnostics::{self, OutputFormat};
+use crate::fixes;
+use crate::module_graph::{self, ModuleGraph};
+use crate::output;
 use crate::project;
+use crate::rust_format;
+use crate::source_map::{self, SourceMap};
+use serde_json::Value;
 use std::path::{PathBuf, Path};
-use std::process::Command;
-use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::collections::{BTreeMap, BTreeSet};
 
-pub fn handle(path: Option<String>) -> Result<()> {
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Profile {
+    Debug,
+    Release,
+}
+
+impl Profile {
+    pub fn from_flags(debug: bool, release: bool, default: Profile) -> Self {
+        if debug {
+            Profile::Debug
+        } else if release {
+            Profile::Release
+        } else {
+            default
+        }
+    }
+
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            Profile::Debug => "debug",
+            Profile::Release => "release",
+        }
+    }
+}
+
+pub fn is_library(root: &Path, lib: bool) -> Result<bool> {
+    if lib {
+        return Ok(true);
+    }
+    let config = project::ProjectConfig::from_path(root)?;
+    match config.build.and_then(|build| build.crate_type).as_deref() {
+        None | Some("bin") => Ok(false),
+        Some("lib") => Ok(true),
+        Some(other) => anyhow::bail!("Unknown crate-type '{}' in Cargo.toml (expected \"bin\" or \"lib\")", other),
+    }
+}
+
+fn naming_policy(config: &project::ProjectConfig) -> Result<NamingPolicy> {
+    match config.build.as_ref().and_then(|build| build.naming.as_deref()) {
+        None | Some("snake_case") => Ok(NamingPolicy::SnakeCase),
+        Some("preserve") => Ok(NamingPolicy::Preserve),
+        Some(other) => anyhow::bail!("Unknown naming '{}' in Cargo.toml (expected \"snake_case\" or \"preserve\")", other),
+    }
+}
+
+pub fn resolve_target(root: &Path, target: Option<String>, profile: Profile) -> Result<Option<String>> {
+    if target.is_some() {
+        return Ok(target);
+    }
+    let config = project::ProjectConfig::from_path(root)?;
+    Ok(config.build_setting(profile.dir_name(), |build| build.target.clone()))
+}
+
+pub fn resolve_opt(root: &Path, opt: bool, profile: Profile) -> Result<bool> {
+    if opt {
+        return Ok(true);
+    }
+    let config = project::ProjectConfig::from_path(root)?;
+    Ok(config.build_setting(profile.dir_name(), |build| build.opt).unwrap_or(false))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Emit {
+    Rust,
+    Tokens,
+    Ast,
+}
+
+/// Builds the project, returning the executables Cargo produced by binary name
+pub fn handle(
+    path: Option<String>,
+    format: OutputFormat,
+    profile: Profile,
+    target: Option<String>,
+    lib: bool,
+    opt: bool,
+    emit: Option<Emit>,
+) -> Result<BTreeMap<String, PathBuf>> {
     let file_path = if let Some(p) = path {
         PathBuf::from(p)
     } else {
-        let root = project::project_root()?;
-        let src_dir = root.join("src");
-        project::find_entry_point(&src_dir)?
+        project::find_entry_point(&project::project_root()?)?
     };
     
     if !file_path.exists() {
         anyhow::bail!("File not found: {:?}", file_path);
     }
     
+    if let Some(stage @ (Emit::Tokens | Emit::Ast)) = emit {
+        emit_stage(&file_path, stage, format)?;
+        return Ok(BTreeMap::new());
+    }
+    
     let root = project::project_root()?;
-    let src_dir = root.join("src");
+    let target = resolve_target(&root, target, profile)?;
+    let lib = is_library(&root, lib)?;
+    let opt = resolve_opt(&root, opt, profile)?;
+    let cfg = Cfg::for_target(profile == Profile::Debug, target.as_deref());
+    let human = format == OutputFormat::Human;
+    let hooks = project::ProjectConfig::from_path(&root)?.hooks;
+    
+    if human {
+        status!("🔨 Building jRust project...");
+    }
     
-    println!("🔨 Building jRust project...");
+    if let Some(hooks) = &hooks {
+        run_hooks("pre-build", &hooks.pre_build, &root, profile, format)?;
+    }
+    
+    let generated = generate_project(&root, format, lib, opt, &cfg)?;
+    let generated_dir = generated.dir;
+    let source_map = generated.source_map;
+    
+    if emit == Some(Emit::Rust) {
+        if human {
+            status!("📝 Rust code written to {:?}", generated_dir);
+        } else {
+            diagnostics::report_success(format);
+        }
+        return Ok(BTreeMap::new());
+    }
+    
+    if human {
+        status!("🚀 Compiling with Rust...");
+    }
+    let (success, messages) = cargo_build(&generated_dir, profile, target.as_deref(), &source_map, &root, format)?;
+    
+    if !success {
+        if format == OutputFormat::Json {
+            diagnostics::print_json(false, messages.diagnostics);
+        }
+        anyhow::bail!("Cargo build failed");
+    }
+    
+    if let Some(hooks) = &hooks {
+        run_hooks("post-build", &hooks.post_build, &root, profile, format)?;
+    }
+    
+    if human {
+        status!("✅ Build completed successfully!");
+        if lib {
+            status!("📦 Library crate: {:?}", generated_dir);
+        } else {
+            for executable in messages.executables.values() {
+                status!("📦 Executable: {:?}", executable);
+            }
+        }
+    }
+    
+    if format == OutputFormat::Json {
+        diagnostics::print_json(true, messages.diagnostics);
+    }
+    Ok(messages.executables)
+}
+
+/// Runs a `[hooks]` list in order through the shell, from the project root,
+/// stopping at the first command that fails. With JSON output the commands'
+/// stdout goes to stderr so it doesn't mix with the JSON.
+fn run_hooks(stage: &str, commands: &[String], root: &Path, profile: Profile, format: OutputFormat) -> Result<()> {
+    let out_dir = project::ProjectConfig::from_path(root)?.out_dir(root);
+    for command in commands {
+        if format == OutputFormat::Human {
+            status!("🪝 {}: {}", stage, command);
+        }
+        let mut shell = if cfg!(windows) {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C");
+            shell
+        } else {
+            let mut shell = Command::new("sh");
+            shell.arg("-c");
+            shell
+        };
+        shell
+            .arg(command)
+            .current_dir(root)
+            .env("JRUST_PROFILE", profile.dir_name())
+            .env("JRUST_OUT_DIR", &out_dir);
+        if format == OutputFormat::Json {
+            shell.stdout(std::io::stderr());
+        }
+        let status = shell.status().context(format!("Failed to run {} hook `{}`", stage, command))?;
+        if !status.success() {
+            let message = format!("{} hook `{}` failed ({})", stage, command, status);
+            diagnostics::report_message(&message, format);
+            anyhow::bail!(message);
+        }
+    }
+    Ok(())
+}
+
+pub fn cargo_build(
+    dir: &Path,
+    profile: Profile,
+    target: Option<&str>,
+    source_map: &SourceMap,
+    root: &Path,
+    format: OutputFormat,
+) -> Result<(bool, CargoMessages)> {
+    let mut args = vec!["build", "--message-format=json"];
+    if !output::is_verbose() {
+        args.push("--quiet");
+    }
+    if profile == Profile::Release {
+        args.push("--release");
+    }
+    if let Some(triple) = target {
+        args.extend(["--target", triple]);
+    }
+    verbose!("🔧 cargo {}", args.join(" "));
+    let mut cargo = Command::new("cargo")
+        .args(&args)
+        .current_dir(dir)
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to run cargo build")?;
+    
+    let stdout = cargo.stdout.take().context("Failed to capture cargo output")?;
+    let messages = report_compiler_messages(BufReader::new(stdout), dir, source_map, root, format);
+    let status = cargo.wait().context("Failed to run cargo build")?;
+    Ok((status.success(), messages))
+}
+
+fn emit_stage(file_path: &Path, stage: Emit, format: OutputFormat) -> Result<()> {
+    let source = project::read_source_file(file_path)?;
+    let stop_after = if stage == Emit::Tokens { Stage::Lex } else { Stage::Parse };
+    let compilation = Compiler::new(CompileOptions { stop_after, ..CompileOptions::default() })
+        .compile(&source)
+        .map_err(|e| {
+            diagnostics::report(&e.diagnostic, file_path, &source, format);
+            anyhow::anyhow!(e.stage.failure_message())
+        })?;
+    
+    match (compilation.program, format) {
+        (Some(program), OutputFormat::Human) => println!("{:#?}", program),
+        (Some(program), OutputFormat::Json) => println!("{}", ast_to_json(&program)),
+        (None, OutputFormat::Human) => {
+            for token in &compilation.tokens {
+                println!("{}:{}\t{:?}", token.line, token.column, token.kind);
+            }
+        }
+        (None, OutputFormat::Json) => println!("{}", serde_json::to_string_pretty(&compilation.tokens)?),
+    }
+    Ok(())
+}
+
+pub struct GeneratedProject {
+    pub dir: PathBuf,
+    pub source_map: SourceMap,
+    pub tests: Vec<TestCase>,
+}
+
+pub struct TestCase {
+    pub path: String,
+    pub file: String,
+    pub name: String,
+}
+
+pub fn generate_project(root: &Path, format: OutputFormat, lib: bool, opt: bool, cfg: &Cfg) -> Result<GeneratedProject> {
+    let config = project::ProjectConfig::from_path(root)?;
+    generate_crate(root, &config.out_dir(root), format, lib, opt, cfg, true)
+}
+
+/// Generates the crate for the project at `root` into `generated_dir`, along
+/// with each of its `[packages]` as a library crate under `packages/`. Only the
+/// top-level crate is a workspace root; packages become its members.
+fn generate_crate(
+    root: &Path,
+    generated_dir: &Path,
+    format: OutputFormat,
+    lib: bool,
+    opt: bool,
+    cfg: &Cfg,
+    workspace: bool,
+) -> Result<GeneratedProject> {
+    let src_dir = root.join("src");
+    let config = project::ProjectConfig::from_path(root)?;
+    let binaries = config.binaries(root)?;
+    let naming = naming_policy(&config)?;
+    if lib && binaries.len() > 1 {
+        anyhow::bail!("[[bin]] targets can't be combined with a library build");
+    }
+    let is_entry = |module_name: &str| binaries.iter().any(|(_, entry)| entry == module_name);
+    let human = format == OutputFormat::Human;
     
     // Find all .jr files in the project
-    let jr_files = find_all_jr_files(&src_dir)?;
-    println!("📄 Found {} jRust file(s)", jr_files.len());
+    let jr_files = project::find_all_jr_files(&src_dir)?;
+    if human {
+        status!("📄 Found {} jRust file(s)", jr_files.len());
+    }
     
-    let mut modules = HashMap::new();
+    let mut programs = Vec::new();
+    let mut graph = ModuleGraph::with_crates(config.crate_names());
+    let mut runtime: Option<BTreeSet<String>> = None;
     
-    // Compile each .jr file
+    // Parse each .jr file
     for jr_file in &jr_files {
         let relative_path = jr_file.strip_prefix(&src_dir)
             .context("Failed to get relative path")?;
@@ -37,185 +318,470 @@ pub fn handle(path: Option<String>) -> Result<()> {
         let module_path = relative_path.with_extension("");
         let module_name = module_path.to_string_lossy().replace("\\", "/");
         
-        println!("  � Compiling: {}", module_name);
+        if human {
+            status!("  📝 Compiling: {}", module_name);
+        }
         
         let source = project::read_source_file(jr_file)?;
         
-        let mut lexer = Lexer::new(&source);
-        let tokens = lexer.tokenize()
-            .map_err(|e| anyhow::anyhow!("Lexical analysis failed in {}: {}", module_name, e))?;
-        
-        let mut parser = Parser::new(tokens);
-        let program = parser.parse()
-            .map_err(|e| anyhow::anyhow!("Syntax parsing failed in {}: {}", module_name, e))?;
+        let display_path = jr_file.strip_prefix(root).unwrap_or(jr_file).to_path_buf();
+        let options = CompileOptions { stop_after: Stage::Parse, cfg: cfg.clone(), ..CompileOptions::default() };
+        let program = Compiler::new(options)
+            .compile(&source)
+            .map_err(|e| {
+                diagnostics::report(&e.diagnostic, &display_path, &source, format);
+                anyhow::anyhow!("{} in {}", e.stage.failure_message(), module_name)
+            })?
+            .program
+            .context("Parser produced no program")?;
         
-        // Use new_module() for non-main files to avoid wrapping in main()
-        let mut codegen = if module_name == "index" {
-            Codegen::new()
-        } else {
-            Codegen::new_module()
-        };
-        let rust_code = codegen.generate(&program);
-        
-        modules.insert(module_name, rust_code);
+        graph.add_module(&module_name, &program);
+        if program.uses_runtime() {
+            runtime.get_or_insert_with(BTreeSet::new).extend(program.runtime_features());
+        }
+        programs.push((module_name, program, display_path, source));
     }
     
-    println!("✅ All files compiled successfully");
+    if let Some(cycle) = graph.find_cycle() {
+        let message = format!("Circular import detected: {}", module_graph::format_cycle(&cycle));
+        diagnostics::report_message(&message, format);
+        anyhow::bail!(message);
+    }
     
-    let generated_dir = root.join("generated");
+    let mut imported = graph.resolve_imports().map_err(|e| {
+        diagnostics::report_message(&e, format);
+        anyhow::anyhow!("Import resolution failed: {}", e)
+    })?;
     
-    // Write main.rs with module declarations
-    let main_rs = generate_main_rs(&modules)?;
-    project::write_file(&generated_dir.join("main.rs"), &main_rs)?;
+    let mut modules = BTreeMap::new();
+    let mut module_lines = Vec::new();
+    let mut tests = Vec::new();
     
-    // Write module files
-    for (module_name, rust_code) in &modules {
-        if module_name != "index" {
-            let module_file = if module_name.contains('/') {
-                // For nested modules like "utils/random", create utils/random.rs
-                let parts: Vec<&str> = module_name.split('/').collect();
-                let dir = generated_dir.join(parts[0..parts.len()-1].join("/"));
-                std::fs::create_dir_all(&dir)?;
-                dir.join(format!("{}.rs", parts[parts.len()-1]))
-            } else {
-                generated_dir.join(format!("{}.rs", module_name))
-            };
-            project::write_file(&module_file, rust_code)?;
+    for (module_name, program, display_path, source) in programs {
+        let imports = imported.remove(&module_name).unwrap_or_default();
+        // Use a module kind for non-main files to avoid wrapping in main()
+        let kind = match is_entry(&module_name) {
+            true if lib => OutputKind::Library,
+            true => OutputKind::Main,
+            false => OutputKind::Module,
+        };
+        let display = display_path.to_string_lossy().replace('\\', "/");
+        let options = CompileOptions {
+            kind,
+            imported_functions: imports.functions,
+            globals: imports.globals,
+            optimize: opt,
+            file: Some(display.clone()),
+            entry_dir: module_name.rsplit_once('/').filter(|_| is_entry(&module_name)).map(|(dir, _)| dir.to_string()),
+            runtime: runtime.is_some(),
+            naming,
+            ..CompileOptions::default()
+        };
+        let compilation = Compiler::new(options).compile_program(&program).map_err(|e| {
+            diagnostics::report(&e.diagnostic, &display_path, &source, format);
+            anyhow::anyhow!("{} in {}", e.stage.failure_message(), module_name)
+        })?;
+        let rust_code = compilation.rust.unwrap_or_default();
+        
+        let module_path = match is_entry(&module_name) {
+            true => String::new(),
+            false => format!("{}::", module_name.replace('/', "::")),
+        };
+        for (rust_name, name) in &compilation.tests {
+            tests.push(TestCase {
+                path: format!("{}tests::{}", module_path, rust_name),
+                file: display.clone(),
+                name: name.clone(),
+            });
         }
+        module_lines.push((module_name.clone(), display_path, compilation.source_map));
+        modules.insert(module_name, rust_code);
     }
     
-    // Generate mod.rs files for directories
-    let mut directories = std::collections::HashSet::new();
-    for module_name in modules.keys() {
-        if module_name.contains('/') {
-            let parts: Vec<&str> = module_name.split('/').collect();
-            if parts.len() >= 2 {
-                directories.insert(parts[0].to_string());
-            }
-        }
+    if human {
+        status!("✅ All files compiled successfully");
     }
     
-    for dir_name in directories {
-        let mod_rs_path = generated_dir.join(&dir_name).join("mod.rs");
-        let mut mod_content = String::new();
-        
-        // Find all modules in this directory
-        for module_name in modules.keys() {
-            if let Some(stripped) = module_name.strip_prefix(&format!("{}/", dir_name)) {
-                if !stripped.contains('/') {
-                    mod_content.push_str(&format!("pub mod {};\n", stripped));
-                    
-                    // Re-export if the module is "index"
-                    if stripped == "index" {
-                        mod_content.push_str(&format!("pub use {}::*;\n", stripped));
-                    }
-                }
+    let generated_dir = generated_dir.to_path_buf();
+    
+    // Write module files, formatted, with their source maps adjusted to the formatted lines
+    let mut source_map = SourceMap::new();
+    for (module_name, display_path, lines) in &module_lines {
+        let (rust_file, code, offset) = match binaries.iter().position(|(_, entry)| entry == module_name) {
+            Some(index) => {
+                // The first binary declares every module so their tests are built;
+                // the others only declare what their entry imports
+                let mut declared = match index {
+                    0 => modules.keys().cloned().collect(),
+                    _ => graph.reachable(module_name),
+                };
+                declared.retain(|name| !is_entry(name));
+                let rust_file = binary_root(index, &binaries[index].0, lib);
+                let main_rs = generate_main_rs(&modules, module_name, &declared, lib, index > 0)?;
+                let offset = main_rs.matches('\n').count() - modules[module_name].matches('\n').count();
+                (rust_file, main_rs, offset)
             }
-        }
+            None => (source_map::rust_file_for_module(module_name), modules[module_name].clone(), 0),
+        };
+        let lines: Vec<(usize, usize)> = lines.iter().map(|(rust_line, jr_line)| (rust_line + offset, *jr_line)).collect();
+        let (formatted, lines) = rust_format::format(&code, &config.package.edition, &lines);
         
-        project::write_file(&mod_rs_path, &mod_content)?;
+        project::write_file(&generated_dir.join(&rust_file), &formatted)?;
+        let source = display_path.to_string_lossy().replace('\\', "/");
+        source_map.add(&rust_file, &source, &lines, 0);
     }
+    source_map.save(&generated_dir.join("source_map.json"))?;
     
-    println!("✅ Generated Rust code");
-    
-    generate_cargo_toml(&root, &generated_dir)?;
+    // Generate mod.rs files for directories
+    for (dir_name, mod_content) in directory_modules(modules.keys().filter(|name| !is_entry(name))) {
+        project::write_file(&generated_dir.join(&dir_name).join("mod.rs"), &mod_content)?;
+    }
     
-    println!("🚀 Compiling with Rust...");
-    let status = Command::new("cargo")
-        .args(&["build", "--release"])
-        .current_dir(&generated_dir)
-        .status()
-        .context("Failed to run cargo build")?;
+    if human {
+        status!("✅ Generated Rust code");
+    }
     
-    if !status.success() {
-        anyhow::bail!("Cargo build failed");
+    for (name, package) in &config.packages {
+        let package_root = root.join(&package.path);
+        let package_dir = generated_dir.join("packages").join(name);
+        project::find_entry_point(&package_root)
+            .with_context(|| format!("Package '{}' at {:?} has no entry file", name, package_root))?;
+        if human {
+            status!("📦 Package: {}", name);
+        }
+        let package_map = generate_crate(&package_root, &package_dir, format, true, opt, cfg, false)?.source_map;
+        source_map.extend(&format!("packages/{}/", name), &format!("{}/", package.path.trim_end_matches('/')), package_map);
     }
     
-    println!("✅ Build completed successfully!");
-    let executable = if cfg!(windows) {
-        generated_dir.join("target/release/jrust_app.exe")
-    } else {
-        generated_dir.join("target/release/jrust_app")
-    };
-    println!("📦 Executable: {:?}", executable);
+    generate_cargo_toml(root, &generated_dir, lib, runtime.as_ref(), workspace)?;
     
-    Ok(())
+    Ok(GeneratedProject {
+        dir: generated_dir,
+        source_map,
+        tests,
+    })
 }
 
-fn find_all_jr_files(dir: &Path) -> Result<Vec<PathBuf>> {
-    let mut files = Vec::new();
+#[derive(Default)]
+pub struct CargoMessages {
+    pub diagnostics: Vec<Value>,
+    pub fixes: Vec<(PathBuf, Fix)>,
+    pub executables: BTreeMap<String, PathBuf>,
+}
+
+pub fn report_compiler_messages(
+    stdout: impl BufRead,
+    dir: &Path,
+    source_map: &SourceMap,
+    root: &Path,
+    format: OutputFormat,
+) -> CargoMessages {
+    let mut reported = CargoMessages::default();
     
-    if dir.is_dir() {
-        for entry in std::fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            
-            if path.is_dir() {
-                files.extend(find_all_jr_files(&path)?);
-            } else if path.extension().and_then(|s| s.to_str()) == Some("jr") {
-                files.push(path);
+    for line in stdout.lines().map_while(Result::ok) {
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if message["reason"] == "compiler-artifact" {
+            if let (Some(name), Some(executable)) = (message["target"]["name"].as_str(), message["executable"].as_str()) {
+                reported.executables.insert(name.to_string(), PathBuf::from(executable));
             }
+            continue;
+        }
+        if message["reason"] != "compiler-message" {
+            continue;
+        }
+        let compiler_message = &message["message"];
+        let span = compiler_message["spans"]
+            .as_array()
+            .and_then(|spans| spans.iter().find(|span| span["is_primary"] == true));
+        let location = span.and_then(|span| {
+            let file = span["file_name"].as_str()?;
+            let line = span["line_start"].as_u64()? as usize;
+            Some((file, line, span["column_start"].as_u64().unwrap_or(1) as usize))
+        });
+        let original = location.and_then(|(file, line, _)| source_map.lookup(file, line));
+        let source = original
+            .and_then(|(path, _)| std::fs::read_to_string(root.join(path)).ok())
+            .unwrap_or_default();
+        let severity = match compiler_message["level"].as_str() {
+            Some("error") => Severity::Error,
+            Some("warning") => Severity::Warning,
+            _ => Severity::Note,
+        };
+        if severity == Severity::Warning && original.is_none() && location.is_some_and(|(file, ..)| Path::new(file).is_relative()) {
+            continue;
         }
+        let diagnostic = Diagnostic::new(compiler_message["message"].as_str().unwrap_or_default())
+            .with_code(compiler_message["code"]["code"].as_str().map(str::to_string))
+            .with_severity(severity);
+        
+        let translated = match original {
+            Some((path, jr_line)) if severity == Severity::Warning => {
+                let jr_text = source.lines().nth(jr_line.saturating_sub(1)).unwrap_or("");
+                let warning = translate_warning(diagnostic.clone(), span, jr_text, jr_line);
+                let rust_name = span.and_then(highlighted_text);
+                match fixes::for_rust_warning(compiler_message, rust_name.as_deref(), &warning, &source) {
+                    Some(fix) => {
+                        reported.fixes.push((PathBuf::from(path), fix.clone()));
+                        Some((path, warning.with_fix(fix)))
+                    }
+                    None => Some((path, warning)),
+                }
+            }
+            _ => None,
+        };
+        
+        if format == OutputFormat::Human {
+            if let Some((path, warning)) = &translated {
+                eprintln!("{}", warning.render(path, &source, diagnostics::use_color()));
+                continue;
+            }
+            if let Some(rendered) = compiler_message["rendered"].as_str() {
+                eprint!("{}", rendered);
+            }
+            if let Some((path, jr_line)) = original {
+                let note = Diagnostic::new("generated from this jRust code")
+                    .with_code(None)
+                    .with_severity(Severity::Note)
+                    .at_line(jr_line);
+                eprintln!("{}", note.render(path, &source, diagnostics::use_color()));
+            }
+            continue;
+        }
+        
+        let Some((file, line, column)) = location else {
+            continue;
+        };
+        reported.diagnostics.push(match (translated, original) {
+            (Some((path, warning)), _) => diagnostics::to_json(&warning, Some(Path::new(path)), &source),
+            (None, Some((path, jr_line))) => {
+                diagnostics::to_json(&diagnostic.at_line(jr_line), Some(Path::new(path)), &source)
+            }
+            (None, None) => {
+                let generated = dir.strip_prefix(root).unwrap_or(dir).join(file);
+                diagnostics::to_json(&diagnostic.at(line, column), Some(&generated), "")
+            }
+        });
     }
     
-    Ok(files)
+    reported
 }
 
-fn generate_main_rs(modules: &HashMap<String, String>) -> Result<String> {
+fn translate_warning(diagnostic: Diagnostic, span: Option<&Value>, jr_text: &str, jr_line: usize) -> Diagnostic {
+    let Some(rust_name) = span.and_then(highlighted_text) else {
+        return diagnostic.at_line(jr_line);
+    };
+    
+    let mut column = 1;
+    for word in jr_text.split(|c: char| !c.is_alphanumeric() && c != '_') {
+        if !word.is_empty() && (word == rust_name || convert_name(word) == rust_name) {
+            let message = diagnostic
+                .message
+                .replace(&format!("`{}`", rust_name), &format!("`{}`", word));
+            return Diagnostic { message, ..diagnostic }.at(jr_line, column);
+        }
+        column += word.chars().count() + 1;
+    }
+    diagnostic.at_line(jr_line)
+}
+
+fn highlighted_text(span: &Value) -> Option<String> {
+    let text = span["text"].get(0)?;
+    let start = text["highlight_start"].as_u64()? as usize;
+    let end = text["highlight_end"].as_u64()? as usize;
+    let highlighted: String = text["text"]
+        .as_str()?
+        .chars()
+        .skip(start.saturating_sub(1))
+        .take(end.saturating_sub(start))
+        .collect();
+    Some(highlighted).filter(|name| !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_'))
+}
+
+/// The crate root file for the `index`th of the project's binaries
+pub fn binary_root(index: usize, name: &str, lib: bool) -> String {
+    match index {
+        0 if lib => "lib.rs".to_string(),
+        0 => "main.rs".to_string(),
+        _ => format!("bin/{}.rs", name),
+    }
+}
+
+/// The crate root for `entry`: a declaration for each top-level module that
+/// holds one of the `declared` modules, then the entry's own code. Roots
+/// under `bin/` point their declarations back up with `#[path]`.
+fn generate_main_rs(
+    modules: &BTreeMap<String, String>,
+    entry: &str,
+    declared: &BTreeSet<String>,
+    lib: bool,
+    nested: bool,
+) -> Result<String> {
     let mut output = String::new();
+    let visibility = if lib { "pub " } else { "" };
     
-    // Add module declarations for non-index modules
-    let mut module_names: Vec<&String> = modules.keys().collect();
-    module_names.sort();
-    
-    for module_name in &module_names {
-        if module_name.as_str() != "index" {
-            if module_name.contains('/') {
-                // For nested modules like "utils/random"
-                let parts: Vec<&str> = module_name.split('/').collect();
-                if parts.len() == 2 {
-                    // Only declare the top-level module once
-                    if !output.contains(&format!("mod {};", parts[0])) {
-                        output.push_str(&format!("mod {};\n", parts[0]));
-                    }
-                }
-            } else {
-                output.push_str(&format!("mod {};\n", module_name));
-            }
+    for module_name in declared {
+        // For nested modules like "utils/math/vectors", only declare the top-level module once
+        let (name, file) = match module_name.split_once('/') {
+            None => (module_name.as_str(), format!("{}.rs", module_name)),
+            Some((name, _)) => (name, format!("{}/mod.rs", name)),
+        };
+        let declaration = format!("{}mod {};\n", visibility, name);
+        if output.contains(&declaration) {
+            continue;
         }
+        if nested {
+            // Exports other binaries use aren't dead code
+            output.push_str(&format!("#[allow(dead_code)]\n#[path = \"../{}\"]\n", file));
+        }
+        output.push_str(&declaration);
     }
     
-    output.push_str("\n");
+    output.push('\n');
     
-    // Add the index module code (main code)
-    if let Some(index_code) = modules.get("index") {
+    // Add the entry module code (main code)
+    if let Some(index_code) = modules.get(entry) {
         output.push_str(index_code);
     }
     
     Ok(output)
 }
 
-fn generate_cargo_toml(project_root: &std::path::Path, generated_dir: &std::path::Path) -> Result<()> {
+/// The `mod.rs` of every directory holding modules, by directory: a `pub mod`
+/// for each file and subdirectory in it, and a re-export of its `index` module
+fn directory_modules<'a>(module_names: impl Iterator<Item = &'a String>) -> BTreeMap<String, String> {
+    let mut children: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+    for module_name in module_names {
+        let parts: Vec<&str> = module_name.split('/').collect();
+        for depth in 1..parts.len() {
+            children.entry(parts[..depth].join("/")).or_default().insert(parts[depth]);
+        }
+    }
+    children
+        .into_iter()
+        .map(|(dir_name, names)| {
+            let mut mod_content = String::new();
+            for name in names {
+                mod_content.push_str(&format!("pub mod {};\n", name));
+                if name == "index" {
+                    mod_content.push_str("pub use index::*;\n");
+                }
+            }
+            (dir_name, mod_content)
+        })
+        .collect()
+}
+
+pub fn project_runtime(root: &Path) -> Result<Option<BTreeSet<String>>> {
+    let options = CompileOptions { stop_after: Stage::Parse, ..CompileOptions::default() };
+    let mut runtime: Option<BTreeSet<String>> = None;
+    for jr_file in project::find_all_jr_files(&root.join("src"))? {
+        let source = project::read_source_file(&jr_file)?;
+        let program = Compiler::new(options.clone()).compile(&source).ok().and_then(|compilation| compilation.program);
+        if let Some(program) = program.filter(|program| program.uses_runtime()) {
+            runtime.get_or_insert_with(BTreeSet::new).extend(program.runtime_features());
+        }
+    }
+    Ok(runtime)
+}
+
+pub fn runtime_dependency(features: &BTreeSet<String>) -> String {
+    let source = match std::env::var_os("JRUST_RUNTIME_PATH") {
+        Some(path) => {
+            let path = PathBuf::from(path);
+            let path = path.canonicalize().unwrap_or(path);
+            format!("path = {}", toml::Value::String(path.to_string_lossy().into_owned()))
+        }
+        None => format!("version = \"{}\"", env!("CARGO_PKG_VERSION")),
+    };
+    if features.is_empty() {
+        format!("jrust_runtime = {{ {} }}\n", source)
+    } else {
+        format!("jrust_runtime = {{ {}, features = {:?} }}\n", source, features.iter().collect::<Vec<_>>())
+    }
+}
+
+pub fn generate_cargo_toml(
+    project_root: &std::path::Path,
+    generated_dir: &std::path::Path,
+    lib: bool,
+    runtime: Option<&BTreeSet<String>>,
+    workspace: bool,
+) -> Result<()> {
     let config = project::ProjectConfig::from_path(project_root)?;
     
-    let cargo_toml = format!(
+    let (name, target) = if lib {
+        (config.package.name.replace('-', "_"), "[lib]\npath = \"lib.rs\"".to_string())
+    } else {
+        let binaries = config.binaries(project_root)?;
+        let targets: Vec<String> = binaries
+            .iter()
+            .enumerate()
+            .map(|(index, (name, _))| format!("[[bin]]\nname = \"{}\"\npath = \"{}\"", name, binary_root(index, name, false)))
+            .collect();
+        let name = binaries.first().map_or_else(|| config.binary().to_string(), |(name, _)| name.clone());
+        (name, targets.join("\n\n"))
+    };
+    let mut cargo_toml = format!(
         r#"[package]
-name = "jrust_app"
+name = "{}"
 version = "{}"
 edition = "{}"
-authors = {}
-
-[workspace]
-
-[[bin]]
-name = "jrust_app"
-path = "main.rs"
+authors = {:?}
+{}
+{}
 "#,
+        name,
         config.package.version,
         config.package.edition,
-        format!("{:?}", config.package.authors)
+        config.package.authors,
+        if workspace { "\n[workspace]\n" } else { "" },
+        target
     );
     
+    let runtime = runtime.filter(|_| !config.dependencies.contains_key("jrust_runtime"));
+    if !config.dependencies.is_empty() || !config.packages.is_empty() || runtime.is_some() {
+        cargo_toml.push_str("\n[dependencies]\n");
+        if let Some(features) = runtime {
+            cargo_toml.push_str(&runtime_dependency(features));
+        }
+        for (name, package) in &config.packages {
+            let package_config = project::ProjectConfig::from_path(&project_root.join(&package.path))?;
+            cargo_toml.push_str(&format!(
+                "{} = {{ path = \"packages/{}\", package = \"{}\" }}\n",
+                name,
+                name,
+                package_config.package.name.replace('-', "_")
+            ));
+        }
+        for (name, value) in &config.dependencies {
+            let mut value = value.clone();
+            if let Some(toml::Value::String(dep_path)) = value.get_mut("path") {
+                if Path::new(dep_path.as_str()).is_relative() {
+                    *dep_path = relative_to(project_root, generated_dir, dep_path);
+                }
+            }
+            cargo_toml.push_str(&format!("{} = {}\n", name, project::inline_toml(&value)));
+        }
+    }
+    
+    cargo_toml.push_str(&project::tables_toml("lints", &config.lints));
+    // Cargo only reads profiles from the workspace root
+    if workspace {
+        cargo_toml.push_str(&project::tables_toml("profile", &config.profile));
+    }
+    
     project::write_file(&generated_dir.join("Cargo.toml"), &cargo_toml)?;
     Ok(())
 }
+
+/// `path`, relative to `project_root`, rewritten relative to `dir`
+fn relative_to(project_root: &Path, dir: &Path, path: &str) -> String {
+    match dir.strip_prefix(project_root) {
+        Ok(inside) if inside.components().all(|part| matches!(part, std::path::Component::Normal(_))) => {
+            format!("{}{}", "../".repeat(inside.components().count()), path)
+        }
+        _ => project_root.join(path).to_string_lossy().into_owned(),
+    }
+}
@@ -1,34 +1,225 @@
 use anyhow::Result;
-use jrust_transpiler_core::{Lexer, Parser};
+use jrust_transpiler_core::{cfg, unused, Cfg, Lexer, Parser, Checker, Diagnostic, Program};
+use crate::commands::build;
+use crate::diagnostics::{self, OutputFormat};
+use crate::module_graph::{self, ModuleGraph};
 use crate::project;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-pub fn handle(path: Option<String>) -> Result<()> {
-    let file_path = if let Some(p) = path {
-        std::path::PathBuf::from(p)
-    } else {
-        let root = project::project_root()?;
-        let src_dir = root.join("src");
-        project::find_entry_point(&src_dir)?
+struct SourceFile {
+    display: PathBuf,
+    source: String,
+    module: Option<String>,
+    selected: bool,
+    program: Option<Program>,
+    failed: bool,
+    warnings: Vec<Diagnostic>,
+}
+
+struct Reporter {
+    format: OutputFormat,
+    diagnostics: Vec<Value>,
+}
+
+impl Reporter {
+    fn report(&mut self, diagnostic: &Diagnostic, file: &SourceFile) {
+        match self.format {
+            OutputFormat::Human => diagnostics::report(diagnostic, &file.display, &file.source, self.format),
+            OutputFormat::Json => self.diagnostics.push(diagnostics::to_json(diagnostic, Some(&file.display), &file.source)),
+        }
+    }
+
+    fn report_message(&mut self, message: &str) {
+        match self.format {
+            OutputFormat::Human => eout!("❌ {}", message),
+            OutputFormat::Json => self.diagnostics.push(diagnostics::to_json(&Diagnostic::new(message), None, "")),
+        }
+    }
+}
+
+pub fn handle(path: Option<String>, format: OutputFormat) -> Result<()> {
+    let root = project::project_root();
+    let src_dir = root.as_ref().ok().map(|root| root.join("src"));
+    let config = root.as_ref().ok().map(|root| project::ProjectConfig::from_path(root)).transpose()?;
+    let entries: Vec<String> = match (&root, &config) {
+        (Ok(root), Some(config)) => config.binaries(root)?.into_iter().map(|(_, entry)| entry).collect(),
+        _ => vec!["index".to_string()],
+    };
+    let library = match (&root, &config) {
+        (Ok(root), Some(_)) => build::is_library(root, false)?,
+        _ => false,
+    };
+
+    let selected = match path {
+        Some(p) => {
+            let path = PathBuf::from(p);
+            if !path.exists() {
+                anyhow::bail!("File not found: {:?}", path);
+            }
+            if path.is_dir() {
+                project::find_all_jr_files(&path)?
+            } else {
+                vec![path]
+            }
+        }
+        None => project::find_all_jr_files(&root?.join("src"))?,
+    };
+    let selected: Vec<PathBuf> = selected.iter().filter_map(|path| path.canonicalize().ok()).collect();
+
+    let mut paths = selected.clone();
+    if let Some(src_dir) = &src_dir {
+        paths.extend(project::find_all_jr_files(src_dir)?.iter().filter_map(|path| path.canonicalize().ok()));
+    }
+    paths.sort();
+    paths.dedup();
+
+    let cwd = std::env::current_dir()?;
+    let src_dir = src_dir.and_then(|dir| dir.canonicalize().ok());
+    let mut files = Vec::new();
+    for path in paths {
+        let module = src_dir
+            .as_ref()
+            .and_then(|dir| path.strip_prefix(dir).ok())
+            .map(|relative| relative.with_extension("").to_string_lossy().replace('\\', "/"));
+        files.push(SourceFile {
+            display: path.strip_prefix(&cwd).unwrap_or(&path).to_path_buf(),
+            source: project::read_source_file(&path)?,
+            module,
+            selected: selected.contains(&path),
+            program: None,
+            failed: false,
+            warnings: Vec::new(),
+        });
+    }
+
+    let human = format == OutputFormat::Human;
+    let checked = files.iter().filter(|file| file.selected).count();
+    if human {
+        status!("📋 Checking {} jRust file(s)...", checked);
+    }
+
+    let mut reporter = Reporter { format, diagnostics: Vec::new() };
+    let (mut lex_ok, mut parse_ok, mut check_ok) = (true, true, true);
+    let mut graph = match &config {
+        Some(config) => ModuleGraph::with_crates(config.crate_names()),
+        None => ModuleGraph::new(),
     };
-    
-    if !file_path.exists() {
-        anyhow::bail!("File not found: {:?}", file_path);
-    }
-    
-    let source = project::read_source_file(&file_path)?;
-    
-    println!("📋 Checking: {:?}", file_path);
-    
-    let mut lexer = Lexer::new(&source);
-    let tokens = lexer.tokenize()
-        .map_err(|e| anyhow::anyhow!(e))?;
-    println!("✅ Lexical analysis passed");
-    
-    let mut parser = Parser::new(tokens);
-    let _program = parser.parse()
-        .map_err(|e| anyhow::anyhow!(e))?;
-    println!("✅ Syntax parsing passed");
-    
-    println!("✅ All checks passed!");
+
+    for file in &mut files {
+        let tokens = match Lexer::new(&file.source).tokenize() {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                if file.selected {
+                    reporter.report(&e, file);
+                    file.failed = true;
+                    lex_ok = false;
+                }
+                continue;
+            }
+        };
+        match Parser::new(tokens).parse() {
+            Ok(mut program) => {
+                file.warnings = unused::unused_import_warnings(&program, &file.source);
+                cfg::resolve(&mut program, &Cfg::default());
+                if let Some(module) = &file.module {
+                    graph.add_module(module, &program);
+                }
+                file.program = Some(program);
+            }
+            Err(e) => {
+                if file.selected {
+                    reporter.report(&e, file);
+                    file.failed = true;
+                    parse_ok = false;
+                }
+            }
+        }
+    }
+
+    let all_parsed = files.iter().all(|file| file.program.is_some());
+    let mut imported = BTreeMap::new();
+    if let Some(cycle) = graph.find_cycle() {
+        reporter.report_message(&format!(
+            "Circular import detected: {}",
+            module_graph::format_cycle(&cycle)
+        ));
+        check_ok = false;
+    } else {
+        match graph.resolve_imports() {
+            Ok(resolved) => {
+                imported = resolved;
+                if !library {
+                    for export in graph.unused_exports(&entries) {
+                        let Some(file) = files.iter_mut().find(|file| file.module.as_ref() == Some(&export.module)) else {
+                            continue;
+                        };
+                        let warning = unused::unused_export_warning(&file.source, &export.name, export.span, export.removable);
+                        file.warnings.push(warning);
+                    }
+                }
+            }
+            Err(e) if all_parsed => {
+                reporter.report_message(&e);
+                check_ok = false;
+            }
+            Err(_) => {}
+        }
+    }
+
+    for file in &mut files {
+        let (true, Some(program)) = (file.selected, &file.program) else {
+            continue;
+        };
+        let mut checker = match &file.module {
+            Some(module) if !entries.contains(module) => Checker::new_module(),
+            _ => Checker::new(),
+        };
+        if let Some(imports) = file.module.as_ref().and_then(|module| imported.remove(module)) {
+            for (name, func_decl) in imports.functions {
+                checker.declare_function(&name, func_decl);
+            }
+        }
+        if let Err(e) = checker.check(program) {
+            reporter.report(&e, file);
+            file.failed = true;
+            check_ok = false;
+        }
+        for warning in &file.warnings {
+            reporter.report(warning, file);
+        }
+    }
+
+    let failed = files.iter().filter(|file| file.failed).count();
+    if human {
+        for file in files.iter().filter(|file| file.selected) {
+            let mark = if file.failed { "❌" } else { "✅" };
+            status!("  {} {}", mark, display(&file.display));
+        }
+        if lex_ok {
+            status!("✅ Lexical analysis passed");
+        }
+        if lex_ok && parse_ok {
+            status!("✅ Syntax parsing passed");
+        }
+        if lex_ok && parse_ok && check_ok {
+            status!("✅ Semantic checks passed");
+            status!("✅ All checks passed!");
+        }
+    } else {
+        diagnostics::print_json(lex_ok && parse_ok && check_ok, reporter.diagnostics);
+    }
+
+    if failed > 0 {
+        anyhow::bail!("Check failed: {} of {} file(s) have errors", failed, checked);
+    }
+    if !check_ok {
+        anyhow::bail!("Check failed");
+    }
     Ok(())
 }
+
+fn display(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
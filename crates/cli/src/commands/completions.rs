@@ -0,0 +1,11 @@
+use anyhow::{Result, Context};
+use clap_complete::Shell;
+use std::io::Write;
+
+/// Writes the completion script for `shell` to stdout
+pub fn handle(shell: Shell, command: &mut clap::Command) -> Result<()> {
+    let name = command.get_name().to_string();
+    let mut script = Vec::new();
+    clap_complete::generate(shell, command, name, &mut script);
+    std::io::stdout().write_all(&script).context("Failed to write completions")
+}
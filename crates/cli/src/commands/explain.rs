@@ -0,0 +1,13 @@
+use anyhow::Result;
+use jrust_transpiler_core::error_codes;
+
+pub fn handle(code: String) -> Result<()> {
+    let Some(entry) = error_codes::lookup(&code) else {
+        anyhow::bail!("Unknown error code '{}'", code);
+    };
+    
+    println!("{}: {}", entry.code, entry.title);
+    println!();
+    print!("{}", entry.explanation);
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use anyhow::Result;
+use jrust_transpiler_core::{cfg, unused, Cfg, Edit, Fix, Lexer, Parser};
+use crate::commands::build::{self, Profile};
+use crate::diagnostics::OutputFormat;
+use crate::fixes;
+use crate::module_graph::ModuleGraph;
+use crate::project;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const MAX_SYNTAX_FIXES: usize = 100;
+
+struct SourceFile {
+    original: String,
+    fixed: String,
+    fixes: usize,
+}
+
+pub fn handle(dry_run: bool) -> Result<()> {
+    let root = project::project_root()?;
+    let mut files = BTreeMap::new();
+    for path in project::find_all_jr_files(&root.join("src"))? {
+        let source = project::read_source_file(&path)?;
+        let relative = path.strip_prefix(&root).unwrap_or(&path).to_path_buf();
+        files.insert(relative, SourceFile { original: source.clone(), fixed: source, fixes: 0 });
+    }
+
+    status!("🔧 Fixing jRust project...");
+
+    let mut parsed = true;
+    for file in files.values_mut() {
+        for _ in 0..MAX_SYNTAX_FIXES {
+            let error = match Lexer::new(&file.fixed).tokenize() {
+                Ok(tokens) => Parser::new(tokens).parse().err(),
+                Err(e) => Some(e),
+            };
+            let Some(error) = error else {
+                break;
+            };
+            let Some(fix) = error.fix else {
+                parsed = false;
+                break;
+            };
+            let (fixed, applied) = fixes::apply(&file.fixed, &[&*fix]);
+            file.fixed = fixed;
+            file.fixes += applied;
+        }
+    }
+
+    if parsed {
+        fix_unused(&root, &mut files)?;
+    }
+
+    let source_fixed = files.values().any(|file| file.fixed != file.original);
+    if !dry_run {
+        write(&root, &files)?;
+    }
+
+    if !parsed {
+        status!("⚠️  Some files have syntax errors without a fix; run `jrust check` to see them");
+    } else if dry_run && source_fixed {
+        status!("ℹ️  Compiler warnings are fixed once these fixes are applied");
+    } else {
+        let generated = build::generate_project(
+            &root,
+            OutputFormat::Human,
+            build::is_library(&root, false)?,
+            false,
+            &Cfg::default(),
+        )?;
+        status!("🚀 Checking with Rust...");
+        let (_, messages) =
+            build::cargo_build(&generated.dir, Profile::Debug, None, &generated.source_map, &root, OutputFormat::Json)?;
+
+        let renames: Vec<&Fix> = messages.fixes.iter().map(|(_, fix)| fix).filter(|fix| matches!(fix.edit, Edit::Rename { .. })).collect();
+        for (path, file) in files.iter_mut() {
+            let mut file_fixes: Vec<&Fix> = messages
+                .fixes
+                .iter()
+                .filter(|(fix_path, fix)| fix_path == path && matches!(fix.edit, Edit::Replace { .. }))
+                .map(|(_, fix)| fix)
+                .collect();
+            file_fixes.extend(&renames);
+            let (fixed, applied) = fixes::apply(&file.fixed, &file_fixes);
+            file.fixed = fixed;
+            file.fixes += applied;
+        }
+        if !dry_run {
+            write(&root, &files)?;
+        }
+    }
+
+    let changed: Vec<(&PathBuf, &SourceFile)> = files.iter().filter(|(_, file)| file.fixed != file.original).collect();
+    for (path, file) in &changed {
+        let display = path.to_string_lossy().replace('\\', "/");
+        if dry_run {
+            print!("{}", fixes::diff(&display, &file.original, &file.fixed));
+        } else {
+            status!("  🔧 {} ({} fix(es))", display, file.fixes);
+        }
+    }
+
+    let total: usize = changed.iter().map(|(_, file)| file.fixes).sum();
+    match (changed.is_empty(), dry_run) {
+        (true, _) => status!("✅ Nothing to fix"),
+        (false, true) => status!("📝 {} fix(es) in {} file(s) would be applied. Run `jrust fix` to apply them.", total, changed.len()),
+        (false, false) => status!("✅ Applied {} fix(es) to {} file(s)", total, changed.len()),
+    }
+    Ok(())
+}
+
+fn fix_unused(root: &Path, files: &mut BTreeMap<PathBuf, SourceFile>) -> Result<()> {
+    let config = project::ProjectConfig::from_path(root)?;
+    let entries: Vec<String> = config.binaries(root)?.into_iter().map(|(_, entry)| entry).collect();
+    let mut graph = ModuleGraph::with_crates(config.crate_names());
+    let mut fixes: BTreeMap<PathBuf, Vec<Fix>> = BTreeMap::new();
+    let mut modules = BTreeMap::new();
+    for (path, file) in files.iter() {
+        let Ok(tokens) = Lexer::new(&file.fixed).tokenize() else {
+            continue;
+        };
+        let Ok(mut program) = Parser::new(tokens).parse() else {
+            continue;
+        };
+        let warnings = unused::unused_import_warnings(&program, &file.fixed);
+        fixes.entry(path.clone()).or_default().extend(warnings.into_iter().filter_map(|warning| warning.fix.map(|fix| *fix)));
+
+        cfg::resolve(&mut program, &Cfg::default());
+        if let Ok(relative) = path.strip_prefix("src") {
+            let module = relative.with_extension("").to_string_lossy().replace('\\', "/");
+            graph.add_module(&module, &program);
+            modules.insert(module, path.clone());
+        }
+    }
+
+    if !build::is_library(root, false)? && graph.find_cycle().is_none() && graph.resolve_imports().is_ok() {
+        for export in graph.unused_exports(&entries) {
+            let Some(path) = modules.get(&export.module) else {
+                continue;
+            };
+            let warning = unused::unused_export_warning(&files[path].fixed, &export.name, export.span, export.removable);
+            fixes.entry(path.clone()).or_default().extend(warning.fix.map(|fix| *fix));
+        }
+    }
+
+    for (path, file_fixes) in fixes {
+        let file = files.get_mut(&path).expect("fixes are only collected for project files");
+        let (fixed, applied) = fixes::apply(&file.fixed, &file_fixes.iter().collect::<Vec<_>>());
+        file.fixed = fixed;
+        file.fixes += applied;
+    }
+    Ok(())
+}
+
+fn write(root: &Path, files: &BTreeMap<PathBuf, SourceFile>) -> Result<()> {
+    for (path, file) in files.iter() {
+        if file.fixed != file.original {
+            project::write_file(&root.join(path), &file.fixed)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use jrust_transpiler_core::format_source;
+use crate::diagnostics::{self, OutputFormat};
+use crate::project;
+use std::path::PathBuf;
+
+pub fn handle(path: Option<String>, check: bool) -> Result<()> {
+    let mut files = match path {
+        Some(p) => {
+            let path = PathBuf::from(p);
+            if !path.exists() {
+                anyhow::bail!("File not found: {:?}", path);
+            }
+            if path.is_dir() {
+                project::find_all_jr_files(&path)?
+            } else {
+                vec![path]
+            }
+        }
+        None => project::find_all_jr_files(&project::project_root()?.join("src"))?,
+    };
+    files.sort();
+    
+    let current_dir = std::env::current_dir()?;
+    let mut unformatted = 0;
+    let mut failed = 0;
+    
+    for file in &files {
+        let display_path = file.strip_prefix(&current_dir).unwrap_or(file);
+        let source = project::read_source_file(file)?;
+        let formatted = match format_source(&source) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                diagnostics::report(&e, display_path, &source, OutputFormat::Human);
+                failed += 1;
+                continue;
+            }
+        };
+        
+        if formatted == source {
+            continue;
+        }
+        unformatted += 1;
+        if check {
+            out!("❌ Needs formatting: {}", display_path.display());
+        } else {
+            project::write_file(file, &formatted)?;
+            status!("✨ Formatted: {}", display_path.display());
+        }
+    }
+    
+    if failed > 0 {
+        anyhow::bail!("Failed to format {} file(s) with syntax errors", failed);
+    }
+    if check && unformatted > 0 {
+        anyhow::bail!("{} file(s) need formatting. Run `jrust fmt` to fix them.", unformatted);
+    }
+    if unformatted == 0 {
+        status!("✅ All {} file(s) are formatted", files.len());
+    }
+    Ok(())
+}
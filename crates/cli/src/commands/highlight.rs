@@ -0,0 +1,46 @@
+use anyhow::Result;
+use jrust_transpiler_core::highlight;
+use crate::diagnostics::{self, OutputFormat};
+use crate::project;
+use serde_json::json;
+use std::path::PathBuf;
+
+pub fn handle(path: String, format: OutputFormat) -> Result<()> {
+    let path = PathBuf::from(path);
+    if !path.exists() {
+        anyhow::bail!("File not found: {:?}", path);
+    }
+    let source = project::read_source_file(&path)?;
+    let tokens = highlight(&source).map_err(|e| {
+        diagnostics::report(&e, &path, &source, format);
+        anyhow::anyhow!("Lexical analysis failed")
+    })?;
+
+    match format {
+        OutputFormat::Human => {
+            let lines: Vec<Vec<char>> = source.lines().map(|line| line.chars().collect()).collect();
+            for token in &tokens {
+                let text: String = lines
+                    .get(token.line - 1)
+                    .map(|line| line.iter().skip(token.column - 1).take(token.length).collect())
+                    .unwrap_or_default();
+                println!("{}:{}\t{}\t{}", token.line, token.column, token.kind.as_str(), text);
+            }
+        }
+        OutputFormat::Json => {
+            let tokens: Vec<_> = tokens
+                .iter()
+                .map(|token| {
+                    json!({
+                        "line": token.line,
+                        "column": token.column,
+                        "length": token.length,
+                        "kind": token.kind.as_str(),
+                    })
+                })
+                .collect();
+            println!("{}", json!({ "file": path.display().to_string(), "tokens": tokens }));
+        }
+    }
+    Ok(())
+}
@@ -1,21 +1,69 @@
-use anyhow::Result;
-use crate::project;
+use anyhow::{Result, Context};
+use crate::project::{self, ProjectConfig};
+use std::path::PathBuf;
 
-pub fn handle(project_name: String) -> Result<()> {
+const EDITIONS: &[&str] = &["2015", "2018", "2021", "2024"];
+
+pub struct InitOptions {
+    pub path: Option<PathBuf>,
+    pub force: bool,
+    pub authors: Vec<String>,
+    pub edition: Option<String>,
+    pub description: Option<String>,
+}
+
+pub fn handle(name: String, options: InitOptions) -> Result<()> {
     let current_dir = std::env::current_dir()?;
-    let project_path = current_dir.join(&project_name);
+    let in_place = name == ".";
+    let project_path = match (&options.path, in_place) {
+        (Some(path), _) => current_dir.join(path),
+        (None, true) => current_dir.clone(),
+        (None, false) => current_dir.join(&name),
+    };
+    
+    let project_name = if in_place {
+        project_path
+            .canonicalize()
+            .unwrap_or_else(|_| project_path.clone())
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .context("Cannot determine a project name from the current directory")?
+    } else {
+        name
+    };
     
-    if project_path.exists() {
-        anyhow::bail!("Directory '{}' already exists", project_name);
+    let is_empty = project_path
+        .read_dir()
+        .map(|mut entries| entries.next().is_none())
+        .unwrap_or(true);
+    if !is_empty && !options.force {
+        anyhow::bail!(
+            "Directory {:?} already exists and is not empty. Use --force to initialize it anyway",
+            project_path
+        );
     }
     
-    project::create_project_structure(&project_name, &project_path)?;
+    let mut config = ProjectConfig::new(project_name.clone(), options.authors);
+    if let Some(edition) = options.edition {
+        if !EDITIONS.contains(&edition.as_str()) {
+            anyhow::bail!("Unknown edition '{}' (expected one of {})", edition, EDITIONS.join(", "));
+        }
+        config.package.edition = edition;
+    }
+    if options.description.is_some() {
+        config.package.description = options.description;
+    }
     
-    println!("✨ Created new jRust project: {}", project_name);
-    println!();
-    println!("To get started:");
-    println!("  cd {}", project_name);
-    println!("  jrust run");
+    project::create_project_structure(&config, &project_path)?;
+    
+    status!("✨ Created new jRust project: {}", project_name);
+    status!("");
+    status!("To get started:");
+    if project_path != current_dir {
+        let relative = project_path.strip_prefix(&current_dir).unwrap_or(&project_path);
+        status!("  cd {}", relative.display());
+    }
+    status!("  jrust run");
     
     Ok(())
 }
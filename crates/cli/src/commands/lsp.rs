@@ -0,0 +1,7 @@
+use anyhow::Result;
+use crate::lsp::Server;
+
+pub fn handle() -> Result<()> {
+    let stdin = std::io::stdin();
+    Server::new(Box::new(std::io::stdout())).run(&mut stdin.lock())
+}
@@ -1,4 +1,15 @@
 pub mod init;
 pub mod build;
 pub mod run;
+pub mod run_script;
 pub mod check;
+pub mod add;
+pub mod remove;
+pub mod explain;
+pub mod fmt;
+pub mod fix;
+pub mod test;
+pub mod watch;
+pub mod lsp;
+pub mod highlight;
+pub mod completions;
@@ -0,0 +1,19 @@
+use anyhow::Result;
+use crate::commands::build;
+use crate::project::{self, ProjectConfig};
+
+pub fn handle(name: String) -> Result<()> {
+    let root = project::project_root()?;
+    let mut config = ProjectConfig::from_path(&root)?;
+    
+    if config.dependencies.remove(&name).is_none() {
+        anyhow::bail!("Dependency '{}' not found", name);
+    }
+    
+    config.save(&root)?;
+    let runtime = build::project_runtime(&root)?;
+    build::generate_cargo_toml(&root, &ProjectConfig::from_path(&root)?.out_dir(&root), build::is_library(&root, false)?, runtime.as_ref(), true)?;
+    
+    status!("🗑️  Removed dependency: {}", name);
+    Ok(())
+}
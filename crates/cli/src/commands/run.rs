@@ -1,38 +1,180 @@
 use anyhow::{Result, Context};
-use std::process::Command;
-use crate::commands::build;
+use jrust_transpiler_core::{Cfg, CompileOptions, Compiler};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use crate::commands::build::{self, Profile};
+use crate::diagnostics::{self, OutputFormat};
 use crate::project;
+use crate::source_map::SourceMap;
 
-pub fn handle(path: Option<String>) -> Result<()> {
-    build::handle(path)?;
-    
-    let root = project::project_root()?;
-    let generated_dir = root.join("generated");
-    
-    #[cfg(target_os = "windows")]
-    let executable = generated_dir.join("target/release/jrust_app.exe");
-    
-    #[cfg(not(target_os = "windows"))]
-    let executable = generated_dir.join("target/release/jrust_app");
+pub fn handle(path: Option<String>, bin: Option<String>, profile: Profile, opt: bool, banner: bool, args: Vec<String>) -> Result<()> {
+    let executable = match (project::project_root(), path) {
+        (Err(_), Some(script)) => build_script(Path::new(&script), profile, opt)?,
+        (_, path) => {
+            let root = project::project_root()?;
+            if build::is_library(&root, false)? {
+                anyhow::bail!("This project builds a library (crate-type = \"lib\"), so there is nothing to run");
+            }
+            let config = project::ProjectConfig::from_path(&root)?;
+            let binaries = config.binaries(&root)?;
+            let binary = match &bin {
+                Some(name) => binaries.iter().find(|(binary, _)| binary == name).ok_or_else(|| {
+                    let names: Vec<&str> = binaries.iter().map(|(binary, _)| binary.as_str()).collect();
+                    anyhow::anyhow!("No binary named '{}'. Available: {}", name, names.join(", "))
+                })?,
+                None => binaries.first().context("The project has no binaries")?,
+            };
+            let mut executables = build::handle(path, OutputFormat::Human, profile, None, false, opt, None)?;
+            executables.remove(&binary.0).context(format!("Cargo built no executable for '{}'", binary.0))?
+        }
+    };
     
     if !executable.exists() {
         anyhow::bail!("Executable not found: {:?}", executable);
     }
     
-    println!();
-    println!("🎯 Running program...");
-    println!("─────────────────────────");
+    if banner {
+        status!("");
+        status!("🎯 Running program...");
+        status!("─────────────────────────");
+    }
     
-    let status = Command::new(&executable)
-        .status()
-        .context("Failed to execute program")?;
+    let mut command = Command::new(&executable);
+    command.args(&args).stdin(Stdio::inherit()).stdout(Stdio::inherit()).stderr(Stdio::inherit());
+    #[cfg(unix)]
+    signals::prepare(&mut command);
+    let mut child = command.spawn().context("Failed to execute program")?;
+    #[cfg(unix)]
+    signals::forward_to(child.id());
+    let status = child.wait().context("Failed to execute program")?;
     
-    println!("─────────────────────────");
+    if banner {
+        status!("─────────────────────────");
+    }
     
     if !status.success() {
-        anyhow::bail!("Program exited with error");
+        std::process::exit(exit_code(status));
     }
     
-    println!("✅ Program completed successfully!");
+    if banner {
+        status!("✅ Program completed successfully!");
+    }
     Ok(())
 }
+
+/// The code `jrust run` exits with so shells see the program's own result:
+/// its exit code, or `128 + signal` if a signal stopped it
+fn exit_code(status: ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        status!("❌ Program exited with code {}", code);
+        return code;
+    }
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        status!("❌ Program was stopped by signal {}", signal);
+        return 128 + signal;
+    }
+    1
+}
+
+#[cfg(unix)]
+mod signals {
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    static CHILD: AtomicI32 = AtomicI32::new(0);
+
+    extern "C" fn forward(signal: libc::c_int) {
+        match CHILD.load(Ordering::SeqCst) {
+            0 => unsafe { libc::_exit(128 + signal) },
+            pid => unsafe {
+                libc::kill(pid, signal);
+            },
+        }
+    }
+
+    /// Sets the disposition of `signal`, returning the previous one
+    fn set_handler(signal: libc::c_int, handler: libc::sighandler_t) -> libc::sighandler_t {
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            let mut previous: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = handler;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(signal, &action, &mut previous);
+            previous.sa_sigaction
+        }
+    }
+
+    /// Ctrl-C already reaches the program through the terminal's process
+    /// group, so `jrust` ignores it while the program restores it; SIGTERM
+    /// only reaches `jrust`, so it is passed on
+    pub fn prepare(command: &mut Command) {
+        let interrupt = set_handler(libc::SIGINT, libc::SIG_IGN);
+        set_handler(libc::SIGTERM, forward as extern "C" fn(libc::c_int) as libc::sighandler_t);
+        unsafe {
+            command.pre_exec(move || {
+                set_handler(libc::SIGINT, interrupt);
+                Ok(())
+            });
+        }
+    }
+
+    pub fn forward_to(pid: u32) {
+        CHILD.store(pid as i32, Ordering::SeqCst);
+    }
+}
+
+fn build_script(script: &Path, profile: Profile, opt: bool) -> Result<PathBuf> {
+    if !script.exists() {
+        anyhow::bail!("File not found: {:?}", script);
+    }
+    
+    status!("📜 Compiling script: {}", script.display());
+    let source = project::read_source_file(script)?;
+    
+    let options = CompileOptions {
+        optimize: opt,
+        file: Some(script.to_string_lossy().replace('\\', "/")),
+        cfg: Cfg::for_target(profile == Profile::Debug, None),
+        ..CompileOptions::default()
+    };
+    let compilation = Compiler::new(options).compile(&source).map_err(|e| {
+        diagnostics::report(&e.diagnostic, script, &source, OutputFormat::Human);
+        anyhow::anyhow!(e.stage.failure_message())
+    })?;
+    let rust_code = compilation.rust.unwrap_or_default();
+    
+    let dir = script_cache_dir(script)?;
+    project::write_file(&dir.join("main.rs"), &rust_code)?;
+    let mut cargo_toml = "[package]\nname = \"jrust_app\"\nversion = \"0.0.1\"\nedition = \"2021\"\n\n[workspace]\n\n[[bin]]\nname = \"jrust_app\"\npath = \"main.rs\"\n".to_string();
+    if let Some(program) = compilation.program.as_ref().filter(|program| program.uses_runtime()) {
+        cargo_toml.push_str("\n[dependencies]\n");
+        cargo_toml.push_str(&build::runtime_dependency(&program.runtime_features()));
+    }
+    project::write_file(&dir.join("Cargo.toml"), &cargo_toml)?;
+    
+    let mut source_map = SourceMap::new();
+    source_map.add("main.rs", &script.to_string_lossy(), &compilation.source_map, 0);
+    
+    let root = std::env::current_dir().context("Failed to get current directory")?;
+    let (success, mut messages) = build::cargo_build(&dir, profile, None, &source_map, &root, OutputFormat::Human)?;
+    if !success {
+        anyhow::bail!("Cargo build failed");
+    }
+    
+    messages.executables.remove("jrust_app").context("Cargo built no executable for the script")
+}
+
+fn script_cache_dir(script: &Path) -> Result<PathBuf> {
+    let script = script.canonicalize().context(format!("Failed to resolve {:?}", script))?;
+    let mut hasher = DefaultHasher::new();
+    script.hash(&mut hasher);
+    let stem = script.file_stem().and_then(|s| s.to_str()).unwrap_or("script");
+    Ok(std::env::temp_dir()
+        .join("jrust-scripts")
+        .join(format!("{}-{:016x}", stem, hasher.finish())))
+}
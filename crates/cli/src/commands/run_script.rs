@@ -0,0 +1,41 @@
+use anyhow::{Result, Context};
+use crate::output;
+use crate::project::{self, ProjectConfig};
+use std::process::Command;
+
+/// Runs `[scripts] name` as `jrust <script words> <args>`
+pub fn handle(name: String, args: Vec<String>) -> Result<()> {
+    let root = project::project_root()?;
+    let config = ProjectConfig::from_path(&root)?;
+
+    let Some(script) = config.scripts.get(&name) else {
+        if config.scripts.is_empty() {
+            anyhow::bail!("No script named '{}'. Cargo.toml has no [scripts] table", name);
+        }
+        let names: Vec<&str> = config.scripts.keys().map(String::as_str).collect();
+        anyhow::bail!("No script named '{}'. Available: {}", name, names.join(", "));
+    };
+
+    let words: Vec<&str> = script.split_whitespace().collect();
+    if words.is_empty() {
+        anyhow::bail!("Script '{}' is empty", name);
+    }
+    if words[0] == "run-script" {
+        anyhow::bail!("Script '{}' can't run another script", name);
+    }
+
+    status!("▶️  jrust {}", script);
+    let executable = std::env::current_exe().context("Failed to locate the jrust executable")?;
+    let status = Command::new(executable)
+        .args(output::global_args())
+        .args(&words)
+        .args(&args)
+        .current_dir(&root)
+        .status()
+        .context(format!("Failed to run script '{}'", name))?;
+
+    if !status.success() {
+        anyhow::bail!("Script '{}' failed", name);
+    }
+    Ok(())
+}
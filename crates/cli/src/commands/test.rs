@@ -0,0 +1,151 @@
+use anyhow::{Result, Context};
+use crate::commands::build::{self, Profile, TestCase};
+use crate::diagnostics::OutputFormat;
+use crate::project;
+use crate::source_map::SourceMap;
+use jrust_transpiler_core::Cfg;
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufReader;
+use std::process::{Command, Stdio};
+
+pub fn handle() -> Result<()> {
+    let root = project::project_root()?;
+    
+    status!("🧪 Testing jRust project...");
+    let generated = build::generate_project(
+        &root,
+        OutputFormat::Human,
+        build::is_library(&root, false)?,
+        build::resolve_opt(&root, false, Profile::Debug)?,
+        &Cfg::default(),
+    )?;
+    
+    if generated.tests.is_empty() {
+        out!("No tests found. Add a `test \"name\" {{ ... }}` block to a .jr file.");
+        return Ok(());
+    }
+    
+    status!("🚀 Compiling tests with Rust...");
+    let mut cargo = Command::new("cargo")
+        .args(["test", "--no-run", "--quiet", "--message-format=json"])
+        .current_dir(&generated.dir)
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to run cargo test")?;
+    
+    let stdout = cargo.stdout.take().context("Failed to capture cargo output")?;
+    build::report_compiler_messages(BufReader::new(stdout), &generated.dir, &generated.source_map, &root, OutputFormat::Human);
+    if !cargo.wait().context("Failed to run cargo test")?.success() {
+        anyhow::bail!("Cargo build failed");
+    }
+    
+    let output = Command::new("cargo")
+        .args(["test", "--", "--test-threads=1"])
+        .current_dir(&generated.dir)
+        .env("RUST_BACKTRACE", "0")
+        .output()
+        .context("Failed to run cargo test")?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (results, failures) = parse_test_output(&stdout);
+    
+    let mut by_file: BTreeMap<&str, Vec<&TestCase>> = BTreeMap::new();
+    for test in &generated.tests {
+        by_file.entry(test.file.as_str()).or_default().push(test);
+    }
+    
+    let mut passed = 0;
+    let mut failed = Vec::new();
+    status!("");
+    for (file, tests) in &by_file {
+        status!("📄 {}", file);
+        for test in tests {
+            match results.get(test.path.as_str()).map(String::as_str) {
+                Some("ok") => {
+                    passed += 1;
+                    status!("  ✅ {}", test.name);
+                }
+                Some("ignored") => status!("  ⏭️  {}", test.name),
+                _ => {
+                    failed.push(*test);
+                    out!("  ❌ {}", test.name);
+                }
+            }
+        }
+    }
+    
+    if !failed.is_empty() {
+        out!("");
+        out!("Failures:");
+        for test in &failed {
+            out!("");
+            out!("❌ {} ({})", test.name, test.file);
+            let details = failures.get(test.path.as_str()).map(Vec::as_slice).unwrap_or_default();
+            let mut location = None;
+            for line in details {
+                let line = translate_location(line, &generated.source_map);
+                if let Some(jr_location) = line.strip_prefix("panicked at ") {
+                    location = Some(format!(" at {}", jr_location));
+                    out!("   {}", line);
+                    continue;
+                }
+                let message = location.as_deref().and_then(|suffix| line.strip_suffix(suffix)).unwrap_or(&line);
+                out!("   {}", message);
+            }
+        }
+    }
+    
+    out!("");
+    out!("Test result: {} passed, {} failed", passed, failed.len());
+    
+    if !failed.is_empty() {
+        anyhow::bail!("{} test(s) failed", failed.len());
+    }
+    if !output.status.success() {
+        anyhow::bail!("Cargo test failed:\n{}", String::from_utf8_lossy(&output.stderr));
+    }
+    Ok(())
+}
+
+fn parse_test_output(stdout: &str) -> (HashMap<&str, String>, HashMap<&str, Vec<&str>>) {
+    let mut results = HashMap::new();
+    let mut failures: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut current: Option<&str> = None;
+    
+    for line in stdout.lines() {
+        if let Some(rest) = line.strip_prefix("test ") {
+            if let Some((path, status)) = rest.rsplit_once(" ... ") {
+                results.insert(path, status.to_string());
+                continue;
+            }
+        }
+        if let Some(name) = line.strip_prefix("---- ").and_then(|rest| rest.strip_suffix(" stdout ----")) {
+            current = Some(name);
+            continue;
+        }
+        if line == "failures:" || line.starts_with("test result:") {
+            current = None;
+            continue;
+        }
+        if let Some(name) = current {
+            if !line.is_empty() && !line.starts_with("note: run with `RUST_BACKTRACE") {
+                failures.entry(name).or_default().push(line);
+            }
+        }
+    }
+    
+    (results, failures)
+}
+
+fn translate_location(line: &str, source_map: &SourceMap) -> String {
+    let Some((_, location)) = line.split_once(" panicked at ") else {
+        return line.to_string();
+    };
+    let mut parts = location.trim_end_matches(':').splitn(3, ':');
+    let (Some(file), Some(rust_line)) = (parts.next(), parts.next().and_then(|l| l.parse().ok())) else {
+        return line.to_string();
+    };
+    match source_map.lookup(file, rust_line) {
+        Some((jr_file, jr_line)) => format!("panicked at {}:{}", jr_file, jr_line),
+        None => line.to_string(),
+    }
+}
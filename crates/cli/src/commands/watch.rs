@@ -0,0 +1,113 @@
+use anyhow::{Result, Context};
+use clap::ValueEnum;
+use crate::output;
+use crate::project;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum WatchCommand {
+    Check,
+    Build,
+    #[default]
+    Run,
+    Test,
+}
+
+impl WatchCommand {
+    fn as_str(&self) -> &'static str {
+        match self {
+            WatchCommand::Check => "check",
+            WatchCommand::Build => "build",
+            WatchCommand::Run => "run",
+            WatchCommand::Test => "test",
+        }
+    }
+}
+
+type Snapshot = BTreeMap<PathBuf, Option<SystemTime>>;
+
+pub fn handle(command: WatchCommand, clear: bool) -> Result<()> {
+    let root = project::project_root()?;
+    let executable = std::env::current_exe().context("Failed to locate the jrust executable")?;
+
+    let mut snapshot = take_snapshot(&root)?;
+    let mut child = start(&executable, command, clear)?;
+    let mut running = true;
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        if running {
+            if let Some(status) = child.try_wait().context("Failed to wait for jrust")? {
+                running = false;
+                status!("");
+                if status.success() {
+                    status!("✅ Finished. Watching src/ for changes...");
+                } else {
+                    status!("❌ Failed. Watching src/ for changes...");
+                }
+            }
+        }
+
+        let current = take_snapshot(&root)?;
+        if current == snapshot {
+            continue;
+        }
+        snapshot = settle(&root, current)?;
+
+        if running {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        child = start(&executable, command, clear)?;
+        running = true;
+    }
+}
+
+fn start(executable: &Path, command: WatchCommand, clear: bool) -> Result<Child> {
+    if clear {
+        print!("\x1b[2J\x1b[H");
+    }
+    status!("👀 Watching src/ — running `jrust {}` (Ctrl+C to stop)", command.as_str());
+    status!("");
+    std::io::stdout().flush()?;
+
+    Command::new(executable)
+        .args(output::global_args())
+        .arg(command.as_str())
+        .spawn()
+        .context(format!("Failed to run jrust {}", command.as_str()))
+}
+
+fn settle(root: &Path, mut snapshot: Snapshot) -> Result<Snapshot> {
+    loop {
+        thread::sleep(DEBOUNCE);
+        let current = take_snapshot(root)?;
+        if current == snapshot {
+            return Ok(snapshot);
+        }
+        snapshot = current;
+    }
+}
+
+fn take_snapshot(root: &Path) -> Result<Snapshot> {
+    let mut files = project::find_all_jr_files(&root.join("src"))?;
+    files.push(root.join("Cargo.toml"));
+
+    Ok(files
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+            (path, modified)
+        })
+        .collect())
+}
@@ -0,0 +1,64 @@
+use clap::ValueEnum;
+use jrust_transpiler_core::Diagnostic;
+use serde_json::{json, Value};
+use std::path::Path;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+pub fn report(diagnostic: &Diagnostic, path: &Path, source: &str, format: OutputFormat) {
+    let diagnostic = &diagnostic.clone().locate(source);
+    match format {
+        OutputFormat::Human => {
+            eprint!("{}", diagnostic.render(&path.display().to_string(), source, use_color()));
+            if let Some(code) = diagnostic.code.as_deref().filter(|code| code.starts_with("JR")) {
+                eprintln!("For more information about this error, try `jrust explain {}`.", code);
+            }
+        }
+        OutputFormat::Json => print_json(false, vec![to_json(diagnostic, Some(path), source)]),
+    }
+}
+
+pub fn report_message(message: &str, format: OutputFormat) {
+    if format == OutputFormat::Json {
+        print_json(false, vec![to_json(&Diagnostic::new(message), None, "")]);
+    }
+}
+
+pub fn report_success(format: OutputFormat) {
+    if format == OutputFormat::Json {
+        print_json(true, Vec::new());
+    }
+}
+
+pub fn use_color() -> bool {
+    crate::output::use_color()
+}
+
+pub fn print_json(success: bool, diagnostics: Vec<Value>) {
+    println!("{}", json!({ "success": success, "diagnostics": diagnostics }));
+}
+
+pub fn to_json(diagnostic: &Diagnostic, path: Option<&Path>, source: &str) -> Value {
+    let diagnostic = &diagnostic.clone().locate(source);
+    let span = match diagnostic.line {
+        Some(line) => json!({
+            "line": line,
+            "column": diagnostic.column,
+            "end_line": line,
+            "end_column": diagnostic.end_column(source),
+        }),
+        None => Value::Null,
+    };
+    json!({
+        "file": path.map(|path| path.display().to_string()),
+        "span": span,
+        "severity": diagnostic.severity.as_str(),
+        "code": diagnostic.code,
+        "message": diagnostic.message,
+    })
+}
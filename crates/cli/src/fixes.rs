@@ -0,0 +1,135 @@
+use jrust_transpiler_core::codegen::convert_name;
+use jrust_transpiler_core::{unused, Diagnostic, Edit, Fix, Lexer, Parser, TokenKind};
+use serde_json::Value;
+
+pub fn for_rust_warning(compiler_message: &Value, rust_name: Option<&str>, warning: &Diagnostic, source: &str) -> Option<Fix> {
+    let suggestions: Vec<&Value> = compiler_message["children"]
+        .as_array()?
+        .iter()
+        .flat_map(|child| child["spans"].as_array().into_iter().flatten())
+        .filter(|span| span["suggested_replacement"].is_string())
+        .collect();
+    let machine_applicable = suggestions.iter().any(|span| span["suggestion_applicability"] == "MachineApplicable");
+
+    match compiler_message["code"]["code"].as_str()? {
+        "unused_variables" if machine_applicable => {
+            let start = offset(source, warning.line?, warning.column?)?;
+            Some(Fix::replace("prefix it with an underscore", start, start, "_"))
+        }
+        "unused_imports" if machine_applicable => remove_import(source, offset(source, warning.line?, warning.column?)?),
+        "non_snake_case" => {
+            let from = rust_name?;
+            let to = suggestions.first()?["suggested_replacement"].as_str()?;
+            (from != to && convert_name(from) == to).then(|| Fix::rename(from, to))
+        }
+        _ => None,
+    }
+}
+
+fn offset(source: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = match line {
+        0 => return None,
+        1 => 0,
+        _ => source.match_indices('\n').nth(line - 2)?.0 + 1,
+    };
+    let text = &source[line_start..];
+    let within = text.char_indices().nth(column.checked_sub(1)?).map(|(index, _)| index)?;
+    Some(line_start + within)
+}
+
+fn remove_import(source: &str, start: usize) -> Option<Fix> {
+    let tokens = Lexer::new(source).tokenize().ok()?;
+    let program = Parser::new(tokens).parse().ok()?;
+    let statement = program.statements.iter().find(|stmt| stmt.span.start <= start && start < stmt.span.end)?;
+    unused::remove_import(source, statement, start)
+}
+
+pub fn apply(source: &str, fixes: &[&Fix]) -> (String, usize) {
+    let mut replaces: Vec<(usize, usize, &str)> = fixes
+        .iter()
+        .filter_map(|fix| match &fix.edit {
+            Edit::Replace { start, end, text } => Some((*start, *end, text.as_str())),
+            Edit::Rename { .. } => None,
+        })
+        .collect();
+    replaces.sort();
+    replaces.dedup();
+
+    let mut fixed = source.to_string();
+    let mut applied = 0;
+    let mut limit = usize::MAX;
+    for (start, end, text) in replaces.into_iter().rev() {
+        if end > limit {
+            continue;
+        }
+        fixed.replace_range(start..end, text);
+        limit = start;
+        applied += 1;
+    }
+
+    for fix in fixes {
+        if let Edit::Rename { from, to } = &fix.edit {
+            let renamed = rename(&fixed, from, to);
+            if renamed != fixed {
+                fixed = renamed;
+                applied += 1;
+            }
+        }
+    }
+    (fixed, applied)
+}
+
+fn rename(source: &str, from: &str, to: &str) -> String {
+    let Ok(tokens) = Lexer::new(source).tokenize() else {
+        return source.to_string();
+    };
+    let mut renamed = source.to_string();
+    for token in tokens.iter().rev() {
+        if matches!(&token.kind, TokenKind::Identifier(name) if name == from) {
+            renamed.replace_range(token.span.start..token.span.end, to);
+        }
+    }
+    renamed
+}
+
+pub fn diff(path: &str, old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut output = format!("--- {}\n+++ {}\n", path, path);
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let (start_old, start_new) = (i, j);
+        while (i < old.len() || j < new.len()) && !(i < old.len() && j < new.len() && old[i] == new[j]) {
+            if j >= new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        output.push_str(&format!("@@ -{},{} +{},{} @@\n", start_old + 1, i - start_old, start_new + 1, j - start_new));
+        for line in &old[start_old..i] {
+            output.push_str(&format!("-{}\n", line));
+        }
+        for line in &new[start_new..j] {
+            output.push_str(&format!("+{}\n", line));
+        }
+    }
+    output
+}
@@ -0,0 +1,419 @@
+use jrust_transpiler_core::ast::{FunctionDecl, StructDecl, EnumDecl, VariableDecl};
+use jrust_transpiler_core::visit::walk_statement;
+use jrust_transpiler_core::{cfg, unused, Cfg, Lexer, Parser, Checker, Diagnostic, Expression, ExpressionKind, Program, Statement, StatementKind, Token, TokenKind, Type, Visitor};
+use std::path::{Component, Path, PathBuf};
+
+pub const KEYWORDS: &[&str] = &[
+    "let", "const", "mut", "function", "return", "if", "else", "for", "in", "while", "break",
+    "continue", "struct", "enum", "readonly", "try", "catch", "throw", "import", "export", "from",
+    "as", "defer", "lock", "match", "test", "true", "false", "number", "string", "boolean", "void", "any",
+];
+
+pub const BUILTINS: &[(&str, &str)] = &[
+    ("print", "function print(...values: any[]): void\n\nPrints the values separated by spaces, followed by a newline."),
+    ("console", "console.log(...values: any[]): void\n\nPrints like `print`; `console.warn` and `console.error` write to stderr."),
+    ("assert", "function assert(condition: boolean, message?: string): void\n\nPanics with the source location when `condition` is false."),
+    ("assertEq", "function assertEq(actual: any, expected: any, message?: string): void\n\nPanics with the source location when the values differ."),
+    ("panic", "function panic(message?: string): void\n\nStops the program with the message and the source location."),
+    ("channel", "function channel<T>(): [sender, receiver]\n\nCreates a channel for sending values between threads."),
+    ("shared", "function shared<T>(value: T): shared<T>\n\nWraps a value so several owners can read and update it."),
+    ("env", "env.args(): string[]\n\nCommand-line arguments passed to the program, without the program name."),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DeclKind {
+    Function,
+    Variable,
+    Constant,
+    Parameter,
+    Struct,
+    Enum,
+    LoopVariable,
+    Import,
+}
+
+pub struct Declaration {
+    pub name: String,
+    pub original: String,
+    pub kind: DeclKind,
+    pub line: usize,
+    pub column: usize,
+    pub function: Option<String>,
+    pub import_path: Option<String>,
+}
+
+pub struct Document {
+    pub text: String,
+    pub tokens: Vec<Token>,
+    pub program: Option<Program>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub declarations: Vec<Declaration>,
+}
+
+impl Document {
+    pub fn analyze(text: String) -> Self {
+        let mut diagnostics = Vec::new();
+        let tokens = Lexer::new(&text).tokenize().unwrap_or_else(|e| {
+            diagnostics.push(e);
+            Vec::new()
+        });
+        let program = if tokens.is_empty() {
+            None
+        } else {
+            Parser::new(tokens.clone())
+                .parse()
+                .map_err(|e| diagnostics.push(e))
+                .ok()
+                .map(|mut program| {
+                    diagnostics.extend(unused::unused_import_warnings(&program, &text));
+                    cfg::resolve(&mut program, &Cfg::default());
+                    program
+                })
+        };
+        if let Some(program) = &program {
+            if let Err(e) = Checker::new().check(program) {
+                diagnostics.push(locate(e.locate(&text), &text));
+            }
+        }
+        let declarations = collect_declarations(&tokens);
+        Document { text, tokens, program, diagnostics, declarations }
+    }
+
+    pub fn identifier_at(&self, line: usize, column: usize) -> Option<&Token> {
+        self.tokens.iter().find(|token| match &token.kind {
+            TokenKind::Identifier(name) => {
+                token.line == line && column >= token.column && column <= token.column + name.chars().count()
+            }
+            _ => false,
+        })
+    }
+
+    pub fn definition_of(&self, token: &Token) -> Option<&Declaration> {
+        let TokenKind::Identifier(name) = &token.kind else {
+            return None;
+        };
+        let candidates: Vec<&Declaration> = self.declarations.iter().filter(|decl| &decl.name == name).collect();
+        candidates
+            .iter()
+            .rev()
+            .find(|decl| (decl.line, decl.column) <= (token.line, token.column))
+            .or_else(|| candidates.first())
+            .copied()
+    }
+
+    pub fn exported(&self, name: &str) -> Option<&Declaration> {
+        self.declarations
+            .iter()
+            .find(|decl| decl.name == name && decl.kind != DeclKind::Import && decl.function.is_none())
+    }
+
+    pub fn describe(&self, decl: &Declaration) -> String {
+        let program = self.program.as_ref();
+        match decl.kind {
+            DeclKind::Function => program
+                .and_then(|program| find_function(program, &decl.name))
+                .map(function_signature)
+                .unwrap_or_else(|| format!("function {}", decl.name)),
+            DeclKind::Variable | DeclKind::Constant => program
+                .and_then(|program| find_variable(program, &decl.name, decl.line))
+                .map(variable_signature)
+                .unwrap_or_else(|| format!("let {}", decl.name)),
+            DeclKind::Parameter => {
+                let param_type = program
+                    .zip(decl.function.as_ref())
+                    .and_then(|(program, function)| find_function(program, function))
+                    .and_then(|func| func.parameters.iter().find(|param| param.name == decl.name))
+                    .map(|param| format_type(&param.param_type));
+                match param_type {
+                    Some(param_type) => format!("(parameter) {}: {}", decl.name, param_type),
+                    None => format!("(parameter) {}", decl.name),
+                }
+            }
+            DeclKind::Struct => program
+                .and_then(|program| find_struct(program, &decl.name))
+                .map(struct_signature)
+                .unwrap_or_else(|| format!("struct {}", decl.name)),
+            DeclKind::Enum => program
+                .and_then(|program| find_enum(program, &decl.name))
+                .map(enum_signature)
+                .unwrap_or_else(|| format!("enum {}", decl.name)),
+            DeclKind::LoopVariable => format!("(loop variable) {}", decl.name),
+            DeclKind::Import => format!(
+                "import {{{}}} from \"{}\"",
+                decl.original,
+                decl.import_path.as_deref().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+pub fn resolve_import(document: &Path, import_path: &str) -> Option<PathBuf> {
+    if !import_path.starts_with("./") && !import_path.starts_with("../") {
+        return None;
+    }
+    let mut base = document.parent()?.to_path_buf();
+    for component in Path::new(import_path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                base.pop();
+            }
+            component => base.push(component),
+        }
+    }
+    [base.with_extension("jr"), base.join("index.jr")]
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+}
+
+fn locate(diagnostic: Diagnostic, text: &str) -> Diagnostic {
+    if diagnostic.line.is_some() {
+        return diagnostic;
+    }
+    let Some(name) = diagnostic.message.split('\'').nth(1).filter(|name| !name.is_empty()) else {
+        return diagnostic;
+    };
+    for (index, line) in text.lines().enumerate() {
+        let chars: Vec<char> = line.chars().collect();
+        let target: Vec<char> = name.chars().collect();
+        for start in 0..chars.len() {
+            if chars[start..].starts_with(&target) {
+                let before = start.checked_sub(1).map(|i| chars[i]);
+                let after = chars.get(start + target.len());
+                let is_word = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric() || *c == '_');
+                if !is_word(before.as_ref()) && !is_word(after) {
+                    return diagnostic.at(index + 1, start + 1);
+                }
+            }
+        }
+    }
+    diagnostic
+}
+
+fn collect_declarations(tokens: &[Token]) -> Vec<Declaration> {
+    let mut declarations = Vec::new();
+    let mut i = 0;
+
+    let identifier = |index: usize| match tokens.get(index).map(|token| &token.kind) {
+        Some(TokenKind::Identifier(name)) => Some(name.clone()),
+        _ => None,
+    };
+    let mut declare = |name: String, kind: DeclKind, token: &Token, function: Option<String>, import_path: Option<String>, original: Option<String>| {
+        declarations.push(Declaration {
+            original: original.unwrap_or_else(|| name.clone()),
+            name,
+            kind,
+            line: token.line,
+            column: token.column,
+            function,
+            import_path,
+        });
+    };
+
+    while i < tokens.len() {
+        match &tokens[i].kind {
+            TokenKind::Let | TokenKind::Const => {
+                let kind = if tokens[i].kind == TokenKind::Const { DeclKind::Constant } else { DeclKind::Variable };
+                let mut j = i + 1;
+                if tokens.get(j).map(|token| &token.kind) == Some(&TokenKind::Mut) {
+                    j += 1;
+                }
+                if tokens.get(j).map(|token| &token.kind) == Some(&TokenKind::LeftBracket) {
+                    j += 1;
+                    while let Some(token) = tokens.get(j) {
+                        match &token.kind {
+                            TokenKind::Identifier(name) => declare(name.clone(), kind, token, None, None, None),
+                            TokenKind::Comma => {}
+                            _ => break,
+                        }
+                        j += 1;
+                    }
+                } else if let Some(name) = identifier(j) {
+                    declare(name, kind, &tokens[j], None, None, None);
+                }
+            }
+            TokenKind::Function => {
+                if let Some(name) = identifier(i + 1) {
+                    declare(name.clone(), DeclKind::Function, &tokens[i + 1], None, None, None);
+                    let current_function = Some(name);
+                    let mut j = i + 2;
+                    if tokens.get(j).map(|token| &token.kind) == Some(&TokenKind::LeftParen) {
+                        let mut depth = 0;
+                        while let Some(token) = tokens.get(j) {
+                            match &token.kind {
+                                TokenKind::LeftParen => depth += 1,
+                                TokenKind::RightParen => {
+                                    depth -= 1;
+                                    if depth == 0 {
+                                        break;
+                                    }
+                                }
+                                TokenKind::Identifier(param)
+                                    if depth == 1
+                                        && tokens.get(j + 1).map(|token| &token.kind) == Some(&TokenKind::Colon) =>
+                                {
+                                    declare(param.clone(), DeclKind::Parameter, token, current_function.clone(), None, None);
+                                }
+                                _ => {}
+                            }
+                            j += 1;
+                        }
+                        i = j;
+                    }
+                }
+            }
+            TokenKind::Struct | TokenKind::Enum => {
+                let kind = if tokens[i].kind == TokenKind::Struct { DeclKind::Struct } else { DeclKind::Enum };
+                if let Some(name) = identifier(i + 1) {
+                    declare(name, kind, &tokens[i + 1], None, None, None);
+                }
+            }
+            TokenKind::For => {
+                if let Some(name) = identifier(i + 1) {
+                    declare(name, DeclKind::LoopVariable, &tokens[i + 1], None, None, None);
+                }
+            }
+            TokenKind::Catch => {
+                if let Some(name) = identifier(i + 2) {
+                    declare(name, DeclKind::Variable, &tokens[i + 2], None, None, None);
+                }
+            }
+            TokenKind::Import => {
+                let end = tokens[i..]
+                    .iter()
+                    .position(|token| token.kind == TokenKind::From)
+                    .map(|offset| i + offset);
+                let path = end.and_then(|end| match tokens.get(end + 1).map(|token| &token.kind) {
+                    Some(TokenKind::StringLiteral(path)) => Some(path.clone()),
+                    _ => None,
+                });
+                let mut j = i + 1;
+                while j < end.unwrap_or(i + 1) {
+                    if let TokenKind::Identifier(name) = &tokens[j].kind {
+                        let is_alias = j > 0 && tokens[j - 1].kind == TokenKind::As;
+                        let alias_follows = tokens.get(j + 1).map(|token| &token.kind) == Some(&TokenKind::As);
+                        if !alias_follows {
+                            let original = if is_alias { identifier(j - 2) } else { None };
+                            declare(name.clone(), DeclKind::Import, &tokens[j], None, path.clone(), original);
+                        }
+                    }
+                    j += 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    declarations
+}
+
+#[derive(Default)]
+struct Declared<'ast> {
+    functions: Vec<&'ast FunctionDecl>,
+    variables: Vec<(&'ast VariableDecl, usize)>,
+    structs: Vec<&'ast StructDecl>,
+    enums: Vec<&'ast EnumDecl>,
+}
+
+impl<'ast> Visitor<'ast> for Declared<'ast> {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        match &stmt.kind {
+            StatementKind::FunctionDecl(func) => self.functions.push(func),
+            StatementKind::VariableDecl(var) => self.variables.push((var, stmt.span.line)),
+            StatementKind::StructDecl(decl) => self.structs.push(decl),
+            StatementKind::EnumDecl(decl) => self.enums.push(decl),
+            _ => {}
+        }
+        walk_statement(self, stmt);
+    }
+}
+
+fn declared(program: &Program) -> Declared<'_> {
+    let mut declared = Declared::default();
+    declared.visit_program(program);
+    declared
+}
+
+fn find_function<'a>(program: &'a Program, name: &str) -> Option<&'a FunctionDecl> {
+    declared(program).functions.into_iter().find(|func| func.name == name)
+}
+
+fn find_variable<'a>(program: &'a Program, name: &str, line: usize) -> Option<&'a VariableDecl> {
+    declared(program)
+        .variables
+        .into_iter()
+        .find(|(var, var_line)| var.name == name && *var_line == line)
+        .map(|(var, _)| var)
+}
+
+fn find_struct<'a>(program: &'a Program, name: &str) -> Option<&'a StructDecl> {
+    declared(program).structs.into_iter().find(|decl| decl.name == name)
+}
+
+fn find_enum<'a>(program: &'a Program, name: &str) -> Option<&'a EnumDecl> {
+    declared(program).enums.into_iter().find(|decl| decl.name == name)
+}
+
+fn function_signature(func: &FunctionDecl) -> String {
+    let params: Vec<String> = func
+        .parameters
+        .iter()
+        .map(|param| format!("{}: {}", param.name, format_type(&param.param_type)))
+        .collect();
+    format!("function {}({}): {}", func.name, params.join(", "), format_type(&func.return_type))
+}
+
+fn variable_signature(var: &VariableDecl) -> String {
+    let keyword = if var.is_const { "const" } else { "let" };
+    let var_type = var.var_type.clone().or_else(|| literal_type(&var.value));
+    match var_type {
+        Some(var_type) => format!("{} {}: {}", keyword, var.name, format_type(&var_type)),
+        None => format!("{} {}", keyword, var.name),
+    }
+}
+
+fn struct_signature(decl: &StructDecl) -> String {
+    let mut output = format!("struct {} {{\n", decl.name);
+    for field in &decl.fields {
+        let readonly = if field.is_readonly { "readonly " } else { "" };
+        let optional = if field.is_optional { "?" } else { "" };
+        output.push_str(&format!("    {}{}{}: {},\n", readonly, field.name, optional, format_type(&field.field_type)));
+    }
+    output.push('}');
+    output
+}
+
+fn enum_signature(decl: &EnumDecl) -> String {
+    let variants: Vec<&str> = decl.variants.iter().map(|variant| variant.name.as_str()).collect();
+    format!("enum {} {{ {} }}", decl.name, variants.join(", "))
+}
+
+fn literal_type(expr: &Expression) -> Option<Type> {
+    match &expr.kind {
+        ExpressionKind::NumberLiteral(_) => Some(Type::Number),
+        ExpressionKind::StringLiteral(_) => Some(Type::String),
+        ExpressionKind::BooleanLiteral(_) => Some(Type::Boolean),
+        ExpressionKind::StructLiteral { name, .. } => Some(Type::Custom(name.clone())),
+        ExpressionKind::ArrayLiteral(elements) => Some(Type::Array {
+            element_type: Box::new(elements.first().and_then(literal_type).unwrap_or(Type::Any)),
+            size: None,
+        }),
+        _ => None,
+    }
+}
+
+pub fn format_type(ty: &Type) -> String {
+    match ty {
+        Type::Number => "number".to_string(),
+        Type::String => "string".to_string(),
+        Type::Boolean => "boolean".to_string(),
+        Type::Void => "void".to_string(),
+        Type::Any | Type::Inferred => "any".to_string(),
+        Type::Array { element_type, size: None } => format!("{}[]", format_type(element_type)),
+        Type::Array { element_type, size: Some(size) } => {
+            format!("{}[{}]", format_type(element_type), size)
+        }
+        Type::Custom(name) => name.clone(),
+        Type::Shared(inner) => format!("shared<{}>", format_type(inner)),
+    }
+}
@@ -0,0 +1,341 @@
+mod analysis;
+
+use analysis::{Declaration, DeclKind, Document, BUILTINS, KEYWORDS};
+use anyhow::{Context, Result};
+use jrust_transpiler_core::{highlight, HighlightKind, Severity, TokenKind};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+const METHOD_NOT_FOUND: i64 = -32601;
+
+pub struct Server {
+    documents: HashMap<String, Document>,
+    output: Box<dyn Write>,
+}
+
+impl Server {
+    pub fn new(output: Box<dyn Write>) -> Self {
+        Server { documents: HashMap::new(), output }
+    }
+
+    pub fn run(&mut self, input: &mut impl BufRead) -> Result<()> {
+        while let Some(message) = read_message(input)? {
+            let method = message["method"].as_str().unwrap_or_default().to_string();
+            let id = message.get("id").cloned();
+            let params = &message["params"];
+
+            match method.as_str() {
+                "initialize" => self.respond(id, capabilities())?,
+                "shutdown" => self.respond(id, Value::Null)?,
+                "exit" => return Ok(()),
+                "textDocument/didOpen" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                    self.update(uri, text.to_string())?;
+                }
+                "textDocument/didChange" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    if let Some(text) = params["contentChanges"]
+                        .as_array()
+                        .and_then(|changes| changes.last())
+                        .and_then(|change| change["text"].as_str())
+                    {
+                        self.update(uri, text.to_string())?;
+                    }
+                }
+                "textDocument/didClose" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    self.documents.remove(uri);
+                    self.publish(uri, Vec::new())?;
+                }
+                "textDocument/hover" => {
+                    let result = self.hover(params);
+                    self.respond(id, result)?;
+                }
+                "textDocument/definition" => {
+                    let result = self.definition(params);
+                    self.respond(id, result)?;
+                }
+                "textDocument/completion" => {
+                    let result = self.completion(params);
+                    self.respond(id, result)?;
+                }
+                "textDocument/semanticTokens/full" => {
+                    let result = self.semantic_tokens(params);
+                    self.respond(id, result)?;
+                }
+                _ if id.is_some() => self.send(json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": METHOD_NOT_FOUND, "message": format!("Unknown method '{}'", method) },
+                }))?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, uri: &str, text: String) -> Result<()> {
+        let document = Document::analyze(text);
+        let diagnostics = document
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let line = diagnostic.line.unwrap_or(1).saturating_sub(1);
+                let start = diagnostic.column.unwrap_or(1).saturating_sub(1);
+                let end = diagnostic
+                    .end_column(&document.text)
+                    .map(|end| end.saturating_sub(1))
+                    .unwrap_or_else(|| document.text.lines().nth(line).map_or(0, |text| text.chars().count()));
+                let severity = match diagnostic.severity {
+                    Severity::Error => 1,
+                    Severity::Warning => 2,
+                    Severity::Note => 3,
+                };
+                json!({
+                    "range": range(&document.text, line, start, end.max(start)),
+                    "severity": severity,
+                    "code": diagnostic.code,
+                    "source": "jrust",
+                    "message": diagnostic.message,
+                })
+            })
+            .collect();
+        self.documents.insert(uri.to_string(), document);
+        self.publish(uri, diagnostics)
+    }
+
+    fn publish(&mut self, uri: &str, diagnostics: Vec<Value>) -> Result<()> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }))
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let Some((document, line, column)) = self.position(params) else {
+            return Value::Null;
+        };
+        let Some(token) = document.identifier_at(line, column) else {
+            return Value::Null;
+        };
+        let TokenKind::Identifier(name) = &token.kind else {
+            return Value::Null;
+        };
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+        let contents = match document.definition_of(token) {
+            Some(decl) if decl.kind == DeclKind::Import => self
+                .imported(uri, decl)
+                .and_then(|(_, imported)| imported.exported(&decl.original).map(|target| imported.describe(target)))
+                .unwrap_or_else(|| document.describe(decl)),
+            Some(decl) => document.describe(decl),
+            None => match BUILTINS.iter().find(|(builtin, _)| builtin == name) {
+                Some((_, description)) => description.to_string(),
+                None => return Value::Null,
+            },
+        };
+        let (signature, docs) = contents.split_once("\n\n").unwrap_or((&contents, ""));
+        let mut value = format!("```typescript\n{}\n```", signature);
+        if !docs.is_empty() {
+            value.push_str("\n\n");
+            value.push_str(docs);
+        }
+
+        let length = name.chars().count();
+        json!({
+            "contents": { "kind": "markdown", "value": value },
+            "range": range(&document.text, token.line - 1, token.column - 1, token.column - 1 + length),
+        })
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        let Some((document, line, column)) = self.position(params) else {
+            return Value::Null;
+        };
+        let Some(decl) = document.identifier_at(line, column).and_then(|token| document.definition_of(token)) else {
+            return Value::Null;
+        };
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+        if decl.kind == DeclKind::Import {
+            if let Some((uri, imported)) = self.imported(uri, decl).and_then(|(path, imported)| Some((path_to_uri(&path)?, imported))) {
+                let (line, column) = imported
+                    .exported(&decl.original)
+                    .map_or((1, 1), |target| (target.line, target.column));
+                return location(&uri, &imported.text, line, column, decl.original.chars().count());
+            }
+        }
+        location(uri, &document.text, decl.line, decl.column, decl.name.chars().count())
+    }
+
+    fn completion(&self, params: &Value) -> Value {
+        let mut items: Vec<Value> = KEYWORDS
+            .iter()
+            .map(|keyword| json!({ "label": keyword, "kind": 14 }))
+            .collect();
+        items.extend(BUILTINS.iter().map(|(name, description)| {
+            let detail = description.split("\n\n").next().unwrap_or_default();
+            json!({ "label": name, "kind": 3, "detail": detail })
+        }));
+
+        if let Some((document, _, _)) = self.position(params) {
+            let mut seen = Vec::new();
+            for decl in &document.declarations {
+                if seen.contains(&&decl.name) {
+                    continue;
+                }
+                seen.push(&decl.name);
+                let kind = match decl.kind {
+                    DeclKind::Function => 3,
+                    DeclKind::Constant => 21,
+                    DeclKind::Struct => 22,
+                    DeclKind::Enum => 13,
+                    DeclKind::Import => 9,
+                    DeclKind::Variable | DeclKind::Parameter | DeclKind::LoopVariable => 6,
+                };
+                items.push(json!({ "label": decl.name, "kind": kind, "detail": document.describe(decl) }));
+            }
+        }
+        json!({ "isIncomplete": false, "items": items })
+    }
+
+    fn semantic_tokens(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let Some(document) = self.documents.get(uri) else {
+            return Value::Null;
+        };
+        let Ok(tokens) = highlight(&document.text) else {
+            return Value::Null;
+        };
+
+        let lines: Vec<&str> = document.text.lines().collect();
+        let mut data = Vec::with_capacity(tokens.len() * 5);
+        let (mut previous_line, mut previous_start) = (0, 0);
+        for token in tokens {
+            let line = token.line - 1;
+            let text = lines.get(line).copied().unwrap_or_default();
+            let start = utf16_len(text, token.column - 1);
+            let length = utf16_len(text, token.column - 1 + token.length) - start;
+            let delta_start = if line == previous_line { start - previous_start } else { start };
+            let kind = HighlightKind::ALL.iter().position(|kind| *kind == token.kind).unwrap_or_default();
+            data.extend([line - previous_line, delta_start, length, kind, 0]);
+            (previous_line, previous_start) = (line, start);
+        }
+        json!({ "data": data })
+    }
+
+    fn position(&self, params: &Value) -> Option<(&Document, usize, usize)> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let document = self.documents.get(uri)?;
+        let line = params["position"]["line"].as_u64()? as usize;
+        let character = params["position"]["character"].as_u64()? as usize;
+        let column = char_len(document.text.lines().nth(line).unwrap_or_default(), character);
+        Some((document, line + 1, column + 1))
+    }
+
+    fn imported(&self, uri: &str, decl: &Declaration) -> Option<(PathBuf, Document)> {
+        let path = analysis::resolve_import(&uri_to_path(uri)?, decl.import_path.as_deref()?)?;
+        let document = self.load(&path)?;
+        Some((path, document))
+    }
+
+    fn load(&self, path: &Path) -> Option<Document> {
+        let text = match path_to_uri(path).and_then(|uri| self.documents.get(&uri)) {
+            Some(document) => document.text.clone(),
+            None => std::fs::read_to_string(path).ok()?,
+        };
+        Some(Document::analyze(text))
+    }
+
+    fn respond(&mut self, id: Option<Value>, result: Value) -> Result<()> {
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+
+    fn send(&mut self, message: Value) -> Result<()> {
+        let body = message.to_string();
+        write!(self.output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+        self.output.flush()?;
+        Ok(())
+    }
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            "textDocumentSync": 1,
+            "hoverProvider": true,
+            "definitionProvider": true,
+            "completionProvider": { "triggerCharacters": ["."] },
+            "semanticTokensProvider": {
+                "legend": {
+                    "tokenTypes": HighlightKind::ALL.iter().map(HighlightKind::as_str).collect::<Vec<_>>(),
+                    "tokenModifiers": [],
+                },
+                "full": true,
+            },
+        },
+        "serverInfo": { "name": "jrust", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+fn read_message(input: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = Some(value.trim().parse::<usize>().context("Invalid Content-Length header")?);
+        }
+    }
+    let length = length.context("Missing Content-Length header")?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body).context("Invalid JSON-RPC message")?))
+}
+
+fn range(text: &str, line: usize, start: usize, end: usize) -> Value {
+    let line_text = text.lines().nth(line).unwrap_or_default();
+    json!({
+        "start": { "line": line, "character": utf16_len(line_text, start) },
+        "end": { "line": line, "character": utf16_len(line_text, end) },
+    })
+}
+
+fn location(uri: &str, text: &str, line: usize, column: usize, length: usize) -> Value {
+    json!({ "uri": uri, "range": range(text, line - 1, column - 1, column - 1 + length) })
+}
+
+fn utf16_len(line: &str, chars: usize) -> usize {
+    let counted = line.chars().take(chars).fold((0, 0), |(count, units), c| (count + 1, units + c.len_utf16()));
+    counted.1 + (chars - counted.0)
+}
+
+fn char_len(line: &str, units: usize) -> usize {
+    let mut seen = 0;
+    for (index, c) in line.chars().enumerate() {
+        if seen >= units {
+            return index;
+        }
+        seen += c.len_utf16();
+    }
+    line.chars().count() + units.saturating_sub(seen)
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    Url::parse(uri).ok()?.to_file_path().ok()
+}
+
+fn path_to_uri(path: &Path) -> Option<String> {
+    Url::from_file_path(path).ok().map(String::from)
+}
@@ -1,8 +1,22 @@
+#[macro_use]
+mod output;
 mod project;
+mod diagnostics;
+mod fixes;
+mod module_graph;
+mod source_map;
+mod rust_format;
+mod lsp;
 mod commands;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use anyhow::Result;
+use diagnostics::OutputFormat;
+use output::{ColorChoice, Verbosity};
+use commands::build::{Emit, Profile};
+use commands::init::InitOptions;
+use std::path::PathBuf;
+use commands::watch::WatchCommand;
 
 #[derive(Parser)]
 #[command(name = "jrust")]
@@ -12,43 +26,251 @@ use anyhow::Result;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    
+    /// Only print results and errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    
+    /// Print extra detail, including cargo's own progress
+    #[arg(short, long, global = true)]
+    verbose: bool,
+    
+    /// Print plain text markers instead of emoji
+    #[arg(long, global = true)]
+    no_emoji: bool,
+    
+    /// When to color diagnostics
+    #[arg(long, value_enum, global = true, default_value_t)]
+    color: ColorChoice,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize a new jRust project
     Init {
-        /// Project name
+        /// Project name, or `.` to initialize the current directory
         name: String,
+        
+        /// Directory to create the project in (defaults to ./<name>)
+        #[arg(long)]
+        path: Option<PathBuf>,
+        
+        /// Initialize a non-empty directory, keeping files that already exist
+        #[arg(long)]
+        force: bool,
+        
+        /// Package author (repeat for several authors)
+        #[arg(long = "author")]
+        authors: Vec<String>,
+        
+        /// Rust edition for the generated code
+        #[arg(long)]
+        edition: Option<String>,
+        
+        /// Package description
+        #[arg(long)]
+        description: Option<String>,
     },
     
     /// Build a jRust program
     Build {
         /// Path to .jr file (optional, uses src/index.jr by default)
         path: Option<String>,
+        
+        /// Diagnostic output format
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+        
+        /// Build an optimized executable (default)
+        #[arg(long, conflicts_with = "debug")]
+        release: bool,
+        
+        /// Build an unoptimized executable quickly
+        #[arg(long)]
+        debug: bool,
+        
+        /// Build profile, which also selects `[build.<profile>]` settings
+        #[arg(long, value_enum, conflicts_with_all = ["debug", "release"])]
+        profile: Option<Profile>,
+        
+        /// Target triple to compile for (e.g. x86_64-unknown-linux-musl)
+        #[arg(long)]
+        target: Option<String>,
+        
+        /// Build a Rust library crate exposing exported items instead of an executable
+        #[arg(long)]
+        lib: bool,
+        
+        /// Fold constants and drop unreachable code and unused functions
+        #[arg(long)]
+        opt: bool,
+        
+        /// Stop after a stage and output it instead of compiling (rust, tokens, ast)
+        #[arg(long, value_enum)]
+        emit: Option<Emit>,
     },
     
     /// Run a jRust program
     Run {
         /// Path to .jr file (optional, uses src/index.jr by default)
         path: Option<String>,
+        
+        /// Which binary to run when the project declares `[[bin]]` targets
+        #[arg(long)]
+        bin: Option<String>,
+        
+        /// Build an optimized executable before running
+        #[arg(long, conflicts_with = "debug")]
+        release: bool,
+        
+        /// Build an unoptimized executable before running (default)
+        #[arg(long)]
+        debug: bool,
+        
+        /// Build profile, which also selects `[build.<profile>]` settings
+        #[arg(long, value_enum, conflicts_with_all = ["debug", "release"])]
+        profile: Option<Profile>,
+        
+        /// Fold constants and drop unreachable code and unused functions
+        #[arg(long)]
+        opt: bool,
+        
+        /// Don't print the lines framing the program's output
+        #[arg(long)]
+        no_banner: bool,
+        
+        /// Arguments passed to the program (after `--`)
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    
+    /// Run a command alias from the project's `[scripts]` table
+    RunScript {
+        /// Script name
+        name: String,
+        
+        /// Arguments appended to the script's command (after `--`)
+        #[arg(last = true)]
+        args: Vec<String>,
     },
     
     /// Check syntax and types without generating code
     Check {
         /// Path to .jr file (optional, uses src/index.jr by default)
         path: Option<String>,
+        
+        /// Diagnostic output format
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    
+    /// Run the project's test blocks
+    Test,
+    
+    /// Rerun a command whenever a .jr file changes
+    Watch {
+        /// Command to rerun
+        #[arg(value_enum, default_value_t)]
+        command: WatchCommand,
+        
+        /// Keep previous output instead of clearing the screen
+        #[arg(long)]
+        no_clear: bool,
+    },
+    
+    /// Format .jr files in place
+    Fmt {
+        /// Path to a .jr file or directory (optional, formats src/ by default)
+        path: Option<String>,
+        
+        /// Report unformatted files without changing them
+        #[arg(long)]
+        check: bool,
+    },
+    
+    /// Apply the fixes that diagnostics suggest to the project's .jr files
+    Fix {
+        /// Print the changes as a diff without writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    
+    /// Add a crate dependency to the project
+    Add {
+        /// Crate name, optionally with a version (e.g. serde@1.0)
+        name: String,
+        
+        /// Crate features to enable (comma separated)
+        #[arg(long, value_delimiter = ',')]
+        features: Vec<String>,
+    },
+    
+    /// Remove a crate dependency from the project
+    Remove {
+        /// Crate name
+        name: String,
+    },
+    
+    /// Print the classified tokens of a .jr file for syntax highlighting
+    Highlight {
+        /// Path to .jr file
+        path: String,
+        
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    
+    /// Start the language server over stdio
+    Lsp,
+    
+    /// Explain an error code in detail
+    Explain {
+        /// Error code (e.g. JR0102)
+        code: String,
+    },
+    
+    /// Print a shell completion script
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
     },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let verbosity = match (cli.quiet, cli.verbose) {
+        (true, _) => Verbosity::Quiet,
+        (_, true) => Verbosity::Verbose,
+        _ => Verbosity::Normal,
+    };
+    output::init(output::Settings { verbosity, emoji: !cli.no_emoji, color: cli.color });
     
     match cli.command {
-        Commands::Init { name } => commands::init::handle(name)?,
-        Commands::Build { path } => commands::build::handle(path)?,
-        Commands::Run { path } => commands::run::handle(path)?,
-        Commands::Check { path } => commands::check::handle(path)?,
+        Commands::Init { name, path, force, authors, edition, description } => {
+            commands::init::handle(name, InitOptions { path, force, authors, edition, description })?
+        }
+        Commands::Build { path, format, release, debug, profile, target, lib, opt, emit } => {
+            let profile = profile.unwrap_or_else(|| Profile::from_flags(debug, release, Profile::Release));
+            commands::build::handle(path, format, profile, target, lib, opt, emit)?;
+        }
+        Commands::Run { path, bin, release, debug, profile, opt, no_banner, args } => {
+            let profile = profile.unwrap_or_else(|| Profile::from_flags(debug, release, Profile::Debug));
+            commands::run::handle(path, bin, profile, opt, !no_banner, args)?
+        }
+        Commands::RunScript { name, args } => commands::run_script::handle(name, args)?,
+        Commands::Check { path, format } => commands::check::handle(path, format)?,
+        Commands::Test => commands::test::handle()?,
+        Commands::Watch { command, no_clear } => commands::watch::handle(command, !no_clear)?,
+        Commands::Fmt { path, check } => commands::fmt::handle(path, check)?,
+        Commands::Fix { dry_run } => commands::fix::handle(dry_run)?,
+        Commands::Add { name, features } => commands::add::handle(name, features)?,
+        Commands::Remove { name } => commands::remove::handle(name)?,
+        Commands::Highlight { path, format } => commands::highlight::handle(path, format)?,
+        Commands::Lsp => commands::lsp::handle()?,
+        Commands::Explain { code } => commands::explain::handle(code)?,
+        Commands::Completions { shell } => commands::completions::handle(shell, &mut Cli::command())?,
     }
     
     Ok(())
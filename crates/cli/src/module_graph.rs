@@ -0,0 +1,386 @@
+use jrust_transpiler_core::ast::{FunctionDecl, ImportSource, ImportStmt, Visibility, DEFAULT_IMPORT, RUST_CRATES};
+use jrust_transpiler_core::{Program, Span, Statement, StatementKind};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+#[derive(Clone)]
+enum Symbol {
+    Function(FunctionDecl),
+    Global,
+    Other,
+}
+
+enum Export {
+    Item(Symbol),
+    /// Declared `pub`: other modules can import it, but not export it again
+    Crate(Symbol),
+    Reexport { module: String, name: String },
+}
+
+#[derive(Default)]
+pub struct ModuleImports {
+    pub functions: Vec<(String, FunctionDecl)>,
+    pub globals: Vec<String>,
+}
+
+#[derive(Default)]
+struct ModuleInfo {
+    imports: Vec<(String, ImportStmt)>,
+    external: Vec<ImportStmt>,
+    exports: BTreeMap<String, Export>,
+    default_export: Option<String>,
+    wildcard_reexports: Vec<String>,
+    internal: BTreeSet<String>,
+    declared: Vec<DeclaredExport>,
+}
+
+struct DeclaredExport {
+    name: String,
+    span: Span,
+    removable: bool,
+}
+
+pub struct UnusedExport {
+    pub module: String,
+    pub name: String,
+    pub span: Span,
+    pub removable: bool,
+}
+
+#[derive(Default)]
+pub struct ModuleGraph {
+    modules: BTreeMap<String, ModuleInfo>,
+    crates: Option<BTreeSet<String>>,
+}
+
+impl ModuleGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A graph whose external imports must name a Rust standard crate or one
+    /// of `crates`; without them, external imports aren't checked
+    pub fn with_crates(crates: BTreeSet<String>) -> Self {
+        ModuleGraph { crates: Some(crates), ..Self::default() }
+    }
+
+    pub fn add_module(&mut self, module_name: &str, program: &Program) {
+        let mut info = ModuleInfo::default();
+        for stmt in &program.statements {
+            match &stmt.kind {
+                StatementKind::ImportStmt(import_stmt) => {
+                    if import_stmt.source == ImportSource::External {
+                        info.external.push(import_stmt.clone());
+                    }
+                    if let Some(target) = resolve_import(module_name, import_stmt) {
+                        info.imports.push((target, import_stmt.clone()));
+                    }
+                }
+                StatementKind::ExportStmt(inner) => match &inner.kind {
+                    StatementKind::ImportStmt(import_stmt) => {
+                        if import_stmt.source == ImportSource::External {
+                            info.external.push(import_stmt.clone());
+                        }
+                        let Some(target) = resolve_import(module_name, import_stmt) else {
+                            continue;
+                        };
+                        if import_stmt.is_wildcard {
+                            info.wildcard_reexports.push(target.clone());
+                        }
+                        for item in &import_stmt.imports {
+                            let exported = item.alias.clone().unwrap_or_else(|| item.name.clone());
+                            info.exports.insert(
+                                exported,
+                                Export::Reexport { module: target.clone(), name: item.name.clone() },
+                            );
+                        }
+                        info.imports.push((target, import_stmt.clone()));
+                    }
+                    _ => {
+                        if let Some((name, export)) = exported_item(inner) {
+                            info.declared.push(DeclaredExport { name: name.clone(), span: stmt.span, removable: is_removable(&export) });
+                            info.exports.insert(name, export);
+                        }
+                    }
+                },
+                StatementKind::ExportDefault(inner) => {
+                    if let Some((name, export)) = exported_item(inner) {
+                        info.default_export = Some(name.clone());
+                        info.exports.insert(name, export);
+                    }
+                }
+                StatementKind::VisibilityStmt(visibility, inner) => {
+                    let Some((name, Export::Item(symbol))) = exported_item(inner) else {
+                        continue;
+                    };
+                    match visibility {
+                        Visibility::Crate => {
+                            info.declared.push(DeclaredExport { name: name.clone(), span: stmt.span, removable: true });
+                            info.exports.insert(name, Export::Crate(symbol));
+                        }
+                        Visibility::Internal => {
+                            info.internal.insert(name);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.modules.insert(module_name.to_string(), info);
+    }
+
+    pub fn find_cycle(&self) -> Option<Vec<String>> {
+        let mut finished = HashSet::new();
+        for module_name in self.modules.keys() {
+            let mut stack = Vec::new();
+            if let Some(cycle) = self.visit(module_name, &mut stack, &mut finished) {
+                return Some(cycle);
+            }
+        }
+        None
+    }
+
+    pub fn resolve_imports(&self) -> Result<BTreeMap<String, ModuleImports>, String> {
+        let mut imported = BTreeMap::new();
+        for (module_name, info) in &self.modules {
+            let mut imports = ModuleImports::default();
+            for import_stmt in &info.external {
+                self.check_crate(module_name, import_stmt)?;
+            }
+            for export in info.exports.values() {
+                let Export::Reexport { module, name } = export else {
+                    continue;
+                };
+                if let Some(Export::Crate(_)) = self.known_module(module).and_then(|target| self.lookup_export(&target, name)) {
+                    return Err(format!(
+                        "'{}' is declared `pub`, which keeps it inside the project, so {}.jr can't export it; declare it with `export` instead",
+                        name, module_name
+                    ));
+                }
+            }
+            for (target, import_stmt) in &info.imports {
+                let Some(target_module) = self.known_module(target) else {
+                    return Err(format!(
+                        "Cannot find module '{}' imported in {}.jr",
+                        import_stmt.path, module_name
+                    ));
+                };
+                if import_stmt.is_wildcard {
+                    continue;
+                }
+                for item in &import_stmt.imports {
+                    let name = if item.name == DEFAULT_IMPORT {
+                        self.modules[&target_module].default_export.clone().ok_or_else(|| {
+                            format!(
+                                "Module '{}' has no default export (imported in {}.jr)",
+                                import_stmt.path, module_name
+                            )
+                        })?
+                    } else {
+                        item.name.clone()
+                    };
+                    let Some(Export::Item(symbol) | Export::Crate(symbol)) = self.lookup_export(&target_module, &name) else {
+                        if self.modules[&target_module].internal.contains(&name) {
+                            return Err(format!(
+                                "'{}' is internal to module '{}' and can't be imported (imported in {}.jr)",
+                                name, import_stmt.path, module_name
+                            ));
+                        }
+                        return Err(format!(
+                            "Module '{}' has no exported member '{}' (imported in {}.jr)",
+                            import_stmt.path, name, module_name
+                        ));
+                    };
+                    let local_name = item.alias.clone().unwrap_or(name);
+                    match symbol.clone() {
+                        Symbol::Function(func_decl) => imports.functions.push((local_name, func_decl)),
+                        Symbol::Global => imports.globals.push(local_name),
+                        Symbol::Other => {}
+                    }
+                }
+            }
+            imported.insert(module_name.clone(), imports);
+        }
+        Ok(imported)
+    }
+
+    pub fn unused_exports(&self, entries: &[String]) -> Vec<UnusedExport> {
+        let mut used = BTreeSet::new();
+        for info in self.modules.values() {
+            for (target, import_stmt) in &info.imports {
+                let Some(target) = self.known_module(target) else {
+                    continue;
+                };
+                let target_info = &self.modules[&target];
+                if import_stmt.is_wildcard {
+                    used.extend(target_info.exports.keys().map(|name| (target.clone(), name.clone())));
+                    continue;
+                }
+                for item in &import_stmt.imports {
+                    let name = match item.name.as_str() {
+                        DEFAULT_IMPORT => target_info.default_export.clone().unwrap_or_default(),
+                        name => name.to_string(),
+                    };
+                    used.insert((target.clone(), name));
+                }
+            }
+        }
+
+        let mut unused = Vec::new();
+        for (module_name, info) in self.modules.iter().filter(|(name, _)| !entries.contains(name)) {
+            for export in &info.declared {
+                if !used.contains(&(module_name.clone(), export.name.clone())) {
+                    unused.push(UnusedExport {
+                        module: module_name.clone(),
+                        name: export.name.clone(),
+                        span: export.span,
+                        removable: export.removable,
+                    });
+                }
+            }
+        }
+        unused
+    }
+
+    /// `module_name` and every module it imports, directly or not
+    pub fn reachable(&self, module_name: &str) -> BTreeSet<String> {
+        let mut reached = BTreeSet::new();
+        let mut pending = vec![module_name.to_string()];
+        while let Some(module) = pending.pop() {
+            let Some(info) = self.modules.get(&module) else {
+                continue;
+            };
+            if reached.insert(module) {
+                pending.extend(info.imports.iter().filter_map(|(target, _)| self.known_module(target)));
+            }
+        }
+        reached
+    }
+
+    fn check_crate(&self, module_name: &str, import_stmt: &ImportStmt) -> Result<(), String> {
+        let (Some(crates), Some(name)) = (&self.crates, import_stmt.crate_name()) else {
+            return Ok(());
+        };
+        if RUST_CRATES.contains(&name) || crates.contains(name) {
+            return Ok(());
+        }
+        let local = ImportStmt { path: format!("./{}", import_stmt.path), source: ImportSource::Local, ..import_stmt.clone() };
+        let hint = match resolve_import(module_name, &local).and_then(|target| self.known_module(&target)) {
+            Some(_) => format!("local modules are imported with a relative path: \"{}\"", local.path),
+            None => format!("add it with `jrust add {}` or under [packages] in Cargo.toml", name),
+        };
+        Err(format!("Cannot find crate '{}' imported in {}.jr; {}", name, module_name, hint))
+    }
+
+    /// The item `name` names in `module_name`, following re-exports
+    fn lookup_export(&self, module_name: &str, name: &str) -> Option<&Export> {
+        let info = self.modules.get(module_name)?;
+        match info.exports.get(name) {
+            Some(Export::Reexport { module, name }) => {
+                let target = self.known_module(module)?;
+                self.lookup_export(&target, name)
+            }
+            None => info
+                .wildcard_reexports
+                .iter()
+                .filter_map(|module| self.known_module(module))
+                .find_map(|target| self.lookup_export(&target, name)),
+            export => export,
+        }
+    }
+
+    fn visit(
+        &self,
+        module_name: &str,
+        stack: &mut Vec<String>,
+        finished: &mut HashSet<String>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = stack.iter().position(|name| name == module_name) {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(module_name.to_string());
+            return Some(cycle);
+        }
+        if finished.contains(module_name) {
+            return None;
+        }
+
+        stack.push(module_name.to_string());
+        let targets: BTreeSet<String> = self
+            .modules
+            .get(module_name)
+            .map(|info| info.imports.iter().filter_map(|(t, _)| self.known_module(t)).collect())
+            .unwrap_or_default();
+        for target in targets {
+            if let Some(cycle) = self.visit(&target, stack, finished) {
+                return Some(cycle);
+            }
+        }
+        stack.pop();
+        finished.insert(module_name.to_string());
+        None
+    }
+
+    fn known_module(&self, target: &str) -> Option<String> {
+        let index = format!("{}/index", target);
+        if self.modules.contains_key(target) {
+            Some(target.to_string())
+        } else if self.modules.contains_key(&index) {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+fn exported_item(stmt: &Statement) -> Option<(String, Export)> {
+    match &stmt.kind {
+        StatementKind::FunctionDecl(func_decl) => {
+            Some((func_decl.name.clone(), Export::Item(Symbol::Function(func_decl.clone()))))
+        }
+        StatementKind::StructDecl(struct_decl) => Some((struct_decl.name.clone(), Export::Item(Symbol::Other))),
+        StatementKind::EnumDecl(enum_decl) => Some((enum_decl.name.clone(), Export::Item(Symbol::Other))),
+        StatementKind::VariableDecl(var_decl) if var_decl.is_const => {
+            Some((var_decl.name.clone(), Export::Item(Symbol::Other)))
+        }
+        StatementKind::VariableDecl(var_decl) => Some((var_decl.name.clone(), Export::Item(Symbol::Global))),
+        _ => None,
+    }
+}
+
+fn is_removable(export: &Export) -> bool {
+    !matches!(export, Export::Item(Symbol::Global) | Export::Crate(Symbol::Global))
+}
+
+fn resolve_import(module_name: &str, import_stmt: &ImportStmt) -> Option<String> {
+    if import_stmt.source != ImportSource::Local {
+        return None;
+    }
+
+    let mut parts: Vec<&str> = module_name.split('/').collect();
+    parts.pop();
+
+    for segment in import_stmt.path.split('/') {
+        match segment {
+            "" | "." => {}
+            // Climbing above `src/` keeps the `..`, so the import can't be found
+            ".." if matches!(parts.last(), None | Some(&"..")) => parts.push(".."),
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(segment),
+        }
+    }
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("/"))
+    }
+}
+
+pub fn format_cycle(cycle: &[String]) -> String {
+    cycle
+        .iter()
+        .map(|name| format!("{}.jr", name))
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
@@ -0,0 +1,126 @@
+use clap::ValueEnum;
+use std::borrow::Cow;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+/// Prints a progress line, which `--quiet` hides
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if $crate::output::shows_status() {
+            println!("{}", $crate::output::plain(&format!($($arg)*)));
+        }
+    };
+}
+
+/// Prints a detail line, which only `--verbose` shows
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if $crate::output::is_verbose() {
+            println!("{}", $crate::output::plain(&format!($($arg)*)));
+        }
+    };
+}
+
+/// Prints a result line, which is shown even with `--quiet`
+macro_rules! out {
+    ($($arg:tt)*) => {
+        println!("{}", $crate::output::plain(&format!($($arg)*)))
+    };
+}
+
+/// Prints a result line to stderr, which is shown even with `--quiet`
+macro_rules! eout {
+    ($($arg:tt)*) => {
+        eprintln!("{}", $crate::output::plain(&format!($($arg)*)))
+    };
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color diagnostics when stderr is a terminal and `NO_COLOR` isn't set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet,
+    #[default]
+    Normal,
+    Verbose,
+}
+
+/// The global output flags, set once by `main`
+#[derive(Clone, Copy, Default)]
+pub struct Settings {
+    pub verbosity: Verbosity,
+    pub emoji: bool,
+    pub color: ColorChoice,
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+pub fn init(settings: Settings) {
+    let _ = SETTINGS.set(settings);
+}
+
+fn settings() -> Settings {
+    SETTINGS.get().copied().unwrap_or(Settings { emoji: true, ..Settings::default() })
+}
+
+pub fn shows_status() -> bool {
+    settings().verbosity >= Verbosity::Normal
+}
+
+pub fn is_verbose() -> bool {
+    settings().verbosity == Verbosity::Verbose
+}
+
+pub fn use_color() -> bool {
+    match settings().color {
+        ColorChoice::Auto => std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    }
+}
+
+/// The flags to pass on to another `jrust` process so it prints the same way
+pub fn global_args() -> Vec<&'static str> {
+    let settings = settings();
+    let mut args = Vec::new();
+    match settings.verbosity {
+        Verbosity::Quiet => args.push("--quiet"),
+        Verbosity::Normal => {}
+        Verbosity::Verbose => args.push("--verbose"),
+    }
+    if !settings.emoji {
+        args.push("--no-emoji");
+    }
+    match settings.color {
+        ColorChoice::Auto => {}
+        ColorChoice::Always => args.push("--color=always"),
+        ColorChoice::Never => args.push("--color=never"),
+    }
+    args
+}
+
+/// Marks that keep their meaning as text under `--no-emoji`
+const MARKS: &[(&str, &str)] = &[("✅", "[ok]"), ("❌", "[error]"), ("⏭️", "[skipped]")];
+
+/// `line` as printed: with `--no-emoji`, a leading emoji becomes one of the
+/// [`MARKS`] or is dropped, and rules drawn with `─` become dashes
+pub fn plain(line: &str) -> Cow<'_, str> {
+    if settings().emoji || line.is_ascii() {
+        return Cow::Borrowed(line);
+    }
+    let line = line.replace('─', "-");
+    let text = line.trim_start();
+    let indent = &line[..line.len() - text.len()];
+    if let Some((emoji, mark)) = MARKS.iter().find(|(emoji, _)| text.starts_with(emoji)) {
+        return Cow::Owned(format!("{}{}{}", indent, mark, &text[emoji.len()..]));
+    }
+    let text = text.trim_start_matches(|c: char| (!c.is_ascii() && !c.is_alphanumeric()) || c == ' ');
+    Cow::Owned(format!("{}{}", indent, text))
+}
@@ -1,11 +1,74 @@
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 use std::fs;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectConfig {
     pub package: PackageConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<BuildConfig>,
+    #[serde(rename = "bin", default, skip_serializing_if = "Vec::is_empty")]
+    pub bins: Vec<BinConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub packages: BTreeMap<String, PackageDependency>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub scripts: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<HooksConfig>,
+    #[serde(default, skip_serializing)]
+    pub dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default, skip_serializing)]
+    pub lints: BTreeMap<String, toml::Value>,
+    #[serde(default, skip_serializing)]
+    pub profile: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(rename = "crate-type", default, skip_serializing_if = "Option::is_none")]
+    pub crate_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opt: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
+    #[serde(rename = "out-dir", default, skip_serializing_if = "Option::is_none")]
+    pub out_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub naming: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug: Option<Box<BuildConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<Box<BuildConfig>>,
+}
+
+/// A `[[bin]]` table: one more executable, built from its own entry file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinConfig {
+    pub name: String,
+    pub path: String,
+}
+
+/// `[hooks]`: shell commands `jrust build` runs around a build
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Run before any `.jr` file is compiled
+    #[serde(rename = "pre-build", default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_build: Vec<String>,
+    /// Run after cargo has built the generated crate
+    #[serde(rename = "post-build", default, skip_serializing_if = "Vec::is_empty")]
+    pub post_build: Vec<String>,
+}
+
+/// A `[packages]` entry: another jRust project, importable by the entry's name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageDependency {
+    pub path: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,7 +81,10 @@ pub struct PackageConfig {
 }
 
 impl ProjectConfig {
-    pub fn new(name: String, authors: Vec<String>) -> Self {
+    pub fn new(name: String, mut authors: Vec<String>) -> Self {
+        if authors.is_empty() {
+            authors.push("Your Name".to_string());
+        }
         Self {
             package: PackageConfig {
                 name,
@@ -27,11 +93,29 @@ impl ProjectConfig {
                 authors,
                 description: Some("A jRust project".to_string()),
             },
+            build: None,
+            bins: Vec::new(),
+            packages: BTreeMap::new(),
+            scripts: BTreeMap::new(),
+            hooks: None,
+            dependencies: BTreeMap::new(),
+            lints: BTreeMap::new(),
+            profile: BTreeMap::new(),
         }
     }
 
     pub fn to_toml(&self) -> Result<String> {
-        toml::to_string_pretty(self).context("Failed to serialize project config to TOML")
+        let mut output = toml::to_string_pretty(self)
+            .context("Failed to serialize project config to TOML")?;
+        if !self.dependencies.is_empty() {
+            output.push_str("\n[dependencies]\n");
+            for (name, value) in &self.dependencies {
+                output.push_str(&format!("{} = {}\n", name, inline_toml(value)));
+            }
+        }
+        output.push_str(&tables_toml("lints", &self.lints));
+        output.push_str(&tables_toml("profile", &self.profile));
+        Ok(output)
     }
 
     pub fn from_path(path: &Path) -> Result<Self> {
@@ -42,6 +126,69 @@ impl ProjectConfig {
             .context("Failed to parse Cargo.toml")
     }
 
+    pub fn build_setting<T>(&self, profile: &str, setting: impl Fn(&BuildConfig) -> Option<T>) -> Option<T> {
+        let build = self.build.as_ref()?;
+        let overrides = match profile {
+            "debug" => build.debug.as_deref(),
+            "release" => build.release.as_deref(),
+            _ => None,
+        };
+        overrides.and_then(&setting).or_else(|| setting(build))
+    }
+
+    /// The entry file relative to the project root, `src/index.jr` unless
+    /// `[build] entry` says otherwise
+    pub fn entry(&self) -> &str {
+        self.build.as_ref().and_then(|build| build.entry.as_deref()).unwrap_or("src/index.jr")
+    }
+
+    /// The entry file's module name, its path under `src/` without the extension
+    pub fn entry_module(&self) -> Result<String> {
+        entry_module(self.entry())
+    }
+
+    /// Every executable the project builds as `(name, entry module)`: the
+    /// `[build]` entry first, then each `[[bin]]`. With `[[bin]]` tables the
+    /// `[build]` entry is optional and skipped when its file doesn't exist or
+    /// a `[[bin]]` builds it.
+    pub fn binaries(&self, root: &Path) -> Result<Vec<(String, String)>> {
+        let mut binaries: Vec<(String, String)> = Vec::new();
+        for bin in &self.bins {
+            let entry = entry_module(&bin.path)?;
+            if binaries.iter().any(|(name, _)| *name == bin.name) {
+                anyhow::bail!("Binary '{}' is declared more than once in Cargo.toml", bin.name);
+            }
+            if let Some((other, _)) = binaries.iter().find(|(_, other_entry)| *other_entry == entry) {
+                anyhow::bail!("Binaries '{}' and '{}' both build {}", other, bin.name, bin.path);
+            }
+            binaries.push((bin.name.clone(), entry));
+        }
+        let entry = self.entry_module()?;
+        if self.bins.is_empty() || (root.join(self.entry()).exists() && binaries.iter().all(|(_, other)| *other != entry)) {
+            if binaries.iter().any(|(name, _)| name == self.binary()) {
+                anyhow::bail!("Binary '{}' is declared more than once in Cargo.toml", self.binary());
+            }
+            binaries.insert(0, (self.binary().to_string(), entry));
+        }
+        Ok(binaries)
+    }
+
+    /// Where the generated Rust crate is written, `generated/` by default
+    pub fn out_dir(&self, root: &Path) -> PathBuf {
+        root.join(self.build.as_ref().and_then(|build| build.out_dir.as_deref()).unwrap_or("generated"))
+    }
+
+    /// The executable's name, `jrust_app` by default
+    pub fn binary(&self) -> &str {
+        self.build.as_ref().and_then(|build| build.binary.as_deref()).unwrap_or("jrust_app")
+    }
+
+    /// The crates `[dependencies]` and `[packages]` make importable, by the
+    /// name Rust code uses for them
+    pub fn crate_names(&self) -> BTreeSet<String> {
+        self.dependencies.keys().chain(self.packages.keys()).map(|name| name.replace('-', "_")).collect()
+    }
+
     pub fn save(&self, path: &Path) -> Result<()> {
         let config_path = path.join("Cargo.toml");
         let content = self.to_toml()?;
@@ -51,6 +198,47 @@ impl ProjectConfig {
     }
 }
 
+fn entry_module(entry: &str) -> Result<String> {
+    let path = Path::new(entry);
+    match path.strip_prefix("src") {
+        Ok(relative) if path.extension().and_then(|ext| ext.to_str()) == Some("jr") => {
+            Ok(relative.with_extension("").to_string_lossy().replace('\\', "/"))
+        }
+        _ => anyhow::bail!("Entry '{}' in Cargo.toml must be a .jr file under src/", entry),
+    }
+}
+
+pub fn inline_toml(value: &toml::Value) -> String {
+    match value {
+        toml::Value::Table(table) => {
+            let entries: Vec<String> = table
+                .iter()
+                .map(|(key, value)| format!("{} = {}", key, inline_toml(value)))
+                .collect();
+            format!("{{ {} }}", entries.join(", "))
+        }
+        toml::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(inline_toml).collect();
+            format!("[{}]", items.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+pub fn tables_toml(section: &str, tables: &BTreeMap<String, toml::Value>) -> String {
+    let mut output = String::new();
+    for (name, entries) in tables {
+        let Some(entries) = entries.as_table() else {
+            continue;
+        };
+        output.push_str(&format!("\n[{}.{}]\n", section, name));
+        for (key, value) in entries {
+            output.push_str(&format!("{} = {}\n", key, inline_toml(value)));
+        }
+    }
+    output
+}
+
 pub fn project_root() -> Result<PathBuf> {
     let current_dir = std::env::current_dir()
         .context("Failed to get current directory")?;
@@ -69,7 +257,12 @@ pub fn read_source_file(path: &Path) -> Result<String> {
         .context(format!("Failed to read source file: {:?}", path))
 }
 
+/// Writes `content` to `path`, leaving the file untouched when it already
+/// holds exactly that, so Cargo doesn't rebuild unchanged generated code
 pub fn write_file(path: &Path, content: &str) -> Result<()> {
+    if fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(());
+    }
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
             .context(format!("Failed to create directory: {:?}", parent))?;
@@ -79,7 +272,7 @@ pub fn write_file(path: &Path, content: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn create_project_structure(project_name: &str, project_path: &Path) -> Result<()> {
+pub fn create_project_structure(config: &ProjectConfig, project_path: &Path) -> Result<()> {
     fs::create_dir_all(project_path)
         .context(format!("Failed to create project directory: {:?}", project_path))?;
     
@@ -87,50 +280,53 @@ pub fn create_project_structure(project_name: &str, project_path: &Path) -> Resu
     fs::create_dir_all(&src_dir)
         .context("Failed to create src directory")?;
     
+    let has_entry = src_dir.join("index.jr").exists();
     let utils_dir = src_dir.join("utils");
-    fs::create_dir_all(&utils_dir)
-        .context("Failed to create utils directory")?;
+    if !has_entry {
+        fs::create_dir_all(&utils_dir)
+            .context("Failed to create utils directory")?;
+    }
     
     let generated_dir = project_path.join("generated");
     fs::create_dir_all(&generated_dir)
         .context("Failed to create generated directory")?;
     
-    let config = ProjectConfig::new(
-        project_name.to_string(),
-        vec!["Your Name".to_string()],
-    );
-    config.save(project_path)?;
+    if !project_path.join("Cargo.toml").exists() {
+        config.save(project_path)?;
+    }
     
     // Create utils/random.jr
-    let utils_random = r#"export function randomInRange(min: number, max: number): number {
-    return min + 7;
-}
+    let utils_random = r#"import {randomInt, uuid} from "std/random";
 
-export function generateUniqueId(): number {
-    return 42;
+export function randomInRange(min: number, max: number): number {
+    return randomInt(min, max);
 }
 
-export const SEED_VALUE: number = 123;
+export function generateUniqueId(): string {
+    return uuid();
+}
 "#;
-    write_file(&utils_dir.join("random.jr"), utils_random)?;
+    if !has_entry {
+        write_file(&utils_dir.join("random.jr"), utils_random)?;
+    }
     
     // Create utils/index.jr (module entry point)
-    let utils_index = r#"import {randomInRange, generateUniqueId, SEED_VALUE} from "./random";
+    let utils_index = r#"import {randomInRange, generateUniqueId} from "./random";
 
 export function getRandom(min: number, max: number): number {
     return randomInRange(min, max);
 }
 
-export function createId(): number {
+export function createId(): string {
     return generateUniqueId();
 }
-
-export const RANDOM_SEED: number = SEED_VALUE;
 "#;
-    write_file(&utils_dir.join("index.jr"), utils_index)?;
+    if !has_entry {
+        write_file(&utils_dir.join("index.jr"), utils_index)?;
+    }
     
     // Create main index.jr
-    let index_jr = r#"import {createId, getRandom, RANDOM_SEED} from "./utils";
+    let index_jr = r#"import {createId, getRandom} from "./utils";
 
 const VERSION: string = "1.0.0";
 const MAX_COUNT: number = 5;
@@ -162,17 +358,19 @@ function main(): void {
     print("");
     
     let id = createId();
-    print("Generated ID: 42");
+    print("Generated ID: " + id);
     print("");
     
     let randomNum = getRandom(1, 100);
-    print("Random number: 8");
+    print("Random number: " + randomNum);
     print("");
     
     print("✅ Demo complete!");
 }
 "#;
-    write_file(&src_dir.join("index.jr"), index_jr)?;
+    if !has_entry {
+        write_file(&src_dir.join("index.jr"), index_jr)?;
+    }
     
     let gitignore = r#"/target/
 /generated/
@@ -185,18 +383,48 @@ function main(): void {
 *.swo
 *~
 "#;
-    write_file(&project_path.join(".gitignore"), gitignore)?;
+    let gitignore_path = project_path.join(".gitignore");
+    if !gitignore_path.exists() {
+        write_file(&gitignore_path, gitignore)?;
+    }
     
     Ok(())
 }
 
-pub fn find_entry_point(src_dir: &Path) -> Result<PathBuf> {
-    let index_jr = src_dir.join("index.jr");
-    if index_jr.exists() {
-        return Ok(index_jr);
+pub fn find_all_jr_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    
+    if dir.is_dir() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            
+            if path.is_dir() {
+                files.extend(find_all_jr_files(&path)?);
+            } else if path.extension().and_then(|s| s.to_str()) == Some("jr") {
+                files.push(path);
+            }
+        }
+    }
+    
+    files.sort();
+    Ok(files)
+}
+
+pub fn find_entry_point(root: &Path) -> Result<PathBuf> {
+    let config = ProjectConfig::from_path(root)?;
+    let entry = match config.bins.first() {
+        Some(bin) if !root.join(config.entry()).exists() => &bin.path,
+        _ => config.entry(),
+    };
+    entry_module(entry)?;
+    let path = root.join(entry);
+    if path.exists() {
+        return Ok(path);
     }
     
     Err(anyhow::anyhow!(
-        "No entry point found. Create src/index.jr to get started."
+        "No entry point found. Create {} to get started.",
+        entry
     ))
 }
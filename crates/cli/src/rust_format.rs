@@ -0,0 +1,144 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const ANCHOR_WINDOW: usize = 64;
+
+pub fn format(code: &str, edition: &str, lines: &[(usize, usize)]) -> (String, Vec<(usize, usize)>) {
+    let formatted = rustfmt(code, edition).unwrap_or_else(|| tidy(code));
+    let remapped = remap_lines(code, &formatted);
+    let lines = lines
+        .iter()
+        .map(|(rust_line, jr_line)| {
+            let line = remapped.get(rust_line.saturating_sub(1)).copied().unwrap_or(*rust_line);
+            (line, *jr_line)
+        })
+        .collect();
+    (formatted, lines)
+}
+
+fn rustfmt(code: &str, edition: &str) -> Option<String> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", edition, "--emit", "stdout", "--quiet"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    child.stdin.take()?.write_all(code.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok().filter(|formatted| !formatted.trim().is_empty())
+}
+
+fn tidy(code: &str) -> String {
+    let mut output = String::new();
+    let mut open_lines: Vec<usize> = Vec::new();
+    let mut in_string = false;
+    let mut blank = true;
+
+    for line in code.lines() {
+        if in_string {
+            output.push_str(line);
+            output.push('\n');
+            in_string = scan(line, &mut open_lines, true);
+            blank = false;
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            if !blank {
+                output.push('\n');
+            }
+            blank = true;
+            continue;
+        }
+
+        let closers = trimmed.chars().take_while(|c| matches!(c, '}' | ')' | ']')).count();
+        for _ in 0..closers {
+            close(&mut open_lines);
+        }
+        output.push_str(&"    ".repeat(open_lines.len()));
+        output.push_str(trimmed);
+        output.push('\n');
+        in_string = scan(&trimmed[closers..], &mut open_lines, false);
+        blank = false;
+    }
+
+    while output.ends_with("\n\n") {
+        output.pop();
+    }
+    output
+}
+
+fn close(open_lines: &mut Vec<usize>) {
+    if let Some(open) = open_lines.last_mut() {
+        *open -= 1;
+        if *open == 0 {
+            open_lines.pop();
+        }
+    }
+}
+
+fn scan(line: &str, open_lines: &mut Vec<usize>, mut in_string: bool) -> bool {
+    let chars: Vec<char> = line.chars().collect();
+    let mut opened = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            match c {
+                '\\' => i += 1,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else {
+            match c {
+                '"' => in_string = true,
+                '/' if chars.get(i + 1) == Some(&'/') => break,
+                '\'' if chars.get(i + 1) == Some(&'\\') => {
+                    while i + 1 < chars.len() && chars[i + 1] != '\'' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                '\'' if chars.get(i + 2) == Some(&'\'') => i += 2,
+                '{' | '(' | '[' => opened += 1,
+                '}' | ')' | ']' if opened > 0 => opened -= 1,
+                '}' | ')' | ']' => close(open_lines),
+                _ => {}
+            }
+        }
+        i += 1;
+    }
+    if opened > 0 {
+        open_lines.push(opened);
+    }
+    in_string
+}
+
+fn remap_lines(before: &str, after: &str) -> Vec<usize> {
+    let normalize = |line: &str| line.split_whitespace().collect::<String>();
+    let after: Vec<String> = after.lines().map(normalize).collect();
+
+    let mut next = 0;
+    before
+        .lines()
+        .map(|line| {
+            let key = normalize(line);
+            let end = (next + ANCHOR_WINDOW).min(after.len());
+            let found = (key.chars().count() > 2)
+                .then(|| (next..end).find(|&index| after[index] == key))
+                .flatten();
+            match found {
+                Some(index) => {
+                    next = index + 1;
+                    index + 1
+                }
+                None => next + 1,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,60 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct SourceMap {
+    files: BTreeMap<String, FileMap>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileMap {
+    source: String,
+    lines: Vec<(usize, usize)>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, rust_file: &str, source: &str, lines: &[(usize, usize)], offset: usize) {
+        let lines = lines
+            .iter()
+            .map(|(rust_line, jr_line)| (rust_line + offset, *jr_line))
+            .collect();
+        self.files.insert(
+            rust_file.to_string(),
+            FileMap { source: source.to_string(), lines },
+        );
+    }
+
+    pub fn lookup(&self, rust_file: &str, rust_line: usize) -> Option<(&str, usize)> {
+        let file = self.files.get(rust_file)?;
+        file.lines
+            .iter()
+            .rev()
+            .find(|(mapped, _)| *mapped <= rust_line)
+            .filter(|(_, jr_line)| *jr_line > 0)
+            .map(|(_, jr_line)| (file.source.as_str(), *jr_line))
+    }
+
+    /// Adds a package's map, with its Rust files under `rust_prefix` and its
+    /// sources under `source_prefix`
+    pub fn extend(&mut self, rust_prefix: &str, source_prefix: &str, other: SourceMap) {
+        for (rust_file, file) in other.files {
+            let source = format!("{}{}", source_prefix, file.source);
+            self.files.insert(format!("{}{}", rust_prefix, rust_file), FileMap { source, lines: file.lines });
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize source map")?;
+        crate::project::write_file(path, &content)
+    }
+}
+
+pub fn rust_file_for_module(module_name: &str) -> String {
+    format!("{}.rs", module_name)
+}
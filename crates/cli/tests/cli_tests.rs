@@ -81,6 +81,61 @@ fn test_init_creates_project_structure() {
     );
 }
 
+#[test]
+fn test_init_in_current_directory_with_options() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let project_path = temp_dir.path().join("in-place");
+    fs::create_dir_all(project_path.join("src")).expect("Failed to create directory");
+    fs::write(project_path.join("src/index.jr"), "print(\"mine\");\n").expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["init", "."])
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["init", ".", "--force", "--author", "Ada", "--edition", "2018", "--description", "Scripts"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created new jRust project: in-place"));
+
+    let config = fs::read_to_string(project_path.join("Cargo.toml")).expect("Failed to read Cargo.toml");
+    assert!(config.contains("name = 'in-place'"));
+    assert!(config.contains("edition = '2018'"));
+    assert!(config.contains("'Ada'"));
+    assert!(config.contains("description = 'Scripts'"));
+    let index = fs::read_to_string(project_path.join("src/index.jr")).expect("Failed to read index.jr");
+    assert_eq!(index, "print(\"mine\");\n");
+    assert!(!project_path.join("src/utils").exists());
+}
+
+#[test]
+fn test_init_with_path_and_invalid_edition() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["init", "app", "--path", "nested/app-dir"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cd nested/app-dir"));
+    assert!(temp_dir.path().join("nested/app-dir/src/index.jr").exists());
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["init", "other", "--edition", "2030"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown edition '2030'"));
+}
+
 #[test]
 fn test_init_creates_valid_cargo_toml() {
     let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -146,7 +201,7 @@ fn test_init_creates_index_jr_template() {
     // Check main index.jr content
     let content = fs::read_to_string(&index_jr_path).expect("Failed to read index.jr");
     assert!(
-        content.contains("import {createId, getRandom, RANDOM_SEED} from \"./utils\""),
+        content.contains("import {createId, getRandom} from \"./utils\""),
         "Template should import from utils"
     );
     assert!(
@@ -177,7 +232,7 @@ fn test_init_creates_index_jr_template() {
     // Check utils/index.jr content
     let utils_content = fs::read_to_string(&utils_index_path).expect("Failed to read utils/index.jr");
     assert!(
-        utils_content.contains("import {randomInRange, generateUniqueId, SEED_VALUE} from \"./random\""),
+        utils_content.contains("import {randomInRange, generateUniqueId} from \"./random\""),
         "utils/index.jr should import from random"
     );
     assert!(
@@ -196,8 +251,8 @@ fn test_init_creates_index_jr_template() {
         "utils/random.jr should have randomInRange"
     );
     assert!(
-        random_content.contains("export const SEED_VALUE"),
-        "utils/random.jr should have SEED_VALUE"
+        random_content.contains("import {randomInt, uuid} from \"std/random\""),
+        "utils/random.jr should use the std random module"
     );
 }
 
@@ -216,6 +271,136 @@ fn test_check_validates_syntax() {
         .stdout(predicate::str::contains("Syntax parsing passed"));
 }
 
+#[test]
+fn test_check_resolves_crate_imports() {
+    let temp_dir = create_test_project("check-crates");
+    let project_path = temp_dir.path().join("check-crates");
+
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str("\n[dependencies]\nserde-json = \"1\"\n");
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        "import {Value} from \"serde_json\";\nimport {HashMap} from \"std::collections\";\nimport {createId} from \"utils\";\nprint(createId());\n",
+    )
+    .expect("Failed to write code");
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("check")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Cannot find crate 'utils' imported in index.jr; local modules are imported with a relative path: \"./utils\"",
+        ));
+
+    fs::write(project_path.join("src/index.jr"), "import {Client} from \"reqwest\";\n").expect("Failed to write code");
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("check")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot find crate 'reqwest' imported in index.jr; add it with `jrust add reqwest`"));
+}
+
+#[test]
+fn test_check_walks_all_files() {
+    let temp_dir = create_test_project("check-all");
+    let project_path = temp_dir.path().join("check-all");
+    fs::write(project_path.join("src/utils/broken.jr"), "export function broken(): number { return 1 +; }\n")
+        .expect("Failed to write broken.jr");
+    fs::write(project_path.join("src/utils/typo.jr"), "let count: numbr = 2;\n")
+        .expect("Failed to write typo.jr");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("check")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Checking 5 jRust file(s)"))
+        .stdout(predicate::str::contains("✅ src/index.jr"))
+        .stdout(predicate::str::contains("❌ src/utils/broken.jr"))
+        .stdout(predicate::str::contains("❌ src/utils/typo.jr"))
+        .stderr(predicate::str::contains("Unexpected token in expression"))
+        .stderr(predicate::str::contains("Cannot find type 'numbr'"))
+        .stderr(predicate::str::contains("2 of 5 file(s) have errors"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["check", "src/index.jr"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Checking 1 jRust file(s)"));
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        "import {getRandom} from \"./utils\";\nlet n = getRandom(1);\n",
+    )
+    .expect("Failed to write index.jr");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["check", "src/index.jr"])
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("getRandom"));
+}
+
+#[test]
+fn test_check_json_format() {
+    let temp_dir = create_test_project("json-check");
+    let project_path = temp_dir.path().join("json-check");
+    fs::write(project_path.join("src/index.jr"), "let x: number = 5;\nlet y = x +;\n")
+        .expect("Failed to write index.jr");
+
+    let output = Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["check", "--format", "json"])
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+
+    let report: serde_json::Value = serde_json::from_slice(&output).expect("stdout should be JSON");
+    assert_eq!(report["success"], false);
+    let diagnostic = &report["diagnostics"][0];
+    assert_eq!(diagnostic["severity"], "error");
+    assert_eq!(diagnostic["code"], "JR0101");
+    assert_eq!(diagnostic["span"]["line"], 2);
+    assert_eq!(diagnostic["span"]["column"], 12);
+    assert!(diagnostic["file"].as_str().unwrap().ends_with("index.jr"));
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        "function area(w: number): number {\n    return w;\n}\nlet a = area(\"wide\");\n",
+    )
+    .expect("Failed to write index.jr");
+    let output = Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["check", "--format", "json"])
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+
+    let report: serde_json::Value = serde_json::from_slice(&output).expect("stdout should be JSON");
+    let diagnostic = &report["diagnostics"][0];
+    assert!(diagnostic["message"].as_str().unwrap().contains("Argument 1 of 'area' expects number"));
+    assert_eq!(diagnostic["span"]["line"], 4);
+    assert_eq!(diagnostic["span"]["column"], 9);
+    assert_eq!(diagnostic["span"]["end_column"], 21);
+}
+
 #[test]
 fn test_check_fails_on_invalid_syntax() {
     let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -253,10 +438,16 @@ fn test_build_generates_executable() {
         .current_dir(&project_path)
         .assert()
         .success()
+        .stdout(predicate::str::contains("📝 Compiling: index"))
+        .stdout(predicate::str::contains("\u{FFFD}").not())
         .stdout(predicate::str::contains("All files compiled successfully"))
         .stdout(predicate::str::contains("Build completed successfully"));
 
-    let executable_path = project_path.join("generated/target/release/jrust_app.exe");
+    let executable_path = if cfg!(windows) {
+        project_path.join("generated/target/release/jrust_app.exe")
+    } else {
+        project_path.join("generated/target/release/jrust_app")
+    };
     assert!(
         executable_path.exists(),
         "Executable should be generated at {}",
@@ -264,6 +455,27 @@ fn test_build_generates_executable() {
     );
 }
 
+#[test]
+fn test_build_keeps_unchanged_generated_files() {
+    let temp_dir = create_test_project("build-cache-test");
+    let project_path = temp_dir.path().join("build-cache-test");
+    let main_rs = project_path.join("generated/main.rs");
+
+    let build = || {
+        Command::cargo_bin("jrust")
+            .expect("Failed to find jrust binary")
+            .args(["build", "--debug"])
+            .current_dir(&project_path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("📦 Executable:"));
+    };
+    build();
+    let modified = fs::metadata(&main_rs).and_then(|meta| meta.modified()).expect("Failed to read main.rs");
+    build();
+    assert_eq!(fs::metadata(&main_rs).and_then(|meta| meta.modified()).expect("Failed to read main.rs"), modified);
+}
+
 #[test]
 fn test_build_custom_file() {
     let temp_dir = create_test_project("custom-file-test");
@@ -291,182 +503,507 @@ fn test_build_custom_file() {
 }
 
 #[test]
-fn test_run_executes_program() {
-    let temp_dir = create_test_project("run-test");
-    let project_path = temp_dir.path().join("run-test");
+fn test_run_nested_modules() {
+    let temp_dir = create_test_project("nested-modules-test");
+    let project_path = temp_dir.path().join("nested-modules-test");
+
+    let vectors_dir = project_path.join("src/utils/math/vectors");
+    fs::create_dir_all(&vectors_dir).expect("Failed to create module directories");
+    fs::write(
+        project_path.join("src/utils/math/half.jr"),
+        "export function half(value: number): number {\n    return value / 2;\n}\n",
+    )
+    .expect("Failed to write half.jr");
+    fs::write(
+        vectors_dir.join("scale.jr"),
+        "export function scale(value: number, factor: number): number {\n    return value * factor;\n}\n",
+    )
+    .expect("Failed to write scale.jr");
+    fs::write(
+        vectors_dir.join("length.jr"),
+        "import {scale} from \"./scale\";\nexport function length(x: number): number {\n    return scale(x, 2);\n}\n",
+    )
+    .expect("Failed to write length.jr");
+    fs::write(
+        vectors_dir.join("index.jr"),
+        "import {length} from \"./length\";\nexport function doubled(x: number): number {\n    return length(x);\n}\n",
+    )
+    .expect("Failed to write vectors/index.jr");
+    fs::write(
+        project_path.join("src/index.jr"),
+        "import {doubled} from \"./utils/math/vectors\";\nimport {half} from \"./utils/math/half\";\nprint(doubled(4) + half(6));\n",
+    )
+    .expect("Failed to write code");
 
     Command::cargo_bin("jrust")
         .expect("Failed to find jrust binary")
-        .arg("run")
+        .args(["-q", "run", "--no-banner"])
         .current_dir(&project_path)
         .assert()
         .success()
-        .stdout(predicate::str::contains("jRust Demo"))
-        .stdout(predicate::str::contains("Program completed successfully"));
+        .stdout("11\n");
+
+    let utils_mod = fs::read_to_string(project_path.join("generated/utils/mod.rs")).expect("Failed to read mod.rs");
+    assert!(utils_mod.contains("pub mod math;"));
+    let math_mod = fs::read_to_string(project_path.join("generated/utils/math/mod.rs")).expect("Failed to read mod.rs");
+    assert!(math_mod.contains("pub mod half;\npub mod vectors;"));
+    let vectors_mod =
+        fs::read_to_string(project_path.join("generated/utils/math/vectors/mod.rs")).expect("Failed to read mod.rs");
+    assert!(vectors_mod.contains("pub mod index;\npub use index::*;\npub mod length;\npub mod scale;"));
 }
 
 #[test]
-fn test_run_custom_file() {
-    let temp_dir = create_test_project("run-custom-test");
-    let project_path = temp_dir.path().join("run-custom-test");
+fn test_run_parent_relative_imports() {
+    let temp_dir = create_test_project("parent-imports-test");
+    let project_path = temp_dir.path().join("parent-imports-test");
 
-    let custom_file = project_path.join("src/greet.jr");
+    fs::create_dir_all(project_path.join("src/shared")).expect("Failed to create shared/");
+    fs::create_dir_all(project_path.join("src/utils/math")).expect("Failed to create utils/math/");
     fs::write(
-        &custom_file,
-        r#"export function sayHello(): void {
-    print("Greetings from jRust!");
-}
-"#,
+        project_path.join("src/shared/helpers.jr"),
+        "export function shout(text: string): string {\n    return text + \"!\";\n}\n",
     )
-    .expect("Failed to write custom file");
+    .expect("Failed to write helpers.jr");
+    fs::write(
+        project_path.join("src/utils/math/format.jr"),
+        "import {shout} from \"../../shared/helpers\";\nimport {createId} from \"../index\";\nexport function label(): string {\n    return shout(\"total\") + \" \" + createId();\n}\n",
+    )
+    .expect("Failed to write format.jr");
+    fs::write(
+        project_path.join("src/index.jr"),
+        "import {label} from \"./utils/math/format\";\nprint(label());\n",
+    )
+    .expect("Failed to write code");
 
-    // Run now compiles all .jr files and runs the generated executable
     Command::cargo_bin("jrust")
         .expect("Failed to find jrust binary")
-        .arg("run")
+        .args(["-q", "run", "--no-banner"])
         .current_dir(&project_path)
         .assert()
         .success()
-        .stdout(predicate::str::contains("jRust Demo"))
-        .stdout(predicate::str::contains("Program completed successfully"));
-}
-
-#[test]
-fn test_check_without_project_fails() {
-    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        .stdout(predicate::str::starts_with("total!"));
 
+    fs::write(project_path.join("src/index.jr"), "import {shout} from \"../shared/helpers\";\nprint(shout(\"hi\"));\n")
+        .expect("Failed to write code");
     Command::cargo_bin("jrust")
         .expect("Failed to find jrust binary")
         .arg("check")
-        .current_dir(temp_dir.path())
+        .current_dir(&project_path)
         .assert()
         .failure()
-        .stderr(predicate::str::contains("jRust project"));
+        .stderr(predicate::str::contains("Cannot find module '../shared/helpers' imported in index.jr"));
 }
 
 #[test]
-fn test_build_without_project_fails() {
-    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+fn test_run_checks_visibility_at_imports() {
+    let temp_dir = create_test_project("visibility-test");
+    let project_path = temp_dir.path().join("visibility-test");
+
+    fs::write(
+        project_path.join("src/utils/shapes.jr"),
+        "internal function square(x: number): number {\n    return x * x;\n}\npub function area(side: number): number {\n    return square(side);\n}\n",
+    )
+    .expect("Failed to write shapes.jr");
+    fs::write(project_path.join("src/index.jr"), "import {area} from \"./utils/shapes\";\nprint(area(3));\n")
+        .expect("Failed to write code");
 
     Command::cargo_bin("jrust")
         .expect("Failed to find jrust binary")
-        .arg("build")
-        .current_dir(temp_dir.path())
+        .args(["-q", "run", "--no-banner"])
+        .current_dir(&project_path)
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("jRust project"));
-}
-
-#[test]
-fn test_run_without_project_fails() {
-    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        .success()
+        .stdout("9\n");
+    let shapes_rs = fs::read_to_string(project_path.join("generated/utils/shapes.rs")).expect("Failed to read shapes.rs");
+    assert!(shapes_rs.contains("pub(crate) fn area("));
 
+    fs::write(project_path.join("src/index.jr"), "import {square} from \"./utils/shapes\";\nprint(square(3));\n")
+        .expect("Failed to write code");
     Command::cargo_bin("jrust")
         .expect("Failed to find jrust binary")
-        .arg("run")
-        .current_dir(temp_dir.path())
+        .arg("check")
+        .current_dir(&project_path)
         .assert()
         .failure()
-        .stderr(predicate::str::contains("jRust project"));
-}
-
-#[test]
-fn test_init_creates_gitignore() {
-    let temp_dir = TempDir::new().expect("Failed to create temp dir");
-    let project_name = "gitignore-test";
+        .stderr(predicate::str::contains(
+            "'square' is internal to module './utils/shapes' and can't be imported (imported in index.jr)",
+        ));
 
+    fs::write(project_path.join("src/index.jr"), "print(1);\n").expect("Failed to write code");
+    let utils_index = fs::read_to_string(project_path.join("src/utils/index.jr")).expect("Failed to read index.jr");
+    fs::write(project_path.join("src/utils/index.jr"), format!("export {{area}} from \"./shapes\";\n{}", utils_index))
+        .expect("Failed to write index.jr");
     Command::cargo_bin("jrust")
         .expect("Failed to find jrust binary")
-        .arg("init")
-        .arg(project_name)
-        .current_dir(temp_dir.path())
+        .arg("check")
+        .current_dir(&project_path)
         .assert()
-        .success();
-
-    let gitignore_path = temp_dir.path().join(project_name).join(".gitignore");
-    let content = fs::read_to_string(&gitignore_path).expect("Failed to read .gitignore");
-
-    assert!(
-        content.contains("/target/"),
-        ".gitignore should ignore /target/"
-    );
-    assert!(
-        content.contains("/generated/"),
-        ".gitignore should ignore /generated/"
-    );
-    assert!(
-        content.contains("*.exe"),
-        ".gitignore should ignore *.exe"
-    );
+        .failure()
+        .stderr(predicate::str::contains("'area' is declared `pub`, which keeps it inside the project, so utils/index.jr can't export it"));
 }
 
 #[test]
-fn test_full_workflow() {
-    let temp_dir = TempDir::new().expect("Failed to create temp dir");
-    let project_name = "workflow-test";
+fn test_run_executes_program() {
+    let temp_dir = create_test_project("run-test");
+    let project_path = temp_dir.path().join("run-test");
 
     Command::cargo_bin("jrust")
         .expect("Failed to find jrust binary")
-        .arg("init")
-        .arg(project_name)
-        .current_dir(temp_dir.path())
+        .arg("run")
+        .current_dir(&project_path)
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("jRust Demo"))
+        .stdout(predicate::str::contains("Program completed successfully"));
+}
 
-    let project_path = temp_dir.path().join(project_name);
+#[test]
+fn test_run_forwards_program_arguments() {
+    let temp_dir = create_test_project("run-args-test");
+    let project_path = temp_dir.path().join("run-args-test");
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        "function main(): void {
+    let args = env.args();
+    print(\"Got \" + args.length + \" argument(s)\");
+    for arg in args {
+        print(\"arg: \" + arg);
+    }
+}
+",
+    )
+    .expect("Failed to write code");
 
     Command::cargo_bin("jrust")
         .expect("Failed to find jrust binary")
-        .arg("check")
+        .args(["run", "--", "hello", "big world"])
         .current_dir(&project_path)
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("Got 2 argument(s)"))
+        .stdout(predicate::str::contains("arg: hello"))
+        .stdout(predicate::str::contains("arg: big world"));
+}
+
+#[test]
+fn test_run_passes_through_exit_code() {
+    let temp_dir = create_test_project("run-exit-test");
+    let project_path = temp_dir.path().join("run-exit-test");
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        "import * as process from \"std/process\";
+
+function main(): void {
+    print(\"exiting\");
+    process.exit(3);
+}
+",
+    )
+    .expect("Failed to write code");
 
     Command::cargo_bin("jrust")
         .expect("Failed to find jrust binary")
-        .arg("build")
+        .arg("run")
         .current_dir(&project_path)
         .assert()
-        .success();
+        .code(3)
+        .stdout(predicate::str::contains("exiting"))
+        .stdout(predicate::str::contains("Program exited with code 3"));
+}
+
+#[test]
+fn test_run_streams_stdin_without_banner() {
+    let temp_dir = create_test_project("run-pipe-test");
+    let project_path = temp_dir.path().join("run-pipe-test");
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        "function main(): void {
+    let name = input(\"\");
+    print(\"Hello, \" + name);
+}
+",
+    )
+    .expect("Failed to write code");
 
     Command::cargo_bin("jrust")
         .expect("Failed to find jrust binary")
-        .arg("run")
+        .args(["-q", "run", "--no-banner"])
         .current_dir(&project_path)
+        .write_stdin("Ada\n")
         .assert()
         .success()
-        .stdout(predicate::str::contains("jRust Demo"));
+        .stdout("Hello, Ada\n");
 }
 
 #[test]
-fn test_variable_assignment_and_print() {
-    let temp_dir = create_test_project("var-test");
-    let project_path = temp_dir.path().join("var-test");
+fn test_run_prints_strings_exactly() {
+    let temp_dir = create_test_project("run-strings-test");
+    let project_path = temp_dir.path().join("run-strings-test");
 
-    let code = r#"function printGreeting(): void {
-    print("Hello, jRust!");
+    fs::write(
+        project_path.join("src/index.jr"),
+        r##"function main(): void {
+    let path = "C:\\temp\\\"new\"";
+    print("{} and {{}} and 100%");
+    print(path + "\t{x}");
+    print(r#"{"raw": "\n"}"#);
+    print("""
+line 1
+  "line 2"
+""");
 }
-
-printGreeting();
-"#;
-    fs::write(project_path.join("src/index.jr"), code).expect("Failed to write code");
+"##,
+    )
+    .expect("Failed to write code");
 
     Command::cargo_bin("jrust")
         .expect("Failed to find jrust binary")
-        .arg("run")
+        .args(["-q", "run", "--no-banner"])
         .current_dir(&project_path)
         .assert()
         .success()
-        .stdout(predicate::str::contains("Hello, jRust!"));
+        .stdout("{} and {{}} and 100%\nC:\\temp\\\"new\"\t{x}\n{\"raw\": \"\\n\"}\nline 1\n  \"line 2\"\n\n");
 }
 
 #[test]
-fn test_multiple_functions() {
-    let temp_dir = create_test_project("multi-func-test");
-    let project_path = temp_dir.path().join("multi-func-test");
+fn test_run_preserve_naming() {
+    let temp_dir = create_test_project("run-naming-test");
+    let project_path = temp_dir.path().join("run-naming-test");
 
-    let code = r#"function greetOne(): void {
-    print("Hello from function one!");
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str("\n[build]\nnaming = \"preserve\"\n");
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    fs::write(
+        project_path.join("src/utils/names.jr"),
+        "export struct Player { playerName: string }
+export function makePlayer(playerName: string): Player {
+    return Player { playerName: playerName };
+}
+",
+    )
+    .expect("Failed to write module");
+    fs::write(
+        project_path.join("src/index.jr"),
+        "import { makePlayer } from \"./utils/names\";
+function main(): void {
+    let firstPlayer = makePlayer(\"Ada\");
+    print(firstPlayer.playerName);
+}
+",
+    )
+    .expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["-q", "run", "--no-banner"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout("Ada\n");
+
+    let main_rs = fs::read_to_string(project_path.join("generated/main.rs")).expect("Failed to read main.rs");
+    assert!(main_rs.contains("let firstPlayer = makePlayer("));
+    assert!(main_rs.contains("firstPlayer.playerName"));
+
+    let manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    fs::write(&manifest_path, manifest.replace("\"preserve\"", "\"camel\"")).expect("Failed to write manifest");
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("build")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown naming 'camel'"));
+}
+
+#[test]
+fn test_run_custom_file() {
+    let temp_dir = create_test_project("run-custom-test");
+    let project_path = temp_dir.path().join("run-custom-test");
+
+    let custom_file = project_path.join("src/greet.jr");
+    fs::write(
+        &custom_file,
+        r#"export function sayHello(): void {
+    print("Greetings from jRust!");
+}
+"#,
+    )
+    .expect("Failed to write custom file");
+
+    // Run now compiles all .jr files and runs the generated executable
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("jRust Demo"))
+        .stdout(predicate::str::contains("Program completed successfully"));
+}
+
+#[test]
+fn test_check_without_project_fails() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("check")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("jRust project"));
+}
+
+#[test]
+fn test_build_without_project_fails() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("build")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("jRust project"));
+}
+
+#[test]
+fn test_run_script_outside_project() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(
+        temp_dir.path().join("hello.jr"),
+        "let name = \"scripts\";\nprint(\"Hello from \" + name);\n",
+    )
+    .expect("Failed to write script");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["run", "hello.jr"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Compiling script: hello.jr"))
+        .stdout(predicate::str::contains("Hello from scripts"));
+
+    assert!(!temp_dir.path().join("generated").exists());
+}
+
+#[test]
+fn test_run_without_project_fails() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("jRust project"));
+}
+
+#[test]
+fn test_init_creates_gitignore() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let project_name = "gitignore-test";
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("init")
+        .arg(project_name)
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let gitignore_path = temp_dir.path().join(project_name).join(".gitignore");
+    let content = fs::read_to_string(&gitignore_path).expect("Failed to read .gitignore");
+
+    assert!(
+        content.contains("/target/"),
+        ".gitignore should ignore /target/"
+    );
+    assert!(
+        content.contains("/generated/"),
+        ".gitignore should ignore /generated/"
+    );
+    assert!(
+        content.contains("*.exe"),
+        ".gitignore should ignore *.exe"
+    );
+}
+
+#[test]
+fn test_full_workflow() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let project_name = "workflow-test";
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("init")
+        .arg(project_name)
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+
+    let project_path = temp_dir.path().join(project_name);
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("check")
+        .current_dir(&project_path)
+        .assert()
+        .success();
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("build")
+        .current_dir(&project_path)
+        .assert()
+        .success();
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("jRust Demo"));
+}
+
+#[test]
+fn test_variable_assignment_and_print() {
+    let temp_dir = create_test_project("var-test");
+    let project_path = temp_dir.path().join("var-test");
+
+    let code = r#"function printGreeting(): void {
+    print("Hello, jRust!");
+}
+
+printGreeting();
+"#;
+    fs::write(project_path.join("src/index.jr"), code).expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Hello, jRust!"));
+}
+
+#[test]
+fn test_multiple_functions() {
+    let temp_dir = create_test_project("multi-func-test");
+    let project_path = temp_dir.path().join("multi-func-test");
+
+    let code = r#"function greetOne(): void {
+    print("Hello from function one!");
 }
 
 function greetTwo(): void {
@@ -485,3 +1022,1312 @@ greetTwo();
         .assert()
         .success();
 }
+
+#[test]
+fn test_build_reports_circular_imports() {
+    let temp_dir = create_test_project("cycle-test");
+    let project_path = temp_dir.path().join("cycle-test");
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        r#"import {ping} from "./a";
+
+ping();
+"#,
+    )
+    .expect("Failed to write code");
+    fs::write(
+        project_path.join("src/a.jr"),
+        r#"import {pong} from "./b";
+
+export function ping(): void {
+    print("ping");
+}
+"#,
+    )
+    .expect("Failed to write code");
+    fs::write(
+        project_path.join("src/b.jr"),
+        r#"import {ping} from "./a";
+
+export function pong(): void {
+    print("pong");
+}
+"#,
+    )
+    .expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("build")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Circular import detected: a.jr -> b.jr -> a.jr"));
+}
+
+#[test]
+fn test_build_reports_missing_exported_member() {
+    let temp_dir = create_test_project("missing-export-test");
+    let project_path = temp_dir.path().join("missing-export-test");
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        r#"import {ad} from "./math";
+
+print(ad(1, 2));
+"#,
+    )
+    .expect("Failed to write code");
+    fs::write(
+        project_path.join("src/math.jr"),
+        r#"export function add(a: number, b: number): number {
+    return a + b;
+}
+"#,
+    )
+    .expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("build")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Module './math' has no exported member 'ad' (imported in index.jr)",
+        ));
+}
+
+#[test]
+fn test_build_checks_calls_to_imported_functions() {
+    let temp_dir = create_test_project("imported-call-test");
+    let project_path = temp_dir.path().join("imported-call-test");
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        r#"import {add} from "./math";
+
+print(add(1));
+"#,
+    )
+    .expect("Failed to write code");
+    fs::write(
+        project_path.join("src/math.jr"),
+        r#"export function add(a: number, b: number): number {
+    return a + b;
+}
+"#,
+    )
+    .expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("build")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Function 'add' expects 2 argument(s) but got 1"));
+}
+
+#[test]
+fn test_build_maps_rust_errors_to_jr_source() {
+    let temp_dir = create_test_project("source-map-test");
+    let project_path = temp_dir.path().join("source-map-test");
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        "let count: number = 1;\nprint(count);\nlet label: number = \"one\";\nprint(label);\n",
+    )
+    .expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("build")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("mismatched types"))
+        .stderr(predicate::str::contains("--> src/index.jr:3"))
+        .stderr(predicate::str::contains("let label: number = \"one\";"));
+
+    let source_map = fs::read_to_string(project_path.join("generated/source_map.json"))
+        .expect("Failed to read source map");
+    assert!(source_map.contains("\"main.rs\""));
+    assert!(source_map.contains("\"src/index.jr\""));
+}
+
+#[test]
+fn test_build_reports_warnings_against_jr_names() {
+    let temp_dir = create_test_project("warning-test");
+    let project_path = temp_dir.path().join("warning-test");
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        "let total: number = 1;\nlet unusedValue: number = 2;\nprint(total);\n",
+    )
+    .expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("build")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("warning[unused_variables]: unused variable: `unusedValue`"))
+        .stderr(predicate::str::contains("--> src/index.jr:2:5"))
+        .stderr(predicate::str::contains("unused_value").not());
+}
+
+#[test]
+fn test_build_forwards_lint_levels() {
+    let temp_dir = create_test_project("lints-test");
+    let project_path = temp_dir.path().join("lints-test");
+
+    fs::write(project_path.join("src/index.jr"), "let unusedValue: number = 2;\n")
+        .expect("Failed to write code");
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str("\n[lints.rust]\nunused_variables = \"allow\"\n");
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("build")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("unused variable").not());
+
+    let generated = fs::read_to_string(project_path.join("generated/Cargo.toml"))
+        .expect("Failed to read generated manifest");
+    assert!(generated.contains("[lints.rust]\nunused_variables = \"allow\""));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["add", "serde@1.0"])
+        .current_dir(&project_path)
+        .assert()
+        .success();
+
+    let manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    assert!(manifest.contains("[lints.rust]\nunused_variables = \"allow\""));
+}
+
+#[test]
+fn test_build_links_runtime_for_std_imports() {
+    let temp_dir = create_test_project("std-test");
+    let project_path = temp_dir.path().join("std-test");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Random number: "));
+
+    let generated = fs::read_to_string(project_path.join("generated/Cargo.toml"))
+        .expect("Failed to read generated manifest");
+    assert!(generated.contains("[dependencies]\njrust_runtime = { path = "));
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        "import { now } from \"std/time\";\n\nfunction divide(a: number, b: number): number {\n    return a / b;\n}\n\nprint(now() > 0);\nprint(divide(1, 0));\n",
+    )
+    .expect("Failed to write code");
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .env("RUST_BACKTRACE", "0")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("jRust runtime error in function divide (src/index.jr:3): attempt to divide by zero"))
+        .stderr(predicate::str::contains("panicked at").not());
+
+    fs::remove_dir_all(project_path.join("src/utils")).expect("Failed to remove utils");
+    fs::write(project_path.join("src/index.jr"), "print(1);\n").expect("Failed to write code");
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--emit", "rust"])
+        .current_dir(&project_path)
+        .assert()
+        .success();
+
+    let generated = fs::read_to_string(project_path.join("generated/Cargo.toml"))
+        .expect("Failed to read generated manifest");
+    assert!(!generated.contains("jrust_runtime"));
+
+    fs::write(project_path.join("src/index.jr"), "import { fetch } from \"std/http\";\nprint(fetch(\"http://localhost\").status);\n")
+        .expect("Failed to write code");
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--emit", "rust"])
+        .current_dir(&project_path)
+        .assert()
+        .success();
+
+    let generated = fs::read_to_string(project_path.join("generated/Cargo.toml"))
+        .expect("Failed to read generated manifest");
+    assert!(generated.contains(r#", features = ["http"] }"#));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--emit", "rust"])
+        .env_remove("JRUST_RUNTIME_PATH")
+        .current_dir(&project_path)
+        .assert()
+        .success();
+
+    let generated = fs::read_to_string(project_path.join("generated/Cargo.toml"))
+        .expect("Failed to read generated manifest");
+    let version = env!("CARGO_PKG_VERSION");
+    assert!(generated.contains(&format!("jrust_runtime = {{ version = \"{}\", features = [\"http\"] }}", version)));
+}
+
+#[test]
+fn test_build_profiles() {
+    let temp_dir = create_test_project("profile-test");
+    let project_path = temp_dir.path().join("profile-test");
+
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str("\n[profile.release]\nopt-level = 2\nlto = true\n");
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--debug"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("target/debug/jrust_app"));
+
+    let generated = fs::read_to_string(project_path.join("generated/Cargo.toml"))
+        .expect("Failed to read generated manifest");
+    assert!(generated.contains("[profile.release]\nlto = true\nopt-level = 2"));
+    assert!(project_path.join("generated/target/debug").exists());
+    assert!(!project_path.join("generated/target/release").exists());
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["run", "--release"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("target/release/jrust_app"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--debug", "--release"])
+        .current_dir(&project_path)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_build_target_triple() {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        return;
+    }
+    let temp_dir = create_test_project("target-test");
+    let project_path = temp_dir.path().join("target-test");
+    let triple = "x86_64-unknown-linux-gnu";
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--debug", "--target", triple])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("target/{}/debug/jrust_app", triple)));
+    assert!(project_path.join("generated/target").join(triple).join("debug/jrust_app").exists());
+
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str(&format!("\n[build]\ntarget = \"{}\"\n", triple));
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Program completed successfully"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["add", "serde@1.0"])
+        .current_dir(&project_path)
+        .assert()
+        .success();
+    let manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    assert!(manifest.contains("[build]"));
+    assert!(manifest.contains(triple));
+}
+
+#[test]
+fn test_build_library_crate() {
+    let temp_dir = create_test_project("lib-test");
+    let project_path = temp_dir.path().join("lib-test");
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        "import {getRandom} from \"./utils\";\n\nexport function roll(): number {\n    return getRandom(1, 6);\n}\n",
+    )
+    .expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--lib", "--debug"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Library crate"));
+
+    let lib_rs = fs::read_to_string(project_path.join("generated/lib.rs")).expect("Failed to read lib.rs");
+    assert!(lib_rs.contains("pub mod utils;"));
+    assert!(lib_rs.contains("pub fn roll() -> i32"));
+    assert!(!lib_rs.contains("fn main()"));
+    let manifest = fs::read_to_string(project_path.join("generated/Cargo.toml")).expect("Failed to read manifest");
+    assert!(manifest.contains("name = \"lib_test\""));
+    assert!(manifest.contains("[lib]\npath = \"lib.rs\""));
+
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str("\n[build]\ncrate-type = \"lib\"\n");
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("nothing to run"));
+}
+
+#[test]
+fn test_build_emit_stages() {
+    let temp_dir = create_test_project("emit-test");
+    let project_path = temp_dir.path().join("emit-test");
+
+    fs::write(project_path.join("src/index.jr"), "let answer = 42;\nprint(answer);\n")
+        .expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--emit", "rust"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Rust code written to"))
+        .stdout(predicate::str::contains("Compiling with Rust").not());
+    assert!(project_path.join("generated/main.rs").exists());
+    assert!(!project_path.join("generated/target").exists());
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--emit", "tokens"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1:5\tIdentifier(\"answer\")"))
+        .stdout(predicate::str::contains("3:1\tEof"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--emit", "ast"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("VariableDecl"))
+        .stdout(predicate::str::contains("name: \"answer\""));
+
+    let output = Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--emit", "ast", "--format", "json"])
+        .current_dir(&project_path)
+        .output()
+        .expect("Failed to run jrust");
+    assert!(output.status.success());
+    let ast: serde_json::Value = serde_json::from_slice(&output.stdout).expect("AST should be JSON");
+    assert_eq!(ast["statements"][0]["kind"]["VariableDecl"]["name"], "answer");
+    assert_eq!(ast["statements"][0]["span"]["start"], 0);
+}
+
+#[test]
+fn test_build_copies_dependencies_into_generated_manifest() {
+    let temp_dir = create_test_project("deps-test");
+    let project_path = temp_dir.path().join("deps-test");
+
+    let lib_path = temp_dir.path().join("greetings");
+    fs::create_dir_all(lib_path.join("src")).expect("Failed to create library");
+    fs::write(
+        lib_path.join("Cargo.toml"),
+        "[package]\nname = \"greetings\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .expect("Failed to write library manifest");
+    fs::write(
+        lib_path.join("src/lib.rs"),
+        "pub fn hello() -> String { \"Hello from a dependency\".to_string() }\n",
+    )
+    .expect("Failed to write library source");
+
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str("\n[dependencies]\ngreetings = { path = \"../greetings\" }\n");
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        r#"import hello from "greetings";
+
+print(hello());
+"#,
+    )
+    .expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Hello from a dependency"));
+
+    let generated = fs::read_to_string(project_path.join("generated/Cargo.toml"))
+        .expect("Failed to read generated manifest");
+    assert!(generated.contains("greetings = { path = \"../../greetings\" }"));
+}
+
+#[test]
+fn test_add_and_remove_dependency() {
+    let temp_dir = create_test_project("add-dep-test");
+    let project_path = temp_dir.path().join("add-dep-test");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["add", "serde@1.0", "--features", "derive"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added dependency: serde = 1.0"));
+
+    let manifest = fs::read_to_string(project_path.join("Cargo.toml")).expect("Failed to read manifest");
+    assert!(manifest.contains("serde = { features = [\"derive\"], version = \"1.0\" }"));
+    let generated = fs::read_to_string(project_path.join("generated/Cargo.toml"))
+        .expect("Failed to read generated manifest");
+    assert!(generated.contains("serde = { features = [\"derive\"], version = \"1.0\" }"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["remove", "serde"])
+        .current_dir(&project_path)
+        .assert()
+        .success();
+
+    let manifest = fs::read_to_string(project_path.join("Cargo.toml")).expect("Failed to read manifest");
+    assert!(!manifest.contains("serde"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["remove", "serde"])
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Dependency 'serde' not found"));
+}
+
+#[test]
+fn test_add_dependency_without_a_resolvable_version() {
+    let temp_dir = create_test_project("add-version-test");
+    let project_path = temp_dir.path().join("add-version-test");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["add", "serde@"])
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Missing version after '@'; use `jrust add serde@<version>`"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["add", "jrust-no-such-crate-anywhere"])
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Couldn't find the latest version of 'jrust-no-such-crate-anywhere' on crates.io",
+        ));
+
+    let manifest = fs::read_to_string(project_path.join("Cargo.toml")).expect("Failed to read manifest");
+    assert!(!manifest.contains("serde"));
+    assert!(!manifest.contains("\"*\""));
+}
+
+#[test]
+fn test_jrust_explain() {
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["explain", "JR0102"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("JR0102: Missing semicolon"))
+        .stdout(predicate::str::contains("Erroneous example"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["explain", "JR9999"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown error code 'JR9999'"));
+}
+
+#[test]
+fn test_fmt_check_and_rewrite() {
+    let temp_dir = create_test_project("fmt-test");
+    let project_path = temp_dir.path().join("fmt-test");
+    let index_path = project_path.join("src/index.jr");
+
+    fs::write(&index_path, "function main():void{\nlet x=1\nprint(x);\n}\n").expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["fmt", "--check"])
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Needs formatting"))
+        .stdout(predicate::str::contains("index.jr"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("fmt")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Formatted"));
+
+    let formatted = fs::read_to_string(&index_path).expect("Failed to read code");
+    assert_eq!(formatted, "function main(): void {\n    let x = 1;\n    print(x);\n}\n");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["fmt", "--check"])
+        .current_dir(&project_path)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_test_command_reports_results_per_file() {
+    let temp_dir = create_test_project("test-cmd");
+    let project_path = temp_dir.path().join("test-cmd");
+
+    fs::write(
+        project_path.join("src/index.jr"),
+        "function add(a: number, b: number): number {
+    return a + b;
+}
+
+function main(): void {
+    print(add(1, 2));
+}
+
+test \"adds numbers\" {
+    assertEq(add(2, 3), 5);
+}
+
+test \"fails on purpose\" {
+    assert(add(1, 1) == 3, \"one plus one\");
+}
+",
+    )
+    .expect("Failed to write code");
+    fs::write(
+        project_path.join("src/plain.jr"),
+        "export function noop(): void {}\n\ntest \"plain math\" {\n    assertEq(1 + 1, 2);\n}\n",
+    )
+    .expect("Failed to write plain.jr");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("test")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("📄 src/index.jr"))
+        .stdout(predicate::str::contains("✅ adds numbers"))
+        .stdout(predicate::str::contains("❌ fails on purpose"))
+        .stdout(predicate::str::contains("panicked at src/index.jr:14"))
+        .stdout(predicate::str::contains("   one plus one\n"))
+        .stdout(predicate::str::contains("one plus one at").not())
+        .stdout(predicate::str::contains("✅ plain math"))
+        .stdout(predicate::str::contains("Test result: 2 passed, 1 failed"))
+        .stderr(predicate::str::contains("unused import").not());
+}
+
+#[test]
+fn test_watch_runs_command_until_interrupted() {
+    let temp_dir = create_test_project("watch-test");
+    let project_path = temp_dir.path().join("watch-test");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["watch", "check", "--no-clear"])
+        .current_dir(&project_path)
+        .timeout(std::time::Duration::from_secs(3))
+        .assert()
+        .interrupted()
+        .stdout(predicate::str::contains("running `jrust check`"))
+        .stdout(predicate::str::contains("All checks passed"))
+        .stdout(predicate::str::contains("Finished. Watching src/ for changes..."));
+}
+
+#[test]
+fn test_lsp_serves_diagnostics_hover_definition_and_completion() {
+    let temp_dir = create_test_project("lsp-test");
+    let project_path = temp_dir.path().join("lsp-test");
+    let src = project_path.join("src");
+    fs::write(
+        src.join("math.jr"),
+        "export function add(a: number, b: number): number {\n    return a + b;\n}\n",
+    )
+    .unwrap();
+    let index = src.join("index.jr");
+    let uri = format!("file://{}", index.display());
+    let text = "import { add } from \"./math\";\n\nlet total: number = add(1, 2);\nprint(total);\n";
+
+    let messages = [
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": { "uri": uri, "languageId": "jrust", "version": 1, "text": text },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/hover", "params": {
+            "textDocument": { "uri": uri }, "position": { "line": 3, "character": 8 },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 3, "method": "textDocument/hover", "params": {
+            "textDocument": { "uri": uri }, "position": { "line": 2, "character": 21 },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 4, "method": "textDocument/definition", "params": {
+            "textDocument": { "uri": uri }, "position": { "line": 2, "character": 21 },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 5, "method": "textDocument/completion", "params": {
+            "textDocument": { "uri": uri }, "position": { "line": 3, "character": 0 },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 7, "method": "textDocument/semanticTokens/full", "params": {
+            "textDocument": { "uri": uri },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "textDocument/didChange", "params": {
+            "textDocument": { "uri": uri, "version": 2 },
+            "contentChanges": [{ "text": "function half(x: number): number {\n    return x / 2;\n}\nprint(half(1, 2));\n" }],
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 6, "method": "shutdown" }),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ];
+    let input: String = messages
+        .iter()
+        .map(|message| {
+            let body = message.to_string();
+            format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+        })
+        .collect();
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("lsp")
+        .current_dir(&project_path)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"hoverProvider\":true"))
+        .stdout(predicate::str::contains("\"diagnostics\":[]"))
+        .stdout(predicate::str::contains("let total: number"))
+        .stdout(predicate::str::contains("function add(a: number, b: number): number"))
+        .stdout(predicate::str::contains("\"start\":{\"character\":16,\"line\":0}},\"uri\":\"file://"))
+        .stdout(predicate::str::contains("lsp-test/src/math.jr\"}"))
+        .stdout(predicate::str::contains("\"label\":\"assertEq\""))
+        .stdout(predicate::str::contains("\"label\":\"total\""))
+        .stdout(predicate::str::contains("\"data\":[0,0,6,0,0,0,9,3,4,0,0,6,4,0,0,0,5,8,6,0,"))
+        .stdout(predicate::str::contains("Function 'half' expects 1 argument(s) but got 2"))
+        .stdout(predicate::str::contains("\"start\":{\"character\":6,\"line\":3}"));
+}
+
+#[test]
+fn test_lsp_counts_positions_in_utf16_and_decodes_uris() {
+    let temp_dir = create_test_project("lsp-utf16-test");
+    let project_path = temp_dir.path().join("lsp-utf16-test");
+    let src = project_path.join("src");
+    fs::write(src.join("math.jr"), "export function add(a: number, b: number): number {\n    return a + b;\n}\n").unwrap();
+    let uri = format!("file://{}", src.join("index.jr").display()).replace("/src/", "/%73rc/");
+    let text = "import { add } from \"./math\";\nlet face = \"😀\"; let n = add(1, 2);\nlet poem = \"\"\"\nroses\n\"\"\";\nprint(n);\n";
+
+    let messages = [
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": { "uri": uri, "languageId": "jrust", "version": 1, "text": text },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/hover", "params": {
+            "textDocument": { "uri": uri }, "position": { "line": 1, "character": 21 },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 3, "method": "textDocument/definition", "params": {
+            "textDocument": { "uri": uri }, "position": { "line": 1, "character": 25 },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 4, "method": "textDocument/semanticTokens/full", "params": {
+            "textDocument": { "uri": uri },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 5, "method": "shutdown" }),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ];
+    let input: String = messages
+        .iter()
+        .map(|message| {
+            let body = message.to_string();
+            format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+        })
+        .collect();
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("lsp")
+        .current_dir(&project_path)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("let n\\n```\"},\"range\":{\"end\":{\"character\":22,\"line\":1},\"start\":{\"character\":21,\"line\":1}}"))
+        .stdout(predicate::str::contains("\"start\":{\"character\":16,\"line\":0}},\"uri\":\"file://"))
+        .stdout(predicate::str::contains("lsp-utf16-test/src/math.jr\"}"))
+        .stdout(predicate::str::contains("0,2,4,6,0,0,6,3,0,0,"))
+        .stdout(predicate::str::contains("0,2,3,6,0,1,0,5,6,0,1,0,3,6,0,"));
+}
+
+#[test]
+fn test_highlight_outputs_classified_tokens() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let file = temp_dir.path().join("main.jr");
+    fs::write(&file, "// greet\nprint(\"hi\");\n").unwrap();
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("highlight")
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1:1\tcomment\t// greet"))
+        .stdout(predicate::str::contains("2:1\tfunction\tprint"))
+        .stdout(predicate::str::contains("2:7\tstring\t\"hi\""));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["highlight", "--format", "json"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("{\"column\":7,\"kind\":\"string\",\"length\":4,\"line\":2}"));
+}
+
+#[test]
+fn test_generated_output_is_deterministic() {
+    let temp_dir = create_test_project("deterministic-test");
+    let project_path = temp_dir.path().join("deterministic-test");
+    for (file, name) in [("zeta.jr", "zeta"), ("alpha.jr", "alpha"), ("utils/format.jr", "format")] {
+        fs::write(
+            project_path.join("src").join(file),
+            format!("export function {}(): number {{\n    return 1;\n}}\n", name),
+        )
+        .unwrap();
+    }
+
+    let generated = project_path.join("generated");
+    let mut outputs = Vec::new();
+    for _ in 0..2 {
+        Command::cargo_bin("jrust")
+            .expect("Failed to find jrust binary")
+            .args(["build", "--emit", "rust"])
+            .current_dir(&project_path)
+            .assert()
+            .success();
+        let files: Vec<String> = ["main.rs", "utils/mod.rs", "source_map.json", "Cargo.toml"]
+            .iter()
+            .map(|file| fs::read_to_string(generated.join(file)).unwrap())
+            .collect();
+        outputs.push(files);
+        fs::remove_dir_all(&generated).unwrap();
+    }
+
+    assert_eq!(outputs[0], outputs[1]);
+    assert!(outputs[0][0].starts_with("mod alpha;\nmod utils;\nmod zeta;\n"));
+    assert_eq!(outputs[0][1], "pub mod format;\npub mod index;\npub use index::*;\npub mod random;\n");
+}
+
+#[test]
+fn test_build_formats_generated_code() {
+    let temp_dir = create_test_project("format-output-test");
+    let project_path = temp_dir.path().join("format-output-test");
+    fs::write(
+        project_path.join("src/index.jr"),
+        "try {\n    print(\"inside\");\n} catch (e) {\n    print(e);\n}\ndefer {\n    print(\"bye\");\n}\n",
+    )
+    .unwrap();
+
+    for path in [std::env::var_os("PATH").unwrap_or_default(), "/nonexistent".into()] {
+        Command::cargo_bin("jrust")
+            .expect("Failed to find jrust binary")
+            .args(["build", "--emit", "rust"])
+            .env("PATH", path)
+            .current_dir(&project_path)
+            .assert()
+            .success();
+
+        let main_rs = fs::read_to_string(project_path.join("generated/main.rs")).unwrap();
+        assert!(main_rs.contains("std::error::Error>> {\n        println!(\"{}\", \"inside\");\n        Ok(())\n    })() {"));
+        assert!(main_rs.contains("DeferGuard(|| {\n        println!(\"{}\", \"bye\");\n    });"));
+    }
+}
+
+#[test]
+fn test_build_uses_configured_entry_and_output() {
+    let temp_dir = create_test_project("entry-test");
+    let project_path = temp_dir.path().join("entry-test");
+
+    fs::write(project_path.join("src/app.jr"), "import {getRandom} from \"./utils\";\nprint(\"app entry \" + getRandom(1, 1));\n")
+        .expect("Failed to write app.jr");
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str("\n[build]\nentry = \"src/app.jr\"\nout-dir = \"build/rust\"\nbinary = \"greeter\"\n");
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("app entry 1"));
+
+    let main_rs = fs::read_to_string(project_path.join("build/rust/main.rs")).expect("Failed to read main.rs");
+    assert!(main_rs.contains("mod index;"));
+    assert!(main_rs.contains("mod utils;"));
+    let manifest = fs::read_to_string(project_path.join("build/rust/Cargo.toml")).expect("Failed to read manifest");
+    assert!(manifest.contains("name = \"greeter\""));
+    assert!(!project_path.join("generated/main.rs").exists());
+
+    fs::remove_file(project_path.join("src/app.jr")).expect("Failed to remove app.jr");
+    fs::create_dir_all(project_path.join("src/app")).expect("Failed to create src/app");
+    fs::write(project_path.join("src/app/y.jr"), "export function y(): string { return \"nested\"; }\n")
+        .expect("Failed to write y.jr");
+    fs::write(
+        project_path.join("src/app/main.jr"),
+        "import {y} from \"./y\";\nimport {getRandom} from \"../utils\";\nprint(\"app entry \" + y() + \" \" + getRandom(2, 2));\n",
+    )
+    .expect("Failed to write main.jr");
+    let manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    fs::write(&manifest_path, manifest.replace("src/app.jr", "src/app/main.jr")).expect("Failed to write manifest");
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("app entry nested 2"));
+    let main_rs = fs::read_to_string(project_path.join("build/rust/main.rs")).expect("Failed to read main.rs");
+    assert!(main_rs.contains("use crate::app::y::y;"));
+    assert!(main_rs.contains("use crate::utils::get_random;"));
+
+    let manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    fs::write(&manifest_path, manifest.replace("src/app/main.jr", "src/missing.jr")).expect("Failed to write manifest");
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("build")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No entry point found. Create src/missing.jr"));
+}
+
+#[test]
+fn test_run_selects_binary_target() {
+    let temp_dir = create_test_project("bins-test");
+    let project_path = temp_dir.path().join("bins-test");
+
+    fs::write(project_path.join("src/tool2.jr"), "import {createId} from \"./utils\";\nprint(\"tool2 \" + createId().length);\n")
+        .expect("Failed to write tool2.jr");
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str("\n[[bin]]\nname = \"tool2\"\npath = \"src/tool2.jr\"\n");
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["run", "--bin", "tool2"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("tool2 36"))
+        .stdout(predicate::str::contains("Demo complete").not());
+
+    let tool_rs = fs::read_to_string(project_path.join("generated/bin/tool2.rs")).expect("Failed to read tool2.rs");
+    assert!(tool_rs.contains("#[path = \"../utils/mod.rs\"]\nmod utils;"));
+    let main_rs = fs::read_to_string(project_path.join("generated/main.rs")).expect("Failed to read main.rs");
+    assert!(!main_rs.contains("mod tool2;"));
+    let manifest = fs::read_to_string(project_path.join("generated/Cargo.toml")).expect("Failed to read manifest");
+    assert!(manifest.contains("[[bin]]\nname = \"tool2\"\npath = \"bin/tool2.rs\""));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Demo complete"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["run", "--bin", "tool3"])
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No binary named 'tool3'. Available: jrust_app, tool2"));
+}
+
+#[test]
+fn test_run_nested_binary_and_default_entry_binary() {
+    let temp_dir = create_test_project("nested-bins-test");
+    let project_path = temp_dir.path().join("nested-bins-test");
+
+    fs::create_dir_all(project_path.join("src/tools")).expect("Failed to create src/tools");
+    fs::write(project_path.join("src/tools/other.jr"), "import {createId} from \"../utils\";\nprint(\"other \" + createId().length);\n")
+        .expect("Failed to write other.jr");
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str("\n[[bin]]\nname = \"app\"\npath = \"src/index.jr\"\n\n[[bin]]\nname = \"other\"\npath = \"src/tools/other.jr\"\n");
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["run", "--bin", "other"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("other 36"));
+
+    let other_rs = fs::read_to_string(project_path.join("generated/bin/other.rs")).expect("Failed to read other.rs");
+    assert!(other_rs.contains("use crate::utils::create_id;"));
+    let manifest = fs::read_to_string(project_path.join("generated/Cargo.toml")).expect("Failed to read manifest");
+    assert!(manifest.contains("[[bin]]\nname = \"app\"\npath = \"main.rs\""));
+    assert!(!manifest.contains("jrust_app"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["run", "--bin", "app"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Demo complete"));
+
+    let manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    fs::write(&manifest_path, manifest.replace("src/tools/other.jr", "src/index.jr")).expect("Failed to write manifest");
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("build")
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Binaries 'app' and 'other' both build src/index.jr"));
+}
+
+#[test]
+fn test_build_links_local_packages() {
+    let temp_dir = create_test_project("app-test");
+    let project_path = temp_dir.path().join("app-test");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["init", "shapes-lib"])
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    let package_path = temp_dir.path().join("shapes-lib");
+    fs::write(package_path.join("src/index.jr"), "export function triple(x: number): number {\n    return x * 3;\n}\n")
+        .expect("Failed to write package index.jr");
+    fs::write(package_path.join("src/geometry.jr"), "export function square(x: number): number {\n    return x * x;\n}\n")
+        .expect("Failed to write geometry.jr");
+
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str("\n[packages]\nshapes = { path = \"../shapes-lib\" }\n");
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+    fs::write(
+        project_path.join("src/index.jr"),
+        "import {triple} from \"shapes\";\nimport {square} from \"shapes/geometry\";\nprint(\"values \" + triple(3) + \" \" + square(4));\n",
+    )
+    .expect("Failed to write index.jr");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("run")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("values 9 16"));
+
+    let manifest = fs::read_to_string(project_path.join("generated/Cargo.toml")).expect("Failed to read manifest");
+    assert!(manifest.contains("shapes = { path = \"packages/shapes\", package = \"shapes_lib\" }"));
+    let package_manifest = fs::read_to_string(project_path.join("generated/packages/shapes/Cargo.toml"))
+        .expect("Failed to read package manifest");
+    assert!(package_manifest.contains("[lib]"));
+    assert!(!package_manifest.contains("[workspace]"));
+}
+
+#[test]
+fn test_run_script_runs_alias() {
+    let temp_dir = create_test_project("scripts-test");
+    let project_path = temp_dir.path().join("scripts-test");
+
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str("\n[scripts]\nlint = \"check\"\nrecurse = \"run-script lint\"\n");
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["run-script", "lint", "--", "src/index.jr"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("jrust check"))
+        .stdout(predicate::str::contains("Checking 1 jRust file(s)"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["run-script", "deploy"])
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No script named 'deploy'. Available: lint, recurse"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["run-script", "recurse"])
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("can't run another script"));
+}
+
+#[test]
+fn test_build_profile_selects_settings_and_cfg() {
+    let temp_dir = create_test_project("cfg-test");
+    let project_path = temp_dir.path().join("cfg-test");
+
+    let manifest_path = project_path.join("Cargo.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    manifest.push_str("\n[build.release]\nopt = true\n");
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+    fs::write(
+        project_path.join("src/index.jr"),
+        "@cfg(release) {\n    print(2 + 3);\n}\n@cfg(debug) {\n    print(\"debugging\");\n}\n",
+    )
+    .expect("Failed to write index.jr");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--profile", "release", "--emit", "rust"])
+        .current_dir(&project_path)
+        .assert()
+        .success();
+    let main_rs = fs::read_to_string(project_path.join("generated/main.rs")).expect("Failed to read main.rs");
+    assert!(main_rs.contains("println!(\"{}\", 5);"));
+    assert!(!main_rs.contains("debugging"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--profile", "debug", "--emit", "rust"])
+        .current_dir(&project_path)
+        .assert()
+        .success();
+    let main_rs = fs::read_to_string(project_path.join("generated/main.rs")).expect("Failed to read main.rs");
+    assert!(main_rs.contains("debugging"));
+    assert!(!main_rs.contains("2 + 3") && !main_rs.contains(", 5)"));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--profile", "debug", "--release"])
+        .current_dir(&project_path)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_build_runs_hooks() {
+    let temp_dir = create_test_project("hooks-test");
+    let project_path = temp_dir.path().join("hooks-test");
+
+    let manifest_path = project_path.join("Cargo.toml");
+    let original = fs::read_to_string(&manifest_path).expect("Failed to read manifest");
+    let manifest = format!("{}\n[hooks]\npre-build = [\"echo pre > pre.txt\"]\npost-build = [\"echo post > post.txt\"]\n", original);
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--debug"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pre-build: echo pre > pre.txt"))
+        .stdout(predicate::str::contains("post-build: echo post > post.txt"));
+    assert!(project_path.join("pre.txt").exists());
+    assert!(project_path.join("post.txt").exists());
+
+    fs::remove_file(project_path.join("post.txt")).expect("Failed to remove post.txt");
+    let manifest = format!("{}\n[hooks]\npre-build = [\"exit 3\"]\npost-build = [\"echo post > post.txt\"]\n", original);
+    fs::write(&manifest_path, manifest).expect("Failed to write manifest");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["build", "--debug"])
+        .current_dir(&project_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pre-build hook `exit 3` failed"));
+    assert!(!project_path.join("post.txt").exists());
+}
+
+#[test]
+fn test_completions_for_each_shell() {
+    for (shell, expected) in [("bash", "_jrust()"), ("zsh", "#compdef jrust"), ("fish", "complete -c jrust"), ("powershell", "Register-ArgumentCompleter")] {
+        Command::cargo_bin("jrust")
+            .expect("Failed to find jrust binary")
+            .args(["completions", shell])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(expected))
+            .stdout(predicate::str::contains("run-script"));
+    }
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["completions", "tcsh"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_quiet_and_no_emoji_output() {
+    let temp_dir = create_test_project("output-test");
+    let project_path = temp_dir.path().join("output-test");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["-q", "check"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["check", "--no-emoji"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[ok] All checks passed!"))
+        .stdout(predicate::str::contains("Checking").and(predicate::str::contains("📋").not()));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["-q", "-v", "check"])
+        .current_dir(&project_path)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_fix_applies_suggestions() {
+    let temp_dir = create_test_project("fix-test");
+    let project_path = temp_dir.path().join("fix-test");
+    let index = project_path.join("src/index.jr");
+    let broken = "import {createId, getRandom} from \"./utils\";
+
+function main(): void {
+    let unusedValue: number = 2;
+    print(createId())
+}
+";
+    fs::write(&index, broken).expect("Failed to write code");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .args(["fix", "--dry-run"])
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-    print(createId())\n+    print(createId());"));
+    assert_eq!(fs::read_to_string(&index).expect("Failed to read index.jr"), broken);
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("fix")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Applied 3 fix(es) to 1 file(s)"));
+
+    let fixed = fs::read_to_string(&index).expect("Failed to read index.jr");
+    assert!(fixed.contains("import {createId} from \"./utils\";"));
+    assert!(fixed.contains("let _unusedValue: number = 2;"));
+    assert!(fixed.contains("print(createId());"));
+}
+
+#[test]
+fn test_unused_imports_and_exports() {
+    let temp_dir = create_test_project("unused-test");
+    let project_path = temp_dir.path().join("unused-test");
+    let random = project_path.join("src/utils/random.jr");
+    fs::write(
+        &random,
+        "import {randomInt, uuid, shuffle} from \"std/random\";
+
+export function randomInRange(min: number, max: number): number {
+    return randomInt(min, max);
+}
+
+export function generateUniqueId(): string {
+    return uuid();
+}
+
+export function coinFlip(): number {
+    return randomInt(0, 1);
+}
+",
+    )
+    .expect("Failed to write random.jr");
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("check")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("warning[unused_imports]: unused import: `shuffle`"))
+        .stderr(predicate::str::contains(
+            "warning[unused_exports]: `coinFlip` is exported but never imported by another module",
+        ));
+
+    Command::cargo_bin("jrust")
+        .expect("Failed to find jrust binary")
+        .arg("fix")
+        .current_dir(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("src/utils/random.jr (2 fix(es))"));
+
+    let fixed = fs::read_to_string(&random).expect("Failed to read random.jr");
+    assert!(fixed.starts_with("import {randomInt, uuid} from \"std/random\";\n"));
+    assert!(fixed.contains("\nfunction coinFlip(): number {"));
+    assert!(fixed.contains("export function generateUniqueId(): string {"));
+}
@@ -0,0 +1,20 @@
+//! `std/base64`: text to and from standard, padded base64
+
+use ::base64::engine::general_purpose::STANDARD;
+use ::base64::Engine;
+
+use crate::Error;
+
+/// Encodes `text`'s UTF-8 bytes as base64
+pub fn encode(text: impl AsRef<str>) -> String {
+    STANDARD.encode(text.as_ref())
+}
+
+/// Decodes base64 back into text
+pub fn decode(text: impl AsRef<str>) -> Result<String, Error> {
+    let text = text.as_ref();
+    let bytes = STANDARD
+        .decode(text.trim())
+        .map_err(|err| Error::new(format!("Invalid base64 '{}': {}", text, err)))?;
+    String::from_utf8(bytes).map_err(|_| Error::new(format!("Base64 '{}' does not decode to UTF-8 text", text)))
+}
@@ -0,0 +1,17 @@
+//! `std/crypto`: checksums of text, written as lowercase hex digests
+
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use crate::hex;
+
+/// Returns the SHA-256 digest of `text`'s UTF-8 bytes
+pub fn sha256(text: impl AsRef<str>) -> String {
+    hex::encode(Sha256::digest(text.as_ref()))
+}
+
+/// Returns the MD5 digest of `text`'s UTF-8 bytes. MD5 is fine for checksums
+/// but broken for anything security related; prefer [`sha256`] there.
+pub fn md5(text: impl AsRef<str>) -> String {
+    hex::encode(Md5::digest(text.as_ref()))
+}
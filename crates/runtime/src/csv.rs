@@ -0,0 +1,49 @@
+//! `std/csv`: reading and writing comma-separated text
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::Error;
+
+/// Splits CSV text into rows of fields, header row included. Rows may have
+/// different lengths.
+pub fn parse(text: impl AsRef<str>) -> Result<Vec<Vec<String>>, Error> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_ref().as_bytes());
+    reader
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(str::to_string).collect())
+                .map_err(invalid)
+        })
+        .collect()
+}
+
+/// Reads each row after the header into a struct, matching columns to fields
+/// by the header names. Number fields are parsed from their column's text.
+pub fn parse_records<T: DeserializeOwned>(text: impl AsRef<str>) -> Result<Vec<T>, Error> {
+    let mut reader = ::csv::Reader::from_reader(text.as_ref().as_bytes());
+    reader.deserialize().map(|record| record.map_err(invalid)).collect()
+}
+
+/// Turns rows into CSV text, quoting fields where needed. Rows of structs get
+/// a header row made of the field names.
+pub fn write<T: Serialize>(rows: Vec<T>) -> Result<String, Error> {
+    let mut writer = ::csv::WriterBuilder::new().flexible(true).from_writer(Vec::new());
+    for row in rows {
+        writer
+            .serialize(row)
+            .map_err(|err| Error::new(format!("Cannot write CSV: {}", err)))?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|err| Error::new(format!("Cannot write CSV: {}", err.error())))?;
+    String::from_utf8(bytes).map_err(|err| Error::new(format!("Cannot write CSV: {}", err)))
+}
+
+fn invalid(err: ::csv::Error) -> Error {
+    Error::new(format!("Invalid CSV: {}", err))
+}
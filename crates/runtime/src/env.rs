@@ -0,0 +1,17 @@
+//! `std/env`: environment variables and command-line arguments
+
+/// Returns the value of the environment variable `name`, or `None` when it is
+/// unset or not valid Unicode
+pub fn get(name: impl AsRef<str>) -> Option<String> {
+    std::env::var(name.as_ref()).ok()
+}
+
+/// Sets the environment variable `name` for this process and the commands it runs
+pub fn set(name: impl AsRef<str>, value: impl AsRef<str>) {
+    std::env::set_var(name.as_ref(), value.as_ref());
+}
+
+/// Returns the command-line arguments, without the program name
+pub fn args() -> Vec<String> {
+    std::env::args().skip(1).collect()
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+use std::io::{self, ErrorKind};
+
+/// A failed runtime operation. The message names the operation and what it
+/// acted on, so a jRust `catch (e)` can print it as is.
+#[derive(Debug)]
+pub struct Error {
+    message: String,
+}
+
+impl Error {
+    pub(crate) fn new(message: String) -> Self {
+        Error { message }
+    }
+
+    pub(crate) fn io(action: impl fmt::Display, err: io::Error) -> Self {
+        let reason = match err.kind() {
+            ErrorKind::NotFound => "no such file or directory".to_string(),
+            ErrorKind::PermissionDenied => "permission denied".to_string(),
+            ErrorKind::AlreadyExists => "it already exists".to_string(),
+            ErrorKind::InvalidData => "it is not valid UTF-8 text".to_string(),
+            _ => err.to_string(),
+        };
+        Error {
+            message: format!("Cannot {}: {}", action, reason),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,55 @@
+//! `std/fs`: reading and writing files
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::Error;
+
+/// Returns the contents of the file at `path` as text
+pub fn read_file(path: impl AsRef<Path>) -> Result<String, Error> {
+    let path = path.as_ref();
+    fs::read_to_string(path).map_err(|err| Error::io(format!("read file '{}'", path.display()), err))
+}
+
+/// Creates or truncates the file at `path` and writes `content` to it
+pub fn write_file(path: impl AsRef<Path>, content: impl AsRef<str>) -> Result<(), Error> {
+    let path = path.as_ref();
+    fs::write(path, content.as_ref()).map_err(|err| Error::io(format!("write file '{}'", path.display()), err))
+}
+
+/// Adds `content` to the end of the file at `path`, creating it if needed
+pub fn append_file(path: impl AsRef<Path>, content: impl AsRef<str>) -> Result<(), Error> {
+    let path = path.as_ref();
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(content.as_ref().as_bytes()))
+        .map_err(|err| Error::io(format!("append to file '{}'", path.display()), err))
+}
+
+/// Returns whether a file or directory exists at `path`
+pub fn exists(path: impl AsRef<Path>) -> bool {
+    path.as_ref().exists()
+}
+
+/// Returns the names of the entries in the directory at `path`, sorted
+pub fn read_dir(path: impl AsRef<Path>) -> Result<Vec<String>, Error> {
+    let path = path.as_ref();
+    let entries = fs::read_dir(path).map_err(|err| Error::io(format!("read directory '{}'", path.display()), err))?;
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|err| Error::io(format!("read directory '{}'", path.display()), err))?;
+        names.push(entry.file_name().to_string_lossy().into_owned());
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Deletes the file at `path`, or the directory and everything in it
+pub fn remove(path: impl AsRef<Path>) -> Result<(), Error> {
+    let path = path.as_ref();
+    let result = if path.is_dir() { fs::remove_dir_all(path) } else { fs::remove_file(path) };
+    result.map_err(|err| Error::io(format!("remove '{}'", path.display()), err))
+}
@@ -0,0 +1,23 @@
+//! `std/hex`: text to and from hexadecimal
+
+use crate::Error;
+
+/// Returns the lowercase hex digits of `data`'s bytes, two per byte
+pub fn encode(data: impl AsRef<[u8]>) -> String {
+    data.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Decodes hex digits, in either case, back into text
+pub fn decode(text: impl AsRef<str>) -> Result<String, Error> {
+    let text = text.as_ref();
+    let invalid = || Error::new(format!("Invalid hex '{}'", text));
+    if text.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    let bytes = (0..text.len())
+        .step_by(2)
+        .map(|i| text.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(invalid)?;
+    String::from_utf8(bytes).map_err(|_| Error::new(format!("Hex '{}' does not decode to UTF-8 text", text)))
+}
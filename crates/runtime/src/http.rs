@@ -0,0 +1,214 @@
+//! `std/http`: making HTTP requests and serving them
+
+use std::sync::Arc;
+
+use crate::Error;
+
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    pub method: String,
+    pub headers: Vec<Vec<String>>,
+    pub body: String,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        FetchOptions {
+            method: "GET".to_string(),
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+}
+
+/// What a server sent back from [`fetch`], or what a [`serve`] handler sends.
+/// Any status counts as a response; check `ok()` or `status` for errors.
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: i32,
+    pub headers: Vec<Vec<String>>,
+    pub body: String,
+}
+
+/// An empty `200 OK`
+impl Default for Response {
+    fn default() -> Self {
+        Response {
+            status: 200,
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+}
+
+impl Response {
+    pub fn ok(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn text(&self) -> String {
+        self.body.clone()
+    }
+
+    pub fn header(&self, name: impl AsRef<str>) -> Option<String> {
+        find_header(&self.headers, name.as_ref())
+    }
+}
+
+/// A request a [`serve`] handler answers
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// The part of the URL after `?`, or empty
+    pub query: String,
+    /// `[name, value]` pairs, in the order they were sent
+    pub headers: Vec<Vec<String>>,
+    pub body: String,
+}
+
+/// A `GET /` with no headers and no body, handy for calling handlers in tests
+impl Default for Request {
+    fn default() -> Self {
+        Request {
+            method: "GET".to_string(),
+            path: "/".to_string(),
+            query: String::new(),
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+}
+
+impl Request {
+    /// Returns a copy of the body
+    pub fn text(&self) -> String {
+        self.body.clone()
+    }
+
+    /// Returns the value of the header called `name`, ignoring case. Repeated
+    /// headers are joined with `, `.
+    pub fn header(&self, name: impl AsRef<str>) -> Option<String> {
+        find_header(&self.headers, name.as_ref())
+    }
+}
+
+fn find_header(headers: &[Vec<String>], name: &str) -> Option<String> {
+    let values: Vec<&str> = headers
+        .iter()
+        .filter(|header| header.first().is_some_and(|header| header.eq_ignore_ascii_case(name)))
+        .filter_map(|header| header.get(1).map(String::as_str))
+        .collect();
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+pub fn fetch(url: impl AsRef<str>, options: FetchOptions) -> Result<Response, Error> {
+    let url = url.as_ref();
+    let fail = |reason: String| Error::new(format!("Request to '{}' failed: {}", url, reason));
+
+    let method = reqwest::Method::from_bytes(options.method.to_uppercase().as_bytes())
+        .map_err(|_| fail(format!("invalid method '{}'", options.method)))?;
+    let mut request = reqwest::blocking::Client::new().request(method, url);
+    for header in &options.headers {
+        match header.as_slice() {
+            [name, value] => request = request.header(name.as_str(), value.as_str()),
+            _ => return Err(fail("each header must be a [name, value] pair".to_string())),
+        }
+    }
+    if !options.body.is_empty() {
+        request = request.body(options.body);
+    }
+
+    let response = request.send().map_err(|err| fail(reason(&err)))?;
+    let status = response.status().as_u16().into();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| vec![name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()])
+        .collect();
+    let body = response.text().map_err(|err| fail(reason(&err)))?;
+    Ok(Response { status, headers, body })
+}
+
+/// Listens on `port` on all interfaces and answers every request with what
+/// `handler` returns. Handlers run on a thread pool, so they may block. Only
+/// returns if the server can't start or stops with an error.
+pub fn serve(port: i32, handler: impl Fn(Request) -> Response + Send + Sync + 'static) -> Result<(), Error> {
+    let port = u16::try_from(port).map_err(|_| Error::new(format!("Invalid port {}", port)))?;
+    let handler = Arc::new(handler);
+    let app = axum::Router::new().fallback(move |request: axum::extract::Request| {
+        let handler = Arc::clone(&handler);
+        async move {
+            let request = match to_request(request).await {
+                Ok(request) => request,
+                Err(reason) => return to_response(error_response(400, reason)),
+            };
+            // Handlers are plain blocking jRust functions and may call `fetch`
+            let response = tokio::task::spawn_blocking(move || handler(request))
+                .await
+                .unwrap_or_else(|_| error_response(500, "the handler panicked".to_string()));
+            to_response(response)
+        }
+    });
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(|err| Error::io("start the HTTP server", err))?;
+    runtime.block_on(async move {
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", port))
+            .await
+            .map_err(|err| Error::io(format!("listen on port {}", port), err))?;
+        axum::serve(listener, app)
+            .await
+            .map_err(|err| Error::io(format!("serve on port {}", port), err))
+    })
+}
+
+async fn to_request(request: axum::extract::Request) -> Result<Request, String> {
+    let (parts, body) = request.into_parts();
+    let headers = parts
+        .headers
+        .iter()
+        .map(|(name, value)| vec![name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()])
+        .collect();
+    let body = axum::body::to_bytes(body, usize::MAX).await.map_err(|err| err.to_string())?;
+    Ok(Request {
+        method: parts.method.to_string(),
+        path: parts.uri.path().to_string(),
+        query: parts.uri.query().unwrap_or_default().to_string(),
+        headers,
+        body: String::from_utf8(body.to_vec()).map_err(|_| "the body is not valid UTF-8 text".to_string())?,
+    })
+}
+
+fn to_response(response: Response) -> axum::response::Response {
+    let status = u16::try_from(response.status).unwrap_or_default();
+    let mut builder = axum::response::Response::builder().status(status);
+    for header in &response.headers {
+        if let [name, value] = header.as_slice() {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+    }
+    builder
+        .body(axum::body::Body::from(response.body))
+        .unwrap_or_else(|err| to_response(error_response(500, format!("invalid response: {}", err))))
+}
+
+fn error_response(status: i32, reason: String) -> Response {
+    Response {
+        status,
+        body: reason,
+        ..Response::default()
+    }
+}
+
+fn reason(err: &reqwest::Error) -> String {
+    let mut reason = err.to_string();
+    let mut source = std::error::Error::source(err);
+    while let Some(cause) = source {
+        reason = cause.to_string();
+        source = cause.source();
+    }
+    reason
+}
@@ -0,0 +1,20 @@
+//! `std/json`: parsing and producing JSON text
+
+use serde::Serialize;
+
+use crate::Error;
+
+/// A parsed JSON document. Index it with keys and positions, such as
+/// `value["users"][0]["name"]`; missing entries index to `Value::Null`.
+pub use serde_json::Value;
+
+/// Parses JSON text into a [`Value`]
+pub fn parse(text: impl AsRef<str>) -> Result<Value, Error> {
+    serde_json::from_str(text.as_ref()).map_err(|err| Error::new(format!("Invalid JSON: {}", err)))
+}
+
+/// Converts a value to compact JSON text. jRust structs and enums need
+/// `@serializable` for this.
+pub fn stringify(value: impl Serialize) -> Result<String, Error> {
+    serde_json::to_string(&value).map_err(|err| Error::new(format!("Cannot convert to JSON: {}", err)))
+}
@@ -1,8 +1,32 @@
-/// jRust runtime library
-/// 
-/// Provides runtime helpers for compiled jRust programs
+//! jRust runtime library
+//! 
+//! Provides runtime helpers for compiled jRust programs. Each `std/<name>`
+//! import in jRust source maps to the module of the same name here.
 
-/// Runtime initialization (placeholder)
+mod error;
+pub mod base64;
+pub mod crypto;
+pub mod csv;
+pub mod env;
+pub mod fs;
+pub mod hex;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod json;
+pub mod log;
+pub mod process;
+pub mod random;
+pub mod time;
+pub mod trace;
+pub mod url;
+
+pub use error::Error;
+/// Re-exported so `@serializable` types derive through the runtime and the
+/// generated crate doesn't need its own serde dependency
+pub use serde;
+
+/// Called first thing in generated `main`: installs the panic hook that
+/// reports panics as jRust runtime errors, see [`trace`]
 pub fn init() {
-    println!("jRust runtime initialized");
+    trace::install_hook();
 }
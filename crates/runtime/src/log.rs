@@ -0,0 +1,104 @@
+//! `std/log`: leveled, timestamped log lines on stderr
+//!
+//! `JRUST_LOG` sets the lowest level that is written (`debug`, `info`, `warn`,
+//! `error` or `off`; `info` when unset) and `JRUST_LOG_FORMAT=json` writes one
+//! JSON object per line instead of plain text.
+
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use chrono::DateTime;
+
+use crate::time;
+
+/// How serious a log line is, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        })
+    }
+}
+
+/// Parses level names case-insensitively; `warning` is accepted for `warn`
+impl FromStr for Level {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "debug" => Ok(Level::Debug),
+            "info" => Ok(Level::Info),
+            "warn" | "warning" => Ok(Level::Warn),
+            "error" => Ok(Level::Error),
+            _ => Err(()),
+        }
+    }
+}
+
+pub fn debug(message: impl fmt::Display) {
+    write(Level::Debug, message);
+}
+
+pub fn info(message: impl fmt::Display) {
+    write(Level::Info, message);
+}
+
+pub fn warn(message: impl fmt::Display) {
+    write(Level::Warn, message);
+}
+
+pub fn error(message: impl fmt::Display) {
+    write(Level::Error, message);
+}
+
+/// Whether `JRUST_LOG` lets lines of `level` through. Unknown values keep the
+/// default of `info`.
+pub fn enabled(level: Level) -> bool {
+    match std::env::var("JRUST_LOG") {
+        Ok(filter) if filter.trim().eq_ignore_ascii_case("off") => false,
+        Ok(filter) => level >= filter.parse().unwrap_or(Level::Info),
+        Err(_) => level >= Level::Info,
+    }
+}
+
+/// Formats one log line without the trailing newline. `timestamp` is in
+/// milliseconds since the Unix epoch and is written as an RFC 3339 UTC time.
+pub fn format(level: Level, timestamp: i64, message: &str, json: bool) -> String {
+    let time = match DateTime::from_timestamp_millis(timestamp) {
+        Some(time) => time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+        None => timestamp.to_string(),
+    };
+    if json {
+        // Written by hand to keep the keys in this order
+        let quote = |text: &str| serde_json::Value::from(text).to_string();
+        return format!(
+            "{{\"timestamp\":{},\"level\":\"{}\",\"message\":{}}}",
+            quote(&time),
+            level,
+            quote(message)
+        );
+    }
+    format!("{} {:<5} {}", time, level.to_string().to_uppercase(), message)
+}
+
+fn write(level: Level, message: impl fmt::Display) {
+    if !enabled(level) {
+        return;
+    }
+    let json = std::env::var("JRUST_LOG_FORMAT").is_ok_and(|format| format.eq_ignore_ascii_case("json"));
+    let line = format(level, time::now(), &message.to_string(), json);
+    // Logging must never bring the program down, so a closed stderr is ignored
+    let _ = writeln!(std::io::stderr().lock(), "{}", line);
+}
@@ -0,0 +1,41 @@
+//! `std/process`: exiting, the working directory and running commands
+
+use std::process::Command;
+
+use crate::Error;
+
+/// What a command started by [`run`] printed and how it exited
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub stdout: String,
+    pub stderr: String,
+    /// The exit code, or -1 if the command was stopped by a signal
+    pub status: i32,
+}
+
+/// Ends the program immediately with the given exit code
+pub fn exit(code: i32) -> ! {
+    std::process::exit(code)
+}
+
+/// Returns the current working directory
+pub fn cwd() -> Result<String, Error> {
+    std::env::current_dir()
+        .map(|dir| dir.display().to_string())
+        .map_err(|err| Error::io("read the current directory", err))
+}
+
+/// Runs `command` with `args`, waits for it to finish and captures its output.
+/// A command that runs and fails is not an error; check `status` for that.
+pub fn run(command: impl AsRef<str>, args: Vec<String>) -> Result<Output, Error> {
+    let command = command.as_ref();
+    let output = Command::new(command)
+        .args(args)
+        .output()
+        .map_err(|err| Error::io(format!("run '{}'", command), err))?;
+    Ok(Output {
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        status: output.status.code().unwrap_or(-1),
+    })
+}
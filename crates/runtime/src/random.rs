@@ -0,0 +1,32 @@
+//! `std/random`: random numbers, shuffling and identifiers
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Returns a random integer between `min` and `max`, both inclusive
+pub fn random_int(min: i32, max: i32) -> i32 {
+    if min > max {
+        panic!("randomInt: min ({}) is greater than max ({})", min, max);
+    }
+    rand::thread_rng().gen_range(min..=max)
+}
+
+/// Returns a random float in `[0, 1)`
+pub fn random_float() -> f64 {
+    rand::thread_rng().gen()
+}
+
+/// Returns a copy of `items` in random order
+pub fn shuffle<T>(mut items: Vec<T>) -> Vec<T> {
+    items.shuffle(&mut rand::thread_rng());
+    items
+}
+
+/// Returns a random version 4 UUID such as `"7c9e6679-7425-40de-944b-e07fc1f90ae7"`
+pub fn uuid() -> String {
+    let mut bytes: [u8; 16] = rand::thread_rng().gen();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
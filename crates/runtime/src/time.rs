@@ -0,0 +1,130 @@
+//! `std/time`: timestamps, sleeping and date formatting
+//!
+//! Timestamps are milliseconds since the Unix epoch, as 64-bit integers since
+//! they overflow a jRust `number`. Dates are formatted and parsed in UTC.
+
+use std::fmt;
+use std::ops::{Add, Sub};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::Error;
+
+/// A length of time, counted in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Duration {
+    millis: i64,
+}
+
+impl Duration {
+    pub fn to_millis(&self) -> i64 {
+        self.millis
+    }
+
+    pub fn to_seconds(&self) -> i64 {
+        self.millis / 1000
+    }
+}
+
+impl From<i32> for Duration {
+    fn from(millis: i32) -> Self {
+        Duration { millis: millis.into() }
+    }
+}
+
+impl From<i64> for Duration {
+    fn from(millis: i64) -> Self {
+        Duration { millis }
+    }
+}
+
+impl Add for Duration {
+    type Output = Duration;
+
+    fn add(self, other: Duration) -> Duration {
+        Duration { millis: self.millis + other.millis }
+    }
+}
+
+impl Sub for Duration {
+    type Output = Duration;
+
+    fn sub(self, other: Duration) -> Duration {
+        Duration { millis: self.millis - other.millis }
+    }
+}
+
+/// Prints as `250ms` below a second and as `1.5s` from there on
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.millis.abs() < 1000 {
+            write!(f, "{}ms", self.millis)
+        } else {
+            write!(f, "{}s", self.millis as f64 / 1000.0)
+        }
+    }
+}
+
+pub fn millis(n: impl Into<i64>) -> Duration {
+    Duration { millis: n.into() }
+}
+
+pub fn seconds(n: impl Into<i64>) -> Duration {
+    millis(n.into() * 1000)
+}
+
+pub fn minutes(n: impl Into<i64>) -> Duration {
+    seconds(n.into() * 60)
+}
+
+pub fn hours(n: impl Into<i64>) -> Duration {
+    minutes(n.into() * 60)
+}
+
+/// Returns the current time in milliseconds since the Unix epoch
+pub fn now() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_millis() as i64,
+        Err(err) => -(err.duration().as_millis() as i64),
+    }
+}
+
+/// Returns the time elapsed since `start`, a timestamp from [`now`]
+pub fn since(start: i64) -> Duration {
+    millis(now() - start)
+}
+
+/// Pauses the program; takes milliseconds or a [`Duration`]. Negative
+/// durations don't sleep at all.
+pub fn sleep(duration: impl Into<Duration>) {
+    let millis = duration.into().millis.max(0) as u64;
+    std::thread::sleep(std::time::Duration::from_millis(millis));
+}
+
+/// Formats `timestamp` with a strftime pattern such as `"%Y-%m-%d %H:%M:%S"`
+pub fn format_date(timestamp: i64, pattern: impl AsRef<str>) -> Result<String, Error> {
+    let pattern = pattern.as_ref();
+    let items: Vec<Item> = StrftimeItems::new(pattern).collect();
+    if items.contains(&Item::Error) {
+        return Err(Error::new(format!("Invalid date pattern '{}'", pattern)));
+    }
+    let date = DateTime::from_timestamp_millis(timestamp)
+        .ok_or_else(|| Error::new(format!("Timestamp {} is out of range", timestamp)))?;
+    Ok(date.format_with_items(items.into_iter()).to_string())
+}
+
+/// Parses `text` with a strftime pattern into a timestamp. Patterns without a
+/// time of day, such as `"%Y-%m-%d"`, give midnight.
+pub fn parse_date(text: impl AsRef<str>, pattern: impl AsRef<str>) -> Result<i64, Error> {
+    let (text, pattern) = (text.as_ref(), pattern.as_ref());
+    let parsed = NaiveDateTime::parse_from_str(text, pattern).or_else(|err| {
+        NaiveDate::parse_from_str(text, pattern)
+            .map(|date| date.and_time(NaiveTime::MIN))
+            .map_err(|_| err)
+    });
+    parsed
+        .map(|date| date.and_utc().timestamp_millis())
+        .map_err(|err| Error::new(format!("Cannot parse '{}' as '{}': {}", text, pattern, err)))
+}
@@ -0,0 +1,66 @@
+//! Runtime error reports that point at jRust code instead of generated Rust
+//!
+//! Generated functions open a [`Frame`] naming the jRust function and where it
+//! is declared, and generated `main` calls [`init`](crate::init) to install a
+//! panic hook that reports panics against the innermost open frame.
+
+use std::any::Any;
+use std::cell::Cell;
+
+thread_local! {
+    static CURRENT: Cell<Option<(&'static str, &'static str)>> = const { Cell::new(None) };
+}
+
+/// Marks a jRust function as running on this thread until dropped
+#[must_use]
+pub struct Frame {
+    outer: Option<(&'static str, &'static str)>,
+}
+
+impl Drop for Frame {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.set(self.outer));
+    }
+}
+
+/// Opens the frame for `function`, declared at `location` (`file.jr:line`)
+pub fn enter(function: &'static str, location: &'static str) -> Frame {
+    let outer = CURRENT.with(|current| current.replace(Some((function, location))));
+    Frame { outer }
+}
+
+/// The innermost open frame's function name and location
+pub fn current() -> Option<(&'static str, &'static str)> {
+    CURRENT.with(Cell::get)
+}
+
+/// The line reported for a panic carrying `message`, raised inside `frame`
+pub fn report(message: &str, frame: Option<(&str, &str)>) -> String {
+    match frame {
+        Some((function, location)) => {
+            format!("jRust runtime error in function {} ({}): {}", function, location, message)
+        }
+        None => format!("jRust runtime error: {}", message),
+    }
+}
+
+pub(crate) fn install_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        eprintln!("{}", report(&panic_message(info.payload()), current()));
+        // The Rust location and backtrace only help when debugging the generated code
+        if std::env::var_os("RUST_BACKTRACE").is_some_and(|value| value != "0") {
+            default_hook(info);
+        }
+    }));
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "the program panicked".to_string()
+    }
+}
@@ -0,0 +1,23 @@
+//! `std/url`: percent-encoding for query strings and path segments
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use crate::Error;
+
+/// Everything but the unreserved characters of RFC 3986, as JavaScript's
+/// `encodeURIComponent` does
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+/// Percent-encodes `text` so it can be used as one query value or path segment
+pub fn encode(text: impl AsRef<str>) -> String {
+    utf8_percent_encode(text.as_ref(), COMPONENT).to_string()
+}
+
+/// Decodes `%XX` escapes; `+` is left as is
+pub fn decode(text: impl AsRef<str>) -> Result<String, Error> {
+    let text = text.as_ref();
+    percent_decode_str(text)
+        .decode_utf8()
+        .map(|decoded| decoded.into_owned())
+        .map_err(|_| Error::new(format!("URL text '{}' does not decode to UTF-8 text", text)))
+}
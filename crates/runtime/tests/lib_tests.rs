@@ -1,6 +1,289 @@
-use jrust_runtime;
-
 #[test]
 fn test_init() {
     jrust_runtime::init();
 }
+
+#[test]
+fn test_frames_name_the_running_jrust_function() {
+    use jrust_runtime::trace;
+
+    assert_eq!(trace::current(), None);
+    {
+        let _outer = trace::enter("main", "src/index.jr:1");
+        {
+            let _inner = trace::enter("divide", "src/math.jr:4");
+            assert_eq!(trace::current(), Some(("divide", "src/math.jr:4")));
+        }
+        assert_eq!(trace::current(), Some(("main", "src/index.jr:1")));
+    }
+    assert_eq!(trace::current(), None);
+
+    assert_eq!(
+        trace::report("attempt to divide by zero", Some(("divide", "src/math.jr:4"))),
+        "jRust runtime error in function divide (src/math.jr:4): attempt to divide by zero"
+    );
+    assert_eq!(trace::report("boom", None), "jRust runtime error: boom");
+}
+
+#[test]
+fn test_random_int_stays_in_range() {
+    for _ in 0..100 {
+        let n = jrust_runtime::random::random_int(1, 6);
+        assert!((1..=6).contains(&n));
+    }
+    assert_eq!(jrust_runtime::random::random_int(3, 3), 3);
+}
+
+#[test]
+fn test_random_float_and_shuffle() {
+    let f = jrust_runtime::random::random_float();
+    assert!((0.0..1.0).contains(&f));
+
+    let mut shuffled = jrust_runtime::random::shuffle(vec![1, 2, 3, 4, 5]);
+    shuffled.sort();
+    assert_eq!(shuffled, vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_uuid_format() {
+    let id = jrust_runtime::random::uuid();
+    let groups: Vec<usize> = id.split('-').map(str::len).collect();
+    assert_eq!(groups, vec![8, 4, 4, 4, 12]);
+    assert_eq!(&id[14..15], "4");
+}
+
+#[test]
+fn test_fs_round_trip() {
+    use jrust_runtime::fs;
+
+    let dir = std::env::temp_dir().join(format!("jrust_fs_test_{}", std::process::id()));
+    let file = dir.join("notes.txt");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    fs::write_file(&file, "first\n").unwrap();
+    fs::append_file(&file, "second\n").unwrap();
+    assert_eq!(fs::read_file(&file).unwrap(), "first\nsecond\n");
+    assert!(fs::exists(&file));
+    assert_eq!(fs::read_dir(&dir).unwrap(), vec!["notes.txt".to_string()]);
+
+    fs::remove(&dir).unwrap();
+    assert!(!fs::exists(&dir));
+}
+
+#[test]
+fn test_fs_errors_name_the_path() {
+    let err = jrust_runtime::fs::read_file("definitely/missing.txt").unwrap_err();
+    assert_eq!(err.to_string(), "Cannot read file 'definitely/missing.txt': no such file or directory");
+}
+
+#[test]
+fn test_env_get_and_set() {
+    jrust_runtime::env::set("JRUST_RUNTIME_TEST_VAR", "on");
+    assert_eq!(jrust_runtime::env::get("JRUST_RUNTIME_TEST_VAR"), Some("on".to_string()));
+    assert_eq!(jrust_runtime::env::get("JRUST_RUNTIME_TEST_UNSET"), None);
+}
+
+#[test]
+fn test_process_run_and_cwd() {
+    let output = jrust_runtime::process::run("cargo", vec!["--version".to_string()]).unwrap();
+    assert_eq!(output.status, 0);
+    assert!(output.stdout.starts_with("cargo "));
+
+    let err = jrust_runtime::process::run("jrust-no-such-command", vec![]).unwrap_err();
+    assert_eq!(err.to_string(), "Cannot run 'jrust-no-such-command': no such file or directory");
+
+    assert!(!jrust_runtime::process::cwd().unwrap().is_empty());
+}
+
+#[test]
+fn test_time_durations_and_sleep() {
+    use jrust_runtime::time;
+
+    assert_eq!(time::minutes(2).to_seconds(), 120);
+    assert_eq!((time::seconds(1) + time::millis(500)).to_string(), "1.5s");
+    assert_eq!(time::millis(250).to_string(), "250ms");
+
+    let start = time::now();
+    time::sleep(20);
+    assert!(time::since(start).to_millis() >= 20);
+}
+
+#[test]
+fn test_time_format_and_parse_dates() {
+    use jrust_runtime::time;
+
+    assert_eq!(time::format_date(0, "%Y-%m-%d %H:%M").unwrap(), "1970-01-01 00:00");
+    let timestamp = time::parse_date("2024-02-29 13:45", "%Y-%m-%d %H:%M").unwrap();
+    assert_eq!(time::format_date(timestamp, "%d/%m/%Y %H:%M").unwrap(), "29/02/2024 13:45");
+    assert_eq!(time::parse_date("1970-01-02", "%Y-%m-%d").unwrap(), 86_400_000);
+
+    assert!(time::parse_date("yesterday", "%Y-%m-%d").is_err());
+    let err = time::format_date(0, "%Q").unwrap_err();
+    assert_eq!(err.to_string(), "Invalid date pattern '%Q'");
+}
+
+#[test]
+fn test_json_parse_and_stringify() {
+    use jrust_runtime::json;
+
+    let value = json::parse(r#"{"name": "Ada", "langs": ["en", "fr"]}"#).unwrap();
+    assert_eq!(value["name"], "Ada");
+    assert_eq!(value["langs"][1], "fr");
+    assert!(value["missing"].is_null());
+    assert_eq!(json::stringify(&value["langs"]).unwrap(), r#"["en","fr"]"#);
+
+    let err = json::parse("{oops}").unwrap_err();
+    assert!(err.to_string().starts_with("Invalid JSON: key must be a string"));
+}
+
+#[test]
+fn test_csv_rows_records_and_writing() {
+    use jrust_runtime::csv;
+    use jrust_runtime::serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "jrust_runtime::serde")]
+    struct Sale {
+        region: String,
+        amount: i32,
+    }
+
+    let text = "region,amount\nnorth,120\n\"south, east\",75\n";
+    let rows = csv::parse(text).unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[2], vec!["south, east", "75"]);
+
+    let sales: Vec<Sale> = csv::parse_records(text).unwrap();
+    assert_eq!(sales[0], Sale { region: "north".to_string(), amount: 120 });
+    assert_eq!(csv::write(sales).unwrap(), text);
+
+    assert_eq!(csv::write(vec![vec!["a", "say \"hi\""], vec!["b"]]).unwrap(), "a,\"say \"\"hi\"\"\"\nb\n");
+    let err = csv::parse_records::<Sale>("region,amount\nwest,lots\n").unwrap_err();
+    assert!(err.to_string().starts_with("Invalid CSV:"));
+}
+
+#[test]
+fn test_log_levels_and_line_format() {
+    use jrust_runtime::log::{self, Level};
+
+    assert_eq!("WARNING".parse(), Ok(Level::Warn));
+    assert!("loud".parse::<Level>().is_err());
+    assert!(Level::Error > Level::Info && Level::Info > Level::Debug);
+
+    assert_eq!(
+        log::format(Level::Info, 1_700_000_000_123, "server started", false),
+        "2023-11-14T22:13:20.123Z INFO  server started"
+    );
+    assert_eq!(
+        log::format(Level::Error, 0, "bad \"input\"", true),
+        r#"{"timestamp":"1970-01-01T00:00:00.000Z","level":"error","message":"bad \"input\""}"#
+    );
+}
+
+#[test]
+fn test_hashes_and_encodings() {
+    use jrust_runtime::{base64, crypto, hex, url};
+
+    assert_eq!(crypto::sha256(""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    assert_eq!(crypto::md5("hello"), "5d41402abc4b2a76b9719d911017c592");
+
+    assert_eq!(base64::encode("user:secret"), "dXNlcjpzZWNyZXQ=");
+    assert_eq!(base64::decode("dXNlcjpzZWNyZXQ=").unwrap(), "user:secret");
+    assert!(base64::decode("not base64!").unwrap_err().to_string().starts_with("Invalid base64 'not base64!'"));
+
+    assert_eq!(hex::encode("hi!"), "686921");
+    assert_eq!(hex::decode("68692F").unwrap(), "hi/");
+    assert_eq!(hex::decode("abc").unwrap_err().to_string(), "Invalid hex 'abc'");
+
+    assert_eq!(url::encode("a b&c=d/é~"), "a%20b%26c%3Dd%2F%C3%A9~");
+    assert_eq!(url::decode("a%20b%26c").unwrap(), "a b&c");
+    assert!(url::decode("%FF").is_err());
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_http_fetch_from_local_server() {
+    use jrust_runtime::http::{self, FetchOptions};
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/items", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        while !String::from_utf8_lossy(&request).ends_with("hello") {
+            let n = stream.read(&mut buf).unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+        stream
+            .write_all(b"HTTP/1.1 201 Created\r\nContent-Type: text/plain\r\nContent-Length: 5\r\nConnection: close\r\n\r\nsaved")
+            .unwrap();
+        String::from_utf8(request).unwrap()
+    });
+
+    let options = FetchOptions {
+        method: "post".to_string(),
+        headers: vec![vec!["X-Token".to_string(), "abc".to_string()]],
+        body: "hello".to_string(),
+    };
+    let response = http::fetch(&url, options).unwrap();
+    assert_eq!(response.status, 201);
+    assert!(response.ok());
+    assert_eq!(response.header("Content-Type").as_deref(), Some("text/plain"));
+    assert_eq!(response.text(), "saved");
+
+    let request = server.join().unwrap();
+    assert!(request.starts_with("POST /items HTTP/1.1"));
+    assert!(request.to_lowercase().contains("x-token: abc"));
+
+    let err = http::fetch("http://127.0.0.1:1/", FetchOptions::default()).unwrap_err();
+    assert!(err.to_string().starts_with("Request to 'http://127.0.0.1:1/' failed: "));
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_http_serve_answers_with_handler() {
+    use jrust_runtime::http::{self, FetchOptions, Request, Response};
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        http::serve(port.into(), |req: Request| match req.path.as_str() {
+            "/echo" => Response {
+                headers: vec![vec!["X-Method".to_string(), req.method.clone()]],
+                body: format!("{}?{}: {}", req.path, req.query, req.text()),
+                ..Response::default()
+            },
+            _ => Response {
+                status: 404,
+                ..Response::default()
+            },
+        })
+    });
+
+    let url = format!("http://127.0.0.1:{}", port);
+    let options = || FetchOptions {
+        method: "PUT".to_string(),
+        body: "hi".to_string(),
+        ..FetchOptions::default()
+    };
+    let mut attempts = 0;
+    let response = loop {
+        match http::fetch(format!("{}/echo?x=1", url), options()) {
+            Ok(response) => break response,
+            Err(_) if attempts < 50 => attempts += 1,
+            Err(err) => panic!("{}", err),
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    };
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("x-method").as_deref(), Some("PUT"));
+    assert_eq!(response.body, "/echo?x=1: hi");
+
+    let missing = http::fetch(format!("{}/nope", url), FetchOptions::default()).unwrap();
+    assert_eq!(missing.status, 404);
+    assert!(!missing.ok());
+
+    let err = http::serve(-1, |_| Response::default()).unwrap_err();
+    assert_eq!(err.to_string(), "Invalid port -1");
+}
@@ -1,6 +1,6 @@
-/// jRust standard library
-/// 
-/// Core functions and types available to all jRust programs
+//! jRust standard library
+//! 
+//! Core functions and types available to all jRust programs
 
 pub use jrust_runtime;
 
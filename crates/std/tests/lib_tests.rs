@@ -1,5 +1,3 @@
-use jrust_std;
-
 #[test]
 fn test_print() {
     jrust_std::print("Hello from test");
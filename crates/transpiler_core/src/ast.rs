@@ -1,20 +1,115 @@
+use std::collections::BTreeSet;
 
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+use crate::span::Span;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Program {
     pub statements: Vec<Statement>,
 }
 
-#[derive(Debug, Clone)]
-pub enum Statement {
+impl Program {
+    /// Whether the generated crate depends on `jrust_runtime`: a top-level
+    /// import from `std/`, or a struct or enum marked `@serializable`.
+    pub fn uses_runtime(&self) -> bool {
+        fn uses_runtime(stmt: &Statement) -> bool {
+            match &stmt.kind {
+                StatementKind::ImportStmt(import_stmt) => import_stmt.std_module().is_some(),
+                StatementKind::StructDecl(struct_decl) => is_serializable(&struct_decl.attributes),
+                StatementKind::EnumDecl(enum_decl) => is_serializable(&enum_decl.attributes),
+                StatementKind::ExportStmt(inner)
+                | StatementKind::ExportDefault(inner)
+                | StatementKind::VisibilityStmt(_, inner) => uses_runtime(inner),
+                _ => false,
+            }
+        }
+        self.statements.iter().any(uses_runtime)
+    }
+
+    pub fn runtime_features(&self) -> BTreeSet<String> {
+        let mut features = BTreeSet::new();
+        for stmt in &self.statements {
+            let stmt = match &stmt.kind {
+                StatementKind::ExportStmt(inner) => inner,
+                _ => stmt,
+            };
+            if let StatementKind::ImportStmt(import_stmt) = &stmt.kind {
+                if let Some(module) = import_stmt.std_module().filter(|module| RUNTIME_FEATURES.contains(module)) {
+                    features.insert(module.to_string());
+                }
+            }
+        }
+        features
+    }
+}
+
+pub const RUNTIME_FEATURES: &[&str] = &["http"];
+
+pub const SERIALIZABLE: &str = "serializable";
+
+/// Methods of the builtin `console` object. `warn` and `error` write to stderr.
+pub const CONSOLE_METHODS: &[&str] = &["log", "info", "debug", "warn", "error"];
+
+/// Annotations that opt a struct or enum into comparisons, with the Rust
+/// traits each one derives. Ordering and hashing include equality.
+pub const CAPABILITIES: &[(&str, &[&str])] = &[
+    ("equatable", &["PartialEq", "Eq"]),
+    ("comparable", &["PartialEq", "Eq", "PartialOrd", "Ord"]),
+    ("hashable", &["PartialEq", "Eq", "Hash"]),
+];
+
+pub fn is_serializable(attributes: &[Attribute]) -> bool {
+    attributes.iter().any(|attribute| attribute.name == SERIALIZABLE)
+}
+
+/// Annotations that only mean something on a struct or enum
+pub fn is_type_only(attribute: &Attribute) -> bool {
+    attribute.name == SERIALIZABLE || CAPABILITIES.iter().any(|(capability, _)| attribute.name == *capability)
+}
+
+/// Whether `attributes` derive the Rust trait `name`, through `@derive(...)` or
+/// one of the [`CAPABILITIES`]
+pub fn derives(attributes: &[Attribute], name: &str) -> bool {
+    attributes.iter().any(|attribute| {
+        if attribute.name == "derive" {
+            return attribute.arguments.iter().any(|argument| argument == name);
+        }
+        CAPABILITIES
+            .iter()
+            .any(|(capability, traits)| attribute.name == *capability && traits.contains(&name))
+    })
+}
+
+pub fn ast_to_json(program: &Program) -> String {
+    serde_json::to_string_pretty(program).unwrap_or_default()
+}
+
+pub fn ast_from_json(json: &str) -> Result<Program, String> {
+    serde_json::from_str(json).map_err(|e| format!("Invalid AST JSON: {}", e))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StatementKind {
     ImportStmt(ImportStmt),
     ExportStmt(Box<Statement>),
+    ExportDefault(Box<Statement>),
+    /// A declaration marked `pub` or `internal`
+    VisibilityStmt(Visibility, Box<Statement>),
     VariableDecl(VariableDecl),
+    DestructureDecl(DestructureDecl),
     FunctionDecl(FunctionDecl),
     StructDecl(StructDecl),
     EnumDecl(EnumDecl),
     PrintStmt(PrintStmt),
     ReturnStmt(ReturnStmt),
-    ExpressionStmt(Expression),
+    ExpressionStmt(ExpressionStmt),
     IfElse(IfElseStmt),
     ForLoop(ForLoopStmt),
     WhileLoop(WhileLoopStmt),
@@ -22,110 +117,327 @@ pub enum Statement {
     ContinueStmt,
     TryCatch(TryCatchStmt),
     ThrowStmt(ThrowStmt),
+    Block(Vec<Statement>),
+    CfgBlock(CfgBlock),
+    LockStmt(LockStmt),
+    DeferStmt(Vec<Statement>),
+    TestBlock(TestBlock),
+    Match(MatchStmt),
 }
 
-#[derive(Debug, Clone)]
+impl Statement {
+    pub fn new(kind: StatementKind, span: Span) -> Self {
+        Statement { kind, span }
+    }
+
+    /// Declarations, which are hoisted: they can be used before the line that
+    /// declares them, and never run as statements
+    pub fn is_item(&self) -> bool {
+        match &self.kind {
+            StatementKind::VariableDecl(var_decl) => var_decl.is_const,
+            StatementKind::ImportStmt(_)
+            | StatementKind::ExportStmt(_)
+            | StatementKind::ExportDefault(_)
+            | StatementKind::VisibilityStmt(..)
+            | StatementKind::FunctionDecl(_)
+            | StatementKind::StructDecl(_)
+            | StatementKind::EnumDecl(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        match &self.kind {
+            StatementKind::BreakStmt
+            | StatementKind::ContinueStmt
+            | StatementKind::Block(_)
+            | StatementKind::DeferStmt(_) => None,
+            _ => Some(self.span.line),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportStmt {
     pub imports: Vec<ImportItem>,
     pub path: String,
-    pub is_external: bool,
+    pub source: ImportSource,
+    pub is_wildcard: bool,
+    pub namespace: Option<String>,
+}
+
+pub const DEFAULT_IMPORT: &str = "default";
+
+/// Who can use a declaration besides its own module. Unmarked declarations
+/// are private too; `export` makes one public.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Visibility {
+    /// `pub`: every module of the project can import it, but projects using
+    /// this one as a package can't
+    Crate,
+    /// `internal`: a helper only its own module uses
+    Internal,
+}
+
+impl Visibility {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Visibility::Crate => "pub",
+            Visibility::Internal => "internal",
+        }
+    }
+}
+
+/// Crates every Rust program can import without a `[dependencies]` entry
+pub const RUST_CRATES: &[&str] = &["std", "core", "alloc"];
+
+/// What an import path points at, decided from how it is spelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportSource {
+    /// `std/<module>`: jRust's standard library
+    Std,
+    /// `./` or `../`: a module of this project
+    Local,
+    /// Anything else: a Rust crate or a `[packages]` entry, named by the
+    /// path's first segment
+    External,
+}
+
+impl ImportSource {
+    pub fn of(path: &str) -> Self {
+        if path.starts_with("std/") {
+            ImportSource::Std
+        } else if path.starts_with('.') {
+            ImportSource::Local
+        } else {
+            ImportSource::External
+        }
+    }
+}
+
+impl ImportStmt {
+    /// The module name of a `std/<name>` import.
+    pub fn std_module(&self) -> Option<&str> {
+        match self.source {
+            ImportSource::Std => self.path.strip_prefix("std/"),
+            _ => None,
+        }
+    }
+
+    /// The crate or package an external import names: `serde` for
+    /// `"serde::Serialize"`, `shapes` for `"shapes/circle"`.
+    pub fn crate_name(&self) -> Option<&str> {
+        match self.source {
+            ImportSource::External => self.path.split([':', '/']).next(),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportItem {
     pub name: String,
     pub alias: Option<String>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VariableDecl {
     pub name: String,
     pub var_type: Option<Type>,
+    pub type_span: Option<Span>,
     pub value: Expression,
     pub is_const: bool,
+    pub is_mut: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DestructureDecl {
+    pub names: Vec<String>,
+    pub value: Expression,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionDecl {
     pub name: String,
     pub parameters: Vec<Parameter>,
     pub return_type: Type,
+    pub return_type_span: Span,
     pub body: Vec<Statement>,
+    pub attributes: Vec<Attribute>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attribute {
+    pub name: String,
+    pub arguments: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Parameter {
     pub name: String,
     pub param_type: Type,
+    pub type_span: Span,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StructDecl {
     pub name: String,
     pub fields: Vec<StructField>,
+    pub attributes: Vec<Attribute>,
 }
 
-#[derive(Debug, Clone)]
+impl StructDecl {
+    pub fn is_defaultable(&self) -> bool {
+        self.fields.iter().all(StructField::may_omit)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StructField {
     pub name: String,
     pub field_type: Type,
+    pub type_span: Span,
+    pub is_optional: bool,
+    pub is_readonly: bool,
+    pub default: Option<Expression>,
+    pub span: Span,
+}
+
+impl StructField {
+    pub fn may_omit(&self) -> bool {
+        self.is_optional || self.default.is_some()
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnumDecl {
     pub name: String,
     pub variants: Vec<EnumVariant>,
+    pub attributes: Vec<Attribute>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnumVariant {
     pub name: String,
+    /// The types of `Variant(number, string)`
     pub fields: Option<Vec<Type>>,
+    /// The fields of `Variant { radius: number }`
+    pub named_fields: Option<Vec<StructField>>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrintStmt {
-    pub expression: Expression,
+    pub arguments: Vec<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReturnStmt {
     pub value: Option<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IfElseStmt {
     pub condition: Expression,
     pub then_body: Vec<Statement>,
     pub else_body: Option<Vec<Statement>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ForLoopStmt {
     pub variable: String,
     pub iterable: Expression,
     pub body: Vec<Statement>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WhileLoopStmt {
     pub condition: Expression,
     pub body: Vec<Statement>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TryCatchStmt {
     pub try_body: Vec<Statement>,
     pub catch_param: Option<String>,
     pub catch_body: Vec<Statement>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockStmt {
+    pub name: String,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CfgBlock {
+    pub conditions: Vec<String>,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestBlock {
+    pub name: String,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchStmt {
+    pub subject: Expression,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Vec<Statement>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Pattern {
+    /// `_`, which matches anything
+    Wildcard,
+    /// `Enum.Variant`, optionally binding the variant's fields
+    Variant {
+        enum_name: String,
+        variant: String,
+        bindings: PatternBindings,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PatternBindings {
+    None,
+    /// `Variant(a, b)`, one name per positional field
+    Tuple(Vec<String>),
+    /// `Variant { radius, h: height }` as `(field, binding)` pairs
+    Named(Vec<(String, String)>),
+}
+
+impl PatternBindings {
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            PatternBindings::None => Vec::new(),
+            PatternBindings::Tuple(names) => names.iter().map(String::as_str).collect(),
+            PatternBindings::Named(fields) => fields.iter().map(|(_, binding)| binding.as_str()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpressionStmt {
+    pub expression: Expression,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThrowStmt {
     pub expression: Expression,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Type {
     Number,
     String,
@@ -137,11 +449,35 @@ pub enum Type {
         size: Option<usize>,
     },
     Custom(String),
+    Shared(Box<Type>),
     Inferred,
 }
 
-#[derive(Debug, Clone)]
-pub enum Expression {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Expression { kind, span }
+    }
+
+    /// Whether this is a call of one of the [`CONSOLE_METHODS`]
+    pub fn is_console_call(&self) -> bool {
+        match &self.kind {
+            ExpressionKind::MethodCall { object, method, .. } => {
+                matches!(&object.kind, ExpressionKind::Identifier(name) if name == "console")
+                    && CONSOLE_METHODS.contains(&method.as_str())
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ExpressionKind {
     Identifier(String),
     NumberLiteral(i32),
     StringLiteral(String),
@@ -151,6 +487,12 @@ pub enum Expression {
         name: String,
         fields: Vec<(String, Expression)>,
     },
+    /// `Enum.Variant { field: value }`
+    VariantLiteral {
+        enum_name: String,
+        variant: String,
+        fields: Vec<(String, Expression)>,
+    },
     BinaryOp(Box<Expression>, BinaryOp, Box<Expression>),
     FunctionCall(String, Vec<Expression>),
     MethodCall {
@@ -162,13 +504,31 @@ pub enum Expression {
         object: Box<Expression>,
         index: Box<Expression>,
     },
+    SliceAccess {
+        object: Box<Expression>,
+        start: Option<Box<Expression>>,
+        end: Option<Box<Expression>>,
+    },
     MemberAccess {
         object: Box<Expression>,
         member: String,
     },
+    Assignment {
+        target: Box<Expression>,
+        value: Box<Expression>,
+    },
+    Channel(Type),
+    Shared {
+        value_type: Type,
+        value: Box<Expression>,
+    },
+    Closure {
+        parameters: Vec<Parameter>,
+        body: Vec<Statement>,
+    },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Copy, PartialEq)]
 pub enum BinaryOp {
     Add,
     Subtract,
@@ -184,3 +544,24 @@ pub enum BinaryOp {
     And,
     Or,
 }
+
+impl BinaryOp {
+    /// The operator as written, which is the same in jRust and Rust
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Subtract => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "/",
+            BinaryOp::Modulo => "%",
+            BinaryOp::Equal => "==",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterEqual => ">=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessEqual => "<=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        }
+    }
+}
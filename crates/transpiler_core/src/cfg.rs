@@ -0,0 +1,118 @@
+use crate::ast::{Attribute, Program, Statement, StatementKind};
+use crate::visit::{walk_statement_mut, VisitorMut};
+
+pub const CFG: &str = "cfg";
+
+const UNIX: &[&str] = &["linux", "macos", "ios", "android", "freebsd", "netbsd", "openbsd", "dragonfly", "solaris", "illumos"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cfg {
+    pub debug: bool,
+    pub os: String,
+}
+
+impl Default for Cfg {
+    fn default() -> Self {
+        Cfg { debug: true, os: std::env::consts::OS.to_string() }
+    }
+}
+
+impl Cfg {
+    pub fn for_target(debug: bool, target: Option<&str>) -> Self {
+        let os = match target {
+            Some(triple) => target_os(triple).to_string(),
+            None => std::env::consts::OS.to_string(),
+        };
+        Cfg { debug, os }
+    }
+
+    pub fn holds(&self, condition: &str) -> bool {
+        match condition {
+            "debug" => self.debug,
+            "release" => !self.debug,
+            "windows" => self.os == "windows",
+            "unix" => UNIX.contains(&self.os.as_str()),
+            _ => condition_os(condition).is_some_and(|os| os == self.os),
+        }
+    }
+
+    pub fn enables(&self, stmt: &Statement) -> bool {
+        conditions(stmt).iter().all(|condition| self.holds(condition))
+    }
+}
+
+pub fn is_condition(argument: &str) -> bool {
+    matches!(argument, "debug" | "release" | "unix" | "windows") || condition_os(argument).is_some()
+}
+
+fn condition_os(condition: &str) -> Option<&str> {
+    condition.strip_prefix("os = \"")?.strip_suffix('"')
+}
+
+fn target_os(triple: &str) -> &str {
+    let parts: Vec<&str> = triple.split('-').collect();
+    if parts.contains(&"windows") {
+        "windows"
+    } else if parts.contains(&"darwin") {
+        "macos"
+    } else if let Some(os) = ["ios", "android", "freebsd", "netbsd", "openbsd", "dragonfly", "solaris", "illumos"]
+        .into_iter()
+        .find(|os| parts.iter().any(|part| part.starts_with(os)))
+    {
+        os
+    } else if parts.contains(&"linux") {
+        "linux"
+    } else {
+        "unknown"
+    }
+}
+
+fn conditions(stmt: &Statement) -> Vec<&str> {
+    let attributes: &[Attribute] = match &stmt.kind {
+        StatementKind::ExportStmt(inner)
+        | StatementKind::ExportDefault(inner)
+        | StatementKind::VisibilityStmt(_, inner) => return conditions(inner),
+        StatementKind::CfgBlock(block) => return block.conditions.iter().map(String::as_str).collect(),
+        StatementKind::FunctionDecl(func_decl) => &func_decl.attributes,
+        StatementKind::StructDecl(struct_decl) => &struct_decl.attributes,
+        StatementKind::EnumDecl(enum_decl) => &enum_decl.attributes,
+        _ => &[],
+    };
+    attributes
+        .iter()
+        .filter(|attribute| attribute.name == CFG)
+        .flat_map(|attribute| attribute.arguments.iter().map(String::as_str))
+        .collect()
+}
+
+pub fn resolve(program: &mut Program, cfg: &Cfg) {
+    program.statements.retain(|stmt| cfg.enables(stmt));
+    Resolver { cfg }.visit_program_mut(program);
+}
+
+struct Resolver<'a> {
+    cfg: &'a Cfg,
+}
+
+impl VisitorMut for Resolver<'_> {
+    fn visit_statement_mut(&mut self, stmt: &mut Statement) {
+        if !self.cfg.enables(stmt) {
+            stmt.kind = StatementKind::Block(Vec::new());
+            return;
+        }
+        let target = match &mut stmt.kind {
+            StatementKind::ExportStmt(inner)
+            | StatementKind::ExportDefault(inner)
+            | StatementKind::VisibilityStmt(_, inner) => &mut inner.kind,
+            other => other,
+        };
+        match target {
+            StatementKind::CfgBlock(block) => *target = StatementKind::Block(std::mem::take(&mut block.body)),
+            StatementKind::FunctionDecl(func_decl) => func_decl.attributes.retain(|attribute| attribute.name != CFG),
+            StatementKind::StructDecl(struct_decl) => struct_decl.attributes.retain(|attribute| attribute.name != CFG),
+            StatementKind::EnumDecl(enum_decl) => enum_decl.attributes.retain(|attribute| attribute.name != CFG),
+            _ => {}
+        }
+        walk_statement_mut(self, stmt);
+    }
+}
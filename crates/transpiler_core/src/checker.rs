@@ -0,0 +1,1073 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::*;
+use crate::diagnostic::{suggest, Diagnostic};
+use crate::span::Span;
+
+const BUILTIN_TYPES: &[&str] = &["number", "string", "boolean", "void", "any"];
+const PRELUDE_NAMES: &[&str] = &["Some", "None", "Ok", "Err", "env", "console"];
+const ASSERTIONS: &[(&str, usize)] = &[("assert", 1), ("assertEq", 2), ("panic", 0)];
+const STD_MODULES: &[(&str, &[&str])] = &[
+    ("random", &["randomInt", "randomFloat", "shuffle", "uuid"]),
+    ("fs", &["readFile", "writeFile", "appendFile", "exists", "readDir", "remove"]),
+    ("env", &["get", "set", "args"]),
+    ("process", &["exit", "cwd", "run"]),
+    ("json", &["parse", "stringify", "Value"]),
+    ("csv", &["parse", "parseRecords", "write"]),
+    ("crypto", &["sha256", "md5"]),
+    ("base64", &["encode", "decode"]),
+    ("hex", &["encode", "decode"]),
+    ("url", &["encode", "decode"]),
+    ("log", &["debug", "info", "warn", "error"]),
+    ("http", &["fetch", "FetchOptions", "Response", "serve", "Request"]),
+    (
+        "time",
+        &["now", "since", "sleep", "Duration", "millis", "seconds", "minutes", "hours", "formatDate", "parseDate"],
+    ),
+];
+const BUILTIN_FUNCTIONS: &[(&str, &[Type], usize)] = &[
+    ("parseInt", &[Type::String], 1),
+    ("parseFloat", &[Type::String], 1),
+    ("input", &[Type::String], 0),
+];
+const STRING_METHODS: &[(&str, &[Type], usize)] = &[
+    ("replace", &[Type::String, Type::String], 2),
+    ("startsWith", &[Type::String], 1),
+    ("endsWith", &[Type::String], 1),
+    ("padStart", &[Type::Number, Type::String], 1),
+    ("repeat", &[Type::Number], 1),
+    ("slice", &[Type::Number, Type::Number], 0),
+    ("toString", &[], 0),
+];
+const NUMBER_METHODS: &[(&str, &[Type], usize)] = &[("toString", &[], 0), ("toFixed", &[Type::Number], 1)];
+const ARRAY_METHODS: &[(&str, &[Type], usize)] = &[
+    ("map", &[Type::Inferred], 1),
+    ("filter", &[Type::Inferred], 1),
+    ("forEach", &[Type::Inferred], 1),
+    ("find", &[Type::Inferred], 1),
+    ("findIndex", &[Type::Inferred], 1),
+    ("some", &[Type::Inferred], 1),
+    ("every", &[Type::Inferred], 1),
+    ("reduce", &[Type::Inferred, Type::Inferred], 1),
+    ("flat", &[Type::Number], 0),
+    ("concat", &[Type::Inferred], 1),
+    ("sort", &[Type::Inferred], 0),
+    ("sortBy", &[Type::Inferred], 1),
+    ("at", &[Type::Number], 1),
+];
+const CALLBACK_PARAMETERS: &[(&str, usize)] = &[
+    ("map", 1),
+    ("filter", 1),
+    ("forEach", 1),
+    ("find", 1),
+    ("findIndex", 1),
+    ("some", 1),
+    ("every", 1),
+    ("reduce", 2),
+    ("sort", 2),
+    ("sortBy", 1),
+];
+
+#[derive(Default)]
+struct Scope {
+    locals: HashMap<String, Option<Type>>,
+    is_function: bool,
+    expired: HashSet<String>,
+}
+
+impl Scope {
+    fn function() -> Self {
+        Scope {
+            is_function: true,
+            ..Default::default()
+        }
+    }
+}
+
+pub struct Checker {
+    structs: HashMap<String, StructDecl>,
+    enums: HashMap<String, EnumDecl>,
+    functions: HashMap<String, FunctionDecl>,
+    types: HashSet<String>,
+    globals: HashSet<String>,
+    imports: HashSet<String>,
+    has_opaque_import: bool,
+    is_module: bool,
+    scopes: Vec<Scope>,
+    error_span: Option<Span>,
+}
+
+impl Default for Checker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        Checker {
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            functions: HashMap::new(),
+            types: HashSet::new(),
+            globals: HashSet::new(),
+            imports: HashSet::new(),
+            has_opaque_import: false,
+            is_module: false,
+            scopes: vec![Scope::function()],
+            error_span: None,
+        }
+    }
+
+    /// A checker for a file imported by the entry file, which has no top-level
+    /// code of its own
+    pub fn new_module() -> Self {
+        Checker {
+            is_module: true,
+            ..Self::new()
+        }
+    }
+
+    pub fn declare_function(&mut self, name: &str, func_decl: FunctionDecl) {
+        self.functions.insert(name.to_string(), func_decl);
+    }
+
+    pub fn check(&mut self, program: &Program) -> Result<(), Diagnostic> {
+        self.error_span = None;
+        let result = self.check_statements(&program.statements).and_then(|()| self.check_top_level(&program.statements));
+        result.map_err(|message| match self.error_span.take() {
+            Some(span) => Diagnostic::new(message).with_span(span),
+            None => Diagnostic::new(message),
+        })
+    }
+
+    /// Declarations are hoisted, and the entry file's other top-level
+    /// statements run in order as the generated `main`. That leaves no place
+    /// for them in a module or next to a `main` function.
+    fn check_top_level(&mut self, statements: &[Statement]) -> Result<(), String> {
+        let has_main = statements.iter().any(|stmt| {
+            let decl = match &stmt.kind {
+                StatementKind::ExportStmt(inner)
+                | StatementKind::ExportDefault(inner)
+                | StatementKind::VisibilityStmt(_, inner) => &inner.kind,
+                other => other,
+            };
+            matches!(decl, StatementKind::FunctionDecl(func_decl) if func_decl.name == "main")
+        });
+        if !self.is_module && !has_main {
+            return Ok(());
+        }
+        let Some(stmt) = statements
+            .iter()
+            .find(|stmt| !stmt.is_item() && !matches!(stmt.kind, StatementKind::TestBlock(_)))
+        else {
+            return Ok(());
+        };
+        self.error_span = Some(stmt.span);
+        let line = stmt.line().map(|line| format!(" on line {}", line)).unwrap_or_default();
+        if self.is_module {
+            Err(format!(
+                "Top-level statement{} in a module. Only the entry file runs top-level code; move it into a function",
+                line
+            ))
+        } else {
+            Err(format!("Top-level statement{} can't be mixed with a `main` function. Move it into `main`", line))
+        }
+    }
+
+    fn collect_declarations(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            match &stmt.kind {
+                StatementKind::StructDecl(struct_decl) => {
+                    self.types.insert(struct_decl.name.clone());
+                    self.structs.insert(struct_decl.name.clone(), struct_decl.clone());
+                }
+                StatementKind::EnumDecl(enum_decl) => {
+                    self.types.insert(enum_decl.name.clone());
+                    self.enums.insert(enum_decl.name.clone(), enum_decl.clone());
+                }
+                StatementKind::FunctionDecl(func_decl) => {
+                    self.functions.insert(func_decl.name.clone(), func_decl.clone());
+                }
+                StatementKind::VariableDecl(var_decl) if var_decl.is_const => {
+                    self.globals.insert(var_decl.name.clone());
+                }
+                StatementKind::ImportStmt(import_stmt) => {
+                    if let Some(namespace) = &import_stmt.namespace {
+                        self.imports.insert(namespace.clone());
+                    } else if import_stmt.imports.is_empty() {
+                        self.has_opaque_import = true;
+                    }
+                    for item in &import_stmt.imports {
+                        self.imports.insert(item.alias.clone().unwrap_or_else(|| item.name.clone()));
+                    }
+                }
+                StatementKind::ExportStmt(inner) | StatementKind::ExportDefault(inner) => {
+                    if let StatementKind::VariableDecl(var_decl) = &inner.kind {
+                        self.globals.insert(var_decl.name.clone());
+                    }
+                    self.collect_declarations(std::slice::from_ref(inner))
+                }
+                StatementKind::VisibilityStmt(_, inner) => self.collect_declarations(std::slice::from_ref(inner)),
+                _ => {}
+            }
+        }
+    }
+
+    fn check_statements(&mut self, statements: &[Statement]) -> Result<(), String> {
+        self.collect_declarations(statements);
+        for stmt in statements {
+            self.check_statement(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn check_statement(&mut self, stmt: &Statement) -> Result<(), String> {
+        let result = self.check_statement_kind(stmt);
+        if result.is_err() {
+            self.error_span.get_or_insert(stmt.span);
+        }
+        result
+    }
+
+    fn check_statement_kind(&mut self, stmt: &Statement) -> Result<(), String> {
+        match &stmt.kind {
+            StatementKind::ExportStmt(inner) | StatementKind::ExportDefault(inner) => match &inner.kind {
+                StatementKind::VariableDecl(var_decl) => {
+                    let is_literal = matches!(
+                        var_decl.value.kind,
+                        ExpressionKind::NumberLiteral(_)
+                            | ExpressionKind::StringLiteral(_)
+                            | ExpressionKind::BooleanLiteral(_)
+                            | ExpressionKind::ArrayLiteral(_)
+                    );
+                    if !var_decl.is_const && var_decl.var_type.is_none() && !is_literal {
+                        return Err(format!(
+                            "Exported variable '{}' needs a type annotation",
+                            var_decl.name
+                        ));
+                    }
+                    if let Some(var_type) = &var_decl.var_type {
+                        self.check_annotation(var_type, var_decl.type_span)?;
+                        check_array_size(var_type, &var_decl.value)?;
+                    }
+                    self.check_expression(&var_decl.value)
+                }
+                _ => self.check_statement(inner),
+            },
+            StatementKind::VisibilityStmt(_, inner) => self.check_statement(inner),
+            StatementKind::VariableDecl(var_decl) => {
+                if let Some(var_type) = &var_decl.var_type {
+                    self.check_annotation(var_type, var_decl.type_span)?;
+                    check_array_size(var_type, &var_decl.value)?;
+                }
+                self.check_expression(&var_decl.value)?;
+                if var_decl.is_const {
+                    return Ok(());
+                }
+                let var_type = var_decl.var_type.clone().or_else(|| self.value_type(&var_decl.value));
+                self.declare(&var_decl.name, var_type);
+                Ok(())
+            }
+            StatementKind::DestructureDecl(destructure) => {
+                self.check_expression(&destructure.value)?;
+                for name in &destructure.names {
+                    self.declare(name, None);
+                }
+                Ok(())
+            }
+            StatementKind::FunctionDecl(func_decl) => {
+                for param in &func_decl.parameters {
+                    self.check_annotation(&param.param_type, Some(param.type_span))?;
+                }
+                self.check_annotation(&func_decl.return_type, Some(func_decl.return_type_span))?;
+                self.scopes.push(Scope::function());
+                for param in &func_decl.parameters {
+                    self.declare(&param.name, Some(param.param_type.clone()));
+                }
+                let result = self.check_statements(&func_decl.body);
+                self.scopes.pop();
+                result
+            }
+            StatementKind::PrintStmt(print_stmt) => {
+                print_stmt.arguments.iter().try_for_each(|argument| self.check_expression(argument))
+            }
+            StatementKind::ReturnStmt(ret_stmt) => match &ret_stmt.value {
+                Some(value) => self.check_expression(value),
+                None => Ok(()),
+            },
+            StatementKind::ExpressionStmt(expr_stmt) => self.check_expression(&expr_stmt.expression),
+            StatementKind::ThrowStmt(throw_stmt) => self.check_expression(&throw_stmt.expression),
+            StatementKind::IfElse(if_else) => {
+                self.check_expression(&if_else.condition)?;
+                self.check_condition(&if_else.condition)?;
+                self.check_block(&if_else.then_body, &[])?;
+                match &if_else.else_body {
+                    Some(else_body) => self.check_block(else_body, &[]),
+                    None => Ok(()),
+                }
+            }
+            StatementKind::ForLoop(for_loop) => {
+                self.check_expression(&for_loop.iterable)?;
+                self.check_block(&for_loop.body, std::slice::from_ref(&for_loop.variable))
+            }
+            StatementKind::WhileLoop(while_loop) => {
+                self.check_expression(&while_loop.condition)?;
+                self.check_condition(&while_loop.condition)?;
+                self.check_block(&while_loop.body, &[])
+            }
+            StatementKind::TryCatch(try_catch) => {
+                self.check_block(&try_catch.try_body, &[])?;
+                let catch_locals: Vec<String> = try_catch.catch_param.iter().cloned().collect();
+                self.check_block(&try_catch.catch_body, &catch_locals)
+            }
+            StatementKind::Block(body) | StatementKind::DeferStmt(body) => self.check_block(body, &[]),
+            StatementKind::CfgBlock(block) => self.check_block(&block.body, &[]),
+            StatementKind::LockStmt(lock_stmt) => {
+                self.check_identifier(&lock_stmt.name)?;
+                self.check_block(&lock_stmt.body, &[])
+            }
+            StatementKind::TestBlock(test_block) => {
+                if self.scopes.len() > 1 {
+                    return Err(format!(
+                        "Test blocks must be declared at the top level of a file (found test \"{}\")",
+                        test_block.name
+                    ));
+                }
+                self.scopes.push(Scope::function());
+                let result = self.check_statements(&test_block.body);
+                self.scopes.pop();
+                result
+            }
+            StatementKind::StructDecl(struct_decl) => {
+                for field in &struct_decl.fields {
+                    self.check_annotation(&field.field_type, Some(field.type_span))?;
+                }
+                let field_types: Vec<&Type> = struct_decl.fields.iter().map(|field| &field.field_type).collect();
+                self.check_derived_fields(&struct_decl.name, &struct_decl.attributes, &field_types)
+            }
+            StatementKind::EnumDecl(enum_decl) => {
+                let field_types: Vec<&Type> = enum_decl
+                    .variants
+                    .iter()
+                    .flat_map(|variant| {
+                        let named = variant.named_fields.iter().flatten().map(|field| &field.field_type);
+                        variant.fields.iter().flatten().chain(named)
+                    })
+                    .collect();
+                field_types.iter().try_for_each(|field_type| self.check_type(field_type))?;
+                self.check_derived_fields(&enum_decl.name, &enum_decl.attributes, &field_types)
+            }
+            StatementKind::Match(match_stmt) => {
+                self.check_expression(&match_stmt.subject)?;
+                for arm in &match_stmt.arms {
+                    let mut locals = Vec::new();
+                    if let Pattern::Variant { enum_name, variant, bindings } = &arm.pattern {
+                        let declared = self.find_variant(enum_name, variant)?;
+                        locals = self.pattern_locals(declared, bindings)?;
+                    }
+                    self.scopes.push(Scope::default());
+                    for (name, var_type) in locals {
+                        self.declare(&name, var_type);
+                    }
+                    let result = self.check_statements(&arm.body);
+                    self.scopes.pop();
+                    result?;
+                }
+                self.check_exhaustive(match_stmt)
+            }
+            StatementKind::ImportStmt(import_stmt) => check_std_import(import_stmt),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_block(&mut self, statements: &[Statement], locals: &[String]) -> Result<(), String> {
+        self.scopes.push(Scope::default());
+        for local in locals {
+            self.declare(local, None);
+        }
+        let result = self.check_statements(statements);
+        if let Some(scope) = self.scopes.pop() {
+            if let Some(function_scope) = self.scopes.iter_mut().rev().find(|s| s.is_function) {
+                function_scope.expired.extend(scope.locals.into_keys());
+            }
+        }
+        result
+    }
+
+    fn check_expression(&mut self, expr: &Expression) -> Result<(), String> {
+        let result = self.check_expression_kind(expr);
+        if result.is_err() {
+            self.error_span.get_or_insert(expr.span);
+        }
+        result
+    }
+
+    fn check_expression_kind(&mut self, expr: &Expression) -> Result<(), String> {
+        match &expr.kind {
+            ExpressionKind::Identifier(name) => self.check_identifier(name),
+            ExpressionKind::Assignment { target, value } => {
+                if let ExpressionKind::MemberAccess { object, member } = &target.kind {
+                    self.check_readonly_assignment(object, member)?;
+                }
+                self.check_expression(target)?;
+                self.check_expression(value)
+            }
+            ExpressionKind::BinaryOp(left, op, right) => {
+                self.check_expression(left)?;
+                self.check_expression(right)?;
+                if matches!(op, BinaryOp::And | BinaryOp::Or) {
+                    self.check_condition(left)?;
+                    self.check_condition(right)?;
+                }
+                self.check_operands(left, *op, right)?;
+                self.check_comparison(left, *op, right)
+            }
+            ExpressionKind::FunctionCall(name, args) => {
+                args.iter().try_for_each(|arg| self.check_expression(arg))?;
+                self.check_call(name, args)
+            }
+            ExpressionKind::ArrayLiteral(elements) => {
+                elements.iter().try_for_each(|element| self.check_expression(element))
+            }
+            ExpressionKind::MethodCall { object, method, arguments } if self.enum_of(object).is_some() => {
+                arguments.iter().try_for_each(|arg| self.check_expression(arg))?;
+                let enum_name = self.enum_of(object).unwrap_or_default();
+                self.check_variant_value(enum_name, method, Some(arguments))
+            }
+            ExpressionKind::MethodCall { object, method, arguments } => {
+                self.check_expression(object)?;
+                arguments.iter().try_for_each(|arg| self.check_expression(arg))?;
+                self.check_method(object, method, arguments)
+            }
+            ExpressionKind::StructLiteral { name, fields } => {
+                self.check_type(&Type::Custom(name.clone()))?;
+                fields.iter().try_for_each(|(_, value)| self.check_expression(value))?;
+                let Some(struct_decl) = self.structs.get(name) else {
+                    return Ok(());
+                };
+                for (field_name, _) in fields {
+                    if !struct_decl.fields.iter().any(|field| &field.name == field_name) {
+                        return Err(match suggest(field_name, struct_decl.fields.iter().map(|field| field.name.as_str())) {
+                            Some(candidate) => {
+                                format!("Struct '{}' has no field '{}'. Did you mean `{}`?", name, field_name, candidate)
+                            }
+                            None => format!("Struct '{}' has no field '{}'", name, field_name),
+                        });
+                    }
+                }
+                let missing = struct_decl
+                    .fields
+                    .iter()
+                    .find(|field| !field.may_omit() && !fields.iter().any(|(name, _)| *name == field.name));
+                match missing {
+                    Some(missing) => Err(format!("Missing field '{}' in '{}'", missing.name, name)),
+                    None => Ok(()),
+                }
+            }
+            ExpressionKind::VariantLiteral { enum_name, variant, fields } => {
+                fields.iter().try_for_each(|(_, value)| self.check_expression(value))?;
+                let Some(declared) = self.find_variant(enum_name, variant)? else {
+                    return Ok(());
+                };
+                let Some(declared_fields) = &declared.named_fields else {
+                    return Err(format!("Variant '{}.{}' has no named fields", enum_name, variant));
+                };
+                for (field_name, _) in fields {
+                    if !declared_fields.iter().any(|field| &field.name == field_name) {
+                        return Err(match suggest(field_name, declared_fields.iter().map(|field| field.name.as_str())) {
+                            Some(candidate) => format!(
+                                "Variant '{}.{}' has no field '{}'. Did you mean `{}`?",
+                                enum_name, variant, field_name, candidate
+                            ),
+                            None => format!("Variant '{}.{}' has no field '{}'", enum_name, variant, field_name),
+                        });
+                    }
+                }
+                match declared_fields.iter().find(|field| !fields.iter().any(|(name, _)| *name == field.name)) {
+                    Some(missing) => Err(format!("Missing field '{}' in '{}.{}'", missing.name, enum_name, variant)),
+                    None => Ok(()),
+                }
+            }
+            ExpressionKind::IndexAccess { object, index } => {
+                self.check_expression(object)?;
+                self.check_expression(index)
+            }
+            ExpressionKind::SliceAccess { object, start, end } => {
+                self.check_expression(object)?;
+                start.iter().chain(end).try_for_each(|bound| self.check_expression(bound))
+            }
+            ExpressionKind::MemberAccess { object, member } if self.enum_of(object).is_some() => {
+                let enum_name = self.enum_of(object).unwrap_or_default();
+                self.check_variant_value(enum_name, member, None)
+            }
+            ExpressionKind::MemberAccess { object, .. } => self.check_expression(object),
+            ExpressionKind::Shared { value_type, value } => {
+                self.check_type(value_type)?;
+                self.check_expression(value)
+            }
+            ExpressionKind::Channel(element_type) => self.check_type(element_type),
+            ExpressionKind::Closure { parameters, body } => {
+                for param in parameters {
+                    self.check_annotation(&param.param_type, Some(param.type_span))?;
+                }
+                self.scopes.push(Scope::default());
+                for param in parameters {
+                    let param_type = (param.param_type != Type::Inferred).then(|| param.param_type.clone());
+                    self.declare(&param.name, param_type);
+                }
+                let result = self.check_statements(body);
+                self.scopes.pop();
+                result
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_identifier(&self, name: &str) -> Result<(), String> {
+        let mut crossed_function = false;
+        for scope in self.scopes.iter().rev() {
+            if scope.locals.contains_key(name) {
+                if crossed_function {
+                    return Err(format!(
+                        "Nested function cannot capture local variable '{}' from an enclosing function",
+                        name
+                    ));
+                }
+                return Ok(());
+            }
+            if scope.is_function {
+                if !crossed_function && scope.expired.contains(name) {
+                    return Err(format!("Variable '{}' is not in scope", name));
+                }
+                crossed_function = true;
+            }
+        }
+        if self.is_known(name) {
+            return Ok(());
+        }
+        let candidates = self
+            .scopes
+            .iter()
+            .flat_map(|scope| scope.locals.keys())
+            .chain(&self.globals)
+            .chain(self.functions.keys())
+            .chain(&self.imports)
+            .map(String::as_str);
+        Err(match suggest(name, candidates) {
+            Some(candidate) => format!("Cannot find variable '{}' in this scope. Did you mean `{}`?", name, candidate),
+            None => format!("Cannot find variable '{}' in this scope", name),
+        })
+    }
+
+    fn check_annotation(&mut self, ty: &Type, span: Option<Span>) -> Result<(), String> {
+        let result = self.check_type(ty);
+        if let (Err(_), Some(span)) = (&result, span) {
+            self.error_span = Some(span);
+        }
+        result
+    }
+
+    fn check_type(&self, ty: &Type) -> Result<(), String> {
+        match ty {
+            Type::Custom(name) => {
+                if self.is_known(name) {
+                    return Ok(());
+                }
+                let candidates = BUILTIN_TYPES.iter().copied().chain(self.types.iter().map(String::as_str));
+                Err(match suggest(name, candidates) {
+                    Some(candidate) => format!("Cannot find type '{}'. Did you mean `{}`?", name, candidate),
+                    None => format!("Cannot find type '{}'", name),
+                })
+            }
+            Type::Array { element_type, .. } => self.check_type(element_type),
+            Type::Shared(inner) => self.check_type(inner),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_call(&self, name: &str, args: &[Expression]) -> Result<(), String> {
+        if self.is_local(name) {
+            return Ok(());
+        }
+        let Some(func_decl) = self.functions.get(name) else {
+            if let Some((_, required)) = ASSERTIONS.iter().find(|(assertion, _)| *assertion == name) {
+                if args.len() != *required && args.len() != required + 1 {
+                    return Err(format!(
+                        "Function '{}' expects {} or {} argument(s) but got {}",
+                        name,
+                        required,
+                        required + 1,
+                        args.len()
+                    ));
+                }
+                return Ok(());
+            }
+            if let Some((_, params, required)) = BUILTIN_FUNCTIONS.iter().find(|(builtin, _, _)| *builtin == name) {
+                check_argument_count("Function", name, args.len(), params.len(), *required)?;
+                return check_argument_types(name, args, params.iter());
+            }
+            if self.is_known(name) {
+                return Ok(());
+            }
+            let candidates = self.functions.keys().chain(&self.imports).map(String::as_str);
+            return Err(match suggest(name, candidates) {
+                Some(candidate) => format!("Cannot find function '{}'. Did you mean `{}`?", name, candidate),
+                None => format!("Cannot find function '{}'", name),
+            });
+        };
+        if args.len() != func_decl.parameters.len() {
+            return Err(format!(
+                "Function '{}' expects {} argument(s) but got {}",
+                name,
+                func_decl.parameters.len(),
+                args.len()
+            ));
+        }
+        check_argument_types(name, args, func_decl.parameters.iter().map(|param| &param.param_type))
+    }
+
+    fn check_method(&self, object: &Expression, method: &str, args: &[Expression]) -> Result<(), String> {
+        if matches!(&object.kind, ExpressionKind::Identifier(name) if name == "console" && !self.is_local(name))
+            && !CONSOLE_METHODS.contains(&method)
+        {
+            return Err(match suggest(method, CONSOLE_METHODS.iter().copied()) {
+                Some(candidate) => format!("'console' has no method '{}'. Did you mean `{}`?", method, candidate),
+                None => format!("'console' has no method '{}'", method),
+            });
+        }
+        let receiver_type = match &object.kind {
+            ExpressionKind::StringLiteral(_) => Some(&Type::String),
+            ExpressionKind::NumberLiteral(_) => Some(&Type::Number),
+            ExpressionKind::ArrayLiteral(_) => Some(&Type::Array { element_type: Box::new(Type::Inferred), size: None }),
+            ExpressionKind::Identifier(name) => self.lookup_type(name),
+            _ => None,
+        };
+        let methods = match receiver_type {
+            Some(Type::String) => STRING_METHODS,
+            Some(Type::Number) => NUMBER_METHODS,
+            Some(Type::Array { .. }) => ARRAY_METHODS,
+            _ => return Ok(()),
+        };
+        let Some((_, params, required)) = methods.iter().find(|(name, _, _)| *name == method) else {
+            return Ok(());
+        };
+        check_argument_count("Method", method, args.len(), params.len(), *required)?;
+        if let Some((_, max)) = CALLBACK_PARAMETERS.iter().find(|(name, _)| *name == method) {
+            match args.first().map(|arg| &arg.kind) {
+                Some(ExpressionKind::Closure { parameters, .. }) if parameters.len() > *max => {
+                    return Err(format!(
+                        "Callback of '{}' takes at most {} parameter(s) but got {}",
+                        method,
+                        max,
+                        parameters.len()
+                    ));
+                }
+                Some(
+                    ExpressionKind::NumberLiteral(_)
+                    | ExpressionKind::StringLiteral(_)
+                    | ExpressionKind::BooleanLiteral(_),
+                ) => return Err(format!("Argument 1 of '{}' expects a function", method)),
+                _ => {}
+            }
+        }
+        check_argument_types(method, args, params.iter())
+    }
+
+    /// The declaration of `enum_name.variant`, or `None` for enums declared
+    /// elsewhere, like imported ones
+    fn find_variant(&self, enum_name: &str, variant: &str) -> Result<Option<&EnumVariant>, String> {
+        let Some(enum_decl) = self.enums.get(enum_name) else {
+            self.check_type(&Type::Custom(enum_name.to_string()))?;
+            return Ok(None);
+        };
+        match enum_decl.variants.iter().find(|declared| declared.name == variant) {
+            Some(declared) => Ok(Some(declared)),
+            None => Err(match suggest(variant, enum_decl.variants.iter().map(|declared| declared.name.as_str())) {
+                Some(candidate) => format!(
+                    "Enum '{}' has no variant '{}'. Did you mean `{}`?",
+                    enum_name, variant, candidate
+                ),
+                None => format!("Enum '{}' has no variant '{}'", enum_name, variant),
+            }),
+        }
+    }
+
+    /// The type of `expr` when it is evident without inference: literals,
+    /// typed variables and enum values
+    fn value_type(&self, expr: &Expression) -> Option<Type> {
+        match &expr.kind {
+            ExpressionKind::Identifier(name) => self.lookup_type(name).cloned(),
+            ExpressionKind::StructLiteral { name, .. } => Some(Type::Custom(name.clone())),
+            ExpressionKind::VariantLiteral { enum_name, .. } => Some(Type::Custom(enum_name.clone())),
+            ExpressionKind::MemberAccess { object, .. } | ExpressionKind::MethodCall { object, .. } => {
+                self.enum_of(object).map(|name| Type::Custom(name.to_string()))
+            }
+            ExpressionKind::StringLiteral(_) => Some(Type::String),
+            ExpressionKind::NumberLiteral(_) => Some(Type::Number),
+            ExpressionKind::BooleanLiteral(_) => Some(Type::Boolean),
+            ExpressionKind::ArrayLiteral(_) => Some(Type::Array {
+                element_type: Box::new(Type::Inferred),
+                size: None,
+            }),
+            ExpressionKind::BinaryOp(left, op, right) => match op {
+                BinaryOp::Add => match (self.value_type(left), self.value_type(right)) {
+                    (Some(Type::String), _) | (_, Some(Type::String)) => Some(Type::String),
+                    (Some(Type::Number), Some(Type::Number)) => Some(Type::Number),
+                    _ => None,
+                },
+                BinaryOp::Subtract | BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo => {
+                    match (self.value_type(left), self.value_type(right)) {
+                        (Some(Type::Number), Some(Type::Number)) => Some(Type::Number),
+                        _ => None,
+                    }
+                }
+                _ => Some(Type::Boolean),
+            },
+            ExpressionKind::FunctionCall(name, _) if !self.is_local(name) => self
+                .functions
+                .get(name)
+                .map(|func_decl| func_decl.return_type.clone())
+                .filter(|return_type| !matches!(return_type, Type::Void | Type::Inferred | Type::Any)),
+            _ => None,
+        }
+    }
+
+    /// `if` and `while` conditions and the operands of `&&` and `||` must be
+    /// booleans; other values have no truthiness
+    fn check_condition(&self, condition: &Expression) -> Result<(), String> {
+        let Some(condition_type) = self.value_type(condition) else {
+            return Ok(());
+        };
+        let name = match &condition.kind {
+            ExpressionKind::Identifier(name) => name.as_str(),
+            _ => "value",
+        };
+        let (described, hint) = match condition_type {
+            Type::Boolean => return Ok(()),
+            Type::Number => ("a number", format!("`{} != 0`", name)),
+            Type::String => ("a string", format!("`{}.length > 0`", name)),
+            Type::Array { .. } => ("an array", format!("`{}.length > 0`", name)),
+            _ => return Err("Condition must be a boolean".to_string()),
+        };
+        Err(format!("Condition must be a boolean, but got {}. Test it explicitly, as in {}", described, hint))
+    }
+
+    /// `+` joins a string with any value, but the other operators need both
+    /// sides to be numbers, or the same type for comparisons
+    fn check_operands(&self, left: &Expression, op: BinaryOp, right: &Expression) -> Result<(), String> {
+        let (Some(left_type), Some(right_type)) = (self.value_type(left), self.value_type(right)) else {
+            return Ok(());
+        };
+        let primitive = |ty: &Type| matches!(ty, Type::Number | Type::String | Type::Boolean);
+        match op {
+            BinaryOp::Subtract | BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo
+                if left_type == Type::String || right_type == Type::String =>
+            {
+                Err(format!("Operator '{}' cannot be used on a string. Only '+' joins strings", op.symbol()))
+            }
+            BinaryOp::Equal
+            | BinaryOp::NotEqual
+            | BinaryOp::Greater
+            | BinaryOp::GreaterEqual
+            | BinaryOp::Less
+            | BinaryOp::LessEqual
+                if primitive(&left_type) && primitive(&right_type) && left_type != right_type =>
+            {
+                Err(format!(
+                    "Cannot compare a {} with a {} using '{}'",
+                    type_name(&left_type),
+                    type_name(&right_type),
+                    op.symbol()
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// `==` on structs and ordering on structs and enums only work for types
+    /// that opt in. Enums always support `==`.
+    fn check_comparison(&self, left: &Expression, op: BinaryOp, right: &Expression) -> Result<(), String> {
+        let (required, capability) = match op {
+            BinaryOp::Equal | BinaryOp::NotEqual => ("PartialEq", "equatable"),
+            BinaryOp::Greater | BinaryOp::GreaterEqual | BinaryOp::Less | BinaryOp::LessEqual => {
+                ("PartialOrd", "comparable")
+            }
+            _ => return Ok(()),
+        };
+        for operand in [left, right] {
+            let Some(Type::Custom(name)) = self.value_type(operand) else {
+                continue;
+            };
+            let supported = match (self.structs.get(&name), self.enums.get(&name)) {
+                (Some(struct_decl), _) => derives(&struct_decl.attributes, required),
+                (None, Some(enum_decl)) => required == "PartialEq" || derives(&enum_decl.attributes, required),
+                (None, None) => true,
+            };
+            if !supported {
+                let kind = if self.structs.contains_key(&name) { "struct" } else { "enum" };
+                return Err(format!(
+                    "Cannot compare '{}' values with '{}'. Mark {} '{}' @{}",
+                    name,
+                    op.symbol(),
+                    kind,
+                    name,
+                    capability
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// A derived `Eq`, `Ord` or `Hash` needs the same trait on every field type
+    /// declared in this file
+    fn check_derived_fields(&self, name: &str, attributes: &[Attribute], field_types: &[&Type]) -> Result<(), String> {
+        for (required, capability) in [("Eq", "equatable"), ("Ord", "comparable"), ("Hash", "hashable")] {
+            if !derives(attributes, required) {
+                continue;
+            }
+            for field_type in field_types {
+                let mut field_type = *field_type;
+                while let Type::Array { element_type, .. } = field_type {
+                    field_type = element_type;
+                }
+                let Type::Custom(field_name) = field_type else {
+                    continue;
+                };
+                let field_attributes = match (self.structs.get(field_name), self.enums.get(field_name)) {
+                    (Some(struct_decl), _) => &struct_decl.attributes,
+                    (None, Some(enum_decl)) => &enum_decl.attributes,
+                    (None, None) => continue,
+                };
+                if !derives(field_attributes, required) {
+                    return Err(format!(
+                        "'{}' cannot be @{} because its field type '{}' is not. Mark '{}' @{}",
+                        name, capability, field_name, field_name, capability
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The enum `expr` names, unless a variable of the same name hides it
+    fn enum_of<'a>(&self, expr: &'a Expression) -> Option<&'a str> {
+        match &expr.kind {
+            ExpressionKind::Identifier(name) if self.enums.contains_key(name) && !self.is_local(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// `Enum.Variant` (`args` is `None`) or `Enum.Variant(args)` used as a value
+    fn check_variant_value(&self, enum_name: &str, variant: &str, args: Option<&[Expression]>) -> Result<(), String> {
+        let Some(declared) = self.find_variant(enum_name, variant)? else {
+            return Ok(());
+        };
+        let name = format!("{}.{}", enum_name, variant);
+        if declared.named_fields.is_some() {
+            return Err(format!("Variant '{}' has named fields; build it with `{} {{ ... }}`", name, name));
+        }
+        let params = declared.fields.as_deref().unwrap_or_default();
+        let args = args.unwrap_or_default();
+        check_argument_count("Variant", &name, args.len(), params.len(), params.len())?;
+        check_argument_types(&name, args, params.iter())
+    }
+
+    /// Every variant of the matched enum needs an arm unless there is a `_` arm,
+    /// as Rust requires
+    fn check_exhaustive(&self, match_stmt: &MatchStmt) -> Result<(), String> {
+        let mut enum_name = None;
+        let mut handled = HashSet::new();
+        for arm in &match_stmt.arms {
+            match &arm.pattern {
+                Pattern::Wildcard => return Ok(()),
+                Pattern::Variant { enum_name: name, variant, .. } => {
+                    enum_name.get_or_insert(name);
+                    handled.insert(variant.as_str());
+                }
+            }
+        }
+        let Some(enum_decl) = enum_name.and_then(|name| self.enums.get(name)) else {
+            return Ok(());
+        };
+        let missing: Vec<String> = enum_decl
+            .variants
+            .iter()
+            .filter(|variant| !handled.contains(variant.name.as_str()))
+            .map(|variant| format!("`{}.{}`", enum_decl.name, variant.name))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(format!(
+            "Non-exhaustive match on '{}': {} not handled. Add the missing arms or a `_` arm",
+            enum_decl.name,
+            missing.join(", ")
+        ))
+    }
+
+    /// The variables a `match` pattern binds, checked against the variant's fields
+    fn pattern_locals(
+        &self,
+        declared: Option<&EnumVariant>,
+        bindings: &PatternBindings,
+    ) -> Result<Vec<(String, Option<Type>)>, String> {
+        let Some(declared) = declared else {
+            return Ok(bindings.names().into_iter().map(|name| (name.to_string(), None)).collect());
+        };
+        match (bindings, &declared.fields, &declared.named_fields) {
+            (PatternBindings::None, _, _) => Ok(Vec::new()),
+            (PatternBindings::Tuple(names), Some(types), _) => {
+                if names.len() != types.len() {
+                    return Err(format!(
+                        "Variant '{}' has {} field(s) but the pattern binds {}",
+                        declared.name,
+                        types.len(),
+                        names.len()
+                    ));
+                }
+                Ok(names.iter().cloned().zip(types.iter().cloned().map(Some)).collect())
+            }
+            (PatternBindings::Named(bound), _, Some(fields)) => bound
+                .iter()
+                .map(|(field_name, binding)| match fields.iter().find(|field| &field.name == field_name) {
+                    Some(field) => Ok((binding.clone(), Some(field.field_type.clone()))),
+                    None => Err(format!("Variant '{}' has no field '{}'", declared.name, field_name)),
+                })
+                .collect(),
+            (PatternBindings::Tuple(_), _, _) => {
+                Err(format!("Variant '{}' has no positional fields to bind", declared.name))
+            }
+            (PatternBindings::Named(_), _, _) => Err(format!("Variant '{}' has no named fields to bind", declared.name)),
+        }
+    }
+
+    fn check_readonly_assignment(&self, object: &Expression, member: &str) -> Result<(), String> {
+        let ExpressionKind::Identifier(var_name) = &object.kind else {
+            return Ok(());
+        };
+        let Some(Type::Custom(struct_name)) = self.lookup_type(var_name) else {
+            return Ok(());
+        };
+        let Some(struct_decl) = self.structs.get(struct_name) else {
+            return Ok(());
+        };
+        if struct_decl.fields.iter().any(|field| field.name == member && field.is_readonly) {
+            return Err(format!(
+                "Cannot assign to readonly field '{}' of struct '{}'",
+                member, struct_decl.name
+            ));
+        }
+        Ok(())
+    }
+
+    fn declare(&mut self, name: &str, var_type: Option<Type>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.locals.insert(name.to_string(), var_type);
+        }
+    }
+
+    fn is_known(&self, name: &str) -> bool {
+        self.has_opaque_import
+            || self.is_local(name)
+            || self.functions.contains_key(name)
+            || self.types.contains(name)
+            || self.globals.contains(name)
+            || self.imports.contains(name)
+            || PRELUDE_NAMES.contains(&name)
+    }
+
+    fn is_local(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.locals.contains_key(name))
+    }
+
+    fn lookup_type(&self, name: &str) -> Option<&Type> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.locals.get(name))
+            .and_then(|var_type| var_type.as_ref())
+    }
+}
+
+fn check_array_size(declared: &Type, value: &Expression) -> Result<(), String> {
+    let (Type::Array { element_type, size }, ExpressionKind::ArrayLiteral(elements)) = (declared, &value.kind) else {
+        return Ok(());
+    };
+    if let Some(size) = size.filter(|size| *size != elements.len()) {
+        return Err(format!(
+            "Array literal has {} element(s) but its declared size is {}",
+            elements.len(),
+            size
+        ));
+    }
+    elements.iter().try_for_each(|element| check_array_size(element_type, element))
+}
+
+fn check_std_import(import_stmt: &ImportStmt) -> Result<(), String> {
+    let Some(module) = import_stmt.std_module() else {
+        return Ok(());
+    };
+    let Some((_, exports)) = STD_MODULES.iter().find(|(name, _)| *name == module) else {
+        return Err(match suggest(module, STD_MODULES.iter().map(|(name, _)| *name)) {
+            Some(candidate) => format!("Cannot find module 'std/{}'. Did you mean `std/{}`?", module, candidate),
+            None => format!("Cannot find module 'std/{}'", module),
+        });
+    };
+    match import_stmt.imports.iter().find(|item| !exports.contains(&item.name.as_str())) {
+        Some(item) => Err(match suggest(&item.name, exports.iter().copied()) {
+            Some(candidate) => format!(
+                "Module 'std/{}' has no exported member '{}'. Did you mean `{}`?",
+                module, item.name, candidate
+            ),
+            None => format!("Module 'std/{}' has no exported member '{}'", module, item.name),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn check_argument_count(kind: &str, name: &str, found: usize, params: usize, required: usize) -> Result<(), String> {
+    if (required..=params).contains(&found) {
+        return Ok(());
+    }
+    let expected = if required == params {
+        required.to_string()
+    } else {
+        format!("{} to {}", required, params)
+    };
+    Err(format!("{} '{}' expects {} argument(s) but got {}", kind, name, expected, found))
+}
+
+fn check_argument_types<'a>(
+    name: &str,
+    args: &[Expression],
+    params: impl Iterator<Item = &'a Type>,
+) -> Result<(), String> {
+    for (i, (arg, expected)) in args.iter().zip(params).enumerate() {
+        let actual = match &arg.kind {
+            ExpressionKind::NumberLiteral(_) => Type::Number,
+            ExpressionKind::StringLiteral(_) => Type::String,
+            ExpressionKind::BooleanLiteral(_) => Type::Boolean,
+            _ => continue,
+        };
+        if matches!(expected, Type::Number | Type::String | Type::Boolean) && *expected != actual {
+            return Err(format!(
+                "Argument {} of '{}' expects {} but got {}",
+                i + 1,
+                name,
+                type_name(expected),
+                type_name(&actual)
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn type_name(ty: &Type) -> &'static str {
+    match ty {
+        Type::Number => "number",
+        Type::String => "string",
+        Type::Boolean => "boolean",
+        _ => "value",
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use crate::ast::*;
+use crate::visit::{walk_expression, walk_statement, Visitor};
+use super::helpers::collect_mutated_names;
+
+/// Parameters that are never mutated or moved can be taken as `&str`/`&[T]`.
+pub fn infer_borrows(statements: &[Statement]) -> HashMap<String, Vec<bool>> {
+    let mut collector = FunctionCollector { functions: Vec::new() };
+    for stmt in statements {
+        collector.visit_statement(stmt);
+    }
+    let all = collector.functions;
+    let functions: Vec<&FunctionDecl> = all
+        .iter()
+        .copied()
+        .filter(|func_decl| func_decl.name != "main")
+        .filter(|func_decl| all.iter().filter(|other| other.name == func_decl.name).count() == 1)
+        .collect();
+
+    let mut borrows: HashMap<String, Vec<bool>> = functions
+        .iter()
+        .map(|func_decl| {
+            let mut mutated = Default::default();
+            collect_mutated_names(&func_decl.body, &mut mutated);
+            let candidates = func_decl
+                .parameters
+                .iter()
+                .map(|param| is_borrowable_type(&param.param_type) && !mutated.contains(&param.name))
+                .collect();
+            (func_decl.name.clone(), candidates)
+        })
+        .collect();
+
+    loop {
+        let mut changed = false;
+        for func_decl in &functions {
+            for (i, param) in func_decl.parameters.iter().enumerate() {
+                if !borrows[&func_decl.name][i] {
+                    continue;
+                }
+                let mut finder = MoveFinder::new(&param.name, &param.param_type, &borrows);
+                for stmt in &func_decl.body {
+                    finder.visit_statement(stmt);
+                }
+                if finder.moved {
+                    if let Some(params) = borrows.get_mut(&func_decl.name) {
+                        params[i] = false;
+                    }
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return borrows;
+        }
+    }
+}
+
+fn is_borrowable_type(ty: &Type) -> bool {
+    matches!(ty, Type::String | Type::Any | Type::Array { size: None, .. })
+}
+
+fn is_copy_type(element_type: &Type) -> bool {
+    matches!(element_type, Type::Number | Type::Boolean)
+}
+
+struct FunctionCollector<'ast> {
+    functions: Vec<&'ast FunctionDecl>,
+}
+
+impl<'ast> Visitor<'ast> for FunctionCollector<'ast> {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        if let StatementKind::FunctionDecl(func_decl) = &stmt.kind {
+            self.functions.push(func_decl);
+        }
+        walk_statement(self, stmt);
+    }
+}
+
+struct MoveFinder<'a> {
+    name: &'a str,
+    ty: &'a Type,
+    borrows: &'a HashMap<String, Vec<bool>>,
+    moved: bool,
+}
+
+impl<'a> MoveFinder<'a> {
+    fn new(name: &'a str, ty: &'a Type, borrows: &'a HashMap<String, Vec<bool>>) -> Self {
+        MoveFinder { name, ty, borrows, moved: false }
+    }
+
+    fn borrows_in_loop(&self, for_loop: &ForLoopStmt) -> bool {
+        let Type::Array { element_type, size: None } = self.ty else {
+            return false;
+        };
+        if is_copy_type(element_type) {
+            return true;
+        }
+        let mut finder = MoveFinder::new(&for_loop.variable, element_type, self.borrows);
+        for stmt in &for_loop.body {
+            finder.visit_statement(stmt);
+        }
+        !finder.moved
+    }
+
+    fn is_param(&self, expr: &Expression) -> bool {
+        matches!(&expr.kind, ExpressionKind::Identifier(name) if name == self.name)
+    }
+
+    fn visit_operand(&mut self, expr: &Expression) {
+        if !self.is_param(expr) {
+            self.visit_expression(expr);
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for MoveFinder<'_> {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        match &stmt.kind {
+            StatementKind::FunctionDecl(_) => {}
+            StatementKind::PrintStmt(PrintStmt { arguments, .. }) => {
+                arguments.iter().for_each(|argument| self.visit_operand(argument));
+            }
+            StatementKind::ThrowStmt(ThrowStmt { expression, .. }) => self.visit_operand(expression),
+            StatementKind::ForLoop(for_loop) if self.is_param(&for_loop.iterable) => {
+                self.moved |= !self.borrows_in_loop(for_loop);
+                if for_loop.variable != self.name {
+                    for stmt in &for_loop.body {
+                        self.visit_statement(stmt);
+                    }
+                }
+            }
+            _ => walk_statement(self, stmt),
+        }
+    }
+
+    fn visit_expression(&mut self, expr: &'ast Expression) {
+        match &expr.kind {
+            ExpressionKind::Identifier(name) if name == self.name => self.moved = true,
+            ExpressionKind::BinaryOp(left, BinaryOp::Add | BinaryOp::Equal | BinaryOp::NotEqual, right) => {
+                self.visit_operand(left);
+                self.visit_operand(right);
+            }
+            ExpressionKind::FunctionCall(name, args)
+                if matches!(name.as_str(), "assert" | "assertEq" | "panic" | "parseInt" | "parseFloat" | "input") =>
+            {
+                args.iter().for_each(|arg| self.visit_operand(arg));
+            }
+            ExpressionKind::FunctionCall(name, args) => {
+                for (i, arg) in args.iter().enumerate() {
+                    let borrowed = self.borrows.get(name).and_then(|params| params.get(i)) == Some(&true);
+                    if borrowed {
+                        self.visit_operand(arg);
+                    } else {
+                        self.visit_expression(arg);
+                    }
+                }
+            }
+            ExpressionKind::MethodCall { arguments, .. } if expr.is_console_call() => {
+                arguments.iter().for_each(|arg| self.visit_operand(arg));
+            }
+            ExpressionKind::MethodCall { object, arguments, .. } => {
+                self.visit_operand(object);
+                arguments.iter().for_each(|arg| self.visit_expression(arg));
+            }
+            ExpressionKind::MemberAccess { object, .. } => self.visit_operand(object),
+            ExpressionKind::IndexAccess { object, index } => {
+                self.visit_operand(object);
+                self.visit_expression(index);
+            }
+            ExpressionKind::SliceAccess { object, start, end } => {
+                self.visit_operand(object);
+                for bound in start.iter().chain(end) {
+                    self.visit_expression(bound);
+                }
+            }
+            _ => walk_expression(self, expr),
+        }
+    }
+}
+
+pub fn collect_last_uses(statements: &[Statement]) -> HashMap<String, usize> {
+    let mut collector = LastUseCollector { last_uses: HashMap::new() };
+    for stmt in statements {
+        collector.visit_statement(stmt);
+    }
+    collector.last_uses
+}
+
+struct LastUseCollector {
+    last_uses: HashMap<String, usize>,
+}
+
+impl<'ast> Visitor<'ast> for LastUseCollector {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        match &stmt.kind {
+            StatementKind::FunctionDecl(_) | StatementKind::StructDecl(_) | StatementKind::TestBlock(_) => {}
+            _ => walk_statement(self, stmt),
+        }
+    }
+
+    fn visit_expression(&mut self, expr: &'ast Expression) {
+        if let ExpressionKind::Identifier(name) = &expr.kind {
+            let last = self.last_uses.entry(name.clone()).or_insert(expr.span.start);
+            *last = (*last).max(expr.span.start);
+        }
+        walk_expression(self, expr);
+    }
+}
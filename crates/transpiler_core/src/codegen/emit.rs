@@ -0,0 +1,1103 @@
+use crate::ir::*;
+use super::helpers::{escape_format, string_literal, DEFAULT_EXPORT};
+use super::names::NamingPolicy;
+use super::Codegen;
+
+impl Codegen {
+    pub fn emit(&mut self, program: &IrProgram) -> String {
+        for stmt in &program.items {
+            self.emit_stmt(stmt);
+        }
+
+        if let Some(main) = &program.main {
+            self.emit_naming_allow();
+            self.output.push_str("fn main() {\n");
+            self.indent_level = 1;
+            for stmt in main {
+                self.emit_stmt(stmt);
+            }
+            self.indent_level = 0;
+            self.output.push_str("}\n");
+        }
+
+        self.emit_tests(&program.tests);
+
+        if program.uses_defer {
+            self.emit_runtime_helper(&[
+                "struct DeferGuard<F: FnMut()>(F);",
+                "",
+                "impl<F: FnMut()> Drop for DeferGuard<F> {",
+                "    fn drop(&mut self) {",
+                "        (self.0)();",
+                "    }",
+                "}",
+            ]);
+        }
+
+        if program.uses_index {
+            self.emit_runtime_helper(&[
+                "fn jrust_index(index: i32, len: usize) -> usize {",
+                "    let resolved = if index < 0 { index + len as i32 } else { index };",
+                "    match usize::try_from(resolved) {",
+                "        Ok(resolved) if resolved < len => resolved,",
+                "        _ => panic!(\"index {} is out of bounds for length {}\", index, len),",
+                "    }",
+                "}",
+            ]);
+        }
+
+        if program.uses_slot {
+            self.emit_runtime_helper(&[
+                "fn jrust_slot<T>(items: &mut [T], index: i32) -> &mut T {",
+                "    let len = items.len();",
+                "    &mut items[jrust_index(index, len)]",
+                "}",
+            ]);
+        }
+
+        if program.uses_input {
+            self.emit_runtime_helper(&[
+                "fn jrust_input(prompt: &str) -> String {",
+                "    use std::io::Write;",
+                "    print!(\"{}\", prompt);",
+                "    std::io::stdout().flush().expect(\"failed to flush stdout\");",
+                "    let mut line = String::new();",
+                "    std::io::stdin().read_line(&mut line).expect(\"failed to read from stdin\");",
+                "    line.trim().to_string()",
+                "}",
+            ]);
+        }
+
+        if program.uses_at {
+            self.emit_runtime_helper(&[
+                "fn jrust_at<T: Clone>(items: &[T], index: i32) -> Option<T> {",
+                "    let resolved = if index < 0 { index + items.len() as i32 } else { index };",
+                "    usize::try_from(resolved).ok().and_then(|resolved| items.get(resolved)).cloned()",
+                "}",
+            ]);
+        }
+
+        if program.uses_panic_hook {
+            self.emit_runtime_helper(&[
+                "fn jrust_panic_hook() {",
+                "    let default_hook = std::panic::take_hook();",
+                "    std::panic::set_hook(Box::new(move |info| {",
+                "        let payload = info.payload();",
+                "        let message = match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {",
+                "            (Some(message), _) => message.to_string(),",
+                "            (_, Some(message)) => message.clone(),",
+                "            _ => \"the program panicked\".to_string(),",
+                "        };",
+                "        eprintln!(\"jRust runtime error: {}\", message);",
+                "        if std::env::var_os(\"RUST_BACKTRACE\").is_some_and(|value| value != \"0\") {",
+                "            default_hook(info);",
+                "        }",
+                "    }));",
+                "}",
+            ]);
+        }
+
+        self.output.clone()
+    }
+
+    fn emit_runtime_helper(&mut self, lines: &[&str]) {
+        if !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+        self.mark_generated();
+        for line in lines {
+            self.output.push_str(line);
+            self.output.push('\n');
+        }
+    }
+
+    fn emit_tests(&mut self, tests: &[IrTest]) {
+        if tests.is_empty() {
+            return;
+        }
+        if !self.output.is_empty() && !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+        self.mark_generated();
+        self.output.push_str("#[cfg(test)]\n");
+        self.emit_naming_allow();
+        self.output.push_str("mod tests {\n    use super::*;\n");
+        self.indent_level = 1;
+
+        for test in tests {
+            self.output.push('\n');
+            let rust_line = self.output.matches('\n').count() + 1;
+            self.source_map.push((rust_line, test.line));
+            self.emit_indent();
+            self.output.push_str("#[test]\n");
+            self.emit_indent();
+            self.output.push_str(&format!("fn {}() {{\n", test.name));
+            self.emit_body(&test.body);
+            self.emit_indent();
+            self.output.push_str("}\n");
+            self.tests.push((test.name.clone(), test.label.clone()));
+        }
+
+        self.indent_level = 0;
+        self.output.push_str("}\n");
+    }
+
+    fn mark_generated(&mut self) {
+        let rust_line = self.output.matches('\n').count() + 1;
+        self.source_map.push((rust_line, 0));
+    }
+
+    fn emit_body(&mut self, body: &[IrStmt]) {
+        self.indent_level += 1;
+        for stmt in body {
+            self.emit_stmt(stmt);
+        }
+        self.indent_level -= 1;
+    }
+
+    fn emit_stmt(&mut self, stmt: &IrStmt) {
+        if let Some(line) = stmt.line {
+            let rust_line = self.output.matches('\n').count() + 1;
+            self.source_map.push((rust_line, line));
+        }
+
+        match &stmt.kind {
+            IrStmtKind::Use { public, allow_unused, path, tree } => {
+                if *allow_unused {
+                    self.emit_indent();
+                    self.output.push_str("#[allow(unused_imports)]\n");
+                }
+                self.emit_indent();
+                if *public {
+                    self.output.push_str("pub ");
+                }
+                self.output.push_str("use ");
+                self.output.push_str(path);
+                match tree {
+                    UseTree::Glob => self.output.push_str("::*"),
+                    UseTree::Alias(alias) => self.output.push_str(&format!(" as {}", alias)),
+                    UseTree::Names(names) => {
+                        let names: Vec<String> = names
+                            .iter()
+                            .map(|(name, alias)| match alias {
+                                Some(alias) => format!("{} as {}", name, alias),
+                                None => name.clone(),
+                            })
+                            .collect();
+                        if names.len() == 1 {
+                            self.output.push_str(&format!("::{}", names[0]));
+                        } else {
+                            self.output.push_str(&format!("::{{{}}}", names.join(", ")));
+                        }
+                    }
+                }
+                self.output.push_str(";\n");
+            }
+            IrStmtKind::ReexportDefault(name) => {
+                self.emit_indent();
+                self.output.push_str(&format!("pub use self::{} as {};\n\n", name, DEFAULT_EXPORT));
+            }
+            IrStmtKind::Let { name, mutable, ty, value } => {
+                self.emit_indent();
+                self.output.push_str("let ");
+                if *mutable {
+                    self.output.push_str("mut ");
+                }
+                self.output.push_str(name);
+                if let Some(ty) = ty {
+                    self.output.push_str(": ");
+                    self.emit_ir_type(ty);
+                }
+                self.output.push_str(" = ");
+                self.emit_expr(value);
+                self.output.push_str(";\n");
+            }
+            IrStmtKind::LetTuple { names, value } => {
+                self.emit_indent();
+                self.output.push_str(&format!("let ({}) = ", names.join(", ")));
+                self.emit_expr(value);
+                self.output.push_str(";\n");
+            }
+            IrStmtKind::Const { visibility, name, ty, value } => {
+                self.emit_indent();
+                self.output.push_str(visibility.prefix());
+                self.output.push_str("const ");
+                self.output.push_str(name);
+                if let Some(ty) = ty {
+                    self.output.push_str(": ");
+                    self.emit_ir_type(ty);
+                }
+                self.output.push_str(" = ");
+                self.emit_expr(value);
+                self.output.push_str(";\n");
+            }
+            IrStmtKind::Static { name, ty, value } => {
+                if *name != name.to_lowercase() {
+                    self.emit_indent();
+                    self.output.push_str("#[allow(non_snake_case)]\n");
+                }
+                self.emit_indent();
+                self.output.push_str(&format!("pub fn {}() -> &'static std::sync::Mutex<", name));
+                self.emit_ir_type(ty);
+                self.output.push_str("> {\n");
+                self.indent_level += 1;
+                self.emit_indent();
+                self.output.push_str("static VALUE: std::sync::OnceLock<std::sync::Mutex<");
+                self.emit_ir_type(ty);
+                self.output.push_str(">> = std::sync::OnceLock::new();\n");
+                self.emit_indent();
+                self.output.push_str("VALUE.get_or_init(|| std::sync::Mutex::new(");
+                self.emit_expr(value);
+                self.output.push_str("))\n");
+                self.indent_level -= 1;
+                self.emit_indent();
+                self.output.push_str("}\n");
+            }
+            IrStmtKind::Function(function) => self.emit_function(function),
+            IrStmtKind::Struct(ir_struct) => self.emit_struct(ir_struct),
+            IrStmtKind::Enum(ir_enum) => self.emit_enum(ir_enum),
+            IrStmtKind::Print(args) => {
+                self.emit_indent();
+                self.emit_print("println", args);
+                self.output.push_str(";\n");
+            }
+            IrStmtKind::Panic(expr) => {
+                self.emit_indent();
+                self.emit_format_macro("panic", "{}", &[expr]);
+                self.output.push_str(";\n");
+            }
+            IrStmtKind::Return(value) => {
+                self.emit_indent();
+                self.output.push_str("return ");
+                if let Some(value) = value {
+                    self.emit_expr(value);
+                }
+                self.output.push_str(";\n");
+            }
+            IrStmtKind::Expr(expr) => {
+                self.emit_indent();
+                self.emit_expr(expr);
+                self.output.push_str(";\n");
+            }
+            IrStmtKind::If { condition, then_body, else_body } => {
+                self.emit_indent();
+                self.output.push_str("if ");
+                self.emit_expr(condition);
+                self.output.push_str(" {\n");
+                self.emit_body(then_body);
+                self.emit_indent();
+                if let Some(else_body) = else_body {
+                    self.output.push_str("} else {\n");
+                    self.emit_body(else_body);
+                    self.emit_indent();
+                }
+                self.output.push_str("}\n");
+            }
+            IrStmtKind::For { variable, iterable, body } => {
+                self.emit_indent();
+                self.output.push_str(&format!("for {} in ", variable));
+                self.emit_expr(iterable);
+                self.output.push_str(" {\n");
+                self.emit_body(body);
+                self.emit_indent();
+                self.output.push_str("}\n");
+            }
+            IrStmtKind::While { condition, body } => {
+                self.emit_indent();
+                self.output.push_str("while ");
+                self.emit_expr(condition);
+                self.output.push_str(" {\n");
+                self.emit_body(body);
+                self.emit_indent();
+                self.output.push_str("}\n");
+            }
+            IrStmtKind::Break => {
+                self.emit_indent();
+                self.output.push_str("break;\n");
+            }
+            IrStmtKind::Continue => {
+                self.emit_indent();
+                self.output.push_str("continue;\n");
+            }
+            IrStmtKind::Catch { body, binding, handler } => {
+                self.emit_indent();
+                self.output.push_str("match (|| -> Result<(), Box<dyn std::error::Error>> {\n");
+                self.indent_level += 1;
+                for stmt in body {
+                    self.emit_stmt(stmt);
+                }
+                self.emit_indent();
+                self.output.push_str("Ok(())\n");
+                self.indent_level -= 1;
+                self.emit_indent();
+                self.output.push_str("})() {\n");
+                self.indent_level += 1;
+                self.emit_indent();
+                self.output.push_str("Ok(_) => {},\n");
+                self.emit_indent();
+                self.output.push_str(&format!("Err({}) => {{\n", binding));
+                self.emit_body(handler);
+                self.emit_indent();
+                self.output.push_str("}\n");
+                self.indent_level -= 1;
+                self.emit_indent();
+                self.output.push_str("}\n");
+            }
+            IrStmtKind::Block(body) => {
+                self.emit_indent();
+                self.output.push_str("{\n");
+                self.emit_body(body);
+                self.emit_indent();
+                self.output.push_str("}\n");
+            }
+            IrStmtKind::Lock { guard, target, mutable, body } => {
+                self.emit_indent();
+                self.output.push_str("{\n");
+                self.indent_level += 1;
+                self.emit_indent();
+                self.output.push_str("let ");
+                if *mutable {
+                    self.output.push_str("mut ");
+                }
+                self.output.push_str(&format!("{} = {}.lock().unwrap();\n", guard, target));
+                for stmt in body {
+                    self.emit_stmt(stmt);
+                }
+                self.indent_level -= 1;
+                self.emit_indent();
+                self.output.push_str("}\n");
+            }
+            IrStmtKind::Defer { index, body } => {
+                self.emit_indent();
+                self.output.push_str(&format!("let _defer_guard_{} = DeferGuard(|| {{\n", index));
+                self.emit_body(body);
+                self.emit_indent();
+                self.output.push_str("});\n");
+            }
+            IrStmtKind::Match { subject, arms } => {
+                self.emit_indent();
+                self.output.push_str("match ");
+                self.emit_expr(subject);
+                self.output.push_str(" {\n");
+                self.indent_level += 1;
+                for (pattern, body) in arms {
+                    self.emit_indent();
+                    self.emit_match_pattern(pattern);
+                    self.output.push_str(" => {\n");
+                    self.emit_body(body);
+                    self.emit_indent();
+                    self.output.push_str("}\n");
+                }
+                self.indent_level -= 1;
+                self.emit_indent();
+                self.output.push_str("}\n");
+            }
+        }
+    }
+
+    fn emit_match_pattern(&mut self, pattern: &IrPattern) {
+        match pattern {
+            IrPattern::Wildcard => self.output.push('_'),
+            IrPattern::Unit(path) => self.output.push_str(path),
+            IrPattern::Tuple(path, names) => self.output.push_str(&format!("{}({})", path, names.join(", "))),
+            IrPattern::Named { path, fields, rest } => {
+                let mut parts: Vec<String> = fields
+                    .iter()
+                    .map(|(field, binding)| if field == binding { field.clone() } else { format!("{}: {}", field, binding) })
+                    .collect();
+                if *rest {
+                    parts.push("..".to_string());
+                }
+                if parts.is_empty() {
+                    self.output.push_str(&format!("{} {{}}", path));
+                } else {
+                    self.output.push_str(&format!("{} {{ {} }}", path, parts.join(", ")));
+                }
+            }
+        }
+    }
+
+    fn emit_naming_allow(&mut self) {
+        if self.names.policy() == NamingPolicy::Preserve {
+            self.emit_indent();
+            self.output.push_str("#[allow(non_snake_case)]\n");
+        }
+    }
+
+    fn emit_attributes(&mut self, attributes: &[String]) {
+        for attribute in attributes {
+            self.emit_indent();
+            self.output.push_str(&format!("#[{}]\n", attribute));
+        }
+    }
+
+    fn emit_function(&mut self, function: &IrFunction) {
+        self.emit_attributes(&function.attributes);
+        self.emit_naming_allow();
+        self.emit_indent();
+        self.output.push_str(function.visibility.prefix());
+        self.output.push_str(&format!("fn {}(", function.name));
+        for (i, (name, ty)) in function.params.iter().enumerate() {
+            if i > 0 {
+                self.output.push_str(", ");
+            }
+            self.output.push_str(name);
+            self.output.push_str(": ");
+            self.emit_ir_type(ty);
+        }
+        self.output.push(')');
+        if function.return_type != IrType::Unit {
+            self.output.push_str(" -> ");
+            self.emit_ir_type(&function.return_type);
+        }
+        self.output.push_str(" {\n");
+        self.emit_body(&function.body);
+        self.emit_indent();
+        self.output.push_str("}\n\n");
+    }
+
+    fn emit_struct(&mut self, ir_struct: &IrStruct) {
+        self.emit_attributes(&ir_struct.attributes);
+        self.emit_naming_allow();
+        self.emit_indent();
+        let visibility = ir_struct.visibility.prefix();
+        self.output.push_str(&format!("{}struct {} {{\n", visibility, ir_struct.name));
+        self.indent_level += 1;
+        for field in &ir_struct.fields {
+            self.emit_attributes(&field.attributes);
+            self.emit_indent();
+            self.output.push_str(&format!("{}{}: ", visibility, field.name));
+            self.emit_ir_type(&field.ty);
+            self.output.push_str(",\n");
+        }
+        self.indent_level -= 1;
+        self.emit_indent();
+        self.output.push_str("}\n\n");
+
+        if let Some(defaults) = &ir_struct.defaults {
+            self.emit_indent();
+            self.output.push_str(&format!("impl Default for {} {{\n", ir_struct.name));
+            self.indent_level += 1;
+            self.emit_indent();
+            self.output.push_str("fn default() -> Self {\n");
+            self.indent_level += 1;
+            self.emit_indent();
+            self.output.push_str("Self {\n");
+            self.indent_level += 1;
+            for (name, value) in defaults {
+                self.emit_indent();
+                self.output.push_str(&format!("{}: ", name));
+                self.emit_expr(value);
+                self.output.push_str(",\n");
+            }
+            self.indent_level -= 1;
+            self.emit_indent();
+            self.output.push_str("}\n");
+            self.indent_level -= 1;
+            self.emit_indent();
+            self.output.push_str("}\n");
+            self.indent_level -= 1;
+            self.emit_indent();
+            self.output.push_str("}\n\n");
+        }
+    }
+
+    fn emit_enum(&mut self, ir_enum: &IrEnum) {
+        self.emit_attributes(&ir_enum.attributes);
+        self.emit_naming_allow();
+        self.emit_indent();
+        self.output.push_str(ir_enum.visibility.prefix());
+        self.output.push_str(&format!("enum {} {{\n", ir_enum.name));
+        self.indent_level += 1;
+        for (name, fields) in &ir_enum.variants {
+            self.emit_indent();
+            self.output.push_str(name);
+            match fields {
+                IrVariantFields::Unit => {}
+                IrVariantFields::Tuple(types) => {
+                    self.output.push('(');
+                    for (i, ty) in types.iter().enumerate() {
+                        if i > 0 {
+                            self.output.push_str(", ");
+                        }
+                        self.emit_ir_type(ty);
+                    }
+                    self.output.push(')');
+                }
+                IrVariantFields::Named(fields) => {
+                    self.output.push_str(" { ");
+                    for (i, field) in fields.iter().enumerate() {
+                        if i > 0 {
+                            self.output.push_str(", ");
+                        }
+                        for attribute in &field.attributes {
+                            self.output.push_str(&format!("#[{}] ", attribute));
+                        }
+                        self.output.push_str(&format!("{}: ", field.name));
+                        self.emit_ir_type(&field.ty);
+                    }
+                    self.output.push_str(" }");
+                }
+            }
+            self.output.push_str(",\n");
+        }
+        self.indent_level -= 1;
+        self.emit_indent();
+        self.output.push_str("}\n\n");
+    }
+
+    fn emit_ir_type(&mut self, ty: &IrType) {
+        match ty {
+            IrType::Int => self.output.push_str("i32"),
+            IrType::Float => self.output.push_str("f64"),
+            IrType::Str => self.output.push_str("String"),
+            IrType::StaticStr => self.output.push_str("&str"),
+            IrType::Bool => self.output.push_str("bool"),
+            IrType::Char => self.output.push_str("char"),
+            IrType::Unit => self.output.push_str("()"),
+            IrType::Vec(element) => {
+                self.output.push_str("Vec<");
+                self.emit_ir_type(element);
+                self.output.push('>');
+            }
+            IrType::Array(element, size) => {
+                self.output.push('[');
+                self.emit_ir_type(element);
+                self.output.push_str(&format!("; {}]", size));
+            }
+            IrType::Named(name) => self.output.push_str(name),
+            IrType::Option(inner) => {
+                self.output.push_str("Option<");
+                self.emit_ir_type(inner);
+                self.output.push('>');
+            }
+            IrType::Shared(inner) => {
+                self.output.push_str("std::sync::Arc<std::sync::Mutex<");
+                self.emit_ir_type(inner);
+                self.output.push_str(">>");
+            }
+            IrType::Ref(inner) => match &**inner {
+                IrType::Str => self.output.push_str("&str"),
+                IrType::Vec(element) => {
+                    self.output.push_str("&[");
+                    self.emit_ir_type(element);
+                    self.output.push(']');
+                }
+                other => {
+                    self.output.push('&');
+                    self.emit_ir_type(other);
+                }
+            },
+            IrType::AsRef(inner) => {
+                self.output.push_str("impl AsRef<");
+                match &**inner {
+                    IrType::Vec(element) => {
+                        self.output.push('[');
+                        self.emit_ir_type(element);
+                        self.output.push(']');
+                    }
+                    IrType::Str => self.output.push_str("str"),
+                    other => self.emit_ir_type(other),
+                }
+                self.output.push('>');
+            }
+            IrType::Unknown => {}
+        }
+    }
+
+    fn emit_list(&mut self, exprs: &[IrExpr]) {
+        for (i, expr) in exprs.iter().enumerate() {
+            if i > 0 {
+                self.output.push_str(", ");
+            }
+            self.emit_expr(expr);
+        }
+    }
+
+    fn emit_usize(&mut self, expr: &IrExpr) {
+        if matches!(expr.kind, IrExprKind::Binary(..)) {
+            self.output.push('(');
+            self.emit_expr(expr);
+            self.output.push(')');
+        } else {
+            self.emit_expr(expr);
+        }
+        self.output.push_str(" as usize");
+    }
+
+    /// Writes the arguments on one line, separated by spaces
+    fn emit_print(&mut self, macro_name: &str, args: &[FormatArg]) {
+        if args.is_empty() {
+            self.output.push_str(&format!("{}!()", macro_name));
+            return;
+        }
+        let template: Vec<&str> = args.iter().map(FormatArg::spec).collect();
+        let values: Vec<&IrExpr> = args.iter().map(|arg| &arg.value).collect();
+        self.emit_format_macro(macro_name, &template.join(" "), &values);
+    }
+
+    /// Writes `macro_name!("template", args...)`. The format strings built
+    /// from jRust code all go through here, and only hold placeholders and
+    /// text passed through `escape_format`: user strings are arguments.
+    fn emit_format_macro(&mut self, macro_name: &str, template: &str, args: &[&IrExpr]) {
+        self.output.push_str(&format!("{}!({}", macro_name, string_literal(template)));
+        for arg in args {
+            self.output.push_str(", ");
+            self.emit_expr(arg);
+        }
+        self.output.push(')');
+    }
+
+    fn emit_expr(&mut self, expr: &IrExpr) {
+        match &expr.kind {
+            IrExprKind::Int(n) => self.output.push_str(&n.to_string()),
+            IrExprKind::Str(s) => self.output.push_str(&string_literal(s)),
+            IrExprKind::Bool(b) => self.output.push_str(if *b { "true" } else { "false" }),
+            IrExprKind::Path(path) => self.output.push_str(path),
+            IrExprKind::Deref(inner) => {
+                self.output.push('*');
+                self.emit_expr(inner);
+            }
+            IrExprKind::ReadGlobal(name) => {
+                self.output.push_str(&format!("{{ let value = {}().lock().unwrap().clone(); value }}", name));
+            }
+            IrExprKind::LockGlobal(name) => self.output.push_str(&format!("{}().lock().unwrap()", name)),
+            IrExprKind::ToOwned(inner) => {
+                self.emit_expr(inner);
+                self.output.push_str(".to_string()");
+            }
+            IrExprKind::Clone(inner) => {
+                self.emit_expr(inner);
+                self.output.push_str(".clone()");
+            }
+            IrExprKind::Ref(inner) => {
+                self.output.push('&');
+                self.emit_expr(inner);
+            }
+            IrExprKind::Format(parts) => {
+                let template: String = parts.iter().map(FormatArg::spec).collect();
+                let values: Vec<&IrExpr> = parts.iter().map(|part| &part.value).collect();
+                self.emit_format_macro("format", &template, &values);
+            }
+            IrExprKind::Cast(value, ty) => {
+                self.output.push('(');
+                if matches!(value.kind, IrExprKind::Binary(..)) {
+                    self.output.push('(');
+                    self.emit_expr(value);
+                    self.output.push(')');
+                } else {
+                    self.emit_expr(value);
+                }
+                self.output.push_str(" as ");
+                self.emit_ir_type(ty);
+                self.output.push(')');
+            }
+            IrExprKind::Print { args, stderr } => self.emit_print(if *stderr { "eprintln" } else { "println" }, args),
+            IrExprKind::Binary(left, op, right) => {
+                self.emit_expr(left);
+                self.output.push_str(&format!(" {} ", op.symbol()));
+                self.emit_expr(right);
+            }
+            IrExprKind::Vec(elements) => {
+                self.output.push_str("vec![");
+                self.emit_list(elements);
+                self.output.push(']');
+            }
+            IrExprKind::Array(elements) => {
+                self.output.push('[');
+                self.emit_list(elements);
+                self.output.push(']');
+            }
+            IrExprKind::Struct { name, fields, fill_defaults } => {
+                self.output.push_str(&format!("{} {{ ", name));
+                for (i, (field_name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        self.output.push_str(", ");
+                    }
+                    self.output.push_str(&format!("{}: ", field_name));
+                    self.emit_expr(value);
+                }
+                if *fill_defaults {
+                    if !fields.is_empty() {
+                        self.output.push_str(", ");
+                    }
+                    self.output.push_str("..Default::default()");
+                }
+                self.output.push_str(" }");
+            }
+            IrExprKind::Some(inner) => {
+                self.output.push_str("Some(");
+                self.emit_expr(inner);
+                self.output.push(')');
+            }
+            IrExprKind::None => self.output.push_str("None"),
+            IrExprKind::DefaultValue => self.output.push_str("Default::default()"),
+            IrExprKind::Call { path, args } => {
+                self.output.push_str(&format!("{}(", path));
+                self.emit_list(args);
+                self.output.push(')');
+            }
+            IrExprKind::Fallible { call, propagate } => {
+                self.emit_expr(call);
+                if *propagate {
+                    self.output.push('?');
+                } else {
+                    self.output.push_str(".unwrap_or_else(|err| panic!(\"{}\", err))");
+                }
+            }
+            IrExprKind::Assert { macro_name, args, message_at, location } => {
+                self.output.push_str(&format!("{}!(", macro_name));
+                for arg in args.iter().take(*message_at) {
+                    self.emit_expr(arg);
+                    self.output.push_str(", ");
+                }
+                let location = escape_format(location);
+                match args.get(*message_at) {
+                    Some(message) => {
+                        self.output.push_str(&format!("{}, ", string_literal(&format!("{{}} at {}", location))));
+                        self.emit_expr(message);
+                    }
+                    None => {
+                        let failure = match macro_name.as_str() {
+                            "assert" => "assertion failed at",
+                            "panic" => "explicit panic at",
+                            _ => "at",
+                        };
+                        self.output.push_str(&string_literal(&format!("{} {}", failure, location)));
+                    }
+                }
+                self.output.push(')');
+            }
+            IrExprKind::Method { receiver, method, args } => self.emit_method(receiver, method, args),
+            IrExprKind::EnvArgs => self.output.push_str("std::env::args().skip(1).collect::<Vec<String>>()"),
+            IrExprKind::Index { object, index } => {
+                self.emit_expr(object);
+                self.output.push('[');
+                if index.ty.is_string() {
+                    if matches!(index.ty, IrType::Str) {
+                        self.output.push('&');
+                    }
+                    self.emit_expr(index);
+                } else {
+                    self.emit_usize(index);
+                }
+                self.output.push(']');
+            }
+            IrExprKind::CheckedIndex { object, index } => {
+                self.emit_expr(object);
+                self.output.push_str("[jrust_index(");
+                self.emit_expr(index);
+                self.output.push_str(", ");
+                self.emit_expr(object);
+                self.output.push_str(".len())]");
+            }
+            IrExprKind::IndexSlot { object, index } => {
+                self.output.push_str("jrust_slot(&mut ");
+                self.emit_expr(object);
+                if matches!(object.kind, IrExprKind::IndexSlot { .. }) {
+                    self.output.push_str("[..]");
+                }
+                self.output.push_str(", ");
+                self.emit_expr(index);
+                self.output.push(')');
+            }
+            IrExprKind::Field { object, member } => {
+                self.emit_expr(object);
+                self.output.push('.');
+                self.output.push_str(member);
+            }
+            IrExprKind::Len(object) => {
+                self.emit_expr(object);
+                self.output.push_str(".len() as i32");
+            }
+            IrExprKind::Assign { target, value } => {
+                self.emit_expr(target);
+                self.output.push_str(" = ");
+                self.emit_expr(value);
+            }
+            IrExprKind::Channel(element_type) => {
+                self.output.push_str("std::sync::mpsc::channel");
+                if let Some(element_type) = element_type {
+                    self.output.push_str("::<");
+                    self.emit_ir_type(element_type);
+                    self.output.push('>');
+                }
+                self.output.push_str("()");
+            }
+            IrExprKind::Shared(value_type, value) => {
+                self.output.push_str("std::sync::Arc::new(std::sync::Mutex::");
+                if let Some(value_type) = value_type {
+                    self.output.push('<');
+                    self.emit_ir_type(value_type);
+                    self.output.push_str(">::");
+                }
+                self.output.push_str("new(");
+                self.emit_expr(value);
+                self.output.push_str("))");
+            }
+            IrExprKind::Closure { params, body } => {
+                self.output.push_str(&format!("|{}| ", params.join(", ")));
+                if let [IrStmt { kind: IrStmtKind::Return(Some(value)), .. }] = body.as_slice() {
+                    self.emit_expr(value);
+                } else {
+                    self.output.push_str("{\n");
+                    self.emit_body(body);
+                    self.emit_indent();
+                    self.output.push('}');
+                }
+            }
+        }
+    }
+
+    fn emit_method(&mut self, receiver: &IrExpr, method: &Method, args: &[IrExpr]) {
+        if *method == Method::PadStart {
+            self.emit_pad_start(receiver, args);
+            return;
+        }
+        if *method == Method::ToFixed {
+            self.output.push_str("format!(\"{:.1$}\", f64::from(");
+            self.emit_expr(receiver);
+            self.output.push_str("), ");
+            if let Some(digits) = args.first() {
+                self.emit_usize(digits);
+            }
+            self.output.push(')');
+            return;
+        }
+        if *method == Method::At {
+            self.output.push_str("jrust_at(&");
+            self.emit_expr(receiver);
+            self.output.push_str(", ");
+            self.emit_list(args);
+            self.output.push(')');
+            return;
+        }
+        if matches!(receiver.kind, IrExprKind::Binary(..)) {
+            self.output.push('(');
+            self.emit_expr(receiver);
+            self.output.push(')');
+        } else {
+            self.emit_expr(receiver);
+        }
+        if *method == Method::Slice {
+            self.output.push('[');
+            match args.first() {
+                Some(start) => self.emit_usize(start),
+                None => self.output.push('0'),
+            }
+            self.output.push_str("..");
+            if let Some(end) = args.get(1) {
+                self.emit_usize(end);
+            }
+            self.output.push_str("].to_vec()");
+            return;
+        }
+
+        self.output.push('.');
+        match method {
+            Method::Push => {
+                self.output.push_str("push(");
+                self.emit_list(args);
+                self.output.push(')');
+            }
+            Method::Pop => self.output.push_str("pop()"),
+            Method::Send => {
+                self.output.push_str("send(");
+                if let Some(value) = args.first() {
+                    self.emit_expr(value);
+                }
+                self.output.push_str(").unwrap()");
+            }
+            Method::Recv => self.output.push_str("recv().unwrap()"),
+            Method::RemoveFirst => self.output.push_str("remove(0)"),
+            Method::InsertFirst => {
+                self.output.push_str("insert(0, ");
+                if let Some(value) = args.first() {
+                    self.emit_expr(value);
+                }
+                self.output.push(')');
+            }
+            Method::Map => self.emit_iterator_call("map", args, ".collect::<Vec<_>>()"),
+            Method::Filter => self.emit_iterator_call("filter", args, ".cloned().collect::<Vec<_>>()"),
+            Method::ForEach => self.emit_iterator_call("for_each", args, ""),
+            Method::Find => self.emit_iterator_call("find", args, ".cloned()"),
+            Method::FindIndex => self.emit_iterator_call("position", args, ".map(|i| i as i32).unwrap_or(-1)"),
+            Method::Any => self.emit_iterator_call("any", args, ""),
+            Method::All => self.emit_iterator_call("all", args, ""),
+            Method::Reduce => match args {
+                [callback, initial, ..] => {
+                    self.output.push_str("iter().fold(");
+                    self.emit_expr(initial);
+                    self.output.push_str(", ");
+                    self.emit_expr(callback);
+                    self.output.push(')');
+                }
+                _ => {
+                    self.output.push_str("iter().cloned().reduce(");
+                    self.emit_list(args);
+                    self.output.push_str(").unwrap()");
+                }
+            },
+            Method::Flat => self.output.push_str("concat()"),
+            Method::Concat => {
+                self.output.push_str("iter()");
+                for other in args {
+                    self.output.push_str(".chain(");
+                    self.emit_expr(other);
+                    self.output.push_str(".iter())");
+                }
+                self.output.push_str(".cloned().collect::<Vec<_>>()");
+            }
+            Method::CharAt => {
+                self.output.push_str("chars().nth(");
+                if let Some(index) = args.first() {
+                    self.emit_usize(index);
+                }
+                self.output.push_str(").unwrap_or('\\0')");
+            }
+            Method::Substring => {
+                self.output.push_str("chars().skip(");
+                match args.first() {
+                    Some(start) => self.emit_usize(start),
+                    None => self.output.push('0'),
+                }
+                self.output.push_str(").take(");
+                match (args.first(), args.get(1)) {
+                    (Some(start), Some(end)) => {
+                        self.output.push('(');
+                        self.emit_expr(end);
+                        self.output.push_str(" - ");
+                        self.emit_expr(start);
+                        self.output.push_str(") as usize");
+                    }
+                    _ => self.output.push_str("usize::MAX"),
+                }
+                self.output.push_str(").collect::<String>()");
+            }
+            Method::IndexOf => {
+                self.output.push_str("find(");
+                if let Some(needle) = args.first() {
+                    self.emit_expr(needle);
+                }
+                self.output.push_str(").map(|i| i as i32).unwrap_or(-1)");
+            }
+            Method::ToUpperCase => self.output.push_str("to_uppercase()"),
+            Method::ToLowerCase => self.output.push_str("to_lowercase()"),
+            Method::Trim => self.output.push_str("trim().to_string()"),
+            Method::Split => {
+                self.output.push_str("split(");
+                if let Some(separator) = args.first() {
+                    self.emit_expr(separator);
+                }
+                self.output.push_str(").map(|s| s.to_string()).collect::<Vec<String>>()");
+            }
+            Method::Join => {
+                self.output.push_str("join(");
+                match args.first() {
+                    Some(separator) => self.emit_expr(separator),
+                    None => self.output.push_str("\", \""),
+                }
+                self.output.push(')');
+            }
+            Method::Reverse => self.output.push_str("iter().rev().cloned().collect::<Vec<_>>()"),
+            Method::Sort if args.is_empty() => self.output.push_str("sort()"),
+            Method::Sort | Method::SortBy => {
+                let name = if *method == Method::Sort { "sort_by" } else { "sort_by_key" };
+                self.output.push_str(&format!("{}(", name));
+                self.emit_list(args);
+                self.output.push(')');
+            }
+            Method::Contains => {
+                self.output.push_str("contains(");
+                if let Some(needle) = args.first() {
+                    self.output.push('&');
+                    self.emit_expr(needle);
+                }
+                self.output.push(')');
+            }
+            Method::Replace => {
+                self.output.push_str("replacen(");
+                for arg in args.iter().take(2) {
+                    self.emit_pattern(arg);
+                    self.output.push_str(", ");
+                }
+                self.output.push_str("1)");
+            }
+            Method::StartsWith | Method::EndsWith => {
+                let name = if *method == Method::StartsWith { "starts_with" } else { "ends_with" };
+                self.output.push_str(&format!("{}(", name));
+                if let Some(needle) = args.first() {
+                    self.emit_pattern(needle);
+                }
+                self.output.push(')');
+            }
+            Method::Repeat => {
+                self.output.push_str("repeat(");
+                if let Some(count) = args.first() {
+                    self.emit_usize(count);
+                }
+                self.output.push(')');
+            }
+            Method::ParseInt => self.output.push_str("trim().parse::<i32>().ok()"),
+            Method::ParseFloat => self.output.push_str("trim().parse::<f64>().ok()"),
+            Method::ToString => self.output.push_str("to_string()"),
+            Method::Slice | Method::PadStart | Method::At | Method::ToFixed => {}
+            Method::Named(name) => {
+                self.output.push_str(&format!("{}(", name));
+                self.emit_list(args);
+                self.output.push(')');
+            }
+        }
+    }
+
+    fn emit_iterator_call(&mut self, adapter: &str, args: &[IrExpr], suffix: &str) {
+        self.output.push_str(&format!("iter().{}(", adapter));
+        self.emit_list(args);
+        self.output.push(')');
+        self.output.push_str(suffix);
+    }
+
+    fn emit_pattern(&mut self, expr: &IrExpr) {
+        if !matches!(expr.ty, IrType::StaticStr | IrType::Char | IrType::Ref(_)) {
+            self.output.push('&');
+        }
+        self.emit_expr(expr);
+    }
+
+    fn emit_pad_start(&mut self, receiver: &IrExpr, args: &[IrExpr]) {
+        let fill = match args.get(1).map(|fill| &fill.kind) {
+            None => Some(' '),
+            Some(IrExprKind::Str(fill)) if fill.chars().count() == 1 => {
+                fill.chars().next().filter(|c| !matches!(c, '{' | '}' | '"' | '\\'))
+            }
+            Some(_) => None,
+        };
+        match (fill, args.first()) {
+            (Some(fill), Some(width)) => {
+                self.output.push_str(&format!("format!(\"{{:{}>1$}}\", ", fill));
+                self.emit_expr(receiver);
+                self.output.push_str(", ");
+                self.emit_usize(width);
+                self.output.push(')');
+            }
+            (_, Some(width)) => {
+                self.output.push_str("{ let text = &");
+                self.emit_expr(receiver);
+                self.output.push_str("; let pad = (");
+                self.emit_usize(width);
+                self.output.push_str(").saturating_sub(text.chars().count()); format!(\"{}{}\", ");
+                if let Some(fill) = args.get(1) {
+                    self.emit_expr(fill);
+                }
+                self.output.push_str(".chars().cycle().take(pad).collect::<String>(), text) }");
+            }
+            (_, None) => self.emit_expr(receiver),
+        }
+    }
+
+    fn emit_indent(&mut self) {
+        for _ in 0..self.indent_level {
+            self.output.push_str("    ");
+        }
+    }
+}
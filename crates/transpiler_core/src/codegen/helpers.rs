@@ -1,4 +1,57 @@
-use crate::ast::{Expression, BinaryOp};
+use std::collections::HashSet;
+
+use crate::ast::{Expression, ExpressionKind, Statement, StatementKind};
+use crate::visit::{walk_block, walk_expression, walk_statement, Visitor};
+
+pub const MUTATING_METHODS: &[&str] = &[
+    "push", "pop", "shift", "unshift", "sort", "sortBy", "insert", "remove", "clear",
+    "extend", "truncate", "retain", "dedup", "append", "drain",
+];
+
+/// Rust's strict and reserved keywords that aren't jRust keywords, so jRust
+/// code can use them as names
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "async", "await", "become", "box", "crate", "do", "dyn", "extern", "final", "fn", "gen", "impl",
+    "loop", "macro", "match", "mod", "move", "override", "priv", "pub", "ref", "return", "self", "Self", "static",
+    "super", "trait", "typeof", "unsafe", "unsized", "use", "virtual", "where", "yield",
+];
+
+/// `name` made usable as a Rust identifier: a Rust keyword becomes a raw
+/// identifier, or gets a `_` suffix when Rust doesn't allow it raw
+pub fn escape_keyword(name: String) -> String {
+    match name.as_str() {
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        keyword if RUST_KEYWORDS.contains(&keyword) => format!("r#{}", name),
+        _ => name,
+    }
+}
+
+/// `text` as a Rust string literal. Quotes, backslashes and control
+/// characters are escaped; everything else, braces included, is kept as
+/// written since the generated code never uses a literal as a format string.
+pub fn string_literal(text: &str) -> String {
+    let mut literal = String::with_capacity(text.len() + 2);
+    literal.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            '\0' => literal.push_str("\\0"),
+            c if c.is_control() => literal.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// `text` with its braces doubled, so a format string shows it as written
+pub fn escape_format(text: &str) -> String {
+    text.replace('{', "{{").replace('}', "}}")
+}
 
 pub fn to_snake_case(name: &str) -> String {
     let mut result = String::new();
@@ -7,10 +60,8 @@ pub fn to_snake_case(name: &str) -> String {
     
     while let Some(ch) = chars.next() {
         if ch.is_uppercase() {
-            if prev_is_lower || (result.len() > 0 && chars.peek().map_or(false, |c| c.is_lowercase())) {
-                if result.len() > 0 {
-                    result.push('_');
-                }
+            if !result.is_empty() && (prev_is_lower || chars.peek().is_some_and(|c| c.is_lowercase())) {
+                result.push('_');
             }
             result.push(ch.to_lowercase().next().unwrap());
             prev_is_lower = false;
@@ -20,32 +71,70 @@ pub fn to_snake_case(name: &str) -> String {
         }
     }
     
-    result
+    escape_keyword(result)
 }
 
+pub const DEFAULT_EXPORT: &str = "default_export";
+
 pub fn convert_name(name: &str) -> String {
     if name.chars().all(|c| c.is_uppercase() || c == '_' || c.is_numeric()) {
         name.to_uppercase()
+    } else if name.starts_with(|c: char| c.is_uppercase()) {
+        escape_keyword(name.to_string())
     } else {
         to_snake_case(name)
     }
 }
 
-pub fn collect_string_parts<'a>(expr: &'a Expression, parts: &mut Vec<&'a Expression>) {
-    match expr {
-        Expression::BinaryOp(left, BinaryOp::Add, right) => {
-            let left_is_string = matches!(**left, Expression::StringLiteral(_));
-            let right_is_string = matches!(**right, Expression::StringLiteral(_));
-            
-            if left_is_string || right_is_string {
-                collect_string_parts(left, parts);
-                collect_string_parts(right, parts);
-            } else {
-                parts.push(expr);
+pub fn collect_mutated_names(statements: &[Statement], names: &mut HashSet<String>) {
+    walk_block(&mut MutationCollector { names }, statements);
+}
+
+struct MutationCollector<'a> {
+    names: &'a mut HashSet<String>,
+}
+
+impl<'ast> Visitor<'ast> for MutationCollector<'_> {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        match &stmt.kind {
+            StatementKind::ExportStmt(_)
+            | StatementKind::ExportDefault(_)
+            | StatementKind::VisibilityStmt(..)
+            | StatementKind::FunctionDecl(_)
+            | StatementKind::StructDecl(_)
+            | StatementKind::TestBlock(_) => {}
+            StatementKind::LockStmt(lock_stmt) => {
+                let mut inner = HashSet::new();
+                collect_mutated_names(&lock_stmt.body, &mut inner);
+                inner.remove(&lock_stmt.name);
+                self.names.extend(inner);
             }
+            _ => walk_statement(self, stmt),
         }
-        _ => {
-            parts.push(expr);
+    }
+
+    fn visit_expression(&mut self, expr: &'ast Expression) {
+        let mutated = match &expr.kind {
+            ExpressionKind::Assignment { target, .. } => root_identifier(target),
+            ExpressionKind::MethodCall { object, method, .. } if MUTATING_METHODS.contains(&method.as_str()) => {
+                root_identifier(object)
+            }
+            _ => None,
+        };
+        if let Some(root) = mutated {
+            self.names.insert(root.to_string());
         }
+        walk_expression(self, expr);
+    }
+}
+
+pub fn root_identifier(expr: &Expression) -> Option<&str> {
+    match &expr.kind {
+        ExpressionKind::Identifier(name) => Some(name),
+        ExpressionKind::MemberAccess { object, .. }
+        | ExpressionKind::IndexAccess { object, .. }
+        | ExpressionKind::SliceAccess { object, .. }
+        | ExpressionKind::MethodCall { object, .. } => root_identifier(object),
+        _ => None,
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,1737 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::*;
+use crate::ir::*;
+use crate::span::Span;
+use super::borrow::{collect_last_uses, infer_borrows};
+use super::helpers::{collect_mutated_names, convert_name, string_literal, to_snake_case, MUTATING_METHODS};
+use super::names::NameMapper;
+use super::mutability::collect_mutable_declarations;
+use crate::unused::unused_imports;
+
+pub struct Lowerer {
+    is_main_file: bool,
+    is_library: bool,
+    file: Option<String>,
+    entry_dir: Option<String>,
+    line: Option<usize>,
+    runtime: bool,
+    structs: HashMap<String, StructDecl>,
+    enums: HashMap<String, EnumDecl>,
+    functions: HashMap<String, Type>,
+    std_functions: HashMap<String, (String, String)>,
+    std_namespaces: HashMap<String, String>,
+    display_types: HashSet<String>,
+    mutable: HashSet<usize>,
+    namespaces: HashSet<String>,
+    unused_imports: HashSet<Span>,
+    guards: HashMap<String, String>,
+    globals: HashSet<String>,
+    scopes: Vec<HashMap<String, (IrType, usize)>>,
+    borrows: HashMap<String, Vec<bool>>,
+    last_uses: HashMap<String, usize>,
+    loop_depth: usize,
+    in_try: bool,
+    defer_count: usize,
+    uses_index: bool,
+    uses_slot: bool,
+    uses_at: bool,
+    uses_input: bool,
+    uses_panic_hook: bool,
+    names: NameMapper,
+}
+
+impl Lowerer {
+    pub fn new(
+        is_main_file: bool,
+        is_library: bool,
+        globals: HashSet<String>,
+        file: Option<String>,
+        runtime: bool,
+        names: NameMapper,
+    ) -> Self {
+        Lowerer {
+            is_main_file,
+            is_library,
+            file,
+            entry_dir: None,
+            line: None,
+            runtime,
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            functions: HashMap::new(),
+            std_functions: HashMap::new(),
+            std_namespaces: HashMap::new(),
+            display_types: HashSet::new(),
+            mutable: HashSet::new(),
+            namespaces: HashSet::new(),
+            unused_imports: HashSet::new(),
+            guards: HashMap::new(),
+            globals,
+            scopes: vec![HashMap::new()],
+            borrows: HashMap::new(),
+            last_uses: HashMap::new(),
+            loop_depth: 0,
+            in_try: false,
+            defer_count: 0,
+            uses_index: false,
+            uses_slot: false,
+            uses_at: false,
+            uses_input: false,
+            uses_panic_hook: false,
+            names,
+        }
+    }
+
+    pub fn set_entry_dir(&mut self, dir: &str) {
+        self.entry_dir = Some(dir.to_string());
+    }
+
+    pub fn lower_program(mut self, program: &Program) -> IrProgram {
+        self.collect_declarations(&program.statements);
+        for stmt in &program.statements {
+            if let StatementKind::ImportStmt(ImportStmt { namespace: Some(namespace), .. }) = &stmt.kind {
+                self.namespaces.insert(namespace.clone());
+            }
+        }
+        self.unused_imports = unused_imports(program).iter().map(|import| import.statement.span).collect();
+        self.mutable = collect_mutable_declarations(&program.statements);
+        self.borrows = infer_borrows(&program.statements);
+        self.last_uses = collect_last_uses(&program.statements);
+
+        let has_main = program.statements.iter().any(|stmt| {
+            let decl = match &stmt.kind {
+                StatementKind::ExportStmt(inner)
+                | StatementKind::ExportDefault(inner)
+                | StatementKind::VisibilityStmt(_, inner) => &inner.kind,
+                other => other,
+            };
+            matches!(decl, StatementKind::FunctionDecl(func_decl) if func_decl.name == "main")
+        });
+        let tests: Vec<(&TestBlock, usize)> = program
+            .statements
+            .iter()
+            .filter_map(|stmt| match &stmt.kind {
+                StatementKind::TestBlock(test_block) => Some((test_block, stmt.span.line)),
+                _ => None,
+            })
+            .collect();
+        let wraps_main = self.is_main_file && !self.is_library && !has_main;
+        let hoist_items = wraps_main && !tests.is_empty();
+
+        let mut items = Vec::new();
+        if hoist_items {
+            for stmt in program.statements.iter().filter(|stmt| stmt.is_item()) {
+                self.lower_statement(stmt, &mut items);
+            }
+        }
+
+        let mut body = Vec::new();
+        for stmt in &program.statements {
+            if matches!(&stmt.kind, StatementKind::TestBlock(_)) || (hoist_items && stmt.is_item()) {
+                continue;
+            }
+            self.lower_statement(stmt, &mut body);
+        }
+        let main = if wraps_main {
+            body.insert(0, self.panic_hook());
+            Some(body)
+        } else {
+            items.extend(body);
+            None
+        };
+
+        let mut lowered_tests: Vec<IrTest> = Vec::new();
+        for (test_block, line) in tests {
+            let base = format!("test_{}", test_identifier(&test_block.name));
+            let mut name = base.clone();
+            let mut suffix = 2;
+            while lowered_tests.iter().any(|test| test.name == name) {
+                name = format!("{}_{}", base, suffix);
+                suffix += 1;
+            }
+            let body = self.lower_function_body(&test_block.body, &[]);
+            lowered_tests.push(IrTest {
+                name,
+                label: test_block.name.clone(),
+                line,
+                body,
+            });
+        }
+
+        IrProgram {
+            items,
+            main,
+            tests: lowered_tests,
+            uses_defer: self.defer_count > 0,
+            uses_index: self.uses_index,
+            uses_slot: self.uses_slot,
+            uses_at: self.uses_at,
+            uses_input: self.uses_input,
+            uses_panic_hook: self.uses_panic_hook,
+        }
+    }
+
+    fn collect_declarations(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            match &stmt.kind {
+                StatementKind::StructDecl(struct_decl) => {
+                    self.structs.insert(struct_decl.name.clone(), struct_decl.clone());
+                }
+                StatementKind::EnumDecl(enum_decl) => {
+                    self.enums.insert(enum_decl.name.clone(), enum_decl.clone());
+                }
+                StatementKind::FunctionDecl(func_decl) => {
+                    self.functions.insert(func_decl.name.clone(), func_decl.return_type.clone());
+                }
+                StatementKind::ImportStmt(import_stmt) => {
+                    let Some(module) = import_stmt.std_module() else {
+                        continue;
+                    };
+                    if let Some(namespace) = &import_stmt.namespace {
+                        self.std_namespaces.insert(namespace.clone(), module.to_string());
+                    }
+                    for item in &import_stmt.imports {
+                        let local = item.alias.clone().unwrap_or_else(|| item.name.clone());
+                        if std_function(module, &item.name).is_some() {
+                            self.std_functions.insert(local, (module.to_string(), item.name.clone()));
+                        } else if let Some(struct_decl) = std_struct(module, &item.name) {
+                            self.structs.insert(local, struct_decl);
+                        } else if STD_DISPLAY_TYPES.contains(&(module, item.name.as_str())) {
+                            self.display_types.insert(local);
+                        }
+                    }
+                }
+                StatementKind::ExportStmt(inner) | StatementKind::ExportDefault(inner) => {
+                    if let StatementKind::VariableDecl(var_decl) = &inner.kind {
+                        if !var_decl.is_const && matches!(stmt.kind, StatementKind::ExportStmt(_)) {
+                            self.globals.insert(var_decl.name.clone());
+                            let ty = var_decl.var_type.as_ref().map_or_else(|| literal_type(&var_decl.value), lower_type);
+                            self.declare(&var_decl.name, ty);
+                        }
+                    }
+                    self.collect_declarations(std::slice::from_ref(inner));
+                }
+                StatementKind::VisibilityStmt(_, inner) => self.collect_declarations(std::slice::from_ref(inner)),
+                _ => {}
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &str, ty: IrType) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), (ty, self.loop_depth));
+        }
+    }
+
+    fn lookup(&self, name: &str) -> IrType {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).map(|(ty, _)| ty.clone()))
+            .unwrap_or(IrType::Unknown)
+    }
+
+    fn used_again(&self, name: &str, start: usize) -> bool {
+        let declared_depth = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).map(|(_, depth)| *depth))
+            .unwrap_or(self.loop_depth);
+        self.loop_depth > declared_depth || self.last_uses.get(name).is_some_and(|last| *last > start)
+    }
+
+    fn lower_loop_body(&mut self, body: &[Statement], variable: Option<(&str, IrType)>) -> Vec<IrStmt> {
+        self.loop_depth += 1;
+        self.scopes.push(HashMap::new());
+        if let Some((name, ty)) = variable {
+            self.declare(name, ty);
+        }
+        let lowered = self.lower_block(body);
+        self.scopes.pop();
+        self.loop_depth -= 1;
+        lowered
+    }
+
+    fn lower_block(&mut self, statements: &[Statement]) -> Vec<IrStmt> {
+        self.scopes.push(HashMap::new());
+        let mut lowered = Vec::new();
+        for stmt in statements {
+            self.lower_statement(stmt, &mut lowered);
+        }
+        self.scopes.pop();
+        lowered
+    }
+
+    fn lower_function_body(&mut self, body: &[Statement], params: &[(String, IrType)]) -> Vec<IrStmt> {
+        let outer_last_uses = std::mem::replace(&mut self.last_uses, collect_last_uses(body));
+        let outer_loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let outer_in_try = std::mem::replace(&mut self.in_try, false);
+        self.scopes.push(HashMap::new());
+        for (name, ty) in params {
+            self.declare(name, ty.clone());
+        }
+        let mut lowered = Vec::new();
+        for stmt in body {
+            self.lower_statement(stmt, &mut lowered);
+        }
+        self.scopes.pop();
+        self.last_uses = outer_last_uses;
+        self.loop_depth = outer_loop_depth;
+        self.in_try = outer_in_try;
+        lowered
+    }
+
+    fn lower_statement(&mut self, stmt: &Statement, out: &mut Vec<IrStmt>) {
+        let line = stmt.line();
+        if line.is_some() {
+            self.line = line;
+        }
+        let kind = match &stmt.kind {
+            StatementKind::ImportStmt(import_stmt) => {
+                let allow_unused = self.unused_imports.contains(&stmt.span);
+                self.lower_import(import_stmt, false, allow_unused)
+            }
+            StatementKind::ExportStmt(inner) => match self.lower_export(inner) {
+                Some(kind) => kind,
+                None => return,
+            },
+            StatementKind::ExportDefault(inner) => {
+                if let Some(kind) = self.lower_export(inner) {
+                    out.push(IrStmt { kind, line });
+                }
+                let name = match &inner.kind {
+                    StatementKind::FunctionDecl(func_decl) => self.names.function(&func_decl.name),
+                    StatementKind::StructDecl(struct_decl) => struct_decl.name.clone(),
+                    StatementKind::EnumDecl(enum_decl) => enum_decl.name.clone(),
+                    StatementKind::VariableDecl(var_decl) => self.names.value(&var_decl.name),
+                    _ => return,
+                };
+                out.push(IrStmt { kind: IrStmtKind::ReexportDefault(name), line: None });
+                return;
+            }
+            StatementKind::VisibilityStmt(visibility, inner) => match self.lower_item(inner, lower_visibility(*visibility)) {
+                Some(kind) => kind,
+                None => return,
+            },
+            StatementKind::VariableDecl(var_decl) if var_decl.is_const => self.lower_const(var_decl, IrVisibility::Private),
+            StatementKind::VariableDecl(var_decl) => self.lower_let(var_decl, stmt.span.start),
+            StatementKind::DestructureDecl(destructure) => {
+                for name in &destructure.names {
+                    self.declare(name, IrType::Unknown);
+                }
+                IrStmtKind::LetTuple {
+                    names: destructure.names.iter().map(|name| self.names.function(name)).collect(),
+                    value: self.lower_expression(&destructure.value),
+                }
+            }
+            StatementKind::FunctionDecl(func_decl) => {
+                IrStmtKind::Function(self.lower_function(func_decl, IrVisibility::Private))
+            }
+            StatementKind::StructDecl(struct_decl) => IrStmtKind::Struct(self.lower_struct(struct_decl, IrVisibility::Private)),
+            StatementKind::EnumDecl(enum_decl) => IrStmtKind::Enum(self.lower_enum(enum_decl, IrVisibility::Private)),
+            StatementKind::PrintStmt(print_stmt) => {
+                IrStmtKind::Print(print_stmt.arguments.iter().map(|arg| self.lower_format_arg(arg)).collect())
+            }
+            StatementKind::ThrowStmt(throw_stmt) => IrStmtKind::Panic(self.lower_expression(&throw_stmt.expression)),
+            StatementKind::ReturnStmt(ret_stmt) => {
+                IrStmtKind::Return(ret_stmt.value.as_ref().map(|value| clone_place(self.lower_expression(value))))
+            }
+            StatementKind::ExpressionStmt(expr_stmt) => IrStmtKind::Expr(self.lower_expression(&expr_stmt.expression)),
+            StatementKind::IfElse(if_else) => IrStmtKind::If {
+                condition: self.lower_expression(&if_else.condition),
+                then_body: self.lower_block(&if_else.then_body),
+                else_body: if_else.else_body.as_ref().map(|else_body| self.lower_block(else_body)),
+            },
+            StatementKind::ForLoop(for_loop) => {
+                let iterable = match self.lower_moved(&for_loop.iterable) {
+                    borrowed @ IrExpr { ty: IrType::Ref(_), .. } => iterate_borrowed(borrowed),
+                    iterable => iterable,
+                };
+                let body = self.lower_loop_body(&for_loop.body, Some((&for_loop.variable, iterable.ty.element())));
+                IrStmtKind::For {
+                    variable: for_loop.variable.clone(),
+                    iterable,
+                    body,
+                }
+            }
+            StatementKind::WhileLoop(while_loop) => IrStmtKind::While {
+                condition: self.lower_expression(&while_loop.condition),
+                body: self.lower_loop_body(&while_loop.body, None),
+            },
+            StatementKind::BreakStmt => IrStmtKind::Break,
+            StatementKind::ContinueStmt => IrStmtKind::Continue,
+            StatementKind::TryCatch(try_catch) => {
+                let outer_in_try = std::mem::replace(&mut self.in_try, true);
+                let body = self.lower_block(&try_catch.try_body);
+                self.in_try = outer_in_try;
+                let binding = try_catch.catch_param.clone().unwrap_or_else(|| "_err".to_string());
+                self.scopes.push(HashMap::new());
+                self.declare(&binding, IrType::Unknown);
+                let handler = self.lower_block(&try_catch.catch_body);
+                self.scopes.pop();
+                IrStmtKind::Catch { body, binding, handler }
+            }
+            StatementKind::Block(body) => IrStmtKind::Block(self.lower_block(body)),
+            StatementKind::CfgBlock(block) => IrStmtKind::Block(self.lower_block(&block.body)),
+            StatementKind::LockStmt(lock_stmt) => self.lower_lock(lock_stmt),
+            StatementKind::DeferStmt(body) => {
+                let index = self.defer_count;
+                self.defer_count += 1;
+                let outer_in_try = std::mem::replace(&mut self.in_try, false);
+                let body = self.lower_block(body);
+                self.in_try = outer_in_try;
+                IrStmtKind::Defer { index, body }
+            }
+            StatementKind::TestBlock(_) => return,
+            StatementKind::Match(match_stmt) => self.lower_match(match_stmt),
+        };
+        out.push(IrStmt { kind, line });
+    }
+
+    fn lower_import(&mut self, import_stmt: &ImportStmt, public: bool, allow_unused: bool) -> IrStmtKind {
+        let path = match import_stmt.source {
+            ImportSource::Std => format!("jrust_runtime::{}", import_stmt.path["std/".len()..].replace('/', "::")),
+            // A package's modules are imported as `package/module`
+            ImportSource::External => import_stmt.path.replace('/', "::"),
+            ImportSource::Local => self.local_module_path(&import_stmt.path),
+        };
+
+        let tree = if import_stmt.is_wildcard {
+            match &import_stmt.namespace {
+                Some(namespace) => UseTree::Alias(self.names.value(namespace)),
+                None => UseTree::Glob,
+            }
+        } else {
+            UseTree::Names(
+                import_stmt
+                    .imports
+                    .iter()
+                    .map(|item| {
+                        let rust_name = match import_stmt.std_module() {
+                            Some(_) => convert_name(&item.name),
+                            None => self.names.import(&item.name),
+                        };
+                        let local = self.names.value(item.alias.as_deref().unwrap_or(&item.name));
+                        let alias = (item.alias.is_some() || local != rust_name).then_some(local);
+                        (rust_name, alias)
+                    })
+                    .collect(),
+            )
+        };
+        IrStmtKind::Use { public, allow_unused, path, tree }
+    }
+
+    /// The Rust path of a `./` or `../` import. A module file's own directory
+    /// is its parent module, so every `..` climbs one more `super`.
+    fn local_module_path(&self, path: &str) -> String {
+        let mut parents = usize::from(!self.is_main_file);
+        let mut segments = Vec::new();
+        let entry_dir = self.entry_dir.as_deref().filter(|_| self.is_main_file);
+        if let Some(dir) = entry_dir {
+            segments.extend(dir.split('/'));
+        }
+        for segment in path.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." if segments.is_empty() => parents += 1,
+                ".." => {
+                    segments.pop();
+                }
+                _ => segments.push(segment),
+            }
+        }
+        if entry_dir.is_some() {
+            return std::iter::once("crate").chain(segments).collect::<Vec<_>>().join("::");
+        }
+        std::iter::repeat("super").take(parents).chain(segments).collect::<Vec<_>>().join("::")
+    }
+
+    fn lower_export(&mut self, inner: &Statement) -> Option<IrStmtKind> {
+        Some(match &inner.kind {
+            StatementKind::ImportStmt(import_stmt) => self.lower_import(import_stmt, true, false),
+            StatementKind::VariableDecl(var_decl) if !var_decl.is_const => self.lower_global(var_decl),
+            _ => return self.lower_item(inner, IrVisibility::Public),
+        })
+    }
+
+    /// A function, struct, enum or constant declared with `visibility`
+    fn lower_item(&mut self, inner: &Statement, visibility: IrVisibility) -> Option<IrStmtKind> {
+        Some(match &inner.kind {
+            StatementKind::FunctionDecl(func_decl) => IrStmtKind::Function(self.lower_function(func_decl, visibility)),
+            StatementKind::StructDecl(struct_decl) => IrStmtKind::Struct(self.lower_struct(struct_decl, visibility)),
+            StatementKind::EnumDecl(enum_decl) => IrStmtKind::Enum(self.lower_enum(enum_decl, visibility)),
+            StatementKind::VariableDecl(var_decl) if var_decl.is_const => self.lower_const(var_decl, visibility),
+            _ => return None,
+        })
+    }
+
+    fn lower_global(&mut self, var_decl: &VariableDecl) -> IrStmtKind {
+        let ty = match &var_decl.var_type {
+            Some(var_type) => lower_type(var_type),
+            None => literal_type(&var_decl.value),
+        };
+        IrStmtKind::Static {
+            name: self.names.value(&var_decl.name),
+            ty,
+            value: self.lower_owned(&var_decl.value),
+        }
+    }
+
+    fn lower_const(&mut self, var_decl: &VariableDecl, visibility: IrVisibility) -> IrStmtKind {
+        let ty = match &var_decl.var_type {
+            Some(Type::String) => Some(IrType::StaticStr),
+            Some(var_type) => Some(lower_type(var_type)),
+            None if visibility.is_visible_outside() => None,
+            None => Some(match &var_decl.value.kind {
+                ExpressionKind::StringLiteral(_) => IrType::StaticStr,
+                ExpressionKind::BooleanLiteral(_) => IrType::Bool,
+                _ => IrType::Int,
+            }),
+        };
+        let value = self.lower_expression(&var_decl.value);
+        self.declare(&var_decl.name, ty.clone().unwrap_or_else(|| value.ty.clone()));
+        IrStmtKind::Const {
+            visibility,
+            name: var_decl.name.to_uppercase(),
+            ty,
+            value,
+        }
+    }
+
+    fn lower_let(&mut self, var_decl: &VariableDecl, offset: usize) -> IrStmtKind {
+        let ty = var_decl.var_type.as_ref().map(lower_type);
+        let value = match &var_decl.var_type {
+            Some(var_type) => self.lower_declared(&var_decl.value, var_type),
+            None => self.lower_moved(&var_decl.value),
+        };
+        self.declare(&var_decl.name, ty.clone().unwrap_or_else(|| value.ty.clone()));
+
+        IrStmtKind::Let {
+            name: self.names.function(&var_decl.name),
+            mutable: self.mutable.contains(&offset),
+            ty,
+            value,
+        }
+    }
+
+    fn lower_function(&mut self, func_decl: &FunctionDecl, visibility: IrVisibility) -> IrFunction {
+        self.functions.insert(func_decl.name.clone(), func_decl.return_type.clone());
+        let borrows = self.borrows.get(&func_decl.name).cloned().unwrap_or_default();
+        let params: Vec<(String, IrType)> = func_decl
+            .parameters
+            .iter()
+            .enumerate()
+            .map(|(i, param)| {
+                let ty = lower_type(&param.param_type);
+                let ty = if borrows.get(i).copied().unwrap_or(false) { IrType::Ref(Box::new(ty)) } else { ty };
+                (param.name.clone(), ty)
+            })
+            .collect();
+        let location = self.location();
+        let mut body = self.lower_function_body(&func_decl.body, &params);
+        let params = if visibility.is_visible_outside() { accept_owned(params, &mut body) } else { params };
+        match func_decl.return_type {
+            Type::String => map_returns(&mut body, &|value| match value.ty {
+                IrType::StaticStr => IrExpr::new(IrExprKind::ToOwned(Box::new(value)), IrType::Str),
+                _ => value,
+            }),
+            Type::Number => map_returns(&mut body, &|value| match value.ty {
+                IrType::Float => IrExpr::new(IrExprKind::Cast(Box::new(value), IrType::Int), IrType::Int),
+                _ => value,
+            }),
+            _ => {}
+        }
+        if self.runtime {
+            let frame = IrExpr::new(
+                IrExprKind::Call {
+                    path: "jrust_runtime::trace::enter".to_string(),
+                    args: vec![
+                        IrExpr::new(IrExprKind::Str(func_decl.name.clone()), IrType::StaticStr),
+                        IrExpr::new(IrExprKind::Str(location), IrType::StaticStr),
+                    ],
+                },
+                IrType::Unknown,
+            );
+            let frame = IrStmtKind::Let { name: "_frame".to_string(), mutable: false, ty: None, value: frame };
+            body.insert(0, IrStmt { kind: frame, line: None });
+        }
+        if func_decl.name == "main" && self.is_main_file && !self.is_library {
+            body.insert(0, self.panic_hook());
+        }
+        IrFunction {
+            visibility,
+            name: self.names.function(&func_decl.name),
+            params: params.into_iter().map(|(name, ty)| (self.names.function(&name), ty)).collect(),
+            return_type: lower_type(&func_decl.return_type),
+            body,
+            attributes: lower_attributes(&func_decl.attributes, &[]),
+        }
+    }
+
+    fn lower_struct(&mut self, struct_decl: &StructDecl, visibility: IrVisibility) -> IrStruct {
+        let serializable = is_serializable(&struct_decl.attributes);
+        let fields = struct_decl
+            .fields
+            .iter()
+            .map(|field| {
+                let ty = lower_type(&field.field_type);
+                let ty = if field.is_optional { IrType::Option(Box::new(ty)) } else { ty };
+                lower_field_decl(&self.names, &field.name, ty, serializable)
+            })
+            .collect();
+        let defaults = (!struct_decl.fields.is_empty() && struct_decl.is_defaultable()).then(|| {
+            struct_decl
+                .fields
+                .iter()
+                .filter_map(|field| Some((self.names.field(&field.name), self.lower_field_default(field)?)))
+                .collect()
+        });
+        IrStruct {
+            visibility,
+            name: struct_decl.name.clone(),
+            fields,
+            attributes: lower_attributes(&struct_decl.attributes, &["Debug", "Clone"]),
+            defaults,
+        }
+    }
+
+    fn lower_enum(&mut self, enum_decl: &EnumDecl, visibility: IrVisibility) -> IrEnum {
+        let serializable = is_serializable(&enum_decl.attributes);
+        let variants = enum_decl
+            .variants
+            .iter()
+            .map(|variant| {
+                let fields = match (&variant.fields, &variant.named_fields) {
+                    (Some(fields), _) if !visibility.is_visible_outside() || !fields.is_empty() => {
+                        IrVariantFields::Tuple(fields.iter().map(lower_type).collect())
+                    }
+                    (_, Some(fields)) => IrVariantFields::Named(
+                        fields
+                            .iter()
+                            .map(|field| lower_field_decl(&self.names, &field.name, lower_type(&field.field_type), serializable))
+                            .collect(),
+                    ),
+                    _ => IrVariantFields::Unit,
+                };
+                (variant.name.clone(), fields)
+            })
+            .collect();
+        IrEnum {
+            visibility,
+            name: enum_decl.name.clone(),
+            variants,
+            attributes: lower_attributes(&enum_decl.attributes, &["Debug", "Clone", "PartialEq"]),
+        }
+    }
+
+    /// Matches on the subject by value, cloning it if it's used again, so the
+    /// arms bind owned fields
+    fn lower_match(&mut self, match_stmt: &MatchStmt) -> IrStmtKind {
+        let subject = self.lower_moved(&match_stmt.subject);
+        let arms = match_stmt
+            .arms
+            .iter()
+            .map(|arm| {
+                self.scopes.push(HashMap::new());
+                let pattern = match &arm.pattern {
+                    Pattern::Wildcard => IrPattern::Wildcard,
+                    Pattern::Variant { enum_name, variant, bindings } => {
+                        let path = format!("{}::{}", enum_name, variant);
+                        let declared = self
+                            .enums
+                            .get(enum_name)
+                            .and_then(|decl| decl.variants.iter().find(|v| &v.name == variant))
+                            .cloned();
+                        match bindings {
+                            PatternBindings::None => IrPattern::Unit(path),
+                            PatternBindings::Tuple(names) => {
+                                let types = declared.as_ref().and_then(|v| v.fields.clone()).unwrap_or_default();
+                                for (i, name) in names.iter().enumerate() {
+                                    self.declare(name, types.get(i).map_or(IrType::Unknown, lower_type));
+                                }
+                                IrPattern::Tuple(path, names.iter().map(|name| self.names.function(name)).collect())
+                            }
+                            PatternBindings::Named(fields) => {
+                                let declared_fields = declared.and_then(|v| v.named_fields).unwrap_or_default();
+                                for (field, binding) in fields {
+                                    let ty = declared_fields
+                                        .iter()
+                                        .find(|declared| &declared.name == field)
+                                        .map_or(IrType::Unknown, |declared| lower_type(&declared.field_type));
+                                    self.declare(binding, ty);
+                                }
+                                IrPattern::Named {
+                                    path,
+                                    fields: fields
+                                        .iter()
+                                        .map(|(field, binding)| (self.names.field(field), self.names.function(binding)))
+                                        .collect(),
+                                    rest: fields.len() < declared_fields.len(),
+                                }
+                            }
+                        }
+                    }
+                };
+                let body = self.lower_block(&arm.body);
+                self.scopes.pop();
+                (pattern, body)
+            })
+            .collect();
+        IrStmtKind::Match { subject, arms }
+    }
+
+    fn lower_lock(&mut self, lock_stmt: &LockStmt) -> IrStmtKind {
+        let target = self.names.value(&lock_stmt.name);
+        let (guard, target) = if self.is_global(&lock_stmt.name) {
+            (format!("{}_guard", self.names.function(&lock_stmt.name).trim_start_matches("r#")), format!("{}()", target))
+        } else {
+            (target.clone(), target)
+        };
+        let mut mutated = HashSet::new();
+        collect_mutated_names(&lock_stmt.body, &mut mutated);
+
+        let outer_guard = self.guards.insert(lock_stmt.name.clone(), guard.clone());
+        let body = self.lower_block(&lock_stmt.body);
+        match outer_guard {
+            Some(outer_guard) => self.guards.insert(lock_stmt.name.clone(), outer_guard),
+            None => self.guards.remove(&lock_stmt.name),
+        };
+
+        IrStmtKind::Lock {
+            guard,
+            target,
+            mutable: mutated.contains(&lock_stmt.name),
+            body,
+        }
+    }
+
+    fn is_global(&self, name: &str) -> bool {
+        self.globals.contains(name) && !self.guards.contains_key(name)
+    }
+
+    fn lower_field_value(&mut self, field: &StructField, value: &Expression) -> IrExpr {
+        let value = if matches!(field.field_type, Type::String | Type::Array { .. }) {
+            self.lower_declared(value, &field.field_type)
+        } else {
+            let lowered = self.lower_expression(value);
+            self.clone_if_used_again(value, lowered)
+        };
+        if field.is_optional {
+            let ty = IrType::Option(Box::new(value.ty.clone()));
+            IrExpr::new(IrExprKind::Some(Box::new(value)), ty)
+        } else {
+            value
+        }
+    }
+
+    fn lower_field_default(&mut self, field: &StructField) -> Option<IrExpr> {
+        match &field.default {
+            Some(value) => Some(self.lower_field_value(field, value)),
+            None if field.is_optional => Some(IrExpr::new(IrExprKind::None, IrType::Unknown)),
+            None => None,
+        }
+    }
+
+    fn lower_owned(&mut self, expr: &Expression) -> IrExpr {
+        let lowered = self.lower_expression(expr);
+        if matches!(expr.kind, ExpressionKind::StringLiteral(_)) {
+            IrExpr::new(IrExprKind::ToOwned(Box::new(lowered)), IrType::Str)
+        } else {
+            lowered
+        }
+    }
+
+    /// Array literals follow the declared type all the way down, so nested
+    /// fixed-size arrays and `string[][]` elements come out right.
+    fn lower_declared(&mut self, expr: &Expression, declared: &Type) -> IrExpr {
+        match (declared, &expr.kind) {
+            (Type::Array { element_type, size }, ExpressionKind::ArrayLiteral(elements)) => {
+                let elements = elements.iter().map(|elem| self.lower_declared(elem, element_type)).collect();
+                let kind = if size.is_some() { IrExprKind::Array(elements) } else { IrExprKind::Vec(elements) };
+                IrExpr::new(kind, lower_type(declared))
+            }
+            (Type::Number, _) => {
+                // A float stored as a `number` is truncated toward zero
+                let value = self.lower_moved(expr);
+                match value.ty {
+                    IrType::Float => IrExpr::new(IrExprKind::Cast(Box::new(value), IrType::Int), IrType::Int),
+                    _ => value,
+                }
+            }
+            _ => self.lower_moved(expr),
+        }
+    }
+
+    fn lower_moved(&mut self, expr: &Expression) -> IrExpr {
+        let lowered = self.lower_owned(expr);
+        self.clone_if_used_again(expr, lowered)
+    }
+
+    fn clone_if_used_again(&self, expr: &Expression, lowered: IrExpr) -> IrExpr {
+        match (&expr.kind, &lowered.kind) {
+            (ExpressionKind::Identifier(name), IrExprKind::Path(_))
+                if lowered.ty.needs_clone() && self.used_again(name, expr.span.start) =>
+            {
+                let ty = lowered.ty.clone();
+                IrExpr::new(IrExprKind::Clone(Box::new(lowered)), ty)
+            }
+            _ => clone_place(lowered),
+        }
+    }
+
+    /// Lowers an expression that is written through. Negative or computed
+    /// indexes go through `jrust_slot`, since `IndexMut` can't see `items.len()`
+    /// in its own index.
+    fn lower_place(&mut self, expr: &Expression) -> IrExpr {
+        match &expr.kind {
+            ExpressionKind::IndexAccess { object, index } => {
+                let object = self.lower_place(object);
+                self.lower_index(object, index, true)
+            }
+            ExpressionKind::MemberAccess { object, member }
+                if member != "length" && matches!(object.kind, ExpressionKind::IndexAccess { .. } | ExpressionKind::MemberAccess { .. }) =>
+            {
+                let object = self.lower_place(object);
+                self.lower_field(object, member)
+            }
+            _ => self.lower_receiver(expr),
+        }
+    }
+
+    fn lower_index(&mut self, object: IrExpr, index: &Expression, place: bool) -> IrExpr {
+        let index = self.lower_expression(index);
+        let ty = object.ty.element();
+        let (object, index) = (Box::new(object), Box::new(index));
+        if index.ty.is_string() || object.ty.is_string() {
+            return IrExpr::new(IrExprKind::Index { object, index }, ty);
+        }
+        self.uses_index = true;
+        if place || matches!(object.kind, IrExprKind::LockGlobal(_)) {
+            self.uses_slot = true;
+            let slot = IrExpr::new(IrExprKind::IndexSlot { object, index }, ty.clone());
+            return if place { slot } else { IrExpr::new(IrExprKind::Deref(Box::new(slot)), ty) };
+        }
+        IrExpr::new(IrExprKind::CheckedIndex { object, index }, ty)
+    }
+
+    fn lower_field(&self, object: IrExpr, member: &str) -> IrExpr {
+        let struct_type = match &object.ty {
+            IrType::Ref(inner) => &**inner,
+            other => other,
+        };
+        let ty = match struct_type {
+            IrType::Named(name) => self
+                .structs
+                .get(name)
+                .and_then(|decl| decl.fields.iter().find(|field| field.name == member))
+                .map_or(IrType::Unknown, |field| {
+                    let ty = lower_type(&field.field_type);
+                    if field.is_optional { IrType::Option(Box::new(ty)) } else { ty }
+                }),
+            _ => IrType::Unknown,
+        };
+        IrExpr::new(
+            IrExprKind::Field {
+                object: Box::new(object),
+                member: self.names.field(member),
+            },
+            ty,
+        )
+    }
+
+    fn lower_callback(&mut self, callback: &Expression, items: &[(IrType, usize)]) -> IrExpr {
+        match &callback.kind {
+            ExpressionKind::Closure { parameters, body } => self.lower_closure(parameters, body, items),
+            _ => self.lower_expression(callback),
+        }
+    }
+
+    /// `items` gives the type of each value the iterator hands the closure and
+    /// how many references it sits behind; copyable values are destructured.
+    fn lower_closure(&mut self, parameters: &[Parameter], body: &[Statement], items: &[(IrType, usize)]) -> IrExpr {
+        self.loop_depth += 1;
+        let outer_in_try = std::mem::replace(&mut self.in_try, false);
+        self.scopes.push(HashMap::new());
+        let mut params = Vec::new();
+        for (i, param) in parameters.iter().enumerate() {
+            let (item, depth) = items.get(i).cloned().unwrap_or((IrType::Unknown, 0));
+            let declared = match &param.param_type {
+                Type::Inferred => item,
+                ty => lower_type(ty),
+            };
+            let (derefs, ty) = match depth {
+                0 => (0, declared),
+                _ if declared.needs_clone() => (depth - 1, IrType::Ref(Box::new(declared))),
+                _ => (depth, declared),
+            };
+            params.push(format!("{}{}", "&".repeat(derefs), self.names.value(&param.name)));
+            self.declare(&param.name, ty);
+        }
+        let body = self.lower_block(body);
+        self.scopes.pop();
+        self.loop_depth -= 1;
+        self.in_try = outer_in_try;
+        let ty = match body.as_slice() {
+            [IrStmt { kind: IrStmtKind::Return(Some(value)), .. }] => value.ty.clone(),
+            _ => IrType::Unknown,
+        };
+        IrExpr::new(IrExprKind::Closure { params, body }, ty)
+    }
+
+    /// Calls to fallible `std/` functions throw, or propagate with `?` inside a
+    /// try block
+    fn lower_std_call(&mut self, path: String, args: &[Expression], module: &str, name: &str) -> IrExpr {
+        let (ty, fallible) = std_function(module, name).unwrap_or((IrType::Unknown, false));
+        let strings = Type::Array {
+            element_type: Box::new(Type::String),
+            size: None,
+        };
+        // Array parameters of the typed `std/` functions all take strings
+        let mut args: Vec<IrExpr> = args.iter().map(|arg| self.lower_declared(arg, &strings)).collect();
+        if let Some((_, _, arity)) = STD_OPTIONS.iter().find(|(m, n, _)| *m == module && *n == name) {
+            args.resize_with(*arity, || IrExpr::new(IrExprKind::DefaultValue, IrType::Unknown));
+        }
+        let call = IrExpr::new(IrExprKind::Call { path, args }, ty.clone());
+        if !fallible {
+            return call;
+        }
+        let propagate = self.in_try;
+        IrExpr::new(IrExprKind::Fallible { call: Box::new(call), propagate }, ty)
+    }
+
+    fn panic_hook(&mut self) -> IrStmt {
+        let path = if self.runtime {
+            "jrust_runtime::init"
+        } else {
+            self.uses_panic_hook = true;
+            "jrust_panic_hook"
+        };
+        let call = IrExprKind::Call { path: path.to_string(), args: Vec::new() };
+        IrStmt { kind: IrStmtKind::Expr(IrExpr::new(call, IrType::Unit)), line: None }
+    }
+
+    /// Where the statement being lowered sits in the jRust source
+    fn location(&self) -> String {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => format!("{}:{}", file, line),
+            (Some(file), None) => file.clone(),
+            (None, Some(line)) => format!("line {}", line),
+            (None, None) => "unknown location".to_string(),
+        }
+    }
+
+    fn lower_format_arg(&mut self, expr: &Expression) -> FormatArg {
+        let value = self.lower_expression(expr);
+        let debug = !self.has_display(&value.ty);
+        FormatArg { value, debug }
+    }
+
+    /// Whether values of `ty` print with `{}`. jRust structs, enums and arrays
+    /// only derive `Debug`; unknown types are assumed to have `Display`, as
+    /// most `std/` results do.
+    fn has_display(&self, ty: &IrType) -> bool {
+        match ty {
+            IrType::Int
+            | IrType::Float
+            | IrType::Str
+            | IrType::StaticStr
+            | IrType::Bool
+            | IrType::Char
+            | IrType::Unknown => true,
+            IrType::Ref(inner) => self.has_display(inner),
+            IrType::Named(name) => self.display_types.contains(name),
+            _ => false,
+        }
+    }
+
+    fn lower_arguments(&mut self, args: &[Expression]) -> Vec<IrExpr> {
+        args.iter().map(|arg| self.lower_moved(arg)).collect()
+    }
+
+    fn lower_call_arguments(&mut self, name: &str, args: &[Expression]) -> Vec<IrExpr> {
+        let borrows = self.borrows.get(name).cloned().unwrap_or_default();
+        args.iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                if !borrows.get(i).copied().unwrap_or(false) {
+                    return self.lower_moved(arg);
+                }
+                let lowered = self.lower_expression(arg);
+                match (&arg.kind, &lowered.ty) {
+                    (ExpressionKind::StringLiteral(_), _) | (_, IrType::Ref(_)) => lowered,
+                    _ => {
+                        let ty = IrType::Ref(Box::new(lowered.ty.clone()));
+                        IrExpr::new(IrExprKind::Ref(Box::new(lowered)), ty)
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn namespace_of(&self, expr: &Expression) -> Option<String> {
+        match &expr.kind {
+            ExpressionKind::Identifier(name) if self.namespaces.contains(name) => Some(self.names.value(name)),
+            _ => None,
+        }
+    }
+
+    fn namespace_path(&self, object: &Expression, member: &str) -> String {
+        let namespace = self.namespace_of(object).unwrap_or_default();
+        let std = matches!(&object.kind, ExpressionKind::Identifier(name) if self.std_namespaces.contains_key(name));
+        let member = if std { convert_name(member) } else { self.names.value(member) };
+        format!("{}::{}", namespace, member)
+    }
+
+    /// The enum `expr` names, unless a variable of the same name hides it
+    fn enum_of(&self, expr: &Expression) -> Option<&EnumDecl> {
+        match &expr.kind {
+            ExpressionKind::Identifier(name) if !self.scopes.iter().any(|scope| scope.contains_key(name)) => {
+                self.enums.get(name)
+            }
+            _ => None,
+        }
+    }
+
+    /// `Enum.Variant(args)`, with each argument converted to its field's type
+    fn lower_variant_call(&mut self, enum_name: &str, variant: &str, arguments: &[Expression]) -> IrExpr {
+        let path = format!("{}::{}", enum_name, variant);
+        let ty = IrType::Named(enum_name.to_string());
+        let field_types = self
+            .enums
+            .get(enum_name)
+            .and_then(|decl| decl.variants.iter().find(|declared| declared.name == variant))
+            .and_then(|declared| declared.fields.clone())
+            .unwrap_or_default();
+        if arguments.is_empty() {
+            return IrExpr::new(IrExprKind::Path(path), ty);
+        }
+        let args = arguments
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| match field_types.get(i) {
+                Some(field_type @ (Type::String | Type::Array { .. })) => self.lower_declared(arg, field_type),
+                _ => self.lower_moved(arg),
+            })
+            .collect();
+        IrExpr::new(IrExprKind::Call { path, args }, ty)
+    }
+
+    fn lower_receiver(&mut self, object: &Expression) -> IrExpr {
+        match &object.kind {
+            ExpressionKind::Identifier(name) if self.guards.contains_key(name) => {
+                IrExpr::new(IrExprKind::Path(self.guards[name].clone()), shared_inner(self.lookup(name)))
+            }
+            ExpressionKind::Identifier(name) if self.is_global(name) => {
+                IrExpr::new(IrExprKind::LockGlobal(self.names.value(name)), self.lookup(name))
+            }
+            _ => self.lower_expression(object),
+        }
+    }
+
+    pub fn lower_expression(&mut self, expr: &Expression) -> IrExpr {
+        match &expr.kind {
+            ExpressionKind::NumberLiteral(n) => IrExpr::new(IrExprKind::Int(*n), IrType::Int),
+            ExpressionKind::StringLiteral(s) => IrExpr::new(IrExprKind::Str(s.clone()), IrType::StaticStr),
+            ExpressionKind::BooleanLiteral(b) => IrExpr::new(IrExprKind::Bool(*b), IrType::Bool),
+            ExpressionKind::Identifier(name) => {
+                let ty = self.lookup(name);
+                if let Some(guard) = self.guards.get(name) {
+                    let guard = IrExpr::new(IrExprKind::Path(guard.clone()), ty.clone());
+                    IrExpr::new(IrExprKind::Deref(Box::new(guard)), shared_inner(ty))
+                } else if self.is_global(name) {
+                    IrExpr::new(IrExprKind::ReadGlobal(self.names.value(name)), ty)
+                } else {
+                    IrExpr::new(IrExprKind::Path(self.names.value(name)), ty)
+                }
+            }
+            ExpressionKind::ArrayLiteral(elements) => {
+                let elements: Vec<IrExpr> = elements
+                    .iter()
+                    .map(|elem| {
+                        let lowered = self.lower_expression(elem);
+                        self.clone_if_used_again(elem, lowered)
+                    })
+                    .collect();
+                let element_type = elements.first().map_or(IrType::Unknown, |first| first.ty.clone());
+                IrExpr::new(IrExprKind::Vec(elements), IrType::Vec(Box::new(element_type)))
+            }
+            ExpressionKind::StructLiteral { name, fields } => {
+                let struct_decl = self.structs.get(name).cloned();
+                let mut lowered: Vec<(String, IrExpr)> = fields
+                    .iter()
+                    .map(|(name, field_value)| {
+                        let field = struct_decl
+                            .as_ref()
+                            .and_then(|decl| decl.fields.iter().find(|f| &f.name == name));
+                        let value = match field {
+                            Some(field) => self.lower_field_value(field, field_value),
+                            None => self.lower_expression(field_value),
+                        };
+                        (self.names.field(name), value)
+                    })
+                    .collect();
+                let omitted: Vec<StructField> = struct_decl
+                    .iter()
+                    .flat_map(|decl| &decl.fields)
+                    .filter(|field| !fields.iter().any(|(name, _)| *name == field.name))
+                    .cloned()
+                    .collect();
+                let fill_defaults = !omitted.is_empty() && struct_decl.as_ref().is_some_and(StructDecl::is_defaultable);
+                if !fill_defaults {
+                    for field in &omitted {
+                        if let Some(value) = self.lower_field_default(field) {
+                            lowered.push((self.names.field(&field.name), value));
+                        }
+                    }
+                }
+                IrExpr::new(
+                    IrExprKind::Struct {
+                        name: name.clone(),
+                        fields: lowered,
+                        fill_defaults,
+                    },
+                    IrType::Named(name.clone()),
+                )
+            }
+            ExpressionKind::VariantLiteral { enum_name, variant, fields } => {
+                let declared_fields = self
+                    .enums
+                    .get(enum_name)
+                    .and_then(|decl| decl.variants.iter().find(|v| &v.name == variant))
+                    .and_then(|v| v.named_fields.clone())
+                    .unwrap_or_default();
+                let lowered = fields
+                    .iter()
+                    .map(|(name, field_value)| {
+                        let value = match declared_fields.iter().find(|f| &f.name == name) {
+                            Some(field) => self.lower_field_value(field, field_value),
+                            None => self.lower_expression(field_value),
+                        };
+                        (self.names.field(name), value)
+                    })
+                    .collect();
+                IrExpr::new(
+                    IrExprKind::Struct {
+                        name: format!("{}::{}", enum_name, variant),
+                        fields: lowered,
+                        fill_defaults: false,
+                    },
+                    IrType::Named(enum_name.clone()),
+                )
+            }
+            ExpressionKind::BinaryOp(left, op, right) => {
+                let mut left = self.lower_expression(left);
+                let mut right = self.lower_expression(right);
+                if *op == BinaryOp::Add && (left.ty.is_string() || right.ty.is_string()) {
+                    let mut parts = Vec::new();
+                    for side in [left, right] {
+                        match side.kind {
+                            IrExprKind::Format(inner) => parts.extend(inner),
+                            _ => {
+                                let debug = !self.has_display(&side.ty);
+                                parts.push(FormatArg { value: side, debug });
+                            }
+                        }
+                    }
+                    return IrExpr::new(IrExprKind::Format(parts), IrType::Str);
+                }
+                // An integer meeting a float is promoted to `f64`
+                let float = left.ty == IrType::Float || right.ty == IrType::Float;
+                if float {
+                    left = to_float(left);
+                    right = to_float(right);
+                }
+                let ty = match op {
+                    BinaryOp::Equal
+                    | BinaryOp::NotEqual
+                    | BinaryOp::Greater
+                    | BinaryOp::GreaterEqual
+                    | BinaryOp::Less
+                    | BinaryOp::LessEqual
+                    | BinaryOp::And
+                    | BinaryOp::Or => IrType::Bool,
+                    _ if float => IrType::Float,
+                    _ => IrType::Int,
+                };
+                IrExpr::new(IrExprKind::Binary(Box::new(left), *op, Box::new(right)), ty)
+            }
+            ExpressionKind::FunctionCall(name, args)
+                if matches!(name.as_str(), "assert" | "assertEq" | "panic") && !self.functions.contains_key(name) =>
+            {
+                let (macro_name, message_at) = match name.as_str() {
+                    "assert" => ("assert", 1),
+                    "assertEq" => ("assert_eq", 2),
+                    _ => ("panic", 0),
+                };
+                let args = args.iter().map(|arg| self.lower_expression(arg)).collect();
+                IrExpr::new(
+                    IrExprKind::Assert {
+                        macro_name: macro_name.to_string(),
+                        args,
+                        message_at,
+                        location: self.location(),
+                    },
+                    IrType::Unit,
+                )
+            }
+            ExpressionKind::FunctionCall(name, args)
+                if matches!(name.as_str(), "parseInt" | "parseFloat") && args.len() == 1 && !self.functions.contains_key(name) =>
+            {
+                let (method, ty) = match name.as_str() {
+                    "parseInt" => (Method::ParseInt, IrType::Int),
+                    _ => (Method::ParseFloat, IrType::Float),
+                };
+                IrExpr::new(
+                    IrExprKind::Method {
+                        receiver: Box::new(self.lower_receiver(&args[0])),
+                        method,
+                        args: Vec::new(),
+                    },
+                    IrType::Option(Box::new(ty)),
+                )
+            }
+            ExpressionKind::FunctionCall(name, args) if name == "input" && args.len() <= 1 && !self.functions.contains_key(name) => {
+                self.uses_input = true;
+                let prompt = match args.first() {
+                    Some(prompt) => self.lower_receiver(prompt),
+                    None => IrExpr::new(IrExprKind::Str(String::new()), IrType::StaticStr),
+                };
+                let prompt = match prompt.ty {
+                    IrType::Str => IrExpr::new(IrExprKind::Ref(Box::new(prompt)), IrType::StaticStr),
+                    _ => prompt,
+                };
+                IrExpr::new(
+                    IrExprKind::Call {
+                        path: "jrust_input".to_string(),
+                        args: vec![prompt],
+                    },
+                    IrType::Str,
+                )
+            }
+            ExpressionKind::FunctionCall(name, args) if self.std_functions.contains_key(name) => {
+                let (module, std_name) = self.std_functions[name].clone();
+                self.lower_std_call(self.names.value(name), args, &module, &std_name)
+            }
+            ExpressionKind::FunctionCall(name, args) => {
+                let ty = self.functions.get(name).map_or(IrType::Unknown, lower_type);
+                IrExpr::new(
+                    IrExprKind::Call {
+                        path: self.names.function(name),
+                        args: self.lower_call_arguments(name, args),
+                    },
+                    ty,
+                )
+            }
+            ExpressionKind::Channel(element_type) => {
+                let element_type = (*element_type != Type::Inferred).then(|| lower_type(element_type));
+                IrExpr::new(IrExprKind::Channel(element_type), IrType::Unknown)
+            }
+            ExpressionKind::Shared { value_type, value } => {
+                let value = self.lower_moved(value);
+                let declared = (*value_type != Type::Inferred).then(|| lower_type(value_type));
+                let ty = IrType::Shared(Box::new(declared.clone().unwrap_or_else(|| value.ty.clone())));
+                IrExpr::new(IrExprKind::Shared(declared, Box::new(value)), ty)
+            }
+            ExpressionKind::MethodCall { object, method, arguments } if self.namespace_of(object).is_some() => {
+                let path = self.namespace_path(object, method);
+                let std_module = match &object.kind {
+                    ExpressionKind::Identifier(name) => self.std_namespaces.get(name).cloned(),
+                    _ => None,
+                };
+                if let Some(module) = std_module.filter(|module| std_function(module, method).is_some()) {
+                    return self.lower_std_call(path, arguments, &module, method);
+                }
+                IrExpr::new(
+                    IrExprKind::Call {
+                        path,
+                        args: self.lower_arguments(arguments),
+                    },
+                    IrType::Unknown,
+                )
+            }
+            ExpressionKind::MethodCall { object, method, arguments } if self.enum_of(object).is_some() => {
+                let enum_name = self.enum_of(object).map(|decl| decl.name.clone()).unwrap_or_default();
+                self.lower_variant_call(&enum_name, method, arguments)
+            }
+            ExpressionKind::MethodCall { method, arguments, .. } if expr.is_console_call() => {
+                let args = arguments.iter().map(|arg| self.lower_format_arg(arg)).collect();
+                let stderr = matches!(method.as_str(), "warn" | "error");
+                IrExpr::new(IrExprKind::Print { args, stderr }, IrType::Unit)
+            }
+            ExpressionKind::MethodCall { object, method, arguments }
+                if method == "args" && arguments.is_empty() && matches!(&object.kind, ExpressionKind::Identifier(name) if name == "env") =>
+            {
+                IrExpr::new(IrExprKind::EnvArgs, IrType::Vec(Box::new(IrType::Str)))
+            }
+            ExpressionKind::MethodCall { object, method, arguments } => {
+                let receiver = if MUTATING_METHODS.contains(&method.as_str()) {
+                    self.lower_place(object)
+                } else {
+                    self.lower_receiver(object)
+                };
+                let method = match Method::from_name(method) {
+                    Method::Slice if receiver.ty.is_string() => Method::Substring,
+                    Method::Named(name) => Method::Named(to_snake_case(&name)),
+                    method => method,
+                };
+                let element = receiver.ty.element();
+                let initial = match (&method, arguments.get(1)) {
+                    (Method::Reduce, Some(initial)) => Some(self.lower_owned(initial)),
+                    _ => None,
+                };
+                let items = match &method {
+                    Method::Map | Method::ForEach | Method::FindIndex | Method::Any | Method::All => {
+                        vec![(element.clone(), 1)]
+                    }
+                    Method::Filter | Method::Find => vec![(element.clone(), 2)],
+                    Method::Sort if !arguments.is_empty() => vec![(element.clone(), 1), (element.clone(), 1)],
+                    Method::SortBy => vec![(element.clone(), 1)],
+                    Method::Reduce => match &initial {
+                        Some(initial) => vec![(initial.ty.clone(), 0), (element.clone(), 1)],
+                        None => vec![(element.clone(), 0), (element.clone(), 0)],
+                    },
+                    _ => Vec::new(),
+                };
+                let mut args: Vec<IrExpr> = arguments
+                    .iter()
+                    .enumerate()
+                    .map(|(i, arg)| {
+                        if !items.is_empty() && i == 0 {
+                            let mut callback = self.lower_callback(arg, &items);
+                            if let IrExprKind::Closure { body, .. } = &mut callback.kind {
+                                match method {
+                                    Method::Sort => map_returns(body, &compare_to_zero),
+                                    Method::SortBy => map_returns(body, &|key| {
+                                        if key.ty.needs_clone() && !matches!(key.kind, IrExprKind::Clone(_)) {
+                                            let ty = key.ty.clone();
+                                            IrExpr::new(IrExprKind::Clone(Box::new(key)), ty)
+                                        } else {
+                                            key
+                                        }
+                                    }),
+                                    _ => {}
+                                }
+                            }
+                            callback
+                        } else if method == Method::Send && i == 0 {
+                            self.lower_moved(arg)
+                        } else if matches!(method, Method::Push | Method::InsertFirst) {
+                            let lowered = self.lower_expression(arg);
+                            self.clone_if_used_again(arg, lowered)
+                        } else {
+                            self.lower_expression(arg)
+                        }
+                    })
+                    .collect();
+                if let (Some(initial), Some(arg)) = (initial, args.get_mut(1)) {
+                    *arg = initial;
+                }
+                let ty = match &method {
+                    Method::RemoveFirst => element,
+                    Method::Slice | Method::Reverse | Method::Filter | Method::Concat => match &receiver.ty {
+                        IrType::Ref(inner) => (**inner).clone(),
+                        other => other.clone(),
+                    },
+                    Method::Map => IrType::Vec(Box::new(args.first().map_or(IrType::Unknown, |f| f.ty.clone()))),
+                    Method::Find => IrType::Option(Box::new(element)),
+                    Method::At => {
+                        self.uses_at = true;
+                        IrType::Option(Box::new(element))
+                    }
+                    Method::FindIndex => IrType::Int,
+                    Method::Any | Method::All => IrType::Bool,
+                    Method::ForEach => IrType::Unit,
+                    Method::Reduce => args.get(1).map_or(element, |initial| initial.ty.clone()),
+                    Method::Flat => IrType::Vec(Box::new(element.element())),
+                    Method::CharAt => IrType::Char,
+                    Method::Substring
+                    | Method::ToUpperCase
+                    | Method::ToLowerCase
+                    | Method::Trim
+                    | Method::Join
+                    | Method::Replace
+                    | Method::PadStart
+                    | Method::Repeat
+                    | Method::ToString
+                    | Method::ToFixed => IrType::Str,
+                    Method::IndexOf => IrType::Int,
+                    Method::Split => IrType::Vec(Box::new(IrType::Str)),
+                    Method::Contains | Method::StartsWith | Method::EndsWith => IrType::Bool,
+                    Method::Push | Method::Send | Method::InsertFirst | Method::Sort | Method::SortBy => IrType::Unit,
+                    Method::Named(name) if matches!(name.as_str(), "unwrap" | "expect") => match &receiver.ty {
+                        IrType::Option(inner) => (**inner).clone(),
+                        _ => IrType::Unknown,
+                    },
+                    _ => IrType::Unknown,
+                };
+                IrExpr::new(
+                    IrExprKind::Method {
+                        receiver: Box::new(receiver),
+                        method,
+                        args,
+                    },
+                    ty,
+                )
+            }
+            ExpressionKind::IndexAccess { object, index } => {
+                let object = self.lower_receiver(object);
+                self.lower_index(object, index, false)
+            }
+            ExpressionKind::SliceAccess { object, start, end } => {
+                let receiver = self.lower_receiver(object);
+                let mut args = vec![match start {
+                    Some(start) => self.lower_expression(start),
+                    None => IrExpr::new(IrExprKind::Int(0), IrType::Int),
+                }];
+                if let Some(end) = end {
+                    args.push(self.lower_expression(end));
+                }
+                let (method, ty) = if receiver.ty.is_string() {
+                    (Method::Substring, IrType::Str)
+                } else {
+                    (Method::Slice, IrType::Vec(Box::new(receiver.ty.element())))
+                };
+                IrExpr::new(
+                    IrExprKind::Method {
+                        receiver: Box::new(receiver),
+                        method,
+                        args,
+                    },
+                    ty,
+                )
+            }
+            ExpressionKind::MemberAccess { object, member } if self.namespace_of(object).is_some() => {
+                IrExpr::new(
+                    IrExprKind::Path(self.namespace_path(object, member)),
+                    IrType::Unknown,
+                )
+            }
+            ExpressionKind::MemberAccess { object, member } if self.enum_of(object).is_some() => {
+                let enum_name = self.enum_of(object).map(|decl| decl.name.clone()).unwrap_or_default();
+                IrExpr::new(
+                    IrExprKind::Path(format!("{}::{}", enum_name, member)),
+                    IrType::Named(enum_name),
+                )
+            }
+            ExpressionKind::MemberAccess { object, member } => {
+                let object = self.lower_receiver(object);
+                if member == "length" {
+                    return IrExpr::new(IrExprKind::Len(Box::new(object)), IrType::Int);
+                }
+                self.lower_field(object, member)
+            }
+            ExpressionKind::Closure { parameters, body } => self.lower_closure(parameters, body, &[]),
+            ExpressionKind::Assignment { target, value } => {
+                let target = match &target.kind {
+                    ExpressionKind::Identifier(name) if self.is_global(name) => {
+                        let locked = self.lower_receiver(target);
+                        let ty = locked.ty.clone();
+                        IrExpr::new(IrExprKind::Deref(Box::new(locked)), ty)
+                    }
+                    ExpressionKind::IndexAccess { .. } | ExpressionKind::MemberAccess { .. } => {
+                        match self.lower_place(target) {
+                            slot @ IrExpr { kind: IrExprKind::IndexSlot { .. }, .. } => {
+                                let ty = slot.ty.clone();
+                                IrExpr::new(IrExprKind::Deref(Box::new(slot)), ty)
+                            }
+                            place => place,
+                        }
+                    }
+                    _ => self.lower_expression(target),
+                };
+                let value = self.lower_moved(value);
+                IrExpr::new(
+                    IrExprKind::Assign {
+                        target: Box::new(target),
+                        value: Box::new(value),
+                    },
+                    IrType::Unit,
+                )
+            }
+        }
+    }
+}
+
+pub fn lower_type(type_: &Type) -> IrType {
+    match type_ {
+        Type::Number => IrType::Int,
+        Type::String | Type::Any => IrType::Str,
+        Type::Boolean => IrType::Bool,
+        Type::Void => IrType::Unit,
+        Type::Array { element_type, size: Some(size) } => IrType::Array(Box::new(lower_type(element_type)), *size),
+        Type::Array { element_type, size: None } => IrType::Vec(Box::new(lower_type(element_type))),
+        Type::Custom(name) => IrType::Named(name.clone()),
+        Type::Shared(inner) => IrType::Shared(Box::new(lower_type(inner))),
+        Type::Inferred => IrType::Unknown,
+    }
+}
+
+/// The result type of a `std/` function and whether it returns a `Result`
+fn std_function(module: &str, name: &str) -> Option<(IrType, bool)> {
+    Some(match (module, name) {
+        ("fs", "readFile") => (IrType::Str, true),
+        ("fs", "writeFile" | "appendFile" | "remove") => (IrType::Unit, true),
+        ("fs", "exists") => (IrType::Bool, false),
+        ("fs", "readDir") => (IrType::Vec(Box::new(IrType::Str)), true),
+        ("env", "get") => (IrType::Option(Box::new(IrType::Str)), false),
+        ("env", "set") => (IrType::Unit, false),
+        ("env", "args") => (IrType::Vec(Box::new(IrType::Str)), false),
+        ("process", "exit") => (IrType::Unit, false),
+        ("process", "cwd") => (IrType::Str, true),
+        ("process", "run") => (IrType::Unknown, true),
+        ("time", "formatDate") => (IrType::Str, true),
+        ("time", "parseDate") => (IrType::Unknown, true),
+        ("json", "parse") => (IrType::Unknown, true),
+        ("json", "stringify") => (IrType::Str, true),
+        ("log", "debug" | "info" | "warn" | "error") => (IrType::Unit, false),
+        ("csv", "parse") => (IrType::Vec(Box::new(IrType::Vec(Box::new(IrType::Str)))), true),
+        ("csv", "parseRecords") => (IrType::Unknown, true),
+        ("csv", "write") => (IrType::Str, true),
+        ("crypto", "sha256" | "md5") => (IrType::Str, false),
+        ("base64" | "hex" | "url", "encode") => (IrType::Str, false),
+        ("base64" | "hex" | "url", "decode") => (IrType::Str, true),
+        ("http", "fetch") => (IrType::Unknown, true),
+        ("http", "serve") => (IrType::Unit, true),
+        ("random", "randomFloat") => (IrType::Float, false),
+        _ => return None,
+    })
+}
+
+/// `std/` types that implement `Display`, unlike the structs of [`std_struct`]
+const STD_DISPLAY_TYPES: &[(&str, &str)] = &[("time", "Duration"), ("json", "Value")];
+
+const STD_OPTIONS: &[(&str, &str, usize)] = &[("http", "fetch", 2)];
+
+fn std_struct(module: &str, name: &str) -> Option<StructDecl> {
+    let literal = |kind| Some(Expression::new(kind, Span::default()));
+    let string = |value: &str| literal(ExpressionKind::StringLiteral(value.to_string()));
+    let string_pairs = Type::Array {
+        element_type: Box::new(Type::Array {
+            element_type: Box::new(Type::String),
+            size: None,
+        }),
+        size: None,
+    };
+    let fields = match (module, name) {
+        ("http", "FetchOptions") => vec![
+            ("method", Type::String, string("GET")),
+            ("headers", string_pairs, literal(ExpressionKind::ArrayLiteral(Vec::new()))),
+            ("body", Type::String, string("")),
+        ],
+        ("http", "Request") => vec![
+            ("method", Type::String, string("GET")),
+            ("path", Type::String, string("/")),
+            ("query", Type::String, string("")),
+            ("headers", string_pairs, literal(ExpressionKind::ArrayLiteral(Vec::new()))),
+            ("body", Type::String, string("")),
+        ],
+        ("http", "Response") => vec![
+            ("status", Type::Number, literal(ExpressionKind::NumberLiteral(200))),
+            ("headers", string_pairs, literal(ExpressionKind::ArrayLiteral(Vec::new()))),
+            ("body", Type::String, string("")),
+        ],
+        _ => return None,
+    };
+    let fields = fields
+        .into_iter()
+        .map(|(name, field_type, default)| StructField {
+            name: name.to_string(),
+            field_type,
+            type_span: Span::default(),
+            is_optional: false,
+            is_readonly: false,
+            default,
+            span: Span::default(),
+        })
+        .collect();
+    Some(StructDecl {
+        name: name.to_string(),
+        fields,
+        attributes: Vec::new(),
+    })
+}
+
+fn literal_type(value: &Expression) -> IrType {
+    match &value.kind {
+        ExpressionKind::StringLiteral(_) => IrType::Str,
+        ExpressionKind::BooleanLiteral(_) => IrType::Bool,
+        ExpressionKind::ArrayLiteral(elements) => {
+            IrType::Vec(Box::new(elements.first().map_or(IrType::Int, literal_type)))
+        }
+        _ => IrType::Int,
+    }
+}
+
+fn map_returns(body: &mut [IrStmt], map: &impl Fn(IrExpr) -> IrExpr) {
+    for stmt in body {
+        match &mut stmt.kind {
+            IrStmtKind::Return(Some(value)) => {
+                let returned = std::mem::replace(value, IrExpr::new(IrExprKind::None, IrType::Unknown));
+                *value = map(returned);
+            }
+            IrStmtKind::Function(_) => {}
+            kind => kind.bodies_mut().into_iter().for_each(|inner| map_returns(inner, map)),
+        }
+    }
+}
+
+/// JS comparators return a number; `sort_by` wants an `Ordering`.
+fn compare_to_zero(value: IrExpr) -> IrExpr {
+    let zero = IrExpr::new(IrExprKind::Ref(Box::new(IrExpr::new(IrExprKind::Int(0), IrType::Int))), IrType::Unknown);
+    IrExpr::new(
+        IrExprKind::Method {
+            receiver: Box::new(value),
+            method: Method::Named("cmp".to_string()),
+            args: vec![zero],
+        },
+        IrType::Unknown,
+    )
+}
+
+fn to_float(value: IrExpr) -> IrExpr {
+    match value.ty {
+        IrType::Int => IrExpr::new(IrExprKind::Cast(Box::new(value), IrType::Float), IrType::Float),
+        _ => value,
+    }
+}
+
+fn shared_inner(ty: IrType) -> IrType {
+    match ty {
+        IrType::Shared(inner) => *inner,
+        other => other,
+    }
+}
+
+/// A declared field under its Rust name, renamed back for serde when that
+/// differs from the jRust one
+fn lower_field_decl(names: &NameMapper, name: &str, ty: IrType, serializable: bool) -> IrField {
+    let rust_name = names.field(name);
+    let renamed = rust_name.trim_start_matches("r#") != name.trim_start_matches("r#");
+    let attributes = if serializable && renamed {
+        vec![format!("serde(rename = {})", string_literal(name.trim_start_matches("r#")))]
+    } else {
+        Vec::new()
+    };
+    IrField { name: rust_name, ty, attributes }
+}
+
+fn lower_visibility(visibility: Visibility) -> IrVisibility {
+    match visibility {
+        Visibility::Crate => IrVisibility::Crate,
+        Visibility::Internal => IrVisibility::Private,
+    }
+}
+
+fn lower_attributes(attributes: &[Attribute], default_derives: &[&str]) -> Vec<String> {
+    let mut derives: Vec<&str> = default_derives.to_vec();
+    for attribute in attributes.iter().filter(|attribute| attribute.name == "derive") {
+        for derive in &attribute.arguments {
+            if !derives.contains(&derive.as_str()) {
+                derives.push(derive);
+            }
+        }
+    }
+
+    for attribute in attributes {
+        let Some((_, traits)) = CAPABILITIES.iter().find(|(capability, _)| attribute.name == *capability) else {
+            continue;
+        };
+        for derive in traits.iter() {
+            if !derives.contains(derive) {
+                derives.push(derive);
+            }
+        }
+    }
+
+    let serializable = is_serializable(attributes);
+    if serializable {
+        derives.extend(["jrust_runtime::serde::Serialize", "jrust_runtime::serde::Deserialize"]);
+    }
+
+    let mut lowered = Vec::new();
+    if !derives.is_empty() {
+        lowered.push(format!("derive({})", derives.join(", ")));
+    }
+    if serializable {
+        lowered.push("serde(crate = \"jrust_runtime::serde\")".to_string());
+    }
+    for attribute in attributes.iter().filter(|attribute| attribute.name != "derive" && !is_type_only(attribute)) {
+        if attribute.arguments.is_empty() {
+            lowered.push(attribute.name.clone());
+        } else {
+            lowered.push(format!("{}({})", attribute.name, attribute.arguments.join(", ")));
+        }
+    }
+    lowered
+}
+
+fn clone_place(lowered: IrExpr) -> IrExpr {
+    let is_place = matches!(
+        &lowered.kind,
+        IrExprKind::Field { .. } | IrExprKind::CheckedIndex { .. } | IrExprKind::Deref(_)
+    );
+    if is_place && lowered.ty.needs_clone() {
+        let ty = lowered.ty.clone();
+        return IrExpr::new(IrExprKind::Clone(Box::new(lowered)), ty);
+    }
+    lowered
+}
+
+fn accept_owned(params: Vec<(String, IrType)>, body: &mut Vec<IrStmt>) -> Vec<(String, IrType)> {
+    let mut rebinds = Vec::new();
+    let params = params
+        .into_iter()
+        .map(|(name, ty)| match ty {
+            IrType::Ref(inner) => {
+                let param = IrExpr::new(IrExprKind::Path(name.clone()), IrType::Unknown);
+                let as_ref = IrExprKind::Method { receiver: Box::new(param), method: Method::Named("as_ref".to_string()), args: Vec::new() };
+                let value = IrExpr::new(as_ref, IrType::Ref(inner.clone()));
+                let kind = IrStmtKind::Let { name: name.clone(), mutable: false, ty: None, value };
+                rebinds.push(IrStmt { kind, line: None });
+                (name, IrType::AsRef(inner))
+            }
+            other => (name, other),
+        })
+        .collect();
+    body.splice(0..0, rebinds);
+    params
+}
+
+fn iterate_borrowed(borrowed: IrExpr) -> IrExpr {
+    let method = |receiver: IrExpr, name: &str, args: Vec<IrExpr>, ty: IrType| {
+        IrExpr::new(IrExprKind::Method { receiver: Box::new(receiver), method: Method::Named(name.to_string()), args }, ty)
+    };
+    let element = borrowed.ty.element();
+    let items = |element: IrType| IrType::Vec(Box::new(element));
+    match element {
+        IrType::Int | IrType::Float | IrType::Bool | IrType::Char => {
+            let iter = method(borrowed, "iter", Vec::new(), IrType::Unknown);
+            method(iter, "copied", Vec::new(), items(element))
+        }
+        IrType::Str => {
+            let iter = method(borrowed, "iter", Vec::new(), IrType::Unknown);
+            let as_str = IrExpr::new(IrExprKind::Path("String::as_str".to_string()), IrType::Unknown);
+            method(iter, "map", vec![as_str], items(IrType::Ref(Box::new(IrType::Str))))
+        }
+        other => method(borrowed, "iter", Vec::new(), items(IrType::Ref(Box::new(other)))),
+    }
+}
+
+fn test_identifier(name: &str) -> String {
+    let mut identifier = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            identifier.push(c.to_ascii_lowercase());
+        } else if !identifier.is_empty() && !identifier.ends_with('_') {
+            identifier.push('_');
+        }
+    }
+    let identifier = identifier.trim_end_matches('_');
+    if identifier.is_empty() {
+        "unnamed".to_string()
+    } else {
+        identifier.to_string()
+    }
+}
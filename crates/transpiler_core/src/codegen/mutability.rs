@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::*;
+use crate::visit::{walk_expression, walk_statement, Visitor};
+use super::helpers::{root_identifier, MUTATING_METHODS};
+
+const READ_ONLY_METHODS: &[&str] = &[
+    "send", "recv", "slice", "map", "filter", "charAt", "substring", "indexOf", "toUpperCase",
+    "toLowerCase", "trim", "split", "join", "reverse", "includes", "contains", "clone", "len",
+    "iter", "to_string", "replace", "startsWith", "endsWith", "padStart", "repeat",
+    "reduce", "forEach", "find", "findIndex", "some", "every", "flat", "concat", "at", "toString",
+    "toFixed",
+];
+
+/// Offsets of the `let` statements whose variable is reassigned or mutated; an
+/// explicit `let mut` is only kept for receivers of methods we don't know.
+pub fn collect_mutable_declarations(statements: &[Statement]) -> HashSet<usize> {
+    let mut collector = MutabilityCollector {
+        scopes: vec![HashMap::new()],
+        mutable: HashSet::new(),
+    };
+    collector.visit_block(statements);
+    collector.mutable
+}
+
+struct MutabilityCollector {
+    scopes: Vec<HashMap<String, Option<(usize, bool)>>>,
+    mutable: HashSet<usize>,
+}
+
+impl MutabilityCollector {
+    fn visit_block(&mut self, statements: &[Statement]) {
+        self.scopes.push(HashMap::new());
+        for stmt in statements {
+            self.visit_statement(stmt);
+        }
+        self.scopes.pop();
+    }
+
+    fn visit_scoped(&mut self, shadowed: &[&str], statements: &[Statement]) {
+        self.scopes.push(shadowed.iter().map(|name| (name.to_string(), None)).collect());
+        self.visit_block(statements);
+        self.scopes.pop();
+    }
+
+    fn shadow(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), None);
+        }
+    }
+
+    fn mark(&mut self, name: &str, explicit_only: bool) {
+        let declaration = self.scopes.iter().rev().find_map(|scope| scope.get(name));
+        if let Some(Some((offset, is_mut))) = declaration {
+            if !explicit_only || *is_mut {
+                self.mutable.insert(*offset);
+            }
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for MutabilityCollector {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        match &stmt.kind {
+            StatementKind::VariableDecl(var_decl) if !var_decl.is_const => {
+                self.visit_expression(&var_decl.value);
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.insert(var_decl.name.clone(), Some((stmt.span.start, var_decl.is_mut)));
+                }
+            }
+            StatementKind::DestructureDecl(destructure) => {
+                self.visit_expression(&destructure.value);
+                for name in &destructure.names {
+                    self.shadow(name);
+                }
+            }
+            StatementKind::FunctionDecl(func_decl) => {
+                let outer = std::mem::replace(&mut self.scopes, vec![HashMap::new()]);
+                let params: Vec<&str> = func_decl.parameters.iter().map(|param| param.name.as_str()).collect();
+                self.visit_scoped(&params, &func_decl.body);
+                self.scopes = outer;
+            }
+            StatementKind::TestBlock(test_block) => {
+                let outer = std::mem::replace(&mut self.scopes, vec![HashMap::new()]);
+                self.visit_block(&test_block.body);
+                self.scopes = outer;
+            }
+            StatementKind::IfElse(if_else) => {
+                self.visit_expression(&if_else.condition);
+                self.visit_block(&if_else.then_body);
+                if let Some(else_body) = &if_else.else_body {
+                    self.visit_block(else_body);
+                }
+            }
+            StatementKind::ForLoop(for_loop) => {
+                self.visit_expression(&for_loop.iterable);
+                self.visit_scoped(&[&for_loop.variable], &for_loop.body);
+            }
+            StatementKind::WhileLoop(while_loop) => {
+                self.visit_expression(&while_loop.condition);
+                self.visit_block(&while_loop.body);
+            }
+            StatementKind::TryCatch(try_catch) => {
+                self.visit_block(&try_catch.try_body);
+                let binding: Vec<&str> = try_catch.catch_param.iter().map(String::as_str).collect();
+                self.visit_scoped(&binding, &try_catch.catch_body);
+            }
+            StatementKind::Block(body) | StatementKind::DeferStmt(body) => self.visit_block(body),
+            StatementKind::CfgBlock(block) => self.visit_block(&block.body),
+            StatementKind::LockStmt(lock_stmt) => self.visit_scoped(&[&lock_stmt.name], &lock_stmt.body),
+            StatementKind::Match(match_stmt) => {
+                self.visit_expression(&match_stmt.subject);
+                for arm in &match_stmt.arms {
+                    let bindings = match &arm.pattern {
+                        Pattern::Variant { bindings, .. } => bindings.names(),
+                        Pattern::Wildcard => Vec::new(),
+                    };
+                    self.visit_scoped(&bindings, &arm.body);
+                }
+            }
+            _ => walk_statement(self, stmt),
+        }
+    }
+
+    fn visit_expression(&mut self, expr: &'ast Expression) {
+        match &expr.kind {
+            ExpressionKind::Assignment { target, .. } => {
+                if let Some(root) = root_identifier(target) {
+                    self.mark(root, false);
+                }
+            }
+            ExpressionKind::MethodCall { object, method, .. } => {
+                if let Some(root) = root_identifier(object) {
+                    if MUTATING_METHODS.contains(&method.as_str()) {
+                        self.mark(root, false);
+                    } else if !READ_ONLY_METHODS.contains(&method.as_str()) {
+                        self.mark(root, true);
+                    }
+                }
+            }
+            ExpressionKind::Closure { parameters, body } => {
+                let params: Vec<&str> = parameters.iter().map(|param| param.name.as_str()).collect();
+                self.visit_scoped(&params, body);
+                return;
+            }
+            _ => {}
+        }
+        walk_expression(self, expr);
+    }
+}
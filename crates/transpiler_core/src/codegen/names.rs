@@ -0,0 +1,50 @@
+use super::helpers::{convert_name, escape_keyword, to_snake_case, DEFAULT_EXPORT};
+use crate::ast::DEFAULT_IMPORT;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NamingPolicy {
+    #[default]
+    SnakeCase,
+    Preserve,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NameMapper {
+    policy: NamingPolicy,
+}
+
+impl NameMapper {
+    pub fn new(policy: NamingPolicy) -> Self {
+        NameMapper { policy }
+    }
+
+    pub fn policy(&self) -> NamingPolicy {
+        self.policy
+    }
+
+    pub fn value(&self, name: &str) -> String {
+        match self.policy {
+            NamingPolicy::SnakeCase => convert_name(name),
+            NamingPolicy::Preserve => escape_keyword(name.to_string()),
+        }
+    }
+
+    pub fn function(&self, name: &str) -> String {
+        match self.policy {
+            NamingPolicy::SnakeCase => to_snake_case(name),
+            NamingPolicy::Preserve => escape_keyword(name.to_string()),
+        }
+    }
+
+    pub fn field(&self, name: &str) -> String {
+        self.function(name)
+    }
+
+    pub fn import(&self, name: &str) -> String {
+        if name == DEFAULT_IMPORT {
+            DEFAULT_EXPORT.to_string()
+        } else {
+            self.value(name)
+        }
+    }
+}
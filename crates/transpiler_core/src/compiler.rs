@@ -0,0 +1,160 @@
+use crate::ast::{FunctionDecl, Program};
+use crate::cfg::{self, Cfg};
+use crate::checker::Checker;
+use crate::codegen::{Codegen, NamingPolicy};
+use crate::diagnostic::Diagnostic;
+use crate::lexer::Lexer;
+use crate::optimize::optimize;
+use crate::parser::Parser;
+use crate::token::{Comment, Token};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputKind {
+    #[default]
+    Main,
+    Module,
+    Library,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    Lex,
+    Parse,
+    Check,
+    #[default]
+    Codegen,
+}
+
+impl Stage {
+    pub fn failure_message(&self) -> &'static str {
+        match self {
+            Stage::Lex => "Lexical analysis failed",
+            Stage::Parse => "Syntax parsing failed",
+            Stage::Check => "Semantic check failed",
+            Stage::Codegen => "Code generation failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+    pub kind: OutputKind,
+    pub stop_after: Stage,
+    pub imported_functions: Vec<(String, FunctionDecl)>,
+    pub globals: Vec<String>,
+    pub optimize: bool,
+    /// The source file's path, which assertion and panic messages point at
+    pub file: Option<String>,
+    /// The entry file's directory under `src/`, when it isn't `src/` itself
+    pub entry_dir: Option<String>,
+    /// Whether the generated crate links `jrust_runtime` even if this file
+    /// doesn't import from `std/`, see [`Codegen::link_runtime`]
+    pub runtime: bool,
+    pub cfg: Cfg,
+    pub naming: NamingPolicy,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Compilation {
+    pub tokens: Vec<Token>,
+    pub comments: Vec<Comment>,
+    pub program: Option<Program>,
+    pub rust: Option<String>,
+    pub source_map: Vec<(usize, usize)>,
+    pub tests: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompileError {
+    pub stage: Stage,
+    pub diagnostic: Box<Diagnostic>,
+}
+
+pub struct Compiler {
+    options: CompileOptions,
+}
+
+impl Compiler {
+    pub fn new(options: CompileOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn compile(&self, source: &str) -> Result<Compilation, CompileError> {
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize().map_err(|diagnostic| CompileError { stage: Stage::Lex, diagnostic: Box::new(diagnostic) })?;
+        let mut compilation = Compilation {
+            tokens,
+            comments: lexer.comments().to_vec(),
+            ..Compilation::default()
+        };
+        if self.options.stop_after == Stage::Lex {
+            return Ok(compilation);
+        }
+
+        let mut program = Parser::new(compilation.tokens.clone())
+            .parse()
+            .map_err(|diagnostic| CompileError { stage: Stage::Parse, diagnostic: Box::new(diagnostic) })?;
+        cfg::resolve(&mut program, &self.options.cfg);
+        if self.options.stop_after > Stage::Parse {
+            let generated = self
+                .compile_program(&program)
+                .map_err(|error| CompileError { diagnostic: Box::new(error.diagnostic.locate(source)), ..error })?;
+            compilation.rust = generated.rust;
+            compilation.source_map = generated.source_map;
+            compilation.tests = generated.tests;
+        }
+        compilation.program = Some(program);
+        Ok(compilation)
+    }
+
+    pub fn compile_program(&self, program: &Program) -> Result<Compilation, CompileError> {
+        let mut checker = match self.options.kind {
+            OutputKind::Main => Checker::new(),
+            OutputKind::Module | OutputKind::Library => Checker::new_module(),
+        };
+        for (name, func_decl) in &self.options.imported_functions {
+            checker.declare_function(name, func_decl.clone());
+        }
+        checker.check(program).map_err(|diagnostic| CompileError { stage: Stage::Check, diagnostic: Box::new(diagnostic) })?;
+        if self.options.stop_after == Stage::Check {
+            return Ok(Compilation::default());
+        }
+
+        let mut codegen = match self.options.kind {
+            OutputKind::Main => Codegen::new(),
+            OutputKind::Module => Codegen::new_module(),
+            OutputKind::Library => Codegen::new_library(),
+        };
+        for name in &self.options.globals {
+            codegen.declare_global(name);
+        }
+        if let Some(file) = &self.options.file {
+            codegen.set_file(file);
+        }
+        if let Some(dir) = &self.options.entry_dir {
+            codegen.set_entry_dir(dir);
+        }
+        if self.options.runtime {
+            codegen.link_runtime();
+        }
+        codegen.set_naming(self.options.naming);
+        let mut ir = codegen.lower(program);
+        if self.options.optimize {
+            optimize(&mut ir);
+        }
+        let rust = codegen.emit(&ir);
+        Ok(Compilation {
+            rust: Some(rust),
+            source_map: codegen.source_map().to_vec(),
+            tests: codegen.tests().to_vec(),
+            ..Compilation::default()
+        })
+    }
+}
+
+pub fn compile(source: &str) -> Result<String, Diagnostic> {
+    Compiler::new(CompileOptions::default())
+        .compile(source)
+        .map(|compilation| compilation.rust.unwrap_or_default())
+        .map_err(|error| *error.diagnostic)
+}
@@ -0,0 +1,255 @@
+use std::fmt;
+
+use crate::error_codes;
+use crate::span::Span;
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const GREEN: &str = "\x1b[1;32m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+
+    fn style(&self) -> &'static str {
+        match self {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+            Severity::Note => GREEN,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub severity: Severity,
+    pub code: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// How many characters to underline, when more than the word at `column`
+    pub width: Option<usize>,
+    pub span: Option<Box<Span>>,
+    pub fix: Option<Box<Fix>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fix {
+    pub message: String,
+    pub edit: Edit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    Replace { start: usize, end: usize, text: String },
+    Rename { from: String, to: String },
+}
+
+impl Fix {
+    pub fn replace(message: impl Into<String>, start: usize, end: usize, text: impl Into<String>) -> Self {
+        Fix { message: message.into(), edit: Edit::Replace { start, end, text: text.into() } }
+    }
+
+    pub fn rename(from: impl Into<String>, to: impl Into<String>) -> Self {
+        let (from, to) = (from.into(), to.into());
+        Fix { message: format!("rename `{}` to `{}`", from, to), edit: Edit::Rename { from, to } }
+    }
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>) -> Self {
+        let message = message.into();
+        Diagnostic {
+            code: error_codes::classify(&message).map(str::to_string),
+            message,
+            severity: Severity::Error,
+            line: None,
+            column: None,
+            width: None,
+            span: None,
+            fix: None,
+        }
+    }
+
+    pub fn at(mut self, line: usize, column: usize) -> Self {
+        self.line = Some(line);
+        self.column = Some(column);
+        self
+    }
+
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self.column = None;
+        self
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_code(mut self, code: Option<String>) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(Box::new(span));
+        self
+    }
+
+    pub fn locate(mut self, source: &str) -> Self {
+        let Some(span) = self.span.as_deref().copied().filter(|_| self.line.is_none()) else {
+            return self;
+        };
+        let before = &source[..span.start.min(source.len())];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        let (line, column) = (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1);
+        let text = span.text(source);
+        let width = text.split('\n').next().unwrap_or("").trim_end().chars().count();
+        self = self.at(line, column);
+        if width > 0 {
+            self = self.with_width(width);
+        }
+        self
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(Box::new(fix));
+        self
+    }
+
+    pub fn end_column(&self, source: &str) -> Option<usize> {
+        let (line, column) = (self.line?, self.column?);
+        let text = source.lines().nth(line.saturating_sub(1)).unwrap_or("");
+        Some(column + self.width.unwrap_or_else(|| underline_width(text, column)))
+    }
+
+    pub fn render(&self, path: &str, source: &str, color: bool) -> String {
+        let paint = |style: &str, text: &str| {
+            if color {
+                format!("{}{}{}", style, text, RESET)
+            } else {
+                text.to_string()
+            }
+        };
+
+        let header = match &self.code {
+            Some(code) => format!("{}[{}]", self.severity.as_str(), code),
+            None => self.severity.as_str().to_string(),
+        };
+        let mut output = format!(
+            "{}{}\n",
+            paint(self.severity.style(), &header),
+            paint(BOLD, &format!(": {}", self.message))
+        );
+
+        let Some(line) = self.line else {
+            output.push_str(&format!("{} {}\n", paint(BLUE, "-->"), path));
+            return output;
+        };
+
+        let gutter = " ".repeat(line.to_string().len());
+        let location = match self.column {
+            Some(column) => format!("{}:{}:{}", path, line, column),
+            None => format!("{}:{}", path, line),
+        };
+        output.push_str(&format!("{}{} {}\n", gutter, paint(BLUE, "-->"), location));
+
+        let Some(text) = source.lines().nth(line.saturating_sub(1)) else {
+            return output;
+        };
+
+        output.push_str(&format!("{} {}\n", gutter, paint(BLUE, "|")));
+        output.push_str(&format!("{} {}\n", paint(BLUE, &format!("{} |", line)), text));
+
+        if let Some(column) = self.column {
+            let padding: String = text
+                .chars()
+                .take(column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let carets = "^".repeat(self.width.unwrap_or_else(|| underline_width(text, column)));
+            output.push_str(&format!(
+                "{} {} {}{}\n",
+                gutter,
+                paint(BLUE, "|"),
+                padding,
+                paint(self.severity.style(), &carets)
+            ));
+        }
+        if let Some(fix) = &self.fix {
+            output.push_str(&format!("{} {} {}\n", gutter, paint(BLUE, "="), paint(BOLD, &format!("help: {}", fix.message))));
+        }
+        output
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "{} at line {}, column {}", self.message, line, column),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl From<Diagnostic> for String {
+    fn from(diagnostic: Diagnostic) -> String {
+        diagnostic.to_string()
+    }
+}
+
+fn underline_width(text: &str, column: usize) -> usize {
+    let width = text
+        .chars()
+        .skip(column.saturating_sub(1))
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .count();
+    width.max(1)
+}
+
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .filter(|candidate| !candidate.eq_ignore_ascii_case(name))
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
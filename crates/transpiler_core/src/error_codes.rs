@@ -0,0 +1,597 @@
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub explanation: &'static str,
+}
+
+pub const ERROR_CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: "JR0001",
+        title: "Unexpected character",
+        explanation: r#"The source contains a character that is not part of jRust syntax.
+
+Erroneous example:
+
+    let price = 5 # 2;
+
+Remove the character or replace it with a valid operator:
+
+    let price = 5 * 2;
+"#,
+    },
+    ErrorCode {
+        code: "JR0002",
+        title: "Unterminated string",
+        explanation: r##"A string literal was opened with `"` but never closed.
+
+Erroneous example:
+
+    let greeting = "hello;
+
+Close the string on the same line:
+
+    let greeting = "hello";
+
+Multi-line strings end at `"""`, and raw strings at a `"` followed by as
+many `#`s as the opening `r#"` had:
+
+    let query = """
+        SELECT * FROM users
+    """;
+    let json = r#"{"name": "jRust"}"#;
+"##,
+    },
+    ErrorCode {
+        code: "JR0003",
+        title: "Invalid number",
+        explanation: r#"A number literal does not fit in a jRust `number` (a 32-bit signed integer).
+
+Erroneous example:
+
+    let big = 99999999999;
+
+Use a value between -2147483648 and 2147483647.
+"#,
+    },
+    ErrorCode {
+        code: "JR0100",
+        title: "Syntax error",
+        explanation: r#"The parser found a token it did not expect at this position.
+The message names what was expected and what was found instead.
+
+Erroneous example:
+
+    let x: number 5;
+
+Add the missing token:
+
+    let x: number = 5;
+"#,
+    },
+    ErrorCode {
+        code: "JR0101",
+        title: "Unexpected token in expression",
+        explanation: r#"An expression was expected but the next token cannot start one.
+This usually means an operand is missing.
+
+Erroneous example:
+
+    let total = price +;
+
+Complete the expression:
+
+    let total = price + tax;
+"#,
+    },
+    ErrorCode {
+        code: "JR0102",
+        title: "Missing semicolon",
+        explanation: r#"Every statement in jRust ends with a semicolon.
+
+Erroneous example:
+
+    let x: number = 10
+    print(x);
+
+Add the semicolon:
+
+    let x: number = 10;
+    print(x);
+"#,
+    },
+    ErrorCode {
+        code: "JR0103",
+        title: "Unbalanced delimiter",
+        explanation: r#"A bracket, brace or parenthesis was opened but not closed, or a block is
+missing its opening brace.
+
+Erroneous example:
+
+    if x > 5 {
+        print("big");
+
+Close the block:
+
+    if x > 5 {
+        print("big");
+    }
+"#,
+    },
+    ErrorCode {
+        code: "JR0104",
+        title: "Expected identifier",
+        explanation: r#"A name was expected here, such as a variable, function, field or type name.
+
+Erroneous example:
+
+    let 5 = x;
+
+Use a valid identifier:
+
+    let five = x;
+
+Keywords such as `for`, `type` or `new` can't be names either. To use one
+anyway, for example to reach a Rust item called `type`, write it as a raw
+identifier:
+
+    let r#type = "admin";
+"#,
+    },
+    ErrorCode {
+        code: "JR0105",
+        title: "Expected type",
+        explanation: r#"A type annotation was expected but the token is not a type.
+
+Erroneous example:
+
+    let x: 123 = 42;
+
+Use a type such as `number`, `string`, `boolean`, an array type or a struct name:
+
+    let x: number = 42;
+"#,
+    },
+    ErrorCode {
+        code: "JR0106",
+        title: "Invalid assignment target",
+        explanation: r#"Only variables, struct fields and array elements can be assigned to.
+
+Erroneous example:
+
+    5 = x;
+
+Assign to a variable instead:
+
+    x = 5;
+"#,
+    },
+    ErrorCode {
+        code: "JR0107",
+        title: "Invalid import or export",
+        explanation: r#"An import or export statement is malformed.
+
+Erroneous example:
+
+    import {add} "./math";
+
+Imports need `from` followed by a string path:
+
+    import {add} from "./math";
+
+Only functions, structs, enums, constants and variables can be exported.
+"#,
+    },
+    ErrorCode {
+        code: "JR0108",
+        title: "Invalid attribute",
+        explanation: r#"Attributes can only be placed on functions, structs and enums, and their
+arguments must be identifiers, strings or `key = "value"` pairs. `@cfg` can
+also be placed on a block, and its conditions are `debug`, `release`, `unix`,
+`windows` and `os = "<name>"`.
+
+Erroneous example:
+
+    @derive(Hash)
+    let x = 5;
+
+Attach the attribute to a declaration that supports it:
+
+    @derive(Hash)
+    struct Point { x: number, y: number }
+"#,
+    },
+    ErrorCode {
+        code: "JR0109",
+        title: "Unsupported destructuring",
+        explanation: r#"Array destructuring is only supported for channel creation.
+
+Erroneous example:
+
+    let [a, b] = [1, 2];
+
+Destructure a channel instead, or index the array:
+
+    let [sender, receiver] = channel<number>();
+"#,
+    },
+    ErrorCode {
+        code: "JR0110",
+        title: "Unexpected trailing tokens",
+        explanation: r#"A statement is followed by tokens that don't belong to it, or a `;` or
+closing delimiter appears where no statement or block is open.
+
+Erroneous example:
+
+    let total = (price + tax));
+    print(total) extra;
+
+Remove the extra tokens:
+
+    let total = (price + tax);
+    print(total);
+"#,
+    },
+    ErrorCode {
+        code: "JR0201",
+        title: "Assignment to readonly field",
+        explanation: r#"A struct field marked `readonly` cannot be changed after construction.
+
+Erroneous example:
+
+    struct User { readonly id: number, name: string }
+    let user = User { id: 1, name: "Ada" };
+    user.id = 2;
+
+Create a new value instead, or remove `readonly` from the field.
+"#,
+    },
+    ErrorCode {
+        code: "JR0202",
+        title: "Variable not in scope",
+        explanation: r#"A variable was used outside the block that declared it.
+
+Erroneous example:
+
+    if ready {
+        let message = "go";
+    }
+    print(message);
+
+Declare the variable in the enclosing block:
+
+    let message = "wait";
+    if ready {
+        message = "go";
+    }
+    print(message);
+"#,
+    },
+    ErrorCode {
+        code: "JR0203",
+        title: "Nested function captures a local",
+        explanation: r#"Nested functions compile to Rust `fn` items, which cannot capture local
+variables from the enclosing function.
+
+Erroneous example:
+
+    function outer(): void {
+        let count = 1;
+        function inner(): void {
+            print(count);
+        }
+    }
+
+Pass the value as a parameter instead:
+
+    function outer(): void {
+        let count = 1;
+        function inner(count: number): void {
+            print(count);
+        }
+        inner(count);
+    }
+"#,
+    },
+    ErrorCode {
+        code: "JR0204",
+        title: "Wrong number of arguments",
+        explanation: r#"A function was called with a different number of arguments than it declares.
+
+Erroneous example:
+
+    function add(a: number, b: number): number {
+        return a + b;
+    }
+    add(1);
+
+Pass every parameter:
+
+    add(1, 2);
+"#,
+    },
+    ErrorCode {
+        code: "JR0205",
+        title: "Argument type mismatch",
+        explanation: r#"A literal argument does not match the declared parameter type.
+
+Erroneous example:
+
+    function greet(name: string): void {
+        print(name);
+    }
+    greet(42);
+
+Pass a value of the declared type:
+
+    greet("Ada");
+"#,
+    },
+    ErrorCode {
+        code: "JR0206",
+        title: "Exported variable needs a type annotation",
+        explanation: r#"Exported `let` variables become Rust statics, so their type must be known.
+Literal values are inferred; anything else needs an annotation.
+
+Erroneous example:
+
+    export let total = add(1, 2);
+
+Add a type annotation:
+
+    export let total: number = add(1, 2);
+"#,
+    },
+    ErrorCode {
+        code: "JR0207",
+        title: "Unknown type",
+        explanation: r#"A type annotation names a type that is not declared or imported, and it
+closely matches a known type.
+
+Erroneous example:
+
+    let count: numbr = 5;
+
+Use the suggested type:
+
+    let count: number = 5;
+"#,
+    },
+    ErrorCode {
+        code: "JR0208",
+        title: "Unknown variable",
+        explanation: r#"A variable is used that was never declared, and it closely matches a name
+that is in scope.
+
+Erroneous example:
+
+    let counter = 0;
+    print(countr);
+
+Use the suggested name:
+
+    print(counter);
+"#,
+    },
+    ErrorCode {
+        code: "JR0209",
+        title: "Unknown function",
+        explanation: r#"A function is called that was never declared or imported, and it closely
+matches a known function.
+
+Erroneous example:
+
+    function greet(name: string): void {
+        print(name);
+    }
+    gret("Ada");
+
+Use the suggested name:
+
+    greet("Ada");
+"#,
+    },
+    ErrorCode {
+        code: "JR0210",
+        title: "Nested test block",
+        explanation: r#"Test blocks compile to Rust `#[test]` functions, which the test runner only
+finds at the top level of a file.
+
+Erroneous example:
+
+    function main(): void {
+        test "adds numbers" {
+            assertEq(add(1, 2), 3);
+        }
+    }
+
+Move the test out of the function:
+
+    test "adds numbers" {
+        assertEq(add(1, 2), 3);
+    }
+"#,
+    },
+    ErrorCode {
+        code: "JR0211",
+        title: "Array size mismatch",
+        explanation: r#"A fixed-size array was initialized with a literal of a different length.
+Fixed-size arrays compile to Rust `[T; N]`, so the length is part of the type.
+
+Erroneous example:
+
+    let rgb: number[3] = [255, 128];
+
+Give every element, or use a dynamic array instead:
+
+    let rgb: number[3] = [255, 128, 0];
+    let values: number[] = [255, 128];
+"#,
+    },
+    ErrorCode {
+        code: "JR0212",
+        title: "Unknown standard library import",
+        explanation: r#"An import from `std/` names a module or function that the jRust standard
+library does not provide.
+
+Erroneous example:
+
+    import {randInt} from "std/random";
+
+Use a name the module exports:
+
+    import {randomInt} from "std/random";
+"#,
+    },
+    ErrorCode {
+        code: "JR0213",
+        title: "Non-exhaustive match",
+        explanation: r#"A `match` over an enum has no arm for some of its variants and no `_` arm.
+Every value must be handled by some arm.
+
+Erroneous example:
+
+    enum Light { Red, Yellow, Green }
+    match (light) {
+        Light.Red => print("stop");
+        Light.Green => print("go");
+    }
+
+Add an arm for each missing variant, or a `_` arm for the rest:
+
+    match (light) {
+        Light.Red => print("stop");
+        Light.Green => print("go");
+        _ => print("slow down");
+    }
+"#,
+    },
+    ErrorCode {
+        code: "JR0214",
+        title: "Type does not support comparison",
+        explanation: r#"Structs support `==` and `!=`, and structs and enums support `<`, `<=`, `>`
+and `>=`, only when their declaration opts in with `@equatable`, `@comparable`
+or `@hashable`.
+
+Erroneous example:
+
+    struct Point { x: number, y: number }
+    let same = Point { x: 1, y: 2 } == Point { x: 1, y: 2 };
+
+Mark the declaration with the annotation the message names:
+
+    @equatable
+    struct Point { x: number, y: number }
+
+The same applies to the types of its fields: an `@equatable` struct can only
+hold structs and enums that are `@equatable` too.
+"#,
+    },
+    ErrorCode {
+        code: "JR0215",
+        title: "Condition is not a boolean",
+        explanation: r#"`if` and `while` conditions, and the operands of `&&` and `||`, must be
+booleans. Numbers, strings and arrays are not treated as true or false.
+
+Erroneous example:
+
+    let count = 3;
+    if (count) {
+        print("some");
+    }
+
+Compare the value explicitly:
+
+    if (count != 0) {
+        print("some");
+    }
+
+Test strings and arrays with `.length > 0`.
+"#,
+    },
+    ErrorCode {
+        code: "JR0216",
+        title: "Top-level statement outside the entry file",
+        explanation: r#"Functions, structs, enums, imports and constants are declarations and may
+appear anywhere at the top of a file. Other top-level statements only run in
+the entry file, which becomes `main` in order. A module has no `main`, and an
+entry file that declares its own `main` leaves no place for them.
+
+Erroneous example:
+
+    let greeting = "hi";
+
+    function main(): void {
+        print(greeting);
+    }
+
+Move the statement into `main`, or make it a constant:
+
+    const greeting = "hi";
+"#,
+    },
+];
+
+const PATTERNS: &[(&str, &str)] = &[
+    ("Unexpected character", "JR0001"),
+    ("Unterminated string", "JR0002"),
+    ("Unterminated multi-line string", "JR0002"),
+    ("Unterminated raw string", "JR0002"),
+    ("Invalid number", "JR0003"),
+    ("Unexpected token in expression", "JR0101"),
+    ("Unexpected trailing tokens", "JR0110"),
+    ("with no statement before it", "JR0110"),
+    ("with no matching", "JR0110"),
+    ("Expected ';'", "JR0102"),
+    ("import identifier", "JR0107"),
+    ("import statement", "JR0107"),
+    ("import path", "JR0107"),
+    ("import list", "JR0107"),
+    ("'*' import", "JR0107"),
+    ("after export", "JR0107"),
+    ("'export default'", "JR0107"),
+    ("Attributes can only", "JR0108"),
+    ("in attribute", "JR0108"),
+    ("attribute arguments", "JR0108"),
+    ("applied to a block", "JR0108"),
+    ("Destructuring is only", "JR0109"),
+    ("Expected '}'", "JR0103"),
+    ("Expected '{'", "JR0103"),
+    ("Expected ')'", "JR0103"),
+    ("Expected '('", "JR0103"),
+    ("Expected ']'", "JR0103"),
+    ("Expected identifier", "JR0104"),
+    ("is a reserved keyword", "JR0104"),
+    ("can't be a raw identifier", "JR0104"),
+    ("Expected type", "JR0105"),
+    ("Invalid assignment target", "JR0106"),
+    ("Cannot assign to readonly", "JR0201"),
+    ("is not in scope", "JR0202"),
+    ("cannot capture local variable", "JR0203"),
+    ("argument(s) but got", "JR0204"),
+    (" but got ", "JR0205"),
+    ("needs a type annotation", "JR0206"),
+    ("Cannot find type", "JR0207"),
+    ("Cannot find variable", "JR0208"),
+    ("Cannot find function", "JR0209"),
+    ("Test blocks must be", "JR0210"),
+    ("Array literal has", "JR0211"),
+    ("Cannot find module 'std/", "JR0212"),
+    ("Module 'std/", "JR0212"),
+    ("Non-exhaustive match", "JR0213"),
+    ("Cannot compare '", "JR0214"),
+    ("because its field type", "JR0214"),
+    ("Condition must be a boolean", "JR0215"),
+    ("Top-level statement", "JR0216"),
+    ("Expected", "JR0100"),
+];
+
+pub fn lookup(code: &str) -> Option<&'static ErrorCode> {
+    ERROR_CODES.iter().find(|entry| entry.code.eq_ignore_ascii_case(code))
+}
+
+pub fn classify(message: &str) -> Option<&'static str> {
+    PATTERNS
+        .iter()
+        .find(|(pattern, _)| message.contains(pattern))
+        .map(|(_, code)| *code)
+}
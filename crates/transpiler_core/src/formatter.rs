@@ -0,0 +1,469 @@
+use crate::diagnostic::Diagnostic;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::token::{Comment, Token, TokenKind};
+
+const INDENT: &str = "    ";
+
+pub fn format_source(source: &str) -> Result<String, Diagnostic> {
+    let mut lexer = Lexer::new(source);
+    let mut tokens = lexer.tokenize()?;
+    insert_missing_semicolons(&mut tokens)?;
+    tokens.pop();
+
+    let formatter = Formatter::new(source, &tokens, lexer.comments());
+    Ok(formatter.format())
+}
+
+fn insert_missing_semicolons(tokens: &mut Vec<Token>) -> Result<(), Diagnostic> {
+    let limit = tokens.len();
+    for _ in 0..limit {
+        let Err(diagnostic) = Parser::new(tokens.clone()).parse() else {
+            return Ok(());
+        };
+        if diagnostic.code.as_deref() != Some("JR0102") {
+            return Err(diagnostic);
+        }
+        let index = tokens
+            .iter()
+            .position(|token| Some(token.line) == diagnostic.line && Some(token.column) == diagnostic.column)
+            .filter(|index| *index > 0);
+        let Some(index) = index else {
+            return Err(diagnostic);
+        };
+        let (previous, next) = (&tokens[index - 1], &tokens[index]);
+        let at_line_end = next.line > previous.line || matches!(next.kind, TokenKind::RightBrace | TokenKind::Eof);
+        if !at_line_end || matches!(previous.kind, TokenKind::Semicolon) {
+            return Err(diagnostic);
+        }
+        let semicolon = Token::new(TokenKind::Semicolon, previous.line, previous.column);
+        tokens.insert(index, semicolon);
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Item<'a> {
+    Token(usize),
+    Comment(&'a Comment),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Break {
+    None,
+    Line,
+    Blank,
+}
+
+#[derive(Default, Clone, Copy)]
+struct TokenInfo {
+    block: bool,
+    tight: bool,
+    unary: bool,
+    generic: bool,
+    keyword: bool,
+    attribute_end: bool,
+    array_size: bool,
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    tokens: &'a [Token],
+    items: Vec<Item<'a>>,
+    info: Vec<TokenInfo>,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(source: &'a str, tokens: &'a [Token], comments: &'a [Comment]) -> Self {
+        let mut items = Vec::new();
+        let mut pending = comments.iter().peekable();
+        for (index, token) in tokens.iter().enumerate() {
+            while let Some(comment) = pending.next_if(|c| (c.line, c.column) < (token.line, token.column)) {
+                items.push(Item::Comment(comment));
+            }
+            items.push(Item::Token(index));
+        }
+        items.extend(pending.map(Item::Comment));
+
+        Formatter {
+            source,
+            tokens,
+            items,
+            info: analyze(tokens),
+        }
+    }
+
+    fn format(&self) -> String {
+        let mut output: Vec<String> = Vec::new();
+        let mut current = String::new();
+        let mut openers: Vec<bool> = Vec::new();
+        let mut opened_on_line = false;
+        let mut last_token: Option<usize> = None;
+
+        for (position, item) in self.items.iter().enumerate() {
+            let line_break = match position {
+                0 => Break::None,
+                _ => self.line_break(self.items[position - 1], *item),
+            };
+
+            if line_break != Break::None {
+                output.push(current.trim_end().to_string());
+                if line_break == Break::Blank {
+                    output.push(String::new());
+                }
+                current.clear();
+                if opened_on_line {
+                    if let Some(indenting) = openers.last_mut() {
+                        *indenting = true;
+                    }
+                }
+                opened_on_line = false;
+            }
+
+            if current.is_empty() {
+                let level = self.indent_level(*item, &openers, last_token);
+                current.push_str(&INDENT.repeat(level));
+            } else {
+                let spaces = self.spaces_before(self.items[position - 1], *item);
+                current.push_str(&" ".repeat(spaces));
+            }
+
+            match *item {
+                Item::Comment(comment) => current.push_str(&comment.text),
+                Item::Token(index) => {
+                    let token = &self.tokens[index];
+                    current.push_str(&self.token_text(token));
+                    if is_opener(&token.kind) {
+                        openers.push(false);
+                        opened_on_line = true;
+                    } else if is_closer(&token.kind) {
+                        openers.pop();
+                        opened_on_line = false;
+                    }
+                    last_token = Some(index);
+                }
+            }
+        }
+
+        if !self.items.is_empty() {
+            output.push(current.trim_end().to_string());
+        }
+        let mut formatted = output.join("\n");
+        if !formatted.is_empty() {
+            formatted.push('\n');
+        }
+        formatted
+    }
+
+    fn indent_level(&self, item: Item, openers: &[bool], last_token: Option<usize>) -> usize {
+        let level = openers.iter().filter(|indenting| **indenting).count();
+        let closer = matches!(item, Item::Token(index) if is_closer(&self.tokens[index].kind));
+        if closer {
+            return level - usize::from(openers.last() == Some(&true));
+        }
+
+        let Some(last) = last_token else {
+            return level;
+        };
+        let statement_boundary = matches!(
+            self.tokens[last].kind,
+            TokenKind::Semicolon
+                | TokenKind::LeftBrace
+                | TokenKind::RightBrace
+                | TokenKind::Comma
+                | TokenKind::LeftParen
+                | TokenKind::LeftBracket
+        ) || self.info[last].attribute_end;
+        let inside_open_group = openers.last() == Some(&false);
+        level + usize::from(!statement_boundary || inside_open_group)
+    }
+
+    fn line_break(&self, previous: Item, next: Item) -> Break {
+        let line_break = self.preferred_break(previous, next);
+        let after_opener = matches!(previous, Item::Token(index) if is_opener(&self.tokens[index].kind));
+        let before_closer = matches!(next, Item::Token(index) if is_closer(&self.tokens[index].kind));
+        if line_break == Break::Blank && (after_opener || before_closer) {
+            return Break::Line;
+        }
+        line_break
+    }
+
+    fn preferred_break(&self, previous: Item, next: Item) -> Break {
+        let (previous_line, next_line) = (self.item_end_line(previous), self.item_line(next));
+        let preserved = if next_line > previous_line + 1 {
+            Break::Blank
+        } else if next_line > previous_line {
+            Break::Line
+        } else {
+            Break::None
+        };
+
+        let (before, after) = match (previous, next) {
+            (Item::Comment(_), _) => return preserved.max_line(),
+            (_, Item::Comment(_)) => return preserved,
+            (Item::Token(before), Item::Token(after)) => (before, after),
+        };
+        let (a, b) = (&self.tokens[before].kind, &self.tokens[after].kind);
+        let opens_block = matches!(a, TokenKind::LeftBrace) && self.info[before].block;
+        let closes_block = matches!(b, TokenKind::RightBrace) && self.info[after].block;
+
+        if matches!(a, TokenKind::LeftBrace) && matches!(b, TokenKind::RightBrace) {
+            return Break::None;
+        }
+        if matches!(a, TokenKind::RightBrace) && matches!(b, TokenKind::Else | TokenKind::Catch) {
+            return Break::None;
+        }
+        if opens_block || closes_block {
+            return Break::Line;
+        }
+
+        let ends_statement = (matches!(a, TokenKind::Semicolon) && !self.info[before].array_size)
+            || (matches!(a, TokenKind::RightBrace)
+                && self.info[before].block
+                && !matches!(
+                    b,
+                    TokenKind::Semicolon | TokenKind::Comma | TokenKind::RightParen | TokenKind::RightBracket
+                ));
+        if ends_statement {
+            return preserved.max_line();
+        }
+        preserved
+    }
+
+    fn spaces_before(&self, previous: Item, next: Item) -> usize {
+        match (previous, next) {
+            (Item::Token(before), Item::Token(after)) => usize::from(self.space_between(before, after)),
+            (Item::Token(before), Item::Comment(comment)) => {
+                let token = &self.tokens[before];
+                let end = token.column + self.token_text(token).chars().count();
+                comment.column.saturating_sub(end).max(1)
+            }
+            _ => 1,
+        }
+    }
+
+    fn space_between(&self, before: usize, after: usize) -> bool {
+        let (a, b) = (&self.tokens[before].kind, &self.tokens[after].kind);
+        let (a_info, b_info) = (self.info[before], self.info[after]);
+
+        if matches!(a, TokenKind::LeftBrace) && matches!(b, TokenKind::RightBrace) {
+            return false;
+        }
+        if (matches!(a, TokenKind::LeftBrace) && a_info.tight) || (matches!(b, TokenKind::RightBrace) && b_info.tight) {
+            return false;
+        }
+        if matches!(
+            b,
+            TokenKind::RightParen
+                | TokenKind::RightBracket
+                | TokenKind::Comma
+                | TokenKind::Semicolon
+                | TokenKind::Dot
+                | TokenKind::DotDot
+                | TokenKind::Colon
+                | TokenKind::Question
+        ) {
+            return false;
+        }
+        if matches!(a, TokenKind::LeftParen | TokenKind::LeftBracket | TokenKind::Dot | TokenKind::DotDot | TokenKind::At | TokenKind::Bang)
+            || a_info.unary
+            || (a_info.generic && matches!(a, TokenKind::Less))
+            || b_info.generic
+        {
+            return false;
+        }
+
+        let generic_close = a_info.generic && matches!(a, TokenKind::Greater);
+        match b {
+            TokenKind::LeftParen => !(generic_close
+                || matches!(
+                    a,
+                    TokenKind::Identifier(_) | TokenKind::Print | TokenKind::RightParen | TokenKind::RightBracket
+                ) && !a_info.keyword),
+            TokenKind::LeftBracket => !matches!(
+                a,
+                TokenKind::Identifier(_)
+                    | TokenKind::RightParen
+                    | TokenKind::RightBracket
+                    | TokenKind::NumberType
+                    | TokenKind::StringType
+                    | TokenKind::BooleanType
+                    | TokenKind::Any
+            ),
+            _ => true,
+        }
+    }
+
+    fn item_line(&self, item: Item) -> usize {
+        match item {
+            Item::Token(index) => self.tokens[index].line,
+            Item::Comment(comment) => comment.line,
+        }
+    }
+
+    /// The line an item ends on, which is further down than where it starts
+    /// for a multi-line string
+    fn item_end_line(&self, item: Item) -> usize {
+        match item {
+            Item::Token(index) => {
+                let token = &self.tokens[index];
+                token.line + token.span.text(self.source).matches('\n').count()
+            }
+            Item::Comment(comment) => comment.line,
+        }
+    }
+
+    fn token_text(&self, token: &Token) -> String {
+        match &token.kind {
+            TokenKind::Identifier(name) => name.clone(),
+            TokenKind::StringLiteral(_) | TokenKind::NumberLiteral(_) => self.literal_text(token),
+            TokenKind::BooleanLiteral(value) => value.to_string(),
+            kind => kind.text().to_string(),
+        }
+    }
+
+    fn literal_text(&self, token: &Token) -> String {
+        match (&token.kind, token.span.text(self.source)) {
+            (TokenKind::NumberLiteral(value), "") => value.to_string(),
+            (_, text) => text.to_string(),
+        }
+    }
+}
+
+impl Break {
+    fn max_line(self) -> Break {
+        match self {
+            Break::None => Break::Line,
+            other => other,
+        }
+    }
+}
+
+fn analyze(tokens: &[Token]) -> Vec<TokenInfo> {
+    let mut info = vec![TokenInfo::default(); tokens.len()];
+    let mut braces: Vec<(usize, bool)> = Vec::new();
+    let mut in_brackets: Vec<bool> = Vec::new();
+    let mut generics = 0;
+
+    for (index, token) in tokens.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|i| &tokens[i].kind);
+        match &token.kind {
+            TokenKind::LeftBrace => {
+                in_brackets.push(false);
+                braces.push((index, false));
+                info[index].tight = matches!(previous, Some(TokenKind::Import | TokenKind::Export));
+            }
+            TokenKind::RightBrace => {
+                in_brackets.pop();
+                if let Some((open, block)) = braces.pop() {
+                    info[open].block = block;
+                    info[index].block = block;
+                    info[index].tight = info[open].tight;
+                    if let Some(parent) = braces.last_mut().filter(|_| block) {
+                        parent.1 = true;
+                    }
+                }
+            }
+            TokenKind::LeftBracket => in_brackets.push(true),
+            TokenKind::RightBracket => {
+                in_brackets.pop();
+            }
+            TokenKind::Semicolon if in_brackets.last() == Some(&true) => info[index].array_size = true,
+            TokenKind::Semicolon => {
+                if let Some(current) = braces.last_mut() {
+                    current.1 = true;
+                }
+            }
+            TokenKind::Less if matches!(previous, Some(TokenKind::Identifier(name)) if name == "channel" || name == "shared") => {
+                info[index].generic = true;
+                generics += 1;
+            }
+            TokenKind::Greater if generics > 0 => {
+                info[index].generic = true;
+                generics -= 1;
+            }
+            TokenKind::Minus => info[index].unary = !previous.is_some_and(ends_operand),
+            TokenKind::Identifier(name) if name == "lock" => info[index].keyword = is_lock_stmt(tokens, index),
+            TokenKind::Identifier(name) if name == "match" => info[index].keyword = is_match_stmt(tokens, index),
+            TokenKind::At => {
+                if let Some(end) = attribute_end(tokens, index) {
+                    info[end].attribute_end = true;
+                }
+            }
+            _ => {}
+        }
+    }
+    info
+}
+
+fn attribute_end(tokens: &[Token], at: usize) -> Option<usize> {
+    let name = at + 1;
+    if !matches!(tokens.get(name + 1)?.kind, TokenKind::LeftParen) {
+        return Some(name);
+    }
+    let mut depth = 0;
+    for (index, token) in tokens.iter().enumerate().skip(name + 1) {
+        match token.kind {
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_lock_stmt(tokens: &[Token], index: usize) -> bool {
+    let kind_at = |offset: usize| tokens.get(index + offset).map(|token| &token.kind);
+    matches!(kind_at(1), Some(TokenKind::LeftParen))
+        && matches!(kind_at(2), Some(TokenKind::Identifier(_)))
+        && matches!(kind_at(3), Some(TokenKind::RightParen))
+        && matches!(kind_at(4), Some(TokenKind::LeftBrace))
+}
+
+/// `match (subject) {`, as opposed to a call of a function named `match`
+pub(crate) fn is_match_stmt(tokens: &[Token], index: usize) -> bool {
+    if !matches!(tokens.get(index + 1).map(|token| &token.kind), Some(TokenKind::LeftParen)) {
+        return false;
+    }
+    let mut depth = 0;
+    for (index, token) in tokens.iter().enumerate().skip(index + 1) {
+        match token.kind {
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen => {
+                depth -= 1;
+                if depth == 0 {
+                    return matches!(tokens.get(index + 1).map(|token| &token.kind), Some(TokenKind::LeftBrace));
+                }
+            }
+            TokenKind::Eof => break,
+            _ => {}
+        }
+    }
+    false
+}
+
+fn ends_operand(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Identifier(_)
+            | TokenKind::NumberLiteral(_)
+            | TokenKind::StringLiteral(_)
+            | TokenKind::BooleanLiteral(_)
+            | TokenKind::RightParen
+            | TokenKind::RightBracket
+            | TokenKind::This
+    )
+}
+
+fn is_opener(kind: &TokenKind) -> bool {
+    matches!(kind, TokenKind::LeftParen | TokenKind::LeftBracket | TokenKind::LeftBrace)
+}
+
+fn is_closer(kind: &TokenKind) -> bool {
+    matches!(kind, TokenKind::RightParen | TokenKind::RightBracket | TokenKind::RightBrace)
+}
@@ -0,0 +1,175 @@
+use crate::diagnostic::Diagnostic;
+use crate::formatter::is_match_stmt;
+use crate::lexer::Lexer;
+use crate::token::{Token, TokenKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightKind {
+    Keyword,
+    Type,
+    Function,
+    Method,
+    Variable,
+    Property,
+    String,
+    Number,
+    Comment,
+    Operator,
+}
+
+impl HighlightKind {
+    pub const ALL: &'static [HighlightKind] = &[
+        HighlightKind::Keyword,
+        HighlightKind::Type,
+        HighlightKind::Function,
+        HighlightKind::Method,
+        HighlightKind::Variable,
+        HighlightKind::Property,
+        HighlightKind::String,
+        HighlightKind::Number,
+        HighlightKind::Comment,
+        HighlightKind::Operator,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HighlightKind::Keyword => "keyword",
+            HighlightKind::Type => "type",
+            HighlightKind::Function => "function",
+            HighlightKind::Method => "method",
+            HighlightKind::Variable => "variable",
+            HighlightKind::Property => "property",
+            HighlightKind::String => "string",
+            HighlightKind::Number => "number",
+            HighlightKind::Comment => "comment",
+            HighlightKind::Operator => "operator",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightToken {
+    pub kind: HighlightKind,
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+}
+
+pub fn highlight(source: &str) -> Result<Vec<HighlightToken>, Diagnostic> {
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize()?;
+
+    let mut highlighted: Vec<HighlightToken> = Vec::new();
+    for (index, (token, &length)) in tokens.iter().zip(lexer.token_lengths()).enumerate() {
+        let Some(kind) = classify(&tokens, index) else {
+            continue;
+        };
+        let text = token.span.text(source);
+        if !text.contains('\n') {
+            highlighted.push(HighlightToken { kind, line: token.line, column: token.column, length });
+            continue;
+        }
+        // A multi-line string gets a token per line, so each stays on its line
+        for (offset, part) in text.split('\n').enumerate() {
+            let column = if offset == 0 { token.column } else { 1 };
+            let length = part.trim_end_matches('\r').chars().count();
+            highlighted.push(HighlightToken { kind, line: token.line + offset, column, length });
+        }
+    }
+
+    highlighted.extend(lexer.comments().iter().map(|comment| HighlightToken {
+        kind: HighlightKind::Comment,
+        line: comment.line,
+        column: comment.column,
+        length: comment.text.chars().count(),
+    }));
+    highlighted.sort_by_key(|token| (token.line, token.column));
+    Ok(highlighted)
+}
+
+fn classify(tokens: &[Token], index: usize) -> Option<HighlightKind> {
+    let kind = |offset: isize| {
+        index
+            .checked_add_signed(offset)
+            .and_then(|i| tokens.get(i))
+            .map(|token| &token.kind)
+    };
+
+    Some(match &tokens[index].kind {
+        TokenKind::NumberType
+        | TokenKind::StringType
+        | TokenKind::BooleanType
+        | TokenKind::Void
+        | TokenKind::Any => HighlightKind::Type,
+        TokenKind::StringLiteral(_) => HighlightKind::String,
+        TokenKind::NumberLiteral(_) => HighlightKind::Number,
+        TokenKind::BooleanLiteral(_) => HighlightKind::Keyword,
+        TokenKind::Print => HighlightKind::Function,
+        TokenKind::Identifier(name) => {
+            let (previous, next) = (kind(-1), kind(1));
+            let is_type_name = name.starts_with(|c: char| c.is_uppercase());
+            let contextual = match name.as_str() {
+                "defer" => next == Some(&TokenKind::LeftBrace),
+                "test" => matches!(next, Some(TokenKind::StringLiteral(_))) && kind(2) == Some(&TokenKind::LeftBrace),
+                "lock" => {
+                    next == Some(&TokenKind::LeftParen)
+                        && kind(3) == Some(&TokenKind::RightParen)
+                        && kind(4) == Some(&TokenKind::LeftBrace)
+                }
+                "match" => is_match_stmt(tokens, index),
+                _ => false,
+            };
+            if contextual {
+                HighlightKind::Keyword
+            } else if previous == Some(&TokenKind::Dot) {
+                if next == Some(&TokenKind::LeftParen) {
+                    HighlightKind::Method
+                } else {
+                    HighlightKind::Property
+                }
+            } else if previous == Some(&TokenKind::Function) || next == Some(&TokenKind::LeftParen) {
+                HighlightKind::Function
+            } else if matches!(previous, Some(TokenKind::Struct | TokenKind::Enum))
+                || is_type_name && matches!(previous, Some(TokenKind::Colon | TokenKind::Less))
+                || is_type_name && matches!(next, Some(TokenKind::LeftBrace | TokenKind::Dot | TokenKind::LeftBracket))
+            {
+                HighlightKind::Type
+            } else {
+                HighlightKind::Variable
+            }
+        }
+        TokenKind::Plus
+        | TokenKind::Minus
+        | TokenKind::Star
+        | TokenKind::Slash
+        | TokenKind::Percent
+        | TokenKind::Equal
+        | TokenKind::Arrow
+        | TokenKind::Question
+        | TokenKind::Pipe
+        | TokenKind::EqualEqual
+        | TokenKind::BangEqual
+        | TokenKind::Greater
+        | TokenKind::GreaterEqual
+        | TokenKind::Less
+        | TokenKind::LessEqual
+        | TokenKind::AmpersandAmpersand
+        | TokenKind::PipePipe
+        | TokenKind::Bang
+        | TokenKind::DotDot
+        | TokenKind::Ampersand => HighlightKind::Operator,
+        TokenKind::Colon
+        | TokenKind::Semicolon
+        | TokenKind::Comma
+        | TokenKind::Dot
+        | TokenKind::At
+        | TokenKind::LeftParen
+        | TokenKind::RightParen
+        | TokenKind::LeftBrace
+        | TokenKind::RightBrace
+        | TokenKind::LeftBracket
+        | TokenKind::RightBracket
+        | TokenKind::Eof => return None,
+        _ => HighlightKind::Keyword,
+    })
+}
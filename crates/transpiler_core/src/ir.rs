@@ -0,0 +1,462 @@
+use crate::ast::BinaryOp;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IrType {
+    Int,
+    /// `f64`, which only `parseFloat` and `std/` results like `randomFloat` produce
+    Float,
+    Str,
+    StaticStr,
+    Bool,
+    Char,
+    Unit,
+    Vec(Box<IrType>),
+    Array(Box<IrType>, usize),
+    Named(String),
+    Option(Box<IrType>),
+    Shared(Box<IrType>),
+    Ref(Box<IrType>),
+    AsRef(Box<IrType>),
+    Unknown,
+}
+
+impl IrType {
+    pub fn element(&self) -> IrType {
+        match self {
+            IrType::Vec(element) | IrType::Array(element, _) => (**element).clone(),
+            IrType::Str => IrType::Char,
+            IrType::Shared(inner) | IrType::Ref(inner) => inner.element(),
+            _ => IrType::Unknown,
+        }
+    }
+
+    pub fn is_string(&self) -> bool {
+        match self {
+            IrType::Str | IrType::StaticStr => true,
+            IrType::Ref(inner) => inner.is_string(),
+            _ => false,
+        }
+    }
+
+    pub fn needs_clone(&self) -> bool {
+        match self {
+            IrType::Str | IrType::Vec(_) | IrType::Named(_) | IrType::Shared(_) => true,
+            IrType::Option(inner) | IrType::Array(inner, _) => inner.needs_clone(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IrProgram {
+    pub items: Vec<IrStmt>,
+    pub main: Option<Vec<IrStmt>>,
+    pub tests: Vec<IrTest>,
+    pub uses_defer: bool,
+    pub uses_index: bool,
+    pub uses_slot: bool,
+    pub uses_at: bool,
+    pub uses_input: bool,
+    pub uses_panic_hook: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct IrTest {
+    pub name: String,
+    pub label: String,
+    pub line: usize,
+    pub body: Vec<IrStmt>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IrStmt {
+    pub kind: IrStmtKind,
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub enum IrStmtKind {
+    Use {
+        public: bool,
+        allow_unused: bool,
+        path: String,
+        tree: UseTree,
+    },
+    ReexportDefault(String),
+    Let {
+        name: String,
+        mutable: bool,
+        ty: Option<IrType>,
+        value: IrExpr,
+    },
+    LetTuple {
+        names: Vec<String>,
+        value: IrExpr,
+    },
+    Const {
+        visibility: IrVisibility,
+        name: String,
+        ty: Option<IrType>,
+        value: IrExpr,
+    },
+    Static {
+        name: String,
+        ty: IrType,
+        value: IrExpr,
+    },
+    Function(IrFunction),
+    Struct(IrStruct),
+    Enum(IrEnum),
+    Print(Vec<FormatArg>),
+    Panic(IrExpr),
+    Return(Option<IrExpr>),
+    Expr(IrExpr),
+    If {
+        condition: IrExpr,
+        then_body: Vec<IrStmt>,
+        else_body: Option<Vec<IrStmt>>,
+    },
+    For {
+        variable: String,
+        iterable: IrExpr,
+        body: Vec<IrStmt>,
+    },
+    While {
+        condition: IrExpr,
+        body: Vec<IrStmt>,
+    },
+    Break,
+    Continue,
+    Catch {
+        body: Vec<IrStmt>,
+        binding: String,
+        handler: Vec<IrStmt>,
+    },
+    Block(Vec<IrStmt>),
+    Lock {
+        guard: String,
+        target: String,
+        mutable: bool,
+        body: Vec<IrStmt>,
+    },
+    Defer {
+        index: usize,
+        body: Vec<IrStmt>,
+    },
+    Match {
+        subject: IrExpr,
+        arms: Vec<(IrPattern, Vec<IrStmt>)>,
+    },
+}
+
+impl IrStmtKind {
+    pub fn bodies_mut(&mut self) -> Vec<&mut Vec<IrStmt>> {
+        match self {
+            IrStmtKind::Function(function) => vec![&mut function.body],
+            IrStmtKind::If { then_body, else_body, .. } => {
+                let mut bodies = vec![then_body];
+                bodies.extend(else_body.as_mut());
+                bodies
+            }
+            IrStmtKind::For { body, .. }
+            | IrStmtKind::While { body, .. }
+            | IrStmtKind::Block(body)
+            | IrStmtKind::Lock { body, .. }
+            | IrStmtKind::Defer { body, .. } => vec![body],
+            IrStmtKind::Catch { body, handler, .. } => vec![body, handler],
+            IrStmtKind::Match { arms, .. } => arms.iter_mut().map(|(_, body)| body).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// A `match` arm pattern, with the enum variant as a Rust path
+#[derive(Debug, Clone)]
+pub enum IrPattern {
+    Wildcard,
+    Unit(String),
+    Tuple(String, Vec<String>),
+    /// `(field, binding)` pairs, with `..` when `rest` is set for the fields
+    /// the pattern leaves out
+    Named {
+        path: String,
+        fields: Vec<(String, String)>,
+        rest: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum UseTree {
+    Glob,
+    Alias(String),
+    Names(Vec<(String, Option<String>)>),
+}
+
+/// How far an item is visible: `pub(crate)` for declarations marked `pub`,
+/// `pub` for exported ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrVisibility {
+    Private,
+    Crate,
+    Public,
+}
+
+impl IrVisibility {
+    /// Whether other modules can use the item, so its signature can't depend
+    /// on how this module calls it
+    pub fn is_visible_outside(self) -> bool {
+        self != IrVisibility::Private
+    }
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            IrVisibility::Private => "",
+            IrVisibility::Crate => "pub(crate) ",
+            IrVisibility::Public => "pub ",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IrFunction {
+    pub visibility: IrVisibility,
+    pub name: String,
+    pub params: Vec<(String, IrType)>,
+    pub return_type: IrType,
+    pub body: Vec<IrStmt>,
+    pub attributes: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IrStruct {
+    pub visibility: IrVisibility,
+    pub name: String,
+    pub fields: Vec<IrField>,
+    pub attributes: Vec<String>,
+    pub defaults: Option<Vec<(String, IrExpr)>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IrEnum {
+    pub visibility: IrVisibility,
+    pub name: String,
+    pub variants: Vec<(String, IrVariantFields)>,
+    pub attributes: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum IrVariantFields {
+    Unit,
+    Tuple(Vec<IrType>),
+    Named(Vec<IrField>),
+}
+
+/// A struct or variant field with its Rust name; `attributes` are written
+/// before it, like the `serde(rename = "...")` that keeps a serialized
+/// field under its jRust name
+#[derive(Debug, Clone)]
+pub struct IrField {
+    pub name: String,
+    pub ty: IrType,
+    pub attributes: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IrExpr {
+    pub kind: IrExprKind,
+    pub ty: IrType,
+}
+
+impl IrExpr {
+    pub fn new(kind: IrExprKind, ty: IrType) -> Self {
+        IrExpr { kind, ty }
+    }
+}
+
+/// A value written by `print` or `console`, with `{:?}` when `debug` is set because its
+/// type has no `Display`
+#[derive(Debug, Clone)]
+pub struct FormatArg {
+    pub value: IrExpr,
+    pub debug: bool,
+}
+
+impl FormatArg {
+    /// The placeholder for this argument in a format string
+    pub fn spec(&self) -> &'static str {
+        if self.debug { "{:?}" } else { "{}" }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum IrExprKind {
+    Int(i32),
+    Str(String),
+    Bool(bool),
+    Path(String),
+    Deref(Box<IrExpr>),
+    ReadGlobal(String),
+    LockGlobal(String),
+    ToOwned(Box<IrExpr>),
+    Clone(Box<IrExpr>),
+    Ref(Box<IrExpr>),
+    /// `value as i32` or `value as f64`, for numbers mixed in arithmetic
+    Cast(Box<IrExpr>, IrType),
+    Format(Vec<FormatArg>),
+    /// A `console` call: the arguments on one line, separated by spaces
+    Print {
+        args: Vec<FormatArg>,
+        stderr: bool,
+    },
+    Binary(Box<IrExpr>, BinaryOp, Box<IrExpr>),
+    Vec(Vec<IrExpr>),
+    Array(Vec<IrExpr>),
+    Struct {
+        name: String,
+        fields: Vec<(String, IrExpr)>,
+        fill_defaults: bool,
+    },
+    Some(Box<IrExpr>),
+    None,
+    DefaultValue,
+    Call {
+        path: String,
+        args: Vec<IrExpr>,
+    },
+    /// A call returning `Result`: `call?` inside a try block, otherwise a panic
+    /// carrying the error message
+    Fallible {
+        call: Box<IrExpr>,
+        propagate: bool,
+    },
+    /// `assert!`, `assert_eq!` or `panic!`, with the jRust source location
+    /// added to the message, which is the argument at `message_at` if given
+    Assert {
+        macro_name: String,
+        args: Vec<IrExpr>,
+        message_at: usize,
+        location: String,
+    },
+    Method {
+        receiver: Box<IrExpr>,
+        method: Method,
+        args: Vec<IrExpr>,
+    },
+    EnvArgs,
+    Index {
+        object: Box<IrExpr>,
+        index: Box<IrExpr>,
+    },
+    CheckedIndex {
+        object: Box<IrExpr>,
+        index: Box<IrExpr>,
+    },
+    IndexSlot {
+        object: Box<IrExpr>,
+        index: Box<IrExpr>,
+    },
+    Field {
+        object: Box<IrExpr>,
+        member: String,
+    },
+    Len(Box<IrExpr>),
+    Assign {
+        target: Box<IrExpr>,
+        value: Box<IrExpr>,
+    },
+    Channel(Option<IrType>),
+    Shared(Option<IrType>, Box<IrExpr>),
+    Closure {
+        params: Vec<String>,
+        body: Vec<IrStmt>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Method {
+    Push,
+    Pop,
+    Send,
+    Recv,
+    RemoveFirst,
+    InsertFirst,
+    Slice,
+    Map,
+    Filter,
+    CharAt,
+    Substring,
+    IndexOf,
+    ToUpperCase,
+    ToLowerCase,
+    Trim,
+    Split,
+    Join,
+    Reverse,
+    Sort,
+    SortBy,
+    Contains,
+    Replace,
+    StartsWith,
+    EndsWith,
+    PadStart,
+    Repeat,
+    Reduce,
+    ForEach,
+    Find,
+    FindIndex,
+    Any,
+    All,
+    Flat,
+    Concat,
+    At,
+    ParseInt,
+    ParseFloat,
+    ToString,
+    ToFixed,
+    Named(String),
+}
+
+impl Method {
+    pub fn from_name(name: &str) -> Method {
+        match name {
+            "push" => Method::Push,
+            "pop" => Method::Pop,
+            "send" => Method::Send,
+            "recv" => Method::Recv,
+            "shift" => Method::RemoveFirst,
+            "unshift" => Method::InsertFirst,
+            "slice" => Method::Slice,
+            "map" => Method::Map,
+            "filter" => Method::Filter,
+            "charAt" => Method::CharAt,
+            "substring" => Method::Substring,
+            "indexOf" => Method::IndexOf,
+            "toUpperCase" => Method::ToUpperCase,
+            "toLowerCase" => Method::ToLowerCase,
+            "trim" => Method::Trim,
+            "split" => Method::Split,
+            "join" => Method::Join,
+            "reverse" => Method::Reverse,
+            "sort" => Method::Sort,
+            "sortBy" => Method::SortBy,
+            "includes" | "contains" => Method::Contains,
+            "replace" => Method::Replace,
+            "startsWith" => Method::StartsWith,
+            "endsWith" => Method::EndsWith,
+            "padStart" => Method::PadStart,
+            "repeat" => Method::Repeat,
+            "toString" => Method::ToString,
+            "toFixed" => Method::ToFixed,
+            "reduce" => Method::Reduce,
+            "forEach" => Method::ForEach,
+            "find" => Method::Find,
+            "findIndex" => Method::FindIndex,
+            "some" => Method::Any,
+            "every" => Method::All,
+            "flat" => Method::Flat,
+            "concat" => Method::Concat,
+            "at" => Method::At,
+            other => Method::Named(other.to_string()),
+        }
+    }
+}
@@ -1,10 +1,16 @@
-use crate::token::{Token, TokenKind};
+use crate::diagnostic::Diagnostic;
+use crate::span::{FileId, Span};
+use crate::token::{Comment, Token, TokenKind};
 
 pub struct Lexer {
     input: Vec<char>,
     position: usize,
+    offset: usize,
+    file: FileId,
     line: usize,
     column: usize,
+    comments: Vec<Comment>,
+    lengths: Vec<usize>,
 }
 
 impl Lexer {
@@ -12,30 +18,53 @@ impl Lexer {
         Self {
             input: input.chars().collect(),
             position: 0,
+            offset: 0,
+            file: FileId::default(),
             line: 1,
             column: 1,
+            comments: Vec::new(),
+            lengths: Vec::new(),
         }
     }
 
-    pub fn tokenize(&mut self) -> Result<Vec<Token>, String> {
+    pub fn with_file(mut self, file: FileId) -> Self {
+        self.file = file;
+        self
+    }
+
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+
+    pub fn token_lengths(&self) -> &[usize] {
+        &self.lengths
+    }
+
+    pub fn tokenize(&mut self) -> Result<Vec<Token>, Diagnostic> {
         let mut tokens = Vec::new();
 
         loop {
             self.skip_whitespace_and_comments();
 
             if self.is_at_end() {
-                tokens.push(Token::new(TokenKind::Eof, self.line, self.column));
+                let mut eof = Token::new(TokenKind::Eof, self.line, self.column);
+                eof.span = Span::new(self.file, self.offset, self.offset, self.line);
+                tokens.push(eof);
+                self.lengths.push(0);
                 break;
             }
 
-            let token = self.next_token()?;
+            let (start, start_offset) = (self.position, self.offset);
+            let mut token = self.next_token()?;
+            token.span = Span::new(self.file, start_offset, self.offset, token.line);
+            self.lengths.push(self.position - start);
             tokens.push(token);
         }
 
         Ok(tokens)
     }
 
-    fn next_token(&mut self) -> Result<Token, String> {
+    fn next_token(&mut self) -> Result<Token, Diagnostic> {
         let line = self.line;
         let column = self.column;
         let ch = self.current_char();
@@ -75,7 +104,12 @@ impl Lexer {
             }
             '.' => {
                 self.advance();
-                Ok(Token::new(TokenKind::Dot, line, column))
+                if self.current_char() == '.' {
+                    self.advance();
+                    Ok(Token::new(TokenKind::DotDot, line, column))
+                } else {
+                    Ok(Token::new(TokenKind::Dot, line, column))
+                }
             }
             '(' => {
                 self.advance();
@@ -153,6 +187,10 @@ impl Lexer {
                 self.advance();
                 Ok(Token::new(TokenKind::Question, line, column))
             }
+            '@' => {
+                self.advance();
+                Ok(Token::new(TokenKind::At, line, column))
+            }
             '<' => {
                 self.advance();
                 if self.current_char() == '=' {
@@ -165,14 +203,11 @@ impl Lexer {
             '"' => self.read_string(line, column),
             _ if ch.is_ascii_digit() => self.read_number(line, column),
             _ if ch.is_alphabetic() || ch == '_' => self.read_identifier(line, column),
-            _ => Err(format!(
-                "Unexpected character '{}' at line {}, column {}",
-                ch, line, column
-            )),
+            _ => Err(Diagnostic::new(format!("Unexpected character '{}'", ch)).at(line, column)),
         }
     }
 
-    fn read_identifier(&mut self, line: usize, column: usize) -> Result<Token, String> {
+    fn read_identifier(&mut self, line: usize, column: usize) -> Result<Token, Diagnostic> {
         let mut ident = String::new();
 
         while !self.is_at_end() && (self.current_char().is_alphanumeric() || self.current_char() == '_') {
@@ -180,6 +215,27 @@ impl Lexer {
             self.advance();
         }
 
+        if ident == "r" {
+            if let Some(hashes) = self.raw_string_hashes() {
+                return self.read_raw_string(hashes, line, column);
+            }
+        }
+
+        // `r#name` is a raw identifier: a name even when it is a keyword,
+        // kept with its prefix so the generated Rust uses it as one too
+        if ident == "r" && self.current_char() == '#' && self.peek_ahead().is_some_and(|c| c.is_alphabetic() || c == '_') {
+            self.advance();
+            let mut name = String::new();
+            while !self.is_at_end() && (self.current_char().is_alphanumeric() || self.current_char() == '_') {
+                name.push(self.current_char());
+                self.advance();
+            }
+            if matches!(name.as_str(), "self" | "Self" | "super" | "crate" | "_") {
+                return Err(Diagnostic::new(format!("'{}' can't be a raw identifier", name)).at(line, column));
+            }
+            return Ok(Token::new(TokenKind::Identifier(format!("r#{}", name)), line, column));
+        }
+
         let kind = match ident.as_str() {
             "let" => TokenKind::Let,
             "function" => TokenKind::Function,
@@ -208,6 +264,7 @@ impl Lexer {
             "import" => TokenKind::Import,
             "from" => TokenKind::From,
             "export" => TokenKind::Export,
+            "readonly" => TokenKind::Readonly,
             "true" => TokenKind::BooleanLiteral(true),
             "false" => TokenKind::BooleanLiteral(false),
             "number" => TokenKind::NumberType,
@@ -220,43 +277,111 @@ impl Lexer {
         Ok(Token::new(kind, line, column))
     }
 
-    fn read_string(&mut self, line: usize, column: usize) -> Result<Token, String> {
+    fn read_string(&mut self, line: usize, column: usize) -> Result<Token, Diagnostic> {
+        if self.input[self.position..].starts_with(&['"'; 3]) {
+            return self.read_multiline_string(line, column);
+        }
         self.advance();
         let mut value = String::new();
 
         while !self.is_at_end() && self.current_char() != '"' {
-            if self.current_char() == '\\' {
-                self.advance();
-                if self.is_at_end() {
-                    return Err(format!("Unterminated string at line {}, column {}", line, column));
-                }
-                match self.current_char() {
-                    'n' => value.push('\n'),
-                    't' => value.push('\t'),
-                    'r' => value.push('\r'),
-                    '\\' => value.push('\\'),
-                    '"' => value.push('"'),
-                    _ => {
-                        value.push('\\');
-                        value.push(self.current_char());
-                    }
-                }
-                self.advance();
-            } else {
-                value.push(self.current_char());
-                self.advance();
-            }
+            self.read_string_char(&mut value, line, column)?;
         }
 
         if self.is_at_end() {
-            return Err(format!("Unterminated string at line {}, column {}", line, column));
+            return Err(Diagnostic::new("Unterminated string").at(line, column));
         }
 
         self.advance();
         Ok(Token::new(TokenKind::StringLiteral(value), line, column))
     }
 
-    fn read_number(&mut self, line: usize, column: usize) -> Result<Token, String> {
+    /// A `"""` string, which may span lines. A line break right after the
+    /// opening quotes isn't part of the string, so the text can start on
+    /// its own line.
+    fn read_multiline_string(&mut self, line: usize, column: usize) -> Result<Token, Diagnostic> {
+        for _ in 0..3 {
+            self.advance();
+        }
+        if self.current_char() == '\r' && self.peek_ahead() == Some('\n') {
+            self.advance();
+        }
+        if self.current_char() == '\n' {
+            self.advance();
+        }
+
+        let mut value = String::new();
+        while !self.input[self.position..].starts_with(&['"'; 3]) {
+            if self.is_at_end() {
+                return Err(Diagnostic::new("Unterminated multi-line string").at(line, column));
+            }
+            self.read_string_char(&mut value, line, column)?;
+        }
+
+        for _ in 0..3 {
+            self.advance();
+        }
+        Ok(Token::new(TokenKind::StringLiteral(value), line, column))
+    }
+
+    fn read_string_char(&mut self, value: &mut String, line: usize, column: usize) -> Result<(), Diagnostic> {
+        if self.current_char() != '\\' {
+            value.push(self.current_char());
+            self.advance();
+            return Ok(());
+        }
+
+        self.advance();
+        if self.is_at_end() {
+            return Err(Diagnostic::new("Unterminated string").at(line, column));
+        }
+        match self.current_char() {
+            'n' => value.push('\n'),
+            't' => value.push('\t'),
+            'r' => value.push('\r'),
+            '\\' => value.push('\\'),
+            '"' => value.push('"'),
+            _ => {
+                value.push('\\');
+                value.push(self.current_char());
+            }
+        }
+        self.advance();
+        Ok(())
+    }
+
+    /// The number of `#`s in a raw string starting at the current position,
+    /// right after its `r`
+    fn raw_string_hashes(&self) -> Option<usize> {
+        let rest = &self.input[self.position..];
+        let hashes = rest.iter().take_while(|c| **c == '#').count();
+        (rest.get(hashes) == Some(&'"')).then_some(hashes)
+    }
+
+    /// `r"..."` or `r#"..."#`: no escapes are processed, and the text ends at
+    /// a quote followed by as many `#`s as it started with
+    fn read_raw_string(&mut self, hashes: usize, line: usize, column: usize) -> Result<Token, Diagnostic> {
+        for _ in 0..=hashes {
+            self.advance();
+        }
+
+        let closing: Vec<char> = std::iter::once('"').chain(std::iter::repeat('#').take(hashes)).collect();
+        let mut value = String::new();
+        while !self.input[self.position..].starts_with(&closing) {
+            if self.is_at_end() {
+                return Err(Diagnostic::new("Unterminated raw string").at(line, column));
+            }
+            value.push(self.current_char());
+            self.advance();
+        }
+
+        for _ in 0..closing.len() {
+            self.advance();
+        }
+        Ok(Token::new(TokenKind::StringLiteral(value), line, column))
+    }
+
+    fn read_number(&mut self, line: usize, column: usize) -> Result<Token, Diagnostic> {
         let mut num_str = String::new();
 
         while !self.is_at_end() && self.current_char().is_ascii_digit() {
@@ -266,7 +391,7 @@ impl Lexer {
 
         match num_str.parse::<i32>() {
             Ok(num) => Ok(Token::new(TokenKind::NumberLiteral(num), line, column)),
-            Err(_) => Err(format!("Invalid number '{}' at line {}, column {}", num_str, line, column)),
+            Err(_) => Err(Diagnostic::new(format!("Invalid number '{}'", num_str)).at(line, column)),
         }
     }
 
@@ -277,20 +402,17 @@ impl Lexer {
             }
 
             match self.current_char() {
-                ' ' | '\t' | '\r' => {
-                    self.advance();
-                }
-                '\n' => {
-                    self.line += 1;
-                    self.column = 0;
+                ' ' | '\t' | '\r' | '\n' => {
                     self.advance();
                 }
                 '/' if self.peek_ahead() == Some('/') => {
-                    self.advance();
-                    self.advance();
+                    let (line, column) = (self.line, self.column);
+                    let mut text = String::new();
                     while !self.is_at_end() && self.current_char() != '\n' {
+                        text.push(self.current_char());
                         self.advance();
                     }
+                    self.comments.push(Comment { text: text.trim_end().to_string(), line, column });
                 }
                 _ => return,
             }
@@ -315,8 +437,15 @@ impl Lexer {
 
     fn advance(&mut self) {
         if !self.is_at_end() {
+            let ch = self.input[self.position];
+            self.offset += ch.len_utf8();
             self.position += 1;
-            self.column += 1;
+            if ch == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
         }
     }
 
@@ -2,10 +2,32 @@ pub mod token;
 pub mod lexer;
 pub mod ast;
 pub mod parser;
+pub mod checker;
+pub mod cfg;
+pub mod ir;
 pub mod codegen;
+pub mod optimize;
+pub mod diagnostic;
+pub mod error_codes;
+pub mod formatter;
+pub mod highlight;
+pub mod compiler;
+pub mod visit;
+pub mod span;
+pub mod unused;
 
-pub use token::{Token, TokenKind};
+pub use token::{Comment, Token, TokenKind};
 pub use lexer::Lexer;
-pub use ast::{Program, Statement, Expression, Type};
+pub use ast::{ast_from_json, ast_to_json, Program, Statement, StatementKind, Expression, ExpressionKind, Type};
 pub use parser::Parser;
-pub use codegen::Codegen;
+pub use checker::Checker;
+pub use cfg::Cfg;
+pub use ir::IrProgram;
+pub use codegen::{Codegen, NamingPolicy};
+pub use optimize::optimize;
+pub use diagnostic::{Diagnostic, Edit, Fix, Severity};
+pub use formatter::format_source;
+pub use highlight::{highlight, HighlightKind, HighlightToken};
+pub use compiler::{compile, Compilation, CompileError, CompileOptions, Compiler, OutputKind, Stage};
+pub use visit::{Visitor, VisitorMut};
+pub use span::{FileId, Span};
@@ -0,0 +1,250 @@
+use std::collections::HashSet;
+
+use crate::ast::BinaryOp;
+use crate::ir::*;
+
+pub fn optimize(program: &mut IrProgram) {
+    for stmt in program.items.iter_mut().chain(program.main.iter_mut().flatten()) {
+        fold_stmt(stmt);
+    }
+    for test in &mut program.tests {
+        test.body.iter_mut().for_each(fold_stmt);
+    }
+
+    prune(&mut program.items);
+    if let Some(main) = &mut program.main {
+        prune(main);
+    }
+    for test in &mut program.tests {
+        prune(&mut test.body);
+    }
+
+    remove_unused_functions(program);
+}
+
+fn fold_stmt(stmt: &mut IrStmt) {
+    for body in stmt.kind.bodies_mut() {
+        body.iter_mut().for_each(fold_stmt);
+    }
+    for expr in exprs_mut(&mut stmt.kind) {
+        fold_expr(expr);
+    }
+}
+
+fn fold_expr(expr: &mut IrExpr) {
+    if let IrExprKind::Closure { body, .. } = &mut expr.kind {
+        body.iter_mut().for_each(fold_stmt);
+    }
+    for child in children_mut(&mut expr.kind) {
+        fold_expr(child);
+    }
+    let folded = match &expr.kind {
+        IrExprKind::Binary(left, op, right) => fold_binary(&left.kind, *op, &right.kind),
+        IrExprKind::Format(parts) => fold_format(parts),
+        _ => None,
+    };
+    if let Some(folded) = folded {
+        *expr = folded;
+    }
+}
+
+fn fold_binary(left: &IrExprKind, op: BinaryOp, right: &IrExprKind) -> Option<IrExpr> {
+    match (left, right) {
+        (IrExprKind::Int(a), IrExprKind::Int(b)) => {
+            let (a, b) = (*a, *b);
+            let value = match op {
+                BinaryOp::Add => IrExprKind::Int(a.checked_add(b)?),
+                BinaryOp::Subtract => IrExprKind::Int(a.checked_sub(b)?),
+                BinaryOp::Multiply => IrExprKind::Int(a.checked_mul(b)?),
+                BinaryOp::Divide => IrExprKind::Int(a.checked_div(b)?),
+                BinaryOp::Modulo => IrExprKind::Int(a.checked_rem(b)?),
+                BinaryOp::Equal => IrExprKind::Bool(a == b),
+                BinaryOp::NotEqual => IrExprKind::Bool(a != b),
+                BinaryOp::Greater => IrExprKind::Bool(a > b),
+                BinaryOp::GreaterEqual => IrExprKind::Bool(a >= b),
+                BinaryOp::Less => IrExprKind::Bool(a < b),
+                BinaryOp::LessEqual => IrExprKind::Bool(a <= b),
+                BinaryOp::And | BinaryOp::Or => return None,
+            };
+            let ty = if matches!(value, IrExprKind::Int(_)) { IrType::Int } else { IrType::Bool };
+            Some(IrExpr::new(value, ty))
+        }
+        (IrExprKind::Bool(a), IrExprKind::Bool(b)) => {
+            let value = match op {
+                BinaryOp::And => *a && *b,
+                BinaryOp::Or => *a || *b,
+                BinaryOp::Equal => a == b,
+                BinaryOp::NotEqual => a != b,
+                _ => return None,
+            };
+            Some(IrExpr::new(IrExprKind::Bool(value), IrType::Bool))
+        }
+        _ => None,
+    }
+}
+
+fn fold_format(parts: &[FormatArg]) -> Option<IrExpr> {
+    let mut merged: Vec<FormatArg> = Vec::new();
+    for part in parts {
+        let text = match &part.value.kind {
+            IrExprKind::Str(s) => s.clone(),
+            IrExprKind::Int(n) => n.to_string(),
+            IrExprKind::Bool(b) => b.to_string(),
+            _ => {
+                merged.push(part.clone());
+                continue;
+            }
+        };
+        match merged.last_mut() {
+            Some(FormatArg { value: IrExpr { kind: IrExprKind::Str(previous), .. }, .. }) => previous.push_str(&text),
+            _ => merged.push(FormatArg {
+                value: IrExpr::new(IrExprKind::Str(text), IrType::StaticStr),
+                debug: false,
+            }),
+        }
+    }
+
+    if let [FormatArg { value: single @ IrExpr { kind: IrExprKind::Str(_), .. }, .. }] = merged.as_slice() {
+        return Some(IrExpr::new(IrExprKind::ToOwned(Box::new(single.clone())), IrType::Str));
+    }
+    (merged.len() < parts.len()).then(|| IrExpr::new(IrExprKind::Format(merged), IrType::Str))
+}
+
+fn prune(body: &mut Vec<IrStmt>) {
+    let mut pruned = Vec::with_capacity(body.len());
+    for mut stmt in body.drain(..) {
+        let terminates = matches!(
+            stmt.kind,
+            IrStmtKind::Return(_) | IrStmtKind::Break | IrStmtKind::Continue | IrStmtKind::Panic(_)
+        );
+        match stmt.kind {
+            IrStmtKind::If { condition: IrExpr { kind: IrExprKind::Bool(taken), .. }, then_body, else_body } => {
+                let branch = if taken { Some(then_body) } else { else_body };
+                if let Some(branch) = branch {
+                    stmt.kind = IrStmtKind::Block(branch);
+                    pruned.push(stmt);
+                }
+            }
+            IrStmtKind::While { condition: IrExpr { kind: IrExprKind::Bool(false), .. }, .. } => {}
+            _ => pruned.push(stmt),
+        }
+        if terminates {
+            break;
+        }
+    }
+    for stmt in &mut pruned {
+        for inner in stmt.kind.bodies_mut() {
+            prune(inner);
+        }
+    }
+    *body = pruned;
+}
+
+fn remove_unused_functions(program: &mut IrProgram) {
+    loop {
+        let mut used = HashSet::new();
+        for stmt in program.items.iter_mut().chain(program.main.iter_mut().flatten()) {
+            match &mut stmt.kind {
+                IrStmtKind::Function(function) => {
+                    let mut calls = HashSet::new();
+                    function.body.iter_mut().for_each(|stmt| collect_references(stmt, &mut calls));
+                    calls.remove(&function.name);
+                    used.extend(calls);
+                }
+                _ => collect_references(stmt, &mut used),
+            }
+        }
+        for test in &mut program.tests {
+            test.body.iter_mut().for_each(|stmt| collect_references(stmt, &mut used));
+        }
+
+        let is_used = |stmt: &IrStmt| match &stmt.kind {
+            IrStmtKind::Function(function) => {
+                function.visibility.is_visible_outside() || function.name == "main" || used.contains(&function.name)
+            }
+            _ => true,
+        };
+        let before = program.items.len() + program.main.as_ref().map_or(0, Vec::len);
+        program.items.retain(is_used);
+        if let Some(main) = &mut program.main {
+            main.retain(is_used);
+        }
+        if program.items.len() + program.main.as_ref().map_or(0, Vec::len) == before {
+            return;
+        }
+    }
+}
+
+fn collect_references(stmt: &mut IrStmt, used: &mut HashSet<String>) {
+    for body in stmt.kind.bodies_mut() {
+        body.iter_mut().for_each(|inner| collect_references(inner, used));
+    }
+    for expr in exprs_mut(&mut stmt.kind) {
+        collect_expr_references(expr, used);
+    }
+}
+
+fn collect_expr_references(expr: &mut IrExpr, used: &mut HashSet<String>) {
+    if let IrExprKind::Call { path, .. } | IrExprKind::Path(path) = &expr.kind {
+        used.insert(path.clone());
+    }
+    if let IrExprKind::Closure { body, .. } = &mut expr.kind {
+        body.iter_mut().for_each(|stmt| collect_references(stmt, used));
+    }
+    for child in children_mut(&mut expr.kind) {
+        collect_expr_references(child, used);
+    }
+}
+
+fn exprs_mut(kind: &mut IrStmtKind) -> Vec<&mut IrExpr> {
+    match kind {
+        IrStmtKind::Let { value, .. }
+        | IrStmtKind::LetTuple { value, .. }
+        | IrStmtKind::Const { value, .. }
+        | IrStmtKind::Static { value, .. }
+        | IrStmtKind::Panic(value)
+        | IrStmtKind::Expr(value)
+        | IrStmtKind::Return(Some(value))
+        | IrStmtKind::If { condition: value, .. }
+        | IrStmtKind::For { iterable: value, .. }
+        | IrStmtKind::While { condition: value, .. }
+        | IrStmtKind::Match { subject: value, .. } => vec![value],
+        IrStmtKind::Print(args) => args.iter_mut().map(|arg| &mut arg.value).collect(),
+        IrStmtKind::Struct(ir_struct) => ir_struct.defaults.iter_mut().flatten().map(|(_, value)| value).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn children_mut(kind: &mut IrExprKind) -> Vec<&mut IrExpr> {
+    match kind {
+        IrExprKind::Deref(inner)
+        | IrExprKind::ToOwned(inner)
+        | IrExprKind::Clone(inner)
+        | IrExprKind::Ref(inner)
+        | IrExprKind::Cast(inner, _)
+        | IrExprKind::Some(inner)
+        | IrExprKind::Len(inner)
+        | IrExprKind::Fallible { call: inner, .. }
+        | IrExprKind::Shared(_, inner) => vec![&mut **inner],
+        IrExprKind::Field { object, .. } => vec![&mut **object],
+        IrExprKind::Binary(left, _, right) => vec![&mut **left, &mut **right],
+        IrExprKind::Index { object, index }
+        | IrExprKind::CheckedIndex { object, index }
+        | IrExprKind::IndexSlot { object, index } => {
+            vec![&mut **object, &mut **index]
+        }
+        IrExprKind::Assign { target, value } => vec![&mut **target, &mut **value],
+        IrExprKind::Vec(items)
+        | IrExprKind::Array(items)
+        | IrExprKind::Call { args: items, .. }
+        | IrExprKind::Assert { args: items, .. } => items.iter_mut().collect(),
+        IrExprKind::Struct { fields, .. } => fields.iter_mut().map(|(_, value)| value).collect(),
+        IrExprKind::Print { args, .. } | IrExprKind::Format(args) => args.iter_mut().map(|arg| &mut arg.value).collect(),
+        IrExprKind::Method { receiver, args, .. } => {
+            let mut children = vec![&mut **receiver];
+            children.extend(args.iter_mut());
+            children
+        }
+        _ => Vec::new(),
+    }
+}
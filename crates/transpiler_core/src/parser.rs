@@ -1,5 +1,9 @@
+use crate::diagnostic::{Diagnostic, Fix};
 use crate::token::{Token, TokenKind};
 use crate::ast::*;
+use crate::cfg;
+use crate::codegen::string_literal;
+use crate::span::Span;
 
 pub struct Parser {
     tokens: Vec<Token>,
@@ -11,24 +15,68 @@ impl Parser {
         Parser { tokens, current: 0 }
     }
 
-    pub fn parse(&mut self) -> Result<Program, String> {
+    pub fn parse(&mut self) -> Result<Program, Diagnostic> {
         let mut statements = Vec::new();
 
         while !self.is_at_end() {
-            self.skip_newlines();
-            if self.is_at_end() {
-                break;
-            }
-            statements.push(self.parse_statement()?);
+            let stmt = self.parse_statement().map_err(|message| {
+                let token = self.peek();
+                let mut diagnostic = Diagnostic::new(message).at(token.line, token.column);
+                if diagnostic.message.starts_with("Unexpected trailing tokens") {
+                    diagnostic = diagnostic.with_width(self.trailing_width());
+                }
+                match self.fix_for(&diagnostic.message) {
+                    Some(fix) => diagnostic.with_fix(fix),
+                    None => diagnostic,
+                }
+            })?;
+            statements.push(stmt);
         }
 
         Ok(Program { statements })
     }
 
+    /// The fix for an error at the current token: inserting a missing `;`
+    /// right after the last token, or removing a stray one
+    fn fix_for(&self, message: &str) -> Option<Fix> {
+        if message.starts_with("Expected ';'") && self.current > 0 {
+            let end = self.previous_span().end;
+            return Some(Fix::replace("insert ';'", end, end, ";"));
+        }
+        if message.starts_with("Unexpected ';'") {
+            let span = self.peek().span;
+            return Some(Fix::replace("remove the ';'", span.start, span.end, ""));
+        }
+        None
+    }
+
+    /// The width of the tokens from the current one to the end of its line,
+    /// stopping at a `;`
+    fn trailing_width(&self) -> usize {
+        let first = &self.tokens[self.current];
+        let last = self.tokens[self.current..]
+            .iter()
+            .take_while(|token| token.line == first.line && !matches!(token.kind, TokenKind::Semicolon | TokenKind::Eof))
+            .last()
+            .unwrap_or(first);
+        (last.column + last.span.len()).saturating_sub(first.column).max(1)
+    }
+
     fn parse_statement(&mut self) -> Result<Statement, String> {
+        self.spanned(Self::parse_statement_kind)
+    }
+
+    fn spanned(&mut self, parse: impl FnOnce(&mut Self) -> Result<StatementKind, String>) -> Result<Statement, String> {
+        let start = self.peek().span;
+        let kind = parse(self)?;
+        Ok(Statement::new(kind, start.to(self.previous_span())))
+    }
+
+    fn parse_statement_kind(&mut self) -> Result<StatementKind, String> {
         match &self.peek().kind {
             TokenKind::Import => self.parse_import_stmt(),
             TokenKind::Export => self.parse_export_stmt(),
+            TokenKind::At => self.parse_attributed_stmt(),
             TokenKind::Let => self.parse_variable_decl(false),
             TokenKind::Const => self.parse_variable_decl(true),
             TokenKind::Function => self.parse_function_decl(),
@@ -43,31 +91,71 @@ impl Parser {
             TokenKind::Continue => self.parse_continue_stmt(),
             TokenKind::Try => self.parse_try_catch(),
             TokenKind::Throw => self.parse_throw_stmt(),
+            TokenKind::LeftBrace => {
+                self.advance();
+                let body = self.parse_block()?;
+                self.consume(TokenKind::RightBrace, "Expected '}' after block")?;
+                Ok(StatementKind::Block(body))
+            }
+            TokenKind::Semicolon => Err("Unexpected ';' with no statement before it".to_string()),
+            TokenKind::RightBrace => Err("Unexpected '}' with no matching '{'".to_string()),
+            TokenKind::RightParen => Err("Unexpected ')' with no matching '('".to_string()),
+            TokenKind::RightBracket => Err("Unexpected ']' with no matching '['".to_string()),
+            TokenKind::Identifier(name) if (name == "pub" || name == "internal") && self.is_declaration_ahead() => {
+                self.parse_visibility_stmt()
+            }
+            TokenKind::Identifier(name) if name == "lock" && self.is_lock_stmt_ahead() => self.parse_lock_stmt(),
+            TokenKind::Identifier(name) if name == "test" && self.is_test_block_ahead() => self.parse_test_block(),
+            TokenKind::Identifier(name) if name == "match" && self.is_match_stmt_ahead() => self.parse_match_stmt(),
+            TokenKind::Identifier(name)
+                if name == "defer"
+                    && matches!(self.tokens.get(self.current + 1).map(|t| &t.kind), Some(TokenKind::LeftBrace)) =>
+            {
+                self.advance();
+                self.advance();
+                let body = self.parse_block()?;
+                self.consume(TokenKind::RightBrace, "Expected '}' after defer body")?;
+                Ok(StatementKind::DeferStmt(body))
+            }
             _ => {
-                let expr = self.parse_expression()?;
-                self.consume(TokenKind::Semicolon, "Expected ';' after statement")?;
-                Ok(Statement::ExpressionStmt(expr))
+                let expression = self.parse_expression()?;
+                self.end_statement("Expected ';' after statement")?;
+                Ok(StatementKind::ExpressionStmt(ExpressionStmt { expression }))
             }
         }
     }
 
-    fn parse_import_stmt(&mut self) -> Result<Statement, String> {
+    fn parse_import_stmt(&mut self) -> Result<StatementKind, String> {
         self.advance();
-        
+        self.parse_import_clause()
+    }
+
+    fn parse_import_clause(&mut self) -> Result<StatementKind, String> {
         let mut imports = Vec::new();
         let path: String;
-        let is_external: bool;
+        let source: ImportSource;
+        let mut is_wildcard = false;
+        let mut namespace = None;
+        let mut braced = false;
         
-        if self.check(&TokenKind::LeftBrace) {
+        if self.match_token(&TokenKind::Star) {
+            is_wildcard = true;
+            if self.match_token(&TokenKind::As) {
+                namespace = Some(self.expect_identifier()?);
+            }
+            self.consume(TokenKind::From, "Expected 'from' after '*' import")?;
+        } else if self.check(&TokenKind::LeftBrace) {
+            braced = true;
             self.advance();
             loop {
+                let start = self.peek().span;
                 let name = self.expect_identifier()?;
                 let alias = if self.match_token(&TokenKind::As) {
                     Some(self.expect_identifier()?)
                 } else {
                     None
                 };
-                imports.push(ImportItem { name, alias });
+                imports.push(ImportItem { name, alias, span: start.to(self.previous_span()) });
                 
                 if !self.match_token(&TokenKind::Comma) {
                     break;
@@ -76,10 +164,11 @@ impl Parser {
             self.consume(TokenKind::RightBrace, "Expected '}' after import list")?;
             self.consume(TokenKind::From, "Expected 'from' after import list")?;
         } else if matches!(self.peek().kind, TokenKind::Identifier(_)) {
+            let span = self.peek().span;
             let name = self.expect_identifier()?;
             
             if self.match_token(&TokenKind::From) {
-                imports.push(ImportItem { name, alias: None });
+                imports.push(ImportItem { name, alias: None, span });
             } else {
                 return Err("Expected 'from' after import identifier".to_string());
             }
@@ -90,12 +179,21 @@ impl Parser {
         
         if let TokenKind::StringLiteral(ref s) = self.peek().kind {
             path = s.clone();
-            is_external = path.contains("::") || !path.starts_with('.');
+            source = ImportSource::of(&path);
             self.advance();
         } else {
             return Err("Expected string literal for import path".to_string());
         }
         
+        if source == ImportSource::Local && !is_wildcard && !braced && imports.len() == 1 {
+            let local = imports.remove(0);
+            imports.push(ImportItem {
+                name: DEFAULT_IMPORT.to_string(),
+                alias: Some(local.name),
+                span: local.span,
+            });
+        }
+        
         let alias = if self.match_token(&TokenKind::As) {
             Some(self.expect_identifier()?)
         } else {
@@ -106,53 +204,329 @@ impl Parser {
             imports[0].alias = alias;
         }
         
-        self.consume(TokenKind::Semicolon, "Expected ';' after import statement")?;
+        self.end_statement("Expected ';' after import statement")?;
         
-        Ok(Statement::ImportStmt(ImportStmt {
+        Ok(StatementKind::ImportStmt(ImportStmt {
             imports,
             path,
-            is_external,
+            source,
+            is_wildcard,
+            namespace,
         }))
     }
     
-    fn parse_export_stmt(&mut self) -> Result<Statement, String> {
+    fn parse_export_stmt(&mut self) -> Result<StatementKind, String> {
         self.advance();
         
+        if matches!(&self.peek().kind, TokenKind::Identifier(name) if name == "default") {
+            self.advance();
+            let inner_stmt = match &self.peek().kind {
+                TokenKind::Function => self.spanned(Self::parse_function_decl)?,
+                TokenKind::Struct => self.spanned(Self::parse_struct_decl)?,
+                TokenKind::Enum => self.spanned(Self::parse_enum_decl)?,
+                TokenKind::Const => self.spanned(|parser| parser.parse_variable_decl(true))?,
+                _ => return Err("Expected function, struct, enum, or const after 'export default'".to_string()),
+            };
+            return Ok(StatementKind::ExportDefault(Box::new(inner_stmt)));
+        }
+        
         let inner_stmt = match &self.peek().kind {
-            TokenKind::Function => self.parse_function_decl()?,
-            TokenKind::Struct => self.parse_struct_decl()?,
-            TokenKind::Enum => self.parse_enum_decl()?,
-            TokenKind::Const => self.parse_variable_decl(true)?,
-            TokenKind::Let => self.parse_variable_decl(false)?,
-            _ => return Err("Expected function, struct, enum, const, or let after export".to_string()),
+            TokenKind::LeftBrace | TokenKind::Star => self.spanned(Self::parse_import_clause)?,
+            TokenKind::Function => self.spanned(Self::parse_function_decl)?,
+            TokenKind::Struct => self.spanned(Self::parse_struct_decl)?,
+            TokenKind::Enum => self.spanned(Self::parse_enum_decl)?,
+            TokenKind::Const => self.spanned(|parser| parser.parse_variable_decl(true))?,
+            TokenKind::Let => self.spanned(|parser| parser.parse_variable_decl(false))?,
+            _ => return Err("Expected function, struct, enum, const, let, '{' or '*' after export".to_string()),
         };
         
-        Ok(Statement::ExportStmt(Box::new(inner_stmt)))
+        Ok(StatementKind::ExportStmt(Box::new(inner_stmt)))
+    }
+
+    /// `pub` or `internal` only mark a declaration, so elsewhere they stay names
+    fn is_declaration_ahead(&self) -> bool {
+        matches!(
+            self.tokens.get(self.current + 1).map(|t| &t.kind),
+            Some(TokenKind::Function | TokenKind::Struct | TokenKind::Enum | TokenKind::Const | TokenKind::Let | TokenKind::Export)
+        )
     }
 
-    fn parse_variable_decl(&mut self, is_const: bool) -> Result<Statement, String> {
+    fn parse_visibility_stmt(&mut self) -> Result<StatementKind, String> {
+        let visibility = match &self.advance().kind {
+            TokenKind::Identifier(name) if name == "pub" => Visibility::Crate,
+            _ => Visibility::Internal,
+        };
+        let inner_stmt = match &self.peek().kind {
+            TokenKind::Function => self.spanned(Self::parse_function_decl)?,
+            TokenKind::Struct => self.spanned(Self::parse_struct_decl)?,
+            TokenKind::Enum => self.spanned(Self::parse_enum_decl)?,
+            TokenKind::Const => self.spanned(|parser| parser.parse_variable_decl(true))?,
+            TokenKind::Let => {
+                return Err(format!(
+                    "'{}' can't be applied to a variable; export it to share it between modules",
+                    visibility.keyword()
+                ))
+            }
+            _ => {
+                return Err(format!(
+                    "'{}' can't be combined with 'export'; an exported declaration is already public",
+                    visibility.keyword()
+                ))
+            }
+        };
+        Ok(StatementKind::VisibilityStmt(visibility, Box::new(inner_stmt)))
+    }
+
+    fn parse_variable_decl(&mut self, is_const: bool) -> Result<StatementKind, String> {
         self.advance();
+        let is_mut = !is_const && self.match_token(&TokenKind::Mut);
+        
+        if self.match_token(&TokenKind::LeftBracket) {
+            let mut names = Vec::new();
+            loop {
+                names.push(self.expect_identifier()?);
+                if !self.match_token(&TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.consume(TokenKind::RightBracket, "Expected ']' after destructured names")?;
+            self.consume(TokenKind::Equal, "Expected '=' in variable declaration")?;
+            let value = self.parse_expression()?;
+            if !matches!(value.kind, ExpressionKind::Channel(_)) || names.len() != 2 {
+                return Err("Destructuring is only supported as 'let [sender, receiver] = channel<T>()'".to_string());
+            }
+            self.end_statement("Expected ';' after variable declaration")?;
+            return Ok(StatementKind::DestructureDecl(DestructureDecl { names, value }));
+        }
+        
         let name = self.expect_identifier()?;
         
-        let var_type = if self.match_token(&TokenKind::Colon) {
-            Some(self.parse_type()?)
+        let (var_type, type_span) = if self.match_token(&TokenKind::Colon) {
+            let (var_type, type_span) = self.parse_annotation()?;
+            (Some(var_type), Some(type_span))
         } else {
-            None
+            (None, None)
         };
         
         self.consume(TokenKind::Equal, "Expected '=' in variable declaration")?;
         let value = self.parse_expression()?;
-        self.consume(TokenKind::Semicolon, "Expected ';' after variable declaration")?;
+        self.end_statement("Expected ';' after variable declaration")?;
 
-        Ok(Statement::VariableDecl(VariableDecl {
+        Ok(StatementKind::VariableDecl(VariableDecl {
             name,
             var_type,
+            type_span,
             value,
             is_const,
+            is_mut,
         }))
     }
 
-    fn parse_function_decl(&mut self) -> Result<Statement, String> {
+    fn is_lock_stmt_ahead(&self) -> bool {
+        let kind_at = |offset: usize| self.tokens.get(self.current + offset).map(|token| &token.kind);
+        matches!(kind_at(1), Some(TokenKind::LeftParen))
+            && matches!(kind_at(2), Some(TokenKind::Identifier(_)))
+            && matches!(kind_at(3), Some(TokenKind::RightParen))
+            && matches!(kind_at(4), Some(TokenKind::LeftBrace))
+    }
+    
+    fn is_test_block_ahead(&self) -> bool {
+        let kind_at = |offset: usize| self.tokens.get(self.current + offset).map(|token| &token.kind);
+        matches!(kind_at(1), Some(TokenKind::StringLiteral(_))) && matches!(kind_at(2), Some(TokenKind::LeftBrace))
+    }
+    
+    fn parse_test_block(&mut self) -> Result<StatementKind, String> {
+        self.advance();
+        let name = match self.advance().kind.clone() {
+            TokenKind::StringLiteral(name) => name,
+            _ => return Err("Expected test name".to_string()),
+        };
+        self.consume(TokenKind::LeftBrace, "Expected '{' before test body")?;
+        let body = self.parse_block()?;
+        self.consume(TokenKind::RightBrace, "Expected '}' after test body")?;
+        Ok(StatementKind::TestBlock(TestBlock { name, body }))
+    }
+    
+    /// `match (subject) {`, as opposed to a call of a function named `match`
+    fn is_match_stmt_ahead(&self) -> bool {
+        if !matches!(self.tokens.get(self.current + 1).map(|token| &token.kind), Some(TokenKind::LeftParen)) {
+            return false;
+        }
+        let mut depth = 0;
+        for (index, token) in self.tokens.iter().enumerate().skip(self.current + 1) {
+            match token.kind {
+                TokenKind::LeftParen => depth += 1,
+                TokenKind::RightParen => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return matches!(self.tokens.get(index + 1).map(|token| &token.kind), Some(TokenKind::LeftBrace));
+                    }
+                }
+                TokenKind::Eof => break,
+                _ => {}
+            }
+        }
+        false
+    }
+    
+    fn parse_match_stmt(&mut self) -> Result<StatementKind, String> {
+        self.advance();
+        self.consume(TokenKind::LeftParen, "Expected '(' after match")?;
+        let subject = self.parse_expression()?;
+        self.consume(TokenKind::RightParen, "Expected ')' after match subject")?;
+        self.consume(TokenKind::LeftBrace, "Expected '{' before match arms")?;
+        
+        let mut arms = Vec::new();
+        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let start = self.peek().span;
+            let pattern = self.parse_pattern()?;
+            self.consume(TokenKind::Arrow, "Expected '=>' after match pattern")?;
+            let body = if self.match_token(&TokenKind::LeftBrace) {
+                let body = self.parse_block()?;
+                self.consume(TokenKind::RightBrace, "Expected '}' after match arm")?;
+                body
+            } else {
+                vec![self.parse_statement()?]
+            };
+            arms.push(MatchArm { pattern, body, span: start.to(self.previous_span()) });
+            self.match_token(&TokenKind::Comma);
+        }
+        
+        self.consume(TokenKind::RightBrace, "Expected '}' after match arms")?;
+        Ok(StatementKind::Match(MatchStmt { subject, arms }))
+    }
+    
+    fn parse_pattern(&mut self) -> Result<Pattern, String> {
+        let enum_name = self.expect_identifier()?;
+        if enum_name == "_" {
+            return Ok(Pattern::Wildcard);
+        }
+        self.consume(TokenKind::Dot, "Expected '.' after enum name in pattern")?;
+        let variant = self.expect_identifier()?;
+        
+        let bindings = if self.match_token(&TokenKind::LeftParen) {
+            let mut names = Vec::new();
+            while !self.check(&TokenKind::RightParen) && !self.is_at_end() {
+                names.push(self.expect_identifier()?);
+                if !self.match_token(&TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.consume(TokenKind::RightParen, "Expected ')' after pattern bindings")?;
+            PatternBindings::Tuple(names)
+        } else if self.match_token(&TokenKind::LeftBrace) {
+            let mut fields = Vec::new();
+            while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+                let field = self.expect_identifier()?;
+                let binding = if self.match_token(&TokenKind::Colon) { self.expect_identifier()? } else { field.clone() };
+                fields.push((field, binding));
+                if !self.match_token(&TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.consume(TokenKind::RightBrace, "Expected '}' after pattern fields")?;
+            PatternBindings::Named(fields)
+        } else {
+            PatternBindings::None
+        };
+        Ok(Pattern::Variant { enum_name, variant, bindings })
+    }
+    
+    fn parse_lock_stmt(&mut self) -> Result<StatementKind, String> {
+        self.advance();
+        self.consume(TokenKind::LeftParen, "Expected '(' after lock")?;
+        let name = self.expect_identifier()?;
+        self.consume(TokenKind::RightParen, "Expected ')' after lock target")?;
+        self.consume(TokenKind::LeftBrace, "Expected '{' before lock body")?;
+        let body = self.parse_block()?;
+        self.consume(TokenKind::RightBrace, "Expected '}' after lock body")?;
+        Ok(StatementKind::LockStmt(LockStmt { name, body }))
+    }
+    
+    fn parse_attributed_stmt(&mut self) -> Result<StatementKind, String> {
+        let mut attributes = Vec::new();
+        while self.match_token(&TokenKind::At) {
+            attributes.push(self.parse_attribute()?);
+        }
+        for attribute in attributes.iter().filter(|attribute| attribute.name == cfg::CFG) {
+            if attribute.arguments.is_empty() {
+                return Err("Expected a condition in attribute @cfg".to_string());
+            }
+            if let Some(argument) = attribute.arguments.iter().find(|argument| !cfg::is_condition(argument)) {
+                return Err(format!(
+                    "Unknown condition '{}' in attribute @cfg (expected debug, release, unix, windows or os = \"...\")",
+                    argument
+                ));
+            }
+        }
+
+        let mut stmt = self.parse_statement_kind()?;
+        if let StatementKind::Block(body) = &mut stmt {
+            if let Some(attribute) = attributes.iter().find(|attribute| attribute.name != cfg::CFG) {
+                return Err(format!("@{} can't be applied to a block, only @cfg can", attribute.name));
+            }
+            let conditions = attributes.into_iter().flat_map(|attribute| attribute.arguments).collect();
+            return Ok(StatementKind::CfgBlock(CfgBlock { conditions, body: std::mem::take(body) }));
+        }
+        let target = match &mut stmt {
+            StatementKind::ExportStmt(inner)
+            | StatementKind::ExportDefault(inner)
+            | StatementKind::VisibilityStmt(_, inner) => &mut inner.kind,
+            other => other,
+        };
+        match target {
+            StatementKind::FunctionDecl(func_decl) => {
+                if let Some(attribute) = attributes.iter().find(|attribute| is_type_only(attribute)) {
+                    return Err(format!("@{} can only be applied to structs and enums", attribute.name));
+                }
+                func_decl.attributes.extend(attributes)
+            }
+            StatementKind::StructDecl(struct_decl) => struct_decl.attributes.extend(attributes),
+            StatementKind::EnumDecl(enum_decl) => enum_decl.attributes.extend(attributes),
+            _ => return Err("Attributes can only be applied to functions, structs, enums, and @cfg blocks".to_string()),
+        }
+        Ok(stmt)
+    }
+    
+    fn parse_attribute(&mut self) -> Result<Attribute, String> {
+        let name = self.expect_identifier()?;
+        let mut arguments = Vec::new();
+        
+        if self.match_token(&TokenKind::LeftParen) {
+            while !self.check(&TokenKind::RightParen) && !self.is_at_end() {
+                let argument = match self.peek().kind.clone() {
+                    TokenKind::StringLiteral(value) => {
+                        self.advance();
+                        string_literal(&value)
+                    }
+                    _ => {
+                        let key = self.expect_identifier()?;
+                        if self.match_token(&TokenKind::Equal) {
+                            match self.peek().kind.clone() {
+                                TokenKind::StringLiteral(value) => {
+                                    self.advance();
+                                    format!("{} = {}", key, string_literal(&value))
+                                }
+                                _ => return Err("Expected string literal after '=' in attribute".to_string()),
+                            }
+                        } else {
+                            key
+                        }
+                    }
+                };
+                arguments.push(argument);
+                
+                if !self.match_token(&TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.consume(TokenKind::RightParen, "Expected ')' after attribute arguments")?;
+        }
+        
+        Ok(Attribute { name, arguments })
+    }
+
+    fn parse_function_decl(&mut self) -> Result<StatementKind, String> {
         self.advance();
         let name = self.expect_identifier()?;
         self.consume(TokenKind::LeftParen, "Expected '(' after function name")?;
@@ -160,12 +534,15 @@ impl Parser {
         let mut parameters = Vec::new();
         if !self.check(&TokenKind::RightParen) {
             loop {
+                let start = self.peek().span;
                 let param_name = self.expect_identifier()?;
                 self.consume(TokenKind::Colon, "Expected ':' in parameter")?;
-                let param_type = self.parse_type()?;
+                let (param_type, type_span) = self.parse_annotation()?;
                 parameters.push(Parameter {
                     name: param_name,
                     param_type,
+                    type_span,
+                    span: start.to(self.previous_span()),
                 });
 
                 if !self.match_token(&TokenKind::Comma) {
@@ -176,51 +553,57 @@ impl Parser {
 
         self.consume(TokenKind::RightParen, "Expected ')' after parameters")?;
         self.consume(TokenKind::Colon, "Expected ':' after function signature")?;
-        let return_type = self.parse_type()?;
+        let (return_type, return_type_span) = self.parse_annotation()?;
         self.consume(TokenKind::LeftBrace, "Expected '{' before function body")?;
 
         let mut body = Vec::new();
         while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
-            self.skip_newlines();
-            if self.check(&TokenKind::RightBrace) {
-                break;
-            }
             body.push(self.parse_statement()?);
         }
 
         self.consume(TokenKind::RightBrace, "Expected '}' after function body")?;
 
-        Ok(Statement::FunctionDecl(FunctionDecl {
+        Ok(StatementKind::FunctionDecl(FunctionDecl {
             name,
             parameters,
             return_type,
+            return_type_span,
             body,
+            attributes: Vec::new(),
         }))
     }
 
-    fn parse_print_stmt(&mut self) -> Result<Statement, String> {
+    fn parse_print_stmt(&mut self) -> Result<StatementKind, String> {
         self.advance();
         self.consume(TokenKind::LeftParen, "Expected '(' after 'print'")?;
-        let expression = self.parse_expression()?;
-        self.consume(TokenKind::RightParen, "Expected ')' after print expression")?;
-        self.consume(TokenKind::Semicolon, "Expected ';' after print statement")?;
+        let mut arguments = Vec::new();
+        if !self.check(&TokenKind::RightParen) {
+            loop {
+                arguments.push(self.parse_expression()?);
+                if !self.match_token(&TokenKind::Comma) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenKind::RightParen, "Expected ')' after print arguments")?;
+        self.end_statement("Expected ';' after print statement")?;
 
-        Ok(Statement::PrintStmt(PrintStmt { expression }))
+        Ok(StatementKind::PrintStmt(PrintStmt { arguments }))
     }
 
-    fn parse_return_stmt(&mut self) -> Result<Statement, String> {
+    fn parse_return_stmt(&mut self) -> Result<StatementKind, String> {
         self.advance();
         let value = if self.check(&TokenKind::Semicolon) {
             None
         } else {
             Some(self.parse_expression()?)
         };
-        self.consume(TokenKind::Semicolon, "Expected ';' after return statement")?;
+        self.end_statement("Expected ';' after return statement")?;
 
-        Ok(Statement::ReturnStmt(ReturnStmt { value }))
+        Ok(StatementKind::ReturnStmt(ReturnStmt { value }))
     }
 
-    fn parse_if_else_stmt(&mut self) -> Result<Statement, String> {
+    fn parse_if_else_stmt(&mut self) -> Result<StatementKind, String> {
         self.advance();
         let condition = self.parse_expression()?;
         self.consume(TokenKind::LeftBrace, "Expected '{' after if condition")?;
@@ -236,14 +619,14 @@ impl Parser {
             None
         };
 
-        Ok(Statement::IfElse(IfElseStmt {
+        Ok(StatementKind::IfElse(IfElseStmt {
             condition,
             then_body,
             else_body,
         }))
     }
 
-    fn parse_for_loop(&mut self) -> Result<Statement, String> {
+    fn parse_for_loop(&mut self) -> Result<StatementKind, String> {
         self.advance();
         let variable = self.expect_identifier()?;
         self.consume(TokenKind::In, "Expected 'in' in for loop")?;
@@ -252,49 +635,62 @@ impl Parser {
         let body = self.parse_block()?;
         self.consume(TokenKind::RightBrace, "Expected '}' after for block")?;
 
-        Ok(Statement::ForLoop(ForLoopStmt {
+        Ok(StatementKind::ForLoop(ForLoopStmt {
             variable,
             iterable,
             body,
         }))
     }
 
-    fn parse_while_loop(&mut self) -> Result<Statement, String> {
+    fn parse_while_loop(&mut self) -> Result<StatementKind, String> {
         self.advance();
         let condition = self.parse_expression()?;
         self.consume(TokenKind::LeftBrace, "Expected '{' after while condition")?;
         let body = self.parse_block()?;
         self.consume(TokenKind::RightBrace, "Expected '}' after while block")?;
 
-        Ok(Statement::WhileLoop(WhileLoopStmt { condition, body }))
+        Ok(StatementKind::WhileLoop(WhileLoopStmt { condition, body }))
     }
 
-    fn parse_break_stmt(&mut self) -> Result<Statement, String> {
+    fn parse_break_stmt(&mut self) -> Result<StatementKind, String> {
         self.advance();
-        self.consume(TokenKind::Semicolon, "Expected ';' after break")?;
-        Ok(Statement::BreakStmt)
+        self.end_statement("Expected ';' after break")?;
+        Ok(StatementKind::BreakStmt)
     }
 
-    fn parse_continue_stmt(&mut self) -> Result<Statement, String> {
+    fn parse_continue_stmt(&mut self) -> Result<StatementKind, String> {
         self.advance();
-        self.consume(TokenKind::Semicolon, "Expected ';' after continue")?;
-        Ok(Statement::ContinueStmt)
+        self.end_statement("Expected ';' after continue")?;
+        Ok(StatementKind::ContinueStmt)
     }
 
-    fn parse_struct_decl(&mut self) -> Result<Statement, String> {
+    fn parse_struct_decl(&mut self) -> Result<StatementKind, String> {
         self.advance();
         let name = self.expect_identifier()?;
         self.consume(TokenKind::LeftBrace, "Expected '{' after struct name")?;
         
         let mut fields = Vec::new();
         while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let start = self.peek().span;
+            let is_readonly = self.match_token(&TokenKind::Readonly);
             let field_name = self.expect_identifier()?;
+            let is_optional = self.match_token(&TokenKind::Question);
             self.consume(TokenKind::Colon, "Expected ':' after field name")?;
-            let field_type = self.parse_type()?;
+            let (field_type, type_span) = self.parse_annotation()?;
+            let default = if self.match_token(&TokenKind::Equal) {
+                Some(self.parse_expression()?)
+            } else {
+                None
+            };
             
             fields.push(StructField {
                 name: field_name,
                 field_type,
+                type_span,
+                is_optional,
+                is_readonly,
+                default,
+                span: start.to(self.previous_span()),
             });
             
             if !self.match_token(&TokenKind::Comma) && !self.check(&TokenKind::RightBrace) {
@@ -304,16 +700,21 @@ impl Parser {
         
         self.consume(TokenKind::RightBrace, "Expected '}' after struct fields")?;
         
-        Ok(Statement::StructDecl(StructDecl { name, fields }))
+        Ok(StatementKind::StructDecl(StructDecl {
+            name,
+            fields,
+            attributes: Vec::new(),
+        }))
     }
 
-    fn parse_enum_decl(&mut self) -> Result<Statement, String> {
+    fn parse_enum_decl(&mut self) -> Result<StatementKind, String> {
         self.advance();
         let name = self.expect_identifier()?;
         self.consume(TokenKind::LeftBrace, "Expected '{' after enum name")?;
         
         let mut variants = Vec::new();
         while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let start = self.peek().span;
             let variant_name = self.expect_identifier()?;
             
             let fields = if self.match_token(&TokenKind::LeftParen) {
@@ -331,10 +732,37 @@ impl Parser {
             } else {
                 None
             };
+            let named_fields = if fields.is_none() && self.match_token(&TokenKind::LeftBrace) {
+                let mut named_fields = Vec::new();
+                while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+                    let start = self.peek().span;
+                    let field_name = self.expect_identifier()?;
+                    self.consume(TokenKind::Colon, "Expected ':' after field name")?;
+                    let (field_type, type_span) = self.parse_annotation()?;
+                    named_fields.push(StructField {
+                        name: field_name,
+                        field_type,
+                        type_span,
+                        is_optional: false,
+                        is_readonly: false,
+                        default: None,
+                        span: start.to(self.previous_span()),
+                    });
+                    if !self.match_token(&TokenKind::Comma) && !self.check(&TokenKind::RightBrace) {
+                        return Err("Expected ',' or '}' after enum variant field".to_string());
+                    }
+                }
+                self.consume(TokenKind::RightBrace, "Expected '}' after enum variant fields")?;
+                Some(named_fields)
+            } else {
+                None
+            };
             
             variants.push(EnumVariant {
                 name: variant_name,
                 fields,
+                named_fields,
+                span: start.to(self.previous_span()),
             });
             
             if !self.match_token(&TokenKind::Comma) && !self.check(&TokenKind::RightBrace) {
@@ -344,10 +772,14 @@ impl Parser {
         
         self.consume(TokenKind::RightBrace, "Expected '}' after enum variants")?;
         
-        Ok(Statement::EnumDecl(EnumDecl { name, variants }))
+        Ok(StatementKind::EnumDecl(EnumDecl {
+            name,
+            variants,
+            attributes: Vec::new(),
+        }))
     }
 
-    fn parse_try_catch(&mut self) -> Result<Statement, String> {
+    fn parse_try_catch(&mut self) -> Result<StatementKind, String> {
         self.advance();
         self.consume(TokenKind::LeftBrace, "Expected '{' after 'try'")?;
         
@@ -368,19 +800,19 @@ impl Parser {
         let catch_body = self.parse_block()?;
         self.consume(TokenKind::RightBrace, "Expected '}' after catch block")?;
         
-        Ok(Statement::TryCatch(TryCatchStmt {
+        Ok(StatementKind::TryCatch(TryCatchStmt {
             try_body,
             catch_param,
             catch_body,
         }))
     }
 
-    fn parse_throw_stmt(&mut self) -> Result<Statement, String> {
+    fn parse_throw_stmt(&mut self) -> Result<StatementKind, String> {
         self.advance();
         let expression = self.parse_expression()?;
-        self.consume(TokenKind::Semicolon, "Expected ';' after throw statement")?;
+        self.end_statement("Expected ';' after throw statement")?;
         
-        Ok(Statement::ThrowStmt(ThrowStmt { expression }))
+        Ok(StatementKind::ThrowStmt(ThrowStmt { expression }))
     }
 
     fn parse_block(&mut self) -> Result<Vec<Statement>, String> {
@@ -394,7 +826,31 @@ impl Parser {
     }
 
     fn parse_expression(&mut self) -> Result<Expression, String> {
-        self.parse_logical_or()
+        self.parse_assignment()
+    }
+
+    fn parse_assignment(&mut self) -> Result<Expression, String> {
+        let expr = self.parse_logical_or()?;
+
+        if self.match_token(&TokenKind::Equal) {
+            if !matches!(
+                expr.kind,
+                ExpressionKind::Identifier(_) | ExpressionKind::MemberAccess { .. } | ExpressionKind::IndexAccess { .. }
+            ) {
+                return Err("Invalid assignment target".to_string());
+            }
+            let value = self.parse_assignment()?;
+            let span = expr.span.to(value.span);
+            return Ok(Expression::new(
+                ExpressionKind::Assignment {
+                    target: Box::new(expr),
+                    value: Box::new(value),
+                },
+                span,
+            ));
+        }
+
+        Ok(expr)
     }
 
     fn parse_logical_or(&mut self) -> Result<Expression, String> {
@@ -402,11 +858,7 @@ impl Parser {
 
         while let Some(op) = self.match_binary_op(&[TokenKind::PipePipe]) {
             let right = self.parse_logical_and()?;
-            expr = Expression::BinaryOp(
-                Box::new(expr),
-                op,
-                Box::new(right),
-            );
+            expr = self.binary(expr, op, right);
         }
 
         Ok(expr)
@@ -417,11 +869,7 @@ impl Parser {
 
         while let Some(op) = self.match_binary_op(&[TokenKind::AmpersandAmpersand]) {
             let right = self.parse_comparison()?;
-            expr = Expression::BinaryOp(
-                Box::new(expr),
-                op,
-                Box::new(right),
-            );
+            expr = self.binary(expr, op, right);
         }
 
         Ok(expr)
@@ -439,11 +887,7 @@ impl Parser {
             TokenKind::LessEqual,
         ]) {
             let right = self.parse_additive()?;
-            expr = Expression::BinaryOp(
-                Box::new(expr),
-                op,
-                Box::new(right),
-            );
+            expr = self.binary(expr, op, right);
         }
 
         Ok(expr)
@@ -454,11 +898,7 @@ impl Parser {
 
         while let Some(op) = self.match_binary_op(&[TokenKind::Plus, TokenKind::Minus]) {
             let right = self.parse_multiplicative()?;
-            expr = Expression::BinaryOp(
-                Box::new(expr),
-                op,
-                Box::new(right),
-            );
+            expr = self.binary(expr, op, right);
         }
 
         Ok(expr)
@@ -473,38 +913,67 @@ impl Parser {
             TokenKind::Percent,
         ]) {
             let right = self.parse_primary()?;
-            expr = Expression::BinaryOp(
-                Box::new(expr),
-                op,
-                Box::new(right),
-            );
+            expr = self.binary(expr, op, right);
         }
 
         Ok(expr)
     }
 
+    fn binary(&self, left: Expression, op: BinaryOp, right: Expression) -> Expression {
+        let span = left.span.to(right.span);
+        Expression::new(ExpressionKind::BinaryOp(Box::new(left), op, Box::new(right)), span)
+    }
+
     fn parse_primary(&mut self) -> Result<Expression, String> {
         let token = self.peek();
+        let start = token.span;
 
-        let mut expr = match &token.kind {
+        let kind = match &token.kind {
             TokenKind::NumberLiteral(n) => {
                 self.advance();
-                Expression::NumberLiteral(*n)
+                ExpressionKind::NumberLiteral(*n)
             }
             TokenKind::StringLiteral(s) => {
                 self.advance();
-                Expression::StringLiteral(s.clone())
+                ExpressionKind::StringLiteral(s.clone())
             }
             TokenKind::BooleanLiteral(b) => {
                 let val = *b;
                 self.advance();
-                Expression::BooleanLiteral(val)
+                ExpressionKind::BooleanLiteral(val)
             }
+            TokenKind::Identifier(_) | TokenKind::LeftParen if self.is_closure_ahead() => self.parse_closure()?,
             TokenKind::Identifier(name) => {
                 let name = name.clone();
                 self.advance();
 
-                if self.match_token(&TokenKind::LeftParen) {
+                if name == "channel" && (self.check(&TokenKind::Less) || self.check(&TokenKind::LeftParen)) {
+                    let element_type = if self.match_token(&TokenKind::Less) {
+                        let element_type = self.parse_type()?;
+                        self.consume(TokenKind::Greater, "Expected '>' after channel type")?;
+                        element_type
+                    } else {
+                        Type::Inferred
+                    };
+                    self.consume(TokenKind::LeftParen, "Expected '(' after channel")?;
+                    self.consume(TokenKind::RightParen, "Expected ')' after channel(")?;
+                    ExpressionKind::Channel(element_type)
+                } else if name == "shared" && (self.check(&TokenKind::Less) || self.check(&TokenKind::LeftParen)) {
+                    let value_type = if self.match_token(&TokenKind::Less) {
+                        let value_type = self.parse_type()?;
+                        self.consume(TokenKind::Greater, "Expected '>' after shared type")?;
+                        value_type
+                    } else {
+                        Type::Inferred
+                    };
+                    self.consume(TokenKind::LeftParen, "Expected '(' after shared")?;
+                    let value = self.parse_expression()?;
+                    self.consume(TokenKind::RightParen, "Expected ')' after shared value")?;
+                    ExpressionKind::Shared {
+                        value_type,
+                        value: Box::new(value),
+                    }
+                } else if self.match_token(&TokenKind::LeftParen) {
                     let mut args = Vec::new();
                     if !self.check(&TokenKind::RightParen) {
                         loop {
@@ -515,26 +984,12 @@ impl Parser {
                         }
                     }
                     self.consume(TokenKind::RightParen, "Expected ')' after function arguments")?;
-                    Expression::FunctionCall(name, args)
+                    ExpressionKind::FunctionCall(name, args)
                 } else if self.check(&TokenKind::LeftBrace) && self.is_struct_literal_ahead() {
-                    self.advance();
-                    let mut fields = Vec::new();
-                    
-                    while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
-                        let field_name = self.expect_identifier()?;
-                        self.consume(TokenKind::Colon, "Expected ':' after field name")?;
-                        let field_value = self.parse_expression()?;
-                        fields.push((field_name, field_value));
-                        
-                        if !self.match_token(&TokenKind::Comma) {
-                            break;
-                        }
-                    }
-                    
-                    self.consume(TokenKind::RightBrace, "Expected '}' after struct literal")?;
-                    Expression::StructLiteral { name, fields }
+                    let fields = self.parse_struct_literal_fields()?;
+                    ExpressionKind::StructLiteral { name, fields }
                 } else {
-                    Expression::Identifier(name)
+                    ExpressionKind::Identifier(name)
                 }
             }
             TokenKind::LeftBracket => {
@@ -549,34 +1004,64 @@ impl Parser {
                     }
                 }
                 self.consume(TokenKind::RightBracket, "Expected ']' after array elements")?;
-                Expression::ArrayLiteral(elements)
+                ExpressionKind::ArrayLiteral(elements)
+            }
+            TokenKind::Minus => {
+                self.advance();
+                let operand = self.parse_primary()?;
+                match operand.kind {
+                    ExpressionKind::NumberLiteral(n) => ExpressionKind::NumberLiteral(-n),
+                    _ => {
+                        let zero = Expression::new(ExpressionKind::NumberLiteral(0), start);
+                        ExpressionKind::BinaryOp(Box::new(zero), BinaryOp::Subtract, Box::new(operand))
+                    }
+                }
             }
             TokenKind::Bang => {
                 self.advance();
                 let expr = self.parse_primary()?;
-                Expression::BinaryOp(Box::new(Expression::BooleanLiteral(false)), BinaryOp::And, Box::new(expr))
+                let falsy = Expression::new(ExpressionKind::BooleanLiteral(false), start);
+                ExpressionKind::BinaryOp(Box::new(expr), BinaryOp::Equal, Box::new(falsy))
             }
             TokenKind::LeftParen => {
                 self.advance();
                 let expr = self.parse_expression()?;
                 self.consume(TokenKind::RightParen, "Expected ')' after expression")?;
-                expr
+                expr.kind
             }
             _ => return Err(format!(
                 "Unexpected token in expression: {:?}",
                 token.kind
             )),
         };
+        let mut expr = Expression::new(kind, start.to(self.previous_span()));
 
         while self.check(&TokenKind::LeftBracket) || self.check(&TokenKind::Dot) {
-            if self.match_token(&TokenKind::LeftBracket) {
-                let index = self.parse_expression()?;
-                self.consume(TokenKind::RightBracket, "Expected ']' after index")?;
-                expr = Expression::IndexAccess {
-                    object: Box::new(expr),
-                    index: Box::new(index),
-                };
-            } else if self.match_token(&TokenKind::Dot) {
+            let kind = if self.match_token(&TokenKind::LeftBracket) {
+                let start = if self.check(&TokenKind::DotDot) { None } else { Some(self.parse_expression()?) };
+                match start {
+                    Some(index) if !self.match_token(&TokenKind::DotDot) => {
+                        self.consume(TokenKind::RightBracket, "Expected ']' after index")?;
+                        ExpressionKind::IndexAccess {
+                            object: Box::new(expr),
+                            index: Box::new(index),
+                        }
+                    }
+                    start => {
+                        if start.is_none() {
+                            self.advance();
+                        }
+                        let end = if self.check(&TokenKind::RightBracket) { None } else { Some(self.parse_expression()?) };
+                        self.consume(TokenKind::RightBracket, "Expected ']' after slice range")?;
+                        ExpressionKind::SliceAccess {
+                            object: Box::new(expr),
+                            start: start.map(Box::new),
+                            end: end.map(Box::new),
+                        }
+                    }
+                }
+            } else {
+                self.consume(TokenKind::Dot, "Expected '.' before member name")?;
                 let member = self.expect_identifier()?;
                 
                 if self.match_token(&TokenKind::LeftParen) {
@@ -590,23 +1075,122 @@ impl Parser {
                         }
                     }
                     self.consume(TokenKind::RightParen, "Expected ')' after method arguments")?;
-                    expr = Expression::MethodCall {
+                    ExpressionKind::MethodCall {
                         object: Box::new(expr),
                         method: member,
                         arguments: args,
-                    };
+                    }
+                } else if let (ExpressionKind::Identifier(enum_name), true) =
+                    (&expr.kind, self.check(&TokenKind::LeftBrace) && self.is_struct_literal_ahead())
+                {
+                    let enum_name = enum_name.clone();
+                    let fields = self.parse_struct_literal_fields()?;
+                    ExpressionKind::VariantLiteral { enum_name, variant: member, fields }
                 } else {
-                    expr = Expression::MemberAccess {
+                    ExpressionKind::MemberAccess {
                         object: Box::new(expr),
                         member,
-                    };
+                    }
                 }
-            }
+            };
+            expr = Expression::new(kind, start.to(self.previous_span()));
         }
 
         Ok(expr)
     }
 
+    fn parse_struct_literal_fields(&mut self) -> Result<Vec<(String, Expression)>, String> {
+        self.consume(TokenKind::LeftBrace, "Expected '{' before struct literal fields")?;
+        let mut fields = Vec::new();
+        
+        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let field_name = self.expect_identifier()?;
+            self.consume(TokenKind::Colon, "Expected ':' after field name")?;
+            let field_value = self.parse_expression()?;
+            fields.push((field_name, field_value));
+            
+            if !self.match_token(&TokenKind::Comma) {
+                break;
+            }
+        }
+        
+        self.consume(TokenKind::RightBrace, "Expected '}' after struct literal")?;
+        Ok(fields)
+    }
+
+    fn is_closure_ahead(&self) -> bool {
+        let kind_at = |index: usize| self.tokens.get(index).map(|token| &token.kind);
+        if matches!(kind_at(self.current), Some(TokenKind::Identifier(_))) {
+            return matches!(kind_at(self.current + 1), Some(TokenKind::Arrow));
+        }
+        let mut depth = 0;
+        for (index, token) in self.tokens.iter().enumerate().skip(self.current) {
+            match token.kind {
+                TokenKind::LeftParen => depth += 1,
+                TokenKind::RightParen => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return matches!(kind_at(index + 1), Some(TokenKind::Arrow));
+                    }
+                }
+                TokenKind::Eof => break,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    fn parse_closure(&mut self) -> Result<ExpressionKind, String> {
+        let mut parameters = Vec::new();
+        if self.match_token(&TokenKind::LeftParen) {
+            if !self.check(&TokenKind::RightParen) {
+                loop {
+                    parameters.push(self.parse_closure_parameter()?);
+                    if !self.match_token(&TokenKind::Comma) {
+                        break;
+                    }
+                }
+            }
+            self.consume(TokenKind::RightParen, "Expected ')' after closure parameters")?;
+        } else {
+            parameters.push(self.parse_closure_parameter()?);
+        }
+        self.consume(TokenKind::Arrow, "Expected '=>' after closure parameters")?;
+
+        let body = if self.match_token(&TokenKind::LeftBrace) {
+            let body = self.parse_block()?;
+            self.consume(TokenKind::RightBrace, "Expected '}' after closure body")?;
+            body
+        } else {
+            let value = self.parse_expression()?;
+            let span = value.span;
+            vec![Statement::new(StatementKind::ReturnStmt(ReturnStmt { value: Some(value) }), span)]
+        };
+        Ok(ExpressionKind::Closure { parameters, body })
+    }
+
+    fn parse_closure_parameter(&mut self) -> Result<Parameter, String> {
+        let start = self.peek().span;
+        let name = self.expect_identifier()?;
+        let (param_type, type_span) = if self.match_token(&TokenKind::Colon) {
+            self.parse_annotation()?
+        } else {
+            (Type::Inferred, start)
+        };
+        Ok(Parameter {
+            name,
+            param_type,
+            type_span,
+            span: start.to(self.previous_span()),
+        })
+    }
+
+    fn parse_annotation(&mut self) -> Result<(Type, Span), String> {
+        let start = self.peek().span;
+        let ty = self.parse_type()?;
+        Ok((ty, start.to(self.previous_span())))
+    }
+
     fn parse_type(&mut self) -> Result<Type, String> {
         let base_type = match &self.peek().kind {
             TokenKind::NumberType => {
@@ -629,6 +1213,25 @@ impl Parser {
                 self.advance();
                 Type::Any
             }
+            TokenKind::Identifier(name) if name == "shared" => {
+                self.advance();
+                self.consume(TokenKind::Less, "Expected '<' after shared")?;
+                let inner = self.parse_type()?;
+                self.consume(TokenKind::Greater, "Expected '>' after shared type")?;
+                Type::Shared(Box::new(inner))
+            }
+            TokenKind::LeftBracket => {
+                self.advance();
+                let element_type = self.parse_type()?;
+                self.consume(TokenKind::Semicolon, "Expected ';' between array element type and size")?;
+                let size = match &self.peek().kind {
+                    TokenKind::NumberLiteral(n) => *n as usize,
+                    _ => return Err("Expected number literal for array size".to_string()),
+                };
+                self.advance();
+                self.consume(TokenKind::RightBracket, "Expected ']' after array size")?;
+                Type::Array { element_type: Box::new(element_type), size: Some(size) }
+            }
             TokenKind::Identifier(name) => {
                 let name = name.clone();
                 self.advance();
@@ -637,39 +1240,29 @@ impl Parser {
             _ => return Err(format!("Expected type, found: {:?}", self.peek().kind)),
         };
 
-        if self.match_token(&TokenKind::LeftBracket) {
-            if self.check(&TokenKind::RightBracket) {
-                self.advance();
-                return Ok(Type::Array {
-                    element_type: Box::new(base_type),
-                    size: None,
-                });
-            }
-
-            let element_type = self.parse_type()?;
-            
-            let size = if self.match_token(&TokenKind::Comma) {
-                match &self.peek().kind {
-                    TokenKind::NumberLiteral(n) => {
-                        let n = *n as usize;
-                        self.advance();
-                        Some(n)
-                    }
-                    _ => return Err("Expected number literal for array size".to_string()),
+        let mut ty = base_type;
+        while self.match_token(&TokenKind::LeftBracket) {
+            ty = match &self.peek().kind {
+                TokenKind::RightBracket => Type::Array { element_type: Box::new(ty), size: None },
+                TokenKind::NumberLiteral(n) => {
+                    let size = *n as usize;
+                    self.advance();
+                    Type::Array { element_type: Box::new(ty), size: Some(size) }
+                }
+                _ => {
+                    let element_type = self.parse_type()?;
+                    self.consume(TokenKind::Comma, "Expected ',' or ']' in array type")?;
+                    let size = match &self.peek().kind {
+                        TokenKind::NumberLiteral(n) => *n as usize,
+                        _ => return Err("Expected number literal for array size".to_string()),
+                    };
+                    self.advance();
+                    Type::Array { element_type: Box::new(element_type), size: Some(size) }
                 }
-            } else {
-                None
             };
-
             self.consume(TokenKind::RightBracket, "Expected ']' after array type")?;
-
-            Ok(Type::Array {
-                element_type: Box::new(element_type),
-                size,
-            })
-        } else {
-            Ok(base_type)
         }
+        Ok(ty)
     }
 
     fn match_binary_op(&mut self, kinds: &[TokenKind]) -> Option<BinaryOp> {
@@ -705,7 +1298,13 @@ impl Parser {
                 self.advance();
                 Ok(result)
             }
-            _ => Err(format!("Expected identifier, found: {:?}", self.peek().kind)),
+            kind => match kind.keyword() {
+                Some(keyword) => Err(format!(
+                    "'{}' is a reserved keyword and can't be used as a name (write r#{} to use it as one)",
+                    keyword, keyword
+                )),
+                None => Err(format!("Expected identifier, found: {:?}", kind)),
+            },
         }
     }
 
@@ -730,14 +1329,24 @@ impl Parser {
         if self.check(&kind) {
             Ok(self.advance())
         } else {
-            Err(format!(
-                "{} (found: {:?} at line:column {}:{})",
-                message,
-                self.peek().kind,
-                self.peek().line,
-                self.peek().column
-            ))
+            Err(format!("{} (found: {:?})", message, self.peek().kind))
+        }
+    }
+
+    /// Consumes the `;` that ends a statement. Tokens left on the statement's
+    /// line are reported as trailing tokens rather than as a missing `;`,
+    /// since inserting one would turn them into a statement of their own.
+    fn end_statement(&mut self, message: &str) -> Result<(), String> {
+        if self.match_token(&TokenKind::Semicolon) {
+            return Ok(());
         }
+        let next = self.peek();
+        let same_line = self.current > 0 && next.line == self.tokens[self.current - 1].line;
+        if same_line && !matches!(next.kind, TokenKind::RightBrace | TokenKind::Eof) {
+            let after = message.trim_start_matches("Expected ';' ");
+            return Err(format!("Unexpected trailing tokens {} (found: {:?})", after, next.kind));
+        }
+        Err(format!("{} (found: {:?})", message, next.kind))
     }
 
     fn advance(&mut self) -> &Token {
@@ -747,11 +1356,12 @@ impl Parser {
         &self.tokens[self.current - 1]
     }
 
-    fn peek(&self) -> Token {
-        self.tokens[self.current].clone()
+    fn previous_span(&self) -> Span {
+        self.tokens[self.current.saturating_sub(1)].span
     }
 
-    fn skip_newlines(&mut self) {
+    fn peek(&self) -> Token {
+        self.tokens[self.current].clone()
     }
 
     fn is_struct_literal_ahead(&self) -> bool {
@@ -760,7 +1370,17 @@ impl Parser {
         }
         
         let next_token = &self.tokens[self.current + 1].kind;
-        matches!(next_token, TokenKind::Identifier(_) | TokenKind::RightBrace)
+        match next_token {
+            TokenKind::Identifier(_) => matches!(
+                self.tokens.get(self.current + 2).map(|t| &t.kind),
+                Some(TokenKind::Colon)
+            ),
+            TokenKind::RightBrace => matches!(
+                &self.tokens[self.current - 1].kind,
+                TokenKind::Identifier(name) if name.starts_with(|c: char| c.is_uppercase())
+            ),
+            _ => false,
+        }
     }
 
     fn is_at_end(&self) -> bool {
@@ -1,4 +1,8 @@
-#[derive(Debug, Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+use crate::span::Span;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum TokenKind {
     Let,
     Function,
@@ -27,6 +31,7 @@ pub enum TokenKind {
     Import,
     From,
     Export,
+    Readonly,
 
     NumberType,
     StringType,
@@ -47,8 +52,10 @@ pub enum TokenKind {
     Semicolon,
     Comma,
     Dot,
+    DotDot,
     Arrow,
     Question,
+    At,
     Pipe,
 
     LeftParen,
@@ -74,15 +81,110 @@ pub enum TokenKind {
     Eof,
 }
 
-#[derive(Debug, Clone)]
+impl TokenKind {
+    /// The source text of a keyword or punctuation token; empty for tokens
+    /// that carry a value
+    pub fn text(&self) -> &'static str {
+        match self {
+            TokenKind::Let => "let",
+            TokenKind::Function => "function",
+            TokenKind::Return => "return",
+            TokenKind::Void => "void",
+            TokenKind::Const => "const",
+            TokenKind::Print => "print",
+            TokenKind::If => "if",
+            TokenKind::Else => "else",
+            TokenKind::For => "for",
+            TokenKind::While => "while",
+            TokenKind::In => "in",
+            TokenKind::Any => "any",
+            TokenKind::Break => "break",
+            TokenKind::Continue => "continue",
+            TokenKind::Struct => "struct",
+            TokenKind::Enum => "enum",
+            TokenKind::Type => "type",
+            TokenKind::Interface => "interface",
+            TokenKind::Try => "try",
+            TokenKind::Catch => "catch",
+            TokenKind::Throw => "throw",
+            TokenKind::New => "new",
+            TokenKind::This => "this",
+            TokenKind::As => "as",
+            TokenKind::Import => "import",
+            TokenKind::From => "from",
+            TokenKind::Export => "export",
+            TokenKind::Readonly => "readonly",
+            TokenKind::NumberType => "number",
+            TokenKind::StringType => "string",
+            TokenKind::BooleanType => "boolean",
+            TokenKind::Plus => "+",
+            TokenKind::Minus => "-",
+            TokenKind::Star => "*",
+            TokenKind::Slash => "/",
+            TokenKind::Percent => "%",
+            TokenKind::Equal => "=",
+            TokenKind::Colon => ":",
+            TokenKind::Semicolon => ";",
+            TokenKind::Comma => ",",
+            TokenKind::Dot => ".",
+            TokenKind::DotDot => "..",
+            TokenKind::Arrow => "=>",
+            TokenKind::Question => "?",
+            TokenKind::At => "@",
+            TokenKind::Pipe => "|",
+            TokenKind::LeftParen => "(",
+            TokenKind::RightParen => ")",
+            TokenKind::LeftBrace => "{",
+            TokenKind::RightBrace => "}",
+            TokenKind::LeftBracket => "[",
+            TokenKind::RightBracket => "]",
+            TokenKind::EqualEqual => "==",
+            TokenKind::BangEqual => "!=",
+            TokenKind::Greater => ">",
+            TokenKind::GreaterEqual => ">=",
+            TokenKind::Less => "<",
+            TokenKind::LessEqual => "<=",
+            TokenKind::AmpersandAmpersand => "&&",
+            TokenKind::PipePipe => "||",
+            TokenKind::Bang => "!",
+            TokenKind::Ampersand => "&",
+            TokenKind::Mut => "mut",
+            TokenKind::Identifier(_)
+            | TokenKind::NumberLiteral(_)
+            | TokenKind::StringLiteral(_)
+            | TokenKind::BooleanLiteral(_)
+            | TokenKind::Eof => "",
+        }
+    }
+
+    /// The reserved word this token is, if it is one
+    pub fn keyword(&self) -> Option<&'static str> {
+        match self {
+            TokenKind::BooleanLiteral(true) => Some("true"),
+            TokenKind::BooleanLiteral(false) => Some("false"),
+            kind => Some(kind.text()).filter(|text| text.starts_with(|c: char| c.is_alphabetic())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Token {
     pub kind: TokenKind,
     pub line: usize,
     pub column: usize,
+    #[serde(default)]
+    pub span: Span,
 }
 
 impl Token {
     pub fn new(kind: TokenKind, line: usize, column: usize) -> Self {
-        Self { kind, line, column }
+        Self { kind, line, column, span: Span::default() }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Comment {
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+}
@@ -0,0 +1,189 @@
+use std::collections::HashSet;
+
+use crate::ast::{Expression, ExpressionKind, ImportItem, ImportStmt, Pattern, Program, Statement, StatementKind, Type};
+use crate::diagnostic::{Diagnostic, Fix, Severity};
+use crate::lexer::Lexer;
+use crate::span::Span;
+use crate::token::TokenKind;
+use crate::visit::{walk_expression, walk_statement, walk_type, Visitor};
+
+pub const UNUSED_IMPORTS: &str = "unused_imports";
+pub const UNUSED_EXPORTS: &str = "unused_exports";
+
+#[derive(Debug)]
+pub struct UnusedImport<'a> {
+    pub statement: &'a Statement,
+    pub import: &'a ImportStmt,
+    pub item: Option<&'a ImportItem>,
+    pub name: &'a str,
+}
+
+pub fn unused_imports(program: &Program) -> Vec<UnusedImport<'_>> {
+    let mut references = References::default();
+    for stmt in &program.statements {
+        if !matches!(stmt.kind, StatementKind::ImportStmt(_)) {
+            references.visit_statement(stmt);
+        }
+    }
+
+    let mut unused = Vec::new();
+    for statement in &program.statements {
+        let StatementKind::ImportStmt(import) = &statement.kind else {
+            continue;
+        };
+        if let Some(namespace) = &import.namespace {
+            if !references.names.contains(namespace.as_str()) {
+                unused.push(UnusedImport { statement, import, item: None, name: namespace });
+            }
+            continue;
+        }
+        for item in &import.imports {
+            let name = item.alias.as_deref().unwrap_or(&item.name);
+            if !references.names.contains(name) {
+                unused.push(UnusedImport { statement, import, item: Some(item), name });
+            }
+        }
+    }
+    unused
+}
+
+pub fn unused_import_warnings(program: &Program, source: &str) -> Vec<Diagnostic> {
+    let unused = unused_imports(program);
+    unused
+        .iter()
+        .map(|import| {
+            let all_unused = unused.iter().filter(|other| std::ptr::eq(other.statement, import.statement)).count()
+                >= import.import.imports.len();
+            let start = import.item.map_or(import.statement.span.start, |item| item.span.start);
+            let fix = match import.item {
+                Some(item) if !all_unused => remove_import_item(source, item.span),
+                _ => Some(remove_statement(source, import.statement.span)),
+            };
+            let (line, column) = line_column(source, start);
+            let warning = Diagnostic::new(format!("unused import: `{}`", import.name))
+                .with_code(Some(UNUSED_IMPORTS.to_string()))
+                .with_severity(Severity::Warning)
+                .at(line, column);
+            match fix {
+                Some(fix) => warning.with_fix(fix),
+                None => warning,
+            }
+        })
+        .collect()
+}
+
+pub fn remove_import(source: &str, statement: &Statement, start: usize) -> Option<Fix> {
+    let StatementKind::ImportStmt(import) = &statement.kind else {
+        return None;
+    };
+    if import.imports.len() <= 1 {
+        return Some(remove_statement(source, statement.span));
+    }
+    let item = import.imports.iter().find(|item| item.span.start <= start && start < item.span.end)?;
+    remove_import_item(source, item.span)
+}
+
+pub fn unused_export_warning(source: &str, name: &str, span: Span, removable: bool) -> Diagnostic {
+    let keyword = Lexer::new(span.text(source)).tokenize().ok().and_then(|tokens| {
+        tokens.into_iter().find(|token| match &token.kind {
+            TokenKind::Export => true,
+            TokenKind::Identifier(word) => word == "pub",
+            _ => false,
+        })
+    });
+    let start = keyword.as_ref().map_or(span.start, |token| span.start + token.span.start);
+    let (line, column) = line_column(source, start);
+    let warning = Diagnostic::new(format!("`{}` is exported but never imported by another module", name))
+        .with_code(Some(UNUSED_EXPORTS.to_string()))
+        .with_severity(Severity::Warning)
+        .at(line, column);
+    match keyword {
+        Some(token) if removable => {
+            let end = span.start + token.span.end;
+            let rest = &source[end..];
+            let spaces = rest.len() - rest.trim_start().len();
+            let word = &source[start..end];
+            warning.with_fix(Fix::replace(format!("make it private by removing `{}`", word), start, end + spaces, ""))
+        }
+        _ => warning,
+    }
+}
+
+fn remove_statement(source: &str, span: Span) -> Fix {
+    let line_start = source[..span.start].rfind('\n').map_or(0, |index| index + 1);
+    let rest = &source[span.end..];
+    let line_end = match rest.find('\n') {
+        Some(index) if rest[..index].trim().is_empty() => span.end + index + 1,
+        _ => span.end,
+    };
+    let whole_line = source[line_start..span.start].trim().is_empty();
+    let start = if whole_line { line_start } else { span.start };
+    Fix::replace("remove the unused import", start, line_end, "")
+}
+
+fn remove_import_item(source: &str, span: Span) -> Option<Fix> {
+    let message = "remove the unused import";
+    let (start, end) = (span.start, span.end);
+    let after = &source[end..];
+    let spaces = after.len() - after.trim_start_matches([' ', '\t']).len();
+    if after[spaces..].starts_with(',') {
+        let rest = &after[spaces + 1..];
+        let spaces_after = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+        return Some(Fix::replace(message, start, end + spaces + 1 + spaces_after, ""));
+    }
+    let before = source[..start].trim_end();
+    before.ends_with(',').then(|| Fix::replace(message, before.len() - 1, end, ""))
+}
+
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+#[derive(Default)]
+struct References<'ast> {
+    names: HashSet<&'ast str>,
+}
+
+impl<'ast> References<'ast> {
+    fn add(&mut self, name: &'ast str) {
+        let first = name.split(['.', ':']).next().unwrap_or(name);
+        self.names.insert(first);
+    }
+}
+
+impl<'ast> Visitor<'ast> for References<'ast> {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        match &stmt.kind {
+            StatementKind::LockStmt(lock_stmt) => self.add(&lock_stmt.name),
+            StatementKind::Match(match_stmt) => {
+                for arm in &match_stmt.arms {
+                    if let Pattern::Variant { enum_name, .. } = &arm.pattern {
+                        self.add(enum_name);
+                    }
+                }
+            }
+            _ => {}
+        }
+        walk_statement(self, stmt);
+    }
+
+    fn visit_expression(&mut self, expr: &'ast Expression) {
+        match &expr.kind {
+            ExpressionKind::Identifier(name)
+            | ExpressionKind::FunctionCall(name, _)
+            | ExpressionKind::StructLiteral { name, .. }
+            | ExpressionKind::VariantLiteral { enum_name: name, .. } => self.add(name),
+            _ => {}
+        }
+        walk_expression(self, expr);
+    }
+
+    fn visit_type(&mut self, ty: &'ast Type) {
+        if let Type::Custom(name) = ty {
+            self.add(name);
+        }
+        walk_type(self, ty);
+    }
+}
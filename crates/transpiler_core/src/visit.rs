@@ -0,0 +1,347 @@
+use crate::ast::{Expression, ExpressionKind, Program, Statement, StatementKind, Type};
+
+pub trait Visitor<'ast> {
+    fn visit_program(&mut self, program: &'ast Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        walk_statement(self, stmt);
+    }
+
+    fn visit_expression(&mut self, expr: &'ast Expression) {
+        walk_expression(self, expr);
+    }
+
+    fn visit_type(&mut self, ty: &'ast Type) {
+        walk_type(self, ty);
+    }
+}
+
+pub fn walk_program<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, program: &'ast Program) {
+    walk_block(visitor, &program.statements);
+}
+
+pub fn walk_block<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, statements: &'ast [Statement]) {
+    for stmt in statements {
+        visitor.visit_statement(stmt);
+    }
+}
+
+pub fn walk_statement<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmt: &'ast Statement) {
+    match &stmt.kind {
+        StatementKind::ImportStmt(_) | StatementKind::BreakStmt | StatementKind::ContinueStmt => {}
+        StatementKind::ExportStmt(inner)
+        | StatementKind::ExportDefault(inner)
+        | StatementKind::VisibilityStmt(_, inner) => visitor.visit_statement(inner),
+        StatementKind::VariableDecl(var_decl) => {
+            if let Some(var_type) = &var_decl.var_type {
+                visitor.visit_type(var_type);
+            }
+            visitor.visit_expression(&var_decl.value);
+        }
+        StatementKind::DestructureDecl(destructure) => visitor.visit_expression(&destructure.value),
+        StatementKind::FunctionDecl(func_decl) => {
+            for param in &func_decl.parameters {
+                visitor.visit_type(&param.param_type);
+            }
+            visitor.visit_type(&func_decl.return_type);
+            walk_block(visitor, &func_decl.body);
+        }
+        StatementKind::StructDecl(struct_decl) => {
+            for field in &struct_decl.fields {
+                visitor.visit_type(&field.field_type);
+                if let Some(default) = &field.default {
+                    visitor.visit_expression(default);
+                }
+            }
+        }
+        StatementKind::EnumDecl(enum_decl) => {
+            for variant in &enum_decl.variants {
+                for field_type in variant.fields.iter().flatten() {
+                    visitor.visit_type(field_type);
+                }
+                for field in variant.named_fields.iter().flatten() {
+                    visitor.visit_type(&field.field_type);
+                }
+            }
+        }
+        StatementKind::PrintStmt(print_stmt) => {
+            for argument in &print_stmt.arguments {
+                visitor.visit_expression(argument);
+            }
+        }
+        StatementKind::ReturnStmt(ret_stmt) => {
+            if let Some(value) = &ret_stmt.value {
+                visitor.visit_expression(value);
+            }
+        }
+        StatementKind::ExpressionStmt(expr_stmt) => visitor.visit_expression(&expr_stmt.expression),
+        StatementKind::IfElse(if_else) => {
+            visitor.visit_expression(&if_else.condition);
+            walk_block(visitor, &if_else.then_body);
+            if let Some(else_body) = &if_else.else_body {
+                walk_block(visitor, else_body);
+            }
+        }
+        StatementKind::ForLoop(for_loop) => {
+            visitor.visit_expression(&for_loop.iterable);
+            walk_block(visitor, &for_loop.body);
+        }
+        StatementKind::WhileLoop(while_loop) => {
+            visitor.visit_expression(&while_loop.condition);
+            walk_block(visitor, &while_loop.body);
+        }
+        StatementKind::TryCatch(try_catch) => {
+            walk_block(visitor, &try_catch.try_body);
+            walk_block(visitor, &try_catch.catch_body);
+        }
+        StatementKind::ThrowStmt(throw_stmt) => visitor.visit_expression(&throw_stmt.expression),
+        StatementKind::Block(body) | StatementKind::DeferStmt(body) => walk_block(visitor, body),
+        StatementKind::CfgBlock(block) => walk_block(visitor, &block.body),
+        StatementKind::LockStmt(lock_stmt) => walk_block(visitor, &lock_stmt.body),
+        StatementKind::TestBlock(test_block) => walk_block(visitor, &test_block.body),
+        StatementKind::Match(match_stmt) => {
+            visitor.visit_expression(&match_stmt.subject);
+            for arm in &match_stmt.arms {
+                walk_block(visitor, &arm.body);
+            }
+        }
+    }
+}
+
+pub fn walk_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expr: &'ast Expression) {
+    match &expr.kind {
+        ExpressionKind::Identifier(_)
+        | ExpressionKind::NumberLiteral(_)
+        | ExpressionKind::StringLiteral(_)
+        | ExpressionKind::BooleanLiteral(_) => {}
+        ExpressionKind::ArrayLiteral(elements) | ExpressionKind::FunctionCall(_, elements) => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        }
+        ExpressionKind::StructLiteral { fields, .. } | ExpressionKind::VariantLiteral { fields, .. } => {
+            for (_, value) in fields {
+                visitor.visit_expression(value);
+            }
+        }
+        ExpressionKind::BinaryOp(left, _, right) => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+        ExpressionKind::MethodCall { object, arguments, .. } => {
+            visitor.visit_expression(object);
+            for arg in arguments {
+                visitor.visit_expression(arg);
+            }
+        }
+        ExpressionKind::IndexAccess { object, index } => {
+            visitor.visit_expression(object);
+            visitor.visit_expression(index);
+        }
+        ExpressionKind::SliceAccess { object, start, end } => {
+            visitor.visit_expression(object);
+            for bound in start.iter().chain(end) {
+                visitor.visit_expression(bound);
+            }
+        }
+        ExpressionKind::MemberAccess { object, .. } => visitor.visit_expression(object),
+        ExpressionKind::Assignment { target, value } => {
+            visitor.visit_expression(target);
+            visitor.visit_expression(value);
+        }
+        ExpressionKind::Channel(element_type) => visitor.visit_type(element_type),
+        ExpressionKind::Shared { value_type, value } => {
+            visitor.visit_type(value_type);
+            visitor.visit_expression(value);
+        }
+        ExpressionKind::Closure { parameters, body } => {
+            for param in parameters {
+                visitor.visit_type(&param.param_type);
+            }
+            walk_block(visitor, body);
+        }
+    }
+}
+
+pub fn walk_type<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, ty: &'ast Type) {
+    match ty {
+        Type::Array { element_type, .. } => visitor.visit_type(element_type),
+        Type::Shared(inner) => visitor.visit_type(inner),
+        _ => {}
+    }
+}
+
+pub trait VisitorMut {
+    fn visit_program_mut(&mut self, program: &mut Program) {
+        walk_program_mut(self, program);
+    }
+
+    fn visit_statement_mut(&mut self, stmt: &mut Statement) {
+        walk_statement_mut(self, stmt);
+    }
+
+    fn visit_expression_mut(&mut self, expr: &mut Expression) {
+        walk_expression_mut(self, expr);
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        walk_type_mut(self, ty);
+    }
+}
+
+pub fn walk_program_mut<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    walk_block_mut(visitor, &mut program.statements);
+}
+
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(visitor: &mut V, statements: &mut [Statement]) {
+    for stmt in statements {
+        visitor.visit_statement_mut(stmt);
+    }
+}
+
+pub fn walk_statement_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut Statement) {
+    match &mut stmt.kind {
+        StatementKind::ImportStmt(_) | StatementKind::BreakStmt | StatementKind::ContinueStmt => {}
+        StatementKind::ExportStmt(inner)
+        | StatementKind::ExportDefault(inner)
+        | StatementKind::VisibilityStmt(_, inner) => visitor.visit_statement_mut(inner),
+        StatementKind::VariableDecl(var_decl) => {
+            if let Some(var_type) = &mut var_decl.var_type {
+                visitor.visit_type_mut(var_type);
+            }
+            visitor.visit_expression_mut(&mut var_decl.value);
+        }
+        StatementKind::DestructureDecl(destructure) => visitor.visit_expression_mut(&mut destructure.value),
+        StatementKind::FunctionDecl(func_decl) => {
+            for param in &mut func_decl.parameters {
+                visitor.visit_type_mut(&mut param.param_type);
+            }
+            visitor.visit_type_mut(&mut func_decl.return_type);
+            walk_block_mut(visitor, &mut func_decl.body);
+        }
+        StatementKind::StructDecl(struct_decl) => {
+            for field in &mut struct_decl.fields {
+                visitor.visit_type_mut(&mut field.field_type);
+                if let Some(default) = &mut field.default {
+                    visitor.visit_expression_mut(default);
+                }
+            }
+        }
+        StatementKind::EnumDecl(enum_decl) => {
+            for variant in &mut enum_decl.variants {
+                for field_type in variant.fields.iter_mut().flatten() {
+                    visitor.visit_type_mut(field_type);
+                }
+                for field in variant.named_fields.iter_mut().flatten() {
+                    visitor.visit_type_mut(&mut field.field_type);
+                }
+            }
+        }
+        StatementKind::PrintStmt(print_stmt) => {
+            for argument in &mut print_stmt.arguments {
+                visitor.visit_expression_mut(argument);
+            }
+        }
+        StatementKind::ReturnStmt(ret_stmt) => {
+            if let Some(value) = &mut ret_stmt.value {
+                visitor.visit_expression_mut(value);
+            }
+        }
+        StatementKind::ExpressionStmt(expr_stmt) => visitor.visit_expression_mut(&mut expr_stmt.expression),
+        StatementKind::IfElse(if_else) => {
+            visitor.visit_expression_mut(&mut if_else.condition);
+            walk_block_mut(visitor, &mut if_else.then_body);
+            if let Some(else_body) = &mut if_else.else_body {
+                walk_block_mut(visitor, else_body);
+            }
+        }
+        StatementKind::ForLoop(for_loop) => {
+            visitor.visit_expression_mut(&mut for_loop.iterable);
+            walk_block_mut(visitor, &mut for_loop.body);
+        }
+        StatementKind::WhileLoop(while_loop) => {
+            visitor.visit_expression_mut(&mut while_loop.condition);
+            walk_block_mut(visitor, &mut while_loop.body);
+        }
+        StatementKind::TryCatch(try_catch) => {
+            walk_block_mut(visitor, &mut try_catch.try_body);
+            walk_block_mut(visitor, &mut try_catch.catch_body);
+        }
+        StatementKind::ThrowStmt(throw_stmt) => visitor.visit_expression_mut(&mut throw_stmt.expression),
+        StatementKind::Block(body) | StatementKind::DeferStmt(body) => walk_block_mut(visitor, body),
+        StatementKind::CfgBlock(block) => walk_block_mut(visitor, &mut block.body),
+        StatementKind::LockStmt(lock_stmt) => walk_block_mut(visitor, &mut lock_stmt.body),
+        StatementKind::TestBlock(test_block) => walk_block_mut(visitor, &mut test_block.body),
+        StatementKind::Match(match_stmt) => {
+            visitor.visit_expression_mut(&mut match_stmt.subject);
+            for arm in &mut match_stmt.arms {
+                walk_block_mut(visitor, &mut arm.body);
+            }
+        }
+    }
+}
+
+pub fn walk_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expression) {
+    match &mut expr.kind {
+        ExpressionKind::Identifier(_)
+        | ExpressionKind::NumberLiteral(_)
+        | ExpressionKind::StringLiteral(_)
+        | ExpressionKind::BooleanLiteral(_) => {}
+        ExpressionKind::ArrayLiteral(elements) | ExpressionKind::FunctionCall(_, elements) => {
+            for element in elements {
+                visitor.visit_expression_mut(element);
+            }
+        }
+        ExpressionKind::StructLiteral { fields, .. } | ExpressionKind::VariantLiteral { fields, .. } => {
+            for (_, value) in fields {
+                visitor.visit_expression_mut(value);
+            }
+        }
+        ExpressionKind::BinaryOp(left, _, right) => {
+            visitor.visit_expression_mut(left);
+            visitor.visit_expression_mut(right);
+        }
+        ExpressionKind::MethodCall { object, arguments, .. } => {
+            visitor.visit_expression_mut(object);
+            for arg in arguments {
+                visitor.visit_expression_mut(arg);
+            }
+        }
+        ExpressionKind::IndexAccess { object, index } => {
+            visitor.visit_expression_mut(object);
+            visitor.visit_expression_mut(index);
+        }
+        ExpressionKind::SliceAccess { object, start, end } => {
+            visitor.visit_expression_mut(object);
+            for bound in start.iter_mut().chain(end) {
+                visitor.visit_expression_mut(bound);
+            }
+        }
+        ExpressionKind::MemberAccess { object, .. } => visitor.visit_expression_mut(object),
+        ExpressionKind::Assignment { target, value } => {
+            visitor.visit_expression_mut(target);
+            visitor.visit_expression_mut(value);
+        }
+        ExpressionKind::Channel(element_type) => visitor.visit_type_mut(element_type),
+        ExpressionKind::Shared { value_type, value } => {
+            visitor.visit_type_mut(value_type);
+            visitor.visit_expression_mut(value);
+        }
+        ExpressionKind::Closure { parameters, body } => {
+            for param in parameters {
+                visitor.visit_type_mut(&mut param.param_type);
+            }
+            walk_block_mut(visitor, body);
+        }
+    }
+}
+
+pub fn walk_type_mut<V: VisitorMut + ?Sized>(visitor: &mut V, ty: &mut Type) {
+    match ty {
+        Type::Array { element_type, .. } => visitor.visit_type_mut(element_type),
+        Type::Shared(inner) => visitor.visit_type_mut(inner),
+        _ => {}
+    }
+}
@@ -0,0 +1,415 @@
+use jrust_transpiler_core::{Lexer, Parser, Checker};
+
+fn check(source: &str) -> Result<(), String> {
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().expect("Parser failed");
+    Checker::new().check(&program).map_err(|diagnostic| diagnostic.message)
+}
+
+#[test]
+fn check_rejects_assignment_to_readonly_field() {
+    let result = check("struct User { readonly id: number, name: string }
+let u = User { id: 1, name: \"Alice\" };
+u.id = 2;");
+    let err = result.expect_err("Readonly assignment should fail");
+    assert!(err.contains("readonly field 'id'"));
+}
+
+#[test]
+fn check_allows_assignment_to_regular_field() {
+    let result = check("struct User { readonly id: number, name: string }
+let u = User { id: 1, name: \"Alice\" };
+u.name = \"Bob\";");
+    assert!(result.is_ok());
+}
+
+#[test]
+fn check_rejects_readonly_assignment_through_parameter() {
+    let result = check("struct Point { readonly x: number, y: number }
+function shift(p: Point): void { p.x = 5; }");
+    assert!(result.is_err());
+}
+
+#[test]
+fn check_rejects_variable_used_after_its_block() {
+    let result = check("function f(): void { { let y = 1; } print(y); }");
+    let err = result.expect_err("Out-of-scope use should fail");
+    assert!(err.contains("'y' is not in scope"));
+}
+
+#[test]
+fn check_allows_block_variable_inside_block() {
+    let result = check("function f(): void { let x = 1; { let y = x + 1; print(y); } print(x); }");
+    assert!(result.is_ok());
+}
+
+#[test]
+fn check_rejects_nested_function_capturing_outer_local() {
+    let result = check("function outer(): void { let count = 1; function inner(): number { return count; } }");
+    let err = result.expect_err("Capturing an outer local should fail");
+    assert!(err.contains("cannot capture local variable 'count'"));
+}
+
+#[test]
+fn check_allows_nested_function_using_its_parameters() {
+    let result = check("function outer(n: number): number { function square(x: number): number { return x * x; } return square(n); }");
+    assert!(result.is_ok());
+}
+
+#[test]
+fn check_rejects_wrong_argument_count() {
+    let result = check("function add(a: number, b: number): number { return a + b; }
+let total = add(1);");
+    let err = result.expect_err("Arity mismatch should fail");
+    assert!(err.contains("'add' expects 2 argument(s) but got 1"));
+}
+
+#[test]
+fn check_rejects_mismatched_literal_argument() {
+    let result = check("function greet(name: string): void { print(name); }
+greet(42);");
+    let err = result.expect_err("Literal type mismatch should fail");
+    assert!(err.contains("Argument 1 of 'greet' expects string but got number"));
+}
+
+#[test]
+fn check_uses_declared_imported_functions() {
+    let mut lexer = Lexer::new("function area(w: number, h: number): number { return w * h; }");
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+    let jrust_transpiler_core::StatementKind::FunctionDecl(area) = program.statements[0].kind.clone() else {
+        panic!("Expected function declaration");
+    };
+
+    let mut lexer = Lexer::new("let a = rectArea(\"wide\", 2);");
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+    let mut checker = Checker::new();
+    checker.declare_function("rectArea", area);
+    let err = checker.check(&program).expect_err("Imported signature should be checked").message;
+    assert!(err.contains("Argument 1 of 'rectArea' expects number but got string"));
+}
+
+#[test]
+fn check_requires_type_for_exported_non_literal_let() {
+    let result = check("function start(): number { return 1; }
+export let current = start();");
+    let err = result.expect_err("Untyped exported let should fail");
+    assert!(err.contains("Exported variable 'current' needs a type annotation"));
+}
+
+#[test]
+fn check_suggests_similar_type() {
+    let err = check("let count: numbr = 5;").expect_err("Misspelled type should fail");
+    assert_eq!(err, "Cannot find type 'numbr'. Did you mean `number`?");
+
+    let err = check("struct Point { x: number, y: number }
+function origin(): Pointt { return Point { x: 0, y: 0 }; }")
+    .expect_err("Misspelled struct should fail");
+    assert!(err.contains("Did you mean `Point`?"));
+}
+
+#[test]
+fn check_suggests_similar_variable_and_function() {
+    let err = check("let counter = 0;
+print(countr);").expect_err("Misspelled variable should fail");
+    assert_eq!(err, "Cannot find variable 'countr' in this scope. Did you mean `counter`?");
+
+    let err = check("function greet(name: string): void { print(name); }
+gret(\"Ada\");").expect_err("Misspelled function should fail");
+    assert!(err.contains("Did you mean `greet`?"));
+}
+
+#[test]
+fn check_rejects_unknown_names_without_close_match() {
+    let result = check("import {HashMap} from \"std::collections\";
+let map: HashMap = HashMap.default();");
+    assert!(result.is_ok());
+
+    let err = check("print(zzqqyy);").expect_err("Unknown variable should fail");
+    assert_eq!(err, "Cannot find variable 'zzqqyy' in this scope");
+
+    let err = check("let y: Foobarbaz = 3;").expect_err("Unknown type should fail");
+    assert_eq!(err, "Cannot find type 'Foobarbaz'");
+
+    let err = check("let x = 1;
+quxquux(x);").expect_err("Unknown function should fail");
+    assert_eq!(err, "Cannot find function 'quxquux'");
+}
+
+#[test]
+fn check_test_blocks_and_assertions() {
+    let result = check("function add(a: number, b: number): number { return a + b; }
+test \"adds\" {
+    assertEq(add(1, 2), 3);
+    assert(add(1, 1) == 2, \"one plus one\");
+}");
+    assert!(result.is_ok());
+
+    let err = check("test \"empty\" { assertEq(1); }").expect_err("Wrong arity should fail");
+    assert_eq!(err, "Function 'assertEq' expects 2 or 3 argument(s) but got 1");
+
+    assert!(check("function fail(): void { panic(\"stop\"); }\npanic();").is_ok());
+    let err = check("panic(\"a\", \"b\");").expect_err("Wrong arity should fail");
+    assert_eq!(err, "Function 'panic' expects 0 or 1 argument(s) but got 2");
+}
+
+#[test]
+fn check_rejects_nested_test_block() {
+    let err = check("function main(): void {
+    test \"inner\" { assert(true); }
+}").expect_err("Nested test should fail");
+    assert!(err.contains("Test blocks must be declared at the top level"));
+}
+
+#[test]
+fn check_string_method_signatures() {
+    assert!(check("let s = \"abc\";\nlet t = s.padStart(5, \"0\");\nlet u = s.slice(1);").is_ok());
+
+    let err = check("let s: string = \"abc\";\nlet t = s.replace(\"a\");").expect_err("replace needs two arguments");
+    assert!(err.contains("Method 'replace' expects 2 argument(s) but got 1"));
+
+    let err = check("let s = \"abc\";\nlet t = s.repeat(\"3\");").expect_err("repeat takes a number");
+    assert!(err.contains("Argument 1 of 'repeat' expects number but got string"));
+}
+
+#[test]
+fn check_array_method_callbacks() {
+    assert!(check("let nums = [1, 2];\nlet total = nums.reduce((acc, n) => acc + n, 0);").is_ok());
+
+    let err = check("let nums = [1, 2];\nlet big = nums.filter((n, i) => n > i);").expect_err("filter passes one value");
+    assert!(err.contains("Callback of 'filter' takes at most 1 parameter(s) but got 2"));
+
+    let err = check("let nums: number[] = [1];\nnums.forEach(3);").expect_err("forEach needs a function");
+    assert!(err.contains("Argument 1 of 'forEach' expects a function"));
+}
+
+#[test]
+fn check_sort_callbacks() {
+    assert!(check("let nums = [2, 1];\nnums.sort();\nnums.sort((a, b) => a - b);").is_ok());
+
+    let err = check("let nums = [2, 1];\nnums.sortBy((a, b) => a - b);").expect_err("sortBy takes a key function");
+    assert!(err.contains("Callback of 'sortBy' takes at most 1 parameter(s) but got 2"));
+}
+
+#[test]
+fn check_array_at() {
+    assert!(check("let nums = [1, 2];\nlet last = nums.at(-1);").is_ok());
+
+    let err = check("let nums = [1, 2];\nlet last = nums.at(\"x\");").expect_err("at takes a number");
+    assert!(err.contains("at"));
+}
+
+#[test]
+fn check_fixed_array_sizes() {
+    assert!(check("let rgb: number[3] = [255, 128, 0];").is_ok());
+
+    let err = check("let rgb: number[3] = [255, 128];").expect_err("literal is too short");
+    assert!(err.contains("Array literal has 2 element(s) but its declared size is 3"));
+
+    let err = check("let board: number[2][2] = [[1, 2], [3]];").expect_err("inner row is too short");
+    assert!(err.contains("Array literal has 1 element(s) but its declared size is 2"));
+}
+
+#[test]
+fn check_number_conversions() {
+    assert!(check("let age = parseInt(\"42\");\nlet n = 3;\nlet s = n.toFixed(1);").is_ok());
+
+    let err = check("let age = parseInt(42);").expect_err("parseInt takes a string");
+    assert!(err.contains("Argument 1 of 'parseInt' expects string but got number"));
+
+    let err = check("let n = 3;\nlet s = n.toFixed();").expect_err("toFixed needs a digit count");
+    assert!(err.contains("Method 'toFixed' expects 1 argument(s) but got 0"));
+}
+
+#[test]
+fn check_input_arguments() {
+    assert!(check("let a = input();\nlet b = input(\"> \");").is_ok());
+
+    let err = check("let a = input(1);").expect_err("prompt must be a string");
+    assert!(err.contains("Argument 1 of 'input' expects string but got number"));
+
+    let err = check("let a = input(\"a\", \"b\");").expect_err("input takes one prompt");
+    assert!(err.contains("Function 'input' expects 0 to 1 argument(s) but got 2"));
+}
+
+#[test]
+fn check_console_methods() {
+    assert!(check("console.log(\"a\", 1, [2]);\nconsole.error(\"b\");").is_ok());
+    assert!(check("let console = [1];\nconsole.push(2);").is_ok());
+
+    let err = check("console.lg(\"a\");").expect_err("no such console method");
+    assert_eq!(err, "'console' has no method 'lg'. Did you mean `log`?");
+}
+
+#[test]
+fn check_std_imports() {
+    assert!(check("import {randomInt, shuffle} from \"std/random\";").is_ok());
+    assert!(check("import {readFile, writeFile, readDir} from \"std/fs\";").is_ok());
+    assert!(check("import * as process from \"std/process\";\nprocess.exit(0);").is_ok());
+    assert!(check("import {now, sleep, Duration} from \"std/time\";").is_ok());
+
+    let err = check("import {randInt} from \"std/random\";").expect_err("no such export");
+    assert!(err.contains("Module 'std/random' has no exported member 'randInt'. Did you mean `randomInt`?"));
+
+    let err = check("import {randomInt} from \"std/randon\";").expect_err("no such module");
+    assert!(err.contains("Cannot find module 'std/randon'. Did you mean `std/random`?"));
+}
+
+#[test]
+fn check_enum_variant_literals_and_match_patterns() {
+    let shape = "enum Shape { Circle { radius: number }, Label(string), Empty }\n";
+    assert!(check(&format!("{}let s = Shape.Circle {{ radius: 2 }};\nmatch (s) {{\n    Shape.Circle {{ radius: r }} => print(r + 1);\n    Shape.Label(text) => print(text);\n    _ => {{}}\n}}", shape)).is_ok());
+
+    let err = check(&format!("{}let s = Shape.Circle {{ radus: 2 }};", shape)).expect_err("unknown field");
+    assert_eq!(err, "Variant 'Shape.Circle' has no field 'radus'. Did you mean `radius`?");
+
+    let err = check(&format!("{}let s = Shape.Circle {{}};", shape)).expect_err("missing field");
+    assert_eq!(err, "Missing field 'radius' in 'Shape.Circle'");
+
+    let err = check(&format!("{}let s = Shape.Empty;\nmatch (s) {{\n    Shape.Circl {{ radius }} => {{}}\n}}", shape)).expect_err("unknown variant");
+    assert_eq!(err, "Enum 'Shape' has no variant 'Circl'. Did you mean `Circle`?");
+
+    let err = check(&format!("{}let s = Shape.Empty;\nmatch (s) {{\n    Shape.Label(a, b) => {{}}\n}}", shape)).expect_err("wrong binding count");
+    assert_eq!(err, "Variant 'Label' has 1 field(s) but the pattern binds 2");
+}
+
+#[test]
+fn check_errors_point_at_the_failing_expression() {
+    let source = "function area(w: number): number { return w; }\nlet a = area(\"wide\");";
+    let program = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+    let err = Checker::new().check(&program).expect_err("wrong argument type").locate(source);
+    assert_eq!((err.line, err.column, err.width), (Some(2), Some(9), Some(12)));
+
+    let source = "function f(): void {}\nprint(1);";
+    let program = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+    let err = Checker::new_module().check(&program).expect_err("statement in module").locate(source);
+    assert_eq!((err.line, err.column), (Some(2), Some(1)));
+}
+
+#[test]
+fn check_type_errors_point_at_the_annotation() {
+    let cases = [
+        ("let x = 1;
+let y: Foobarbaz = 3;", (2, 8, 9)),
+        ("function f(a: number, b: Foobarbaz): void {}", (1, 26, 9)),
+        ("function f(): Foobarbaz[] { return []; }", (1, 15, 11)),
+        ("struct Point {
+  x: number,
+  y: Foobarbaz
+}", (3, 6, 9)),
+    ];
+    for (source, (line, column, width)) in cases {
+        let program = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+        let err = Checker::new().check(&program).expect_err("unknown type").locate(source);
+        assert_eq!((err.line, err.column, err.width), (Some(line), Some(column), Some(width)), "{}", source);
+    }
+}
+
+#[test]
+fn check_struct_literal_fields() {
+    let config = "struct Config { name: string, retries: number = 3, label?: string }\n";
+    assert!(check(&format!("{}let c = Config {{ name: \"app\" }};", config)).is_ok());
+
+    let err = check(&format!("{}let c = Config {{ retries: 5 }};", config)).expect_err("missing field");
+    assert_eq!(err, "Missing field 'name' in 'Config'");
+
+    let err = check(&format!("{}let c = Config {{ name: \"app\", retrie: 5 }};", config)).expect_err("unknown field");
+    assert_eq!(err, "Struct 'Config' has no field 'retrie'. Did you mean `retries`?");
+}
+
+#[test]
+fn check_qualified_enum_variants() {
+    let status = "enum Status { Active, Pending(string), Moved { to: string } }\n";
+    assert!(check(&format!("{}let s = Status.Active;\nlet p = Status.Pending(\"later\");\ns = p;", status)).is_ok());
+
+    let err = check(&format!("{}let s = Status.Actve;", status)).expect_err("unknown variant");
+    assert_eq!(err, "Enum 'Status' has no variant 'Actve'. Did you mean `Active`?");
+
+    let err = check(&format!("{}let p = Status.Pending;", status)).expect_err("missing arguments");
+    assert_eq!(err, "Variant 'Status.Pending' expects 1 argument(s) but got 0");
+
+    let err = check(&format!("{}let p = Status.Pending(1);", status)).expect_err("wrong argument type");
+    assert_eq!(err, "Argument 1 of 'Status.Pending' expects string but got number");
+
+    let err = check(&format!("{}let m = Status.Moved(\"home\");", status)).expect_err("named fields");
+    assert_eq!(err, "Variant 'Status.Moved' has named fields; build it with `Status.Moved { ... }`");
+}
+
+#[test]
+fn check_match_is_exhaustive() {
+    let light = "enum Light { Red, Yellow, Green }\nlet light = Light.Red;\n";
+    assert!(check(&format!("{}match (light) {{\n    Light.Red => {{}}\n    Light.Yellow => {{}}\n    Light.Green => {{}}\n}}", light)).is_ok());
+    assert!(check(&format!("{}match (light) {{\n    Light.Red => {{}}\n    _ => {{}}\n}}", light)).is_ok());
+
+    let err = check(&format!("{}match (light) {{\n    Light.Yellow => {{}}\n}}", light)).expect_err("missing variants");
+    assert_eq!(
+        err,
+        "Non-exhaustive match on 'Light': `Light.Red`, `Light.Green` not handled. Add the missing arms or a `_` arm"
+    );
+}
+
+#[test]
+fn check_comparisons_need_opt_in() {
+    let point = "struct Point { x: number }\nlet a = Point { x: 1 };\nlet b = Point { x: 2 };\n";
+    let err = check(&format!("{}print(a == b);", point)).expect_err("struct equality");
+    assert_eq!(err, "Cannot compare 'Point' values with '=='. Mark struct 'Point' @equatable");
+    assert!(check(&format!("@equatable {}print(a == b);", point)).is_ok());
+    assert!(check(&format!("@derive(PartialEq) {}print(a != b);", point)).is_ok());
+
+    let err = check(&format!("@equatable {}print(a < b);", point)).expect_err("struct ordering");
+    assert_eq!(err, "Cannot compare 'Point' values with '<'. Mark struct 'Point' @comparable");
+    assert!(check(&format!("@comparable {}print(a < b);", point)).is_ok());
+
+    let light = "enum Light { Red, Green }\nlet l = Light.Red;\n";
+    assert!(check(&format!("{}print(l == Light.Green);", light)).is_ok());
+    let err = check(&format!("{}print(l > Light.Green);", light)).expect_err("enum ordering");
+    assert_eq!(err, "Cannot compare 'Light' values with '>'. Mark enum 'Light' @comparable");
+
+    let err = check("struct Point { x: number }\n@hashable struct Line { points: Point[] }").expect_err("field type");
+    assert_eq!(err, "'Line' cannot be @equatable because its field type 'Point' is not. Mark 'Point' @equatable");
+}
+
+#[test]
+fn check_operand_types() {
+    assert!(check("let name = \"Ann\";\nlet age = 30;\nprint(name + age, age + name);").is_ok());
+
+    let err = check("let name = \"Ann\";\nprint(name - 1);").expect_err("string arithmetic");
+    assert_eq!(err, "Operator '-' cannot be used on a string. Only '+' joins strings");
+
+    let err = check("let count = \"5\";\nprint(count == 5);").expect_err("mixed comparison");
+    assert_eq!(err, "Cannot compare a string with a number using '=='");
+}
+
+#[test]
+fn check_conditions_are_booleans() {
+    assert!(check("let count = 3;\nif (count > 0 && true) {}\nwhile (count != 0) { count = count - 1; }").is_ok());
+
+    let err = check("let count = 3;\nif (count) {}").expect_err("number condition");
+    assert_eq!(err, "Condition must be a boolean, but got a number. Test it explicitly, as in `count != 0`");
+
+    let err = check("let name = \"Ann\";\nwhile (name) {}").expect_err("string condition");
+    assert_eq!(err, "Condition must be a boolean, but got a string. Test it explicitly, as in `name.length > 0`");
+
+    let err = check("function total(): number { return 1; }\nif (true && total()) {}").expect_err("number operand");
+    assert_eq!(err, "Condition must be a boolean, but got a number. Test it explicitly, as in `value != 0`");
+}
+
+#[test]
+fn check_top_level_statements() {
+    assert!(check("print(answer());\nfunction answer(): number { return 42; }").is_ok());
+
+    let err = check("const limit = 3;\nlet count = 0;\nfunction main(): void { print(limit); }")
+        .expect_err("statement beside main");
+    assert_eq!(err, "Top-level statement on line 2 can't be mixed with a `main` function. Move it into `main`");
+
+    let tokens = Lexer::new("function helper(): void {}\nprint(\"loaded\");").tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+    let err = Checker::new_module().check(&program).expect_err("statement in module").message;
+    assert_eq!(
+        err,
+        "Top-level statement on line 2 in a module. Only the entry file runs top-level code; move it into a function"
+    );
+}
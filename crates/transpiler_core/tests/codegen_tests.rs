@@ -1,4 +1,4 @@
-use jrust_transpiler_core::{Lexer, Parser, Codegen};
+use jrust_transpiler_core::{Lexer, Parser, Codegen, NamingPolicy};
 
 fn transpile(source: &str) -> String {
     let mut lexer = Lexer::new(source);
@@ -13,7 +13,7 @@ fn transpile(source: &str) -> String {
 fn codegen_simple_variable() {
     let rust_code = transpile("let x: number = 42;");
     assert!(rust_code.contains("fn main()"));
-    assert!(rust_code.contains("let mut x: i32 = 42;"));
+    assert!(rust_code.contains("let x: i32 = 42;"));
 }
 
 #[test]
@@ -31,7 +31,7 @@ fn codegen_const_string() {
 #[test]
 fn codegen_string_variable() {
     let rust_code = transpile(r#"let name: string = "Alice";"#);
-    assert!(rust_code.contains("let mut name: String"));
+    assert!(rust_code.contains("let name: String"));
     assert!(rust_code.contains("\"Alice\".to_string()"));
 }
 
@@ -42,6 +42,45 @@ fn codegen_print_statement() {
     assert!(rust_code.contains("\"Hello\""));
 }
 
+#[test]
+fn codegen_console_methods() {
+    let rust_code = transpile(
+        r#"struct User {
+    name: string,
+    age: number
+}
+let user = User { name: "Ada", age: 36 };
+let tags = ["math"];
+console.log("user:", user, user.age);
+console.info(tags, tags.length);
+console.warn("careful");
+console.error("failed:", 42);
+console.log();"#,
+    );
+    assert!(rust_code.contains(r#"println!("{} {:?} {}", "user:", user, user.age);"#));
+    assert!(rust_code.contains(r#"println!("{:?} {}", tags, tags.len() as i32);"#));
+    assert!(rust_code.contains(r#"eprintln!("{}", "careful");"#));
+    assert!(rust_code.contains(r#"eprintln!("{} {}", "failed:", 42);"#));
+    assert!(rust_code.contains("println!();"));
+}
+
+#[test]
+fn codegen_print_several_arguments_and_debug_values() {
+    let rust_code = transpile(
+        r#"struct User {
+    name: string
+}
+let user = User { name: "Ada" };
+let scores = [90, 85];
+print(user);
+print("name:", user.name, scores, true);
+print();"#,
+    );
+    assert!(rust_code.contains(r#"println!("{:?}", user);"#));
+    assert!(rust_code.contains(r#"println!("{} {} {:?} {}", "name:", user.name, scores, true);"#));
+    assert!(rust_code.contains("println!();"));
+}
+
 #[test]
 fn codegen_string_concatenation() {
     let rust_code = transpile(r#"print("Hello" + "World");"#);
@@ -52,14 +91,14 @@ fn codegen_string_concatenation() {
 #[test]
 fn codegen_simple_function() {
     let rust_code = transpile("function greet(name: string): void { print(\"Hi\"); }");
-    assert!(rust_code.contains("fn greet(name: String)"));
+    assert!(rust_code.contains("fn greet(name: &str)"));
     assert!(rust_code.contains("println!"));
 }
 
 #[test]
 fn codegen_function_with_return() {
     let rust_code = transpile("function getValue(): number { return 42; }");
-    assert!(rust_code.contains("fn getValue() -> i32"));
+    assert!(rust_code.contains("fn get_value() -> i32"));
     assert!(rust_code.contains("return 42;"));
 }
 
@@ -72,14 +111,14 @@ fn codegen_function_with_params() {
 #[test]
 fn codegen_multiple_statements() {
     let rust_code = transpile("let x: number = 5; let y: number = 10;");
-    assert!(rust_code.contains("let mut x: i32 = 5;"));
-    assert!(rust_code.contains("let mut y: i32 = 10;"));
+    assert!(rust_code.contains("let x: i32 = 5;"));
+    assert!(rust_code.contains("let y: i32 = 10;"));
 }
 
 #[test]
 fn codegen_arithmetic_expression() {
     let rust_code = transpile("let result: number = 5 + 3 * 2;");
-    assert!(rust_code.contains("let mut result: i32"));
+    assert!(rust_code.contains("let result: i32"));
 }
 
 #[test]
@@ -97,7 +136,7 @@ fn codegen_complex_program() {
     "#;
     let rust_code = transpile(source);
     assert!(rust_code.contains("fn main()"));
-    assert!(rust_code.contains("let mut x: i32 = 42;"));
+    assert!(rust_code.contains("let x: i32 = 42;"));
     assert!(rust_code.contains("fn double(n: i32) -> i32"));
     assert!(rust_code.contains("println!"));
 }
@@ -141,14 +180,14 @@ fn codegen_while_loop() {
 #[test]
 fn codegen_array_declaration() {
     let rust_code = transpile("let nums: number[] = [1, 2, 3];");
-    assert!(rust_code.contains("let mut nums: Vec<i32>"));
+    assert!(rust_code.contains("let nums: Vec<i32>"));
     assert!(rust_code.contains("vec![1, 2, 3]"));
 }
 
 #[test]
 fn codegen_array_indexing() {
     let rust_code = transpile("let first: number = nums[0];");
-    assert!(rust_code.contains("nums[0 as usize]"));
+    assert!(rust_code.contains("nums[jrust_index(0, nums.len())]"));
 }
 
 #[test]
@@ -160,13 +199,13 @@ fn codegen_member_access() {
 #[test]
 fn codegen_any_type() {
     let rust_code = transpile("let value: any = 42;");
-    assert!(rust_code.contains("let mut value: String"));
+    assert!(rust_code.contains("let value: String"));
 }
 
 #[test]
 fn codegen_string_array() {
     let rust_code = transpile(r#"let strs: string[] = ["a", "b"];"#);
-    assert!(rust_code.contains("let mut strs: Vec<String>"));
+    assert!(rust_code.contains("let strs: Vec<String>"));
     assert!(rust_code.contains("vec!["));
 }
 
@@ -215,26 +254,823 @@ fn codegen_continue_in_while() {
 #[test]
 fn codegen_dynamic_array() {
     let rust_code = transpile("let nums: number[] = [1, 2, 3];");
-    assert!(rust_code.contains("let mut nums: Vec<i32>"));
+    assert!(rust_code.contains("let nums: Vec<i32>"));
     assert!(rust_code.contains("vec![1, 2, 3]"));
 }
 
 #[test]
 fn codegen_static_array() {
     let rust_code = transpile("let nums: number[number, 5] = [1, 2, 3, 4, 5];");
-    assert!(rust_code.contains("let mut nums: [i32; 5]"));
+    assert!(rust_code.contains("let nums: [i32; 5]"));
     assert!(rust_code.contains("[1, 2, 3, 4, 5]"));
 }
 
 #[test]
 fn codegen_static_string_array() {
     let rust_code = transpile(r#"let names: string[string, 3] = ["Alice", "Bob", "Charlie"];"#);
-    assert!(rust_code.contains("let mut names: [String; 3]"));
+    assert!(rust_code.contains("let names: [String; 3]"));
 }
 
 #[test]
 fn codegen_static_bool_array() {
     let rust_code = transpile("let flags: boolean[boolean, 2] = [true, false];");
-    assert!(rust_code.contains("let mut flags: [bool; 2]"));
+    assert!(rust_code.contains("let flags: [bool; 2]"));
     assert!(rust_code.contains("[true, false]"));
 }
+
+#[test]
+fn codegen_struct_field_defaults() {
+    let rust_code = transpile(r#"struct Config { retries: number = 3, name: string = "app", label?: string }
+let c = Config { retries: 5 };"#);
+    assert!(rust_code.contains("label: Option<String>,"));
+    assert!(rust_code.contains("impl Default for Config {"));
+    assert!(rust_code.contains("retries: 3,"));
+    assert!(rust_code.contains("name: \"app\".to_string(),"));
+    assert!(rust_code.contains("label: None,"));
+    assert!(rust_code.contains("Config { retries: 5, ..Default::default() }"));
+
+    let rust_code = transpile(r#"struct Job { task: Task, retries: number = 3, label?: string }
+struct Task { id: number }
+let job = Job { task: Task { id: 1 } };"#);
+    assert!(!rust_code.contains("impl Default for Job"));
+    assert!(rust_code.contains("Job { task: Task { id: 1 }, retries: 3, label: None }"));
+}
+
+#[test]
+fn codegen_struct_optional_field_wrapped_in_some() {
+    let rust_code = transpile(r#"struct User { name: string, nickname?: string }
+let u = User { name: "Alice", nickname: "Al" };"#);
+    assert!(rust_code.contains(r#"User { name: "Alice".to_string(), nickname: Some("Al".to_string()) }"#));
+}
+
+#[test]
+fn codegen_struct_without_defaults_has_no_default_impl() {
+    let rust_code = transpile("struct Point { x: number, y: number }");
+    assert!(!rust_code.contains("impl Default"));
+}
+
+#[test]
+fn codegen_reassigned_variable_is_mut() {
+    let rust_code = transpile("let count = 0; let limit = 10; count = count + 1;");
+    assert!(rust_code.contains("let mut count = 0;"));
+    assert!(rust_code.contains("let limit = 10;"));
+    assert!(rust_code.contains("count = count + 1;"));
+}
+
+#[test]
+fn codegen_mutating_method_makes_variable_mut() {
+    let rust_code = transpile("let nums: number[] = [1, 2]; nums.push(3);");
+    assert!(rust_code.contains("let mut nums: Vec<i32>"));
+}
+
+#[test]
+fn codegen_explicit_mut_keyword() {
+    let rust_code = transpile("let mut total = 0; total = total + 1;");
+    assert!(rust_code.contains("let mut total = 0;"));
+}
+
+#[test]
+fn codegen_mutability_is_tracked_per_function() {
+    let rust_code = transpile("function a(): void { let x = 1; x = 2; } function b(): void { let x = 1; print(x); }");
+    assert!(rust_code.contains("let mut x = 1;"));
+    assert!(rust_code.contains("let x = 1;"));
+}
+
+#[test]
+fn codegen_nested_function() {
+    let rust_code = transpile("function outer(n: number): number { function square(x: number): number { return x * x; } return square(n); }");
+    assert!(rust_code.contains("fn outer(n: i32) -> i32 {\n        fn square(x: i32) -> i32 {\n            return x * x;\n        }"));
+    assert!(!rust_code.contains("pub fn square"));
+}
+
+#[test]
+fn codegen_block_statement() {
+    let rust_code = transpile("let x = 1; { let y = 2; print(y); }");
+    assert!(rust_code.contains("    {\n        let y = 2;\n        println!(\"{}\", y);\n    }"));
+}
+
+#[test]
+fn codegen_derive_attribute_extends_default_derives() {
+    let rust_code = transpile("@derive(PartialOrd, Clone) struct Score { value: number }");
+    assert!(rust_code.contains("#[derive(Debug, Clone, PartialOrd)]\n    struct Score"));
+}
+
+#[test]
+fn codegen_function_attribute() {
+    let rust_code = transpile("@inline function double(x: number): number { return x * 2; }");
+    assert!(rust_code.contains("#[inline]\n    fn double(x: i32) -> i32"));
+}
+
+#[test]
+fn codegen_enum_attribute_with_arguments() {
+    let rust_code = transpile("@allow(dead_code) enum Color { Red, Green }");
+    assert!(rust_code.contains("#[derive(Debug, Clone, PartialEq)]\n    #[allow(dead_code)]\n    enum Color"));
+}
+
+#[test]
+fn codegen_channel_send_and_recv() {
+    let rust_code = transpile("function main(): void { let [tx, rx] = channel<number>(); tx.send(1); let v = rx.recv(); }");
+    assert!(rust_code.contains("let (tx, rx) = std::sync::mpsc::channel::<i32>();"));
+    assert!(rust_code.contains("tx.send(1).unwrap();"));
+    assert!(rust_code.contains("let v = rx.recv().unwrap();"));
+}
+
+#[test]
+fn codegen_shared_value_and_lock_block() {
+    let rust_code = transpile("function main(): void { let total = shared<number>(0); lock (total) { total = total + 1; } }");
+    assert!(rust_code.contains("let total = std::sync::Arc::new(std::sync::Mutex::<i32>::new(0));"));
+    assert!(rust_code.contains("let mut total = total.lock().unwrap();"));
+    assert!(rust_code.contains("*total = *total + 1;"));
+}
+
+#[test]
+fn codegen_shared_parameter_type() {
+    let rust_code = transpile("function read(count: shared<number>): void { lock (count) { print(count); } }");
+    assert!(rust_code.contains("fn read(count: std::sync::Arc<std::sync::Mutex<i32>>)"));
+    assert!(rust_code.contains("let count = count.lock().unwrap();"));
+}
+
+#[test]
+fn codegen_defer_uses_drop_guard() {
+    let rust_code = transpile("function work(): void { defer { print(\"done\"); } print(\"working\"); }");
+    assert!(rust_code.contains("let _defer_guard_0 = DeferGuard(|| {"));
+    assert!(rust_code.contains("impl<F: FnMut()> Drop for DeferGuard<F>"));
+}
+
+#[test]
+fn codegen_without_defer_has_no_guard_type() {
+    let rust_code = transpile("function work(): void { print(\"working\"); }");
+    assert!(!rust_code.contains("DeferGuard"));
+}
+
+#[test]
+fn codegen_records_source_lines() {
+    let source = "function double(n: number): number {\n    return n * 2;\n}\n\nlet x: number = double(4);\nprint(x);";
+    let tokens = Lexer::new(source).tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+    let mut codegen = Codegen::new_module();
+    let rust_code = codegen.generate(&program);
+    let rust_lines: Vec<&str> = rust_code.lines().collect();
+
+    for (rust_line, jr_line) in codegen.source_map() {
+        let generated = rust_lines[rust_line - 1];
+        match jr_line {
+            1 => assert!(generated.contains("fn double")),
+            2 => assert!(generated.contains("return n * 2")),
+            5 => assert!(generated.contains("let x: i32 = double(4);")),
+            6 => assert!(generated.contains("println!")),
+            other => panic!("unexpected source line {}", other),
+        }
+    }
+    assert_eq!(codegen.source_map().len(), 4);
+}
+
+#[test]
+fn codegen_test_blocks() {
+    let rust_code = transpile("function add(a: number, b: number): number { return a + b; }
+print(add(1, 2));
+test \"adds numbers\" { assertEq(add(2, 3), 5); }
+test \"adds numbers\" { assert(add(1, 1) == 2, \"sum\"); }");
+    assert!(rust_code.contains("fn add(a: i32, b: i32) -> i32"));
+    assert!(!rust_code.contains("fn main() {\n    fn add"));
+    assert!(rust_code.contains("#[cfg(test)]\nmod tests {\n    use super::*;"));
+    assert!(rust_code.contains("#[test]\n    fn test_adds_numbers() {"));
+    assert!(rust_code.contains("fn test_adds_numbers_2() {"));
+    assert!(rust_code.contains("assert_eq!(add(2, 3), 5, \"at line 3\");"));
+    assert!(rust_code.contains("assert!(add(1, 1) == 2, \"{} at line 4\", \"sum\");"));
+}
+
+#[test]
+fn codegen_assertions_and_panic_name_the_source_location() {
+    let source = "function half(n: number): number {
+    if n % 2 != 0 {
+        panic(\"odd: \" + n);
+    }
+    return n / 2;
+}
+assert(half(4) == 2);
+assertEq(half(8), 4, \"halves\");
+panic();";
+    let program = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+    let mut codegen = Codegen::new();
+    codegen.set_file("src/index.jr");
+    let rust_code = codegen.generate(&program);
+    assert!(rust_code.contains(r#"panic!("{} at src/index.jr:3", format!("{}{}", "odd: ", n));"#));
+    assert!(rust_code.contains(r#"assert!(half(4) == 2, "assertion failed at src/index.jr:7");"#));
+    assert!(rust_code.contains(r#"assert_eq!(half(8), 4, "{} at src/index.jr:8", "halves");"#));
+    assert!(rust_code.contains(r#"panic!("explicit panic at src/index.jr:9");"#));
+
+    assert!(transpile("assert(1 < 2);").contains(r#"assert!(1 < 2, "assertion failed at line 1");"#));
+}
+
+#[test]
+fn codegen_runtime_panic_frames() {
+    let source = "import { now } from \"std/time\";
+function divide(a: number, b: number): number {
+    return a / b;
+}
+let start = now();
+print(divide(start, 0));";
+    let program = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+    let mut codegen = Codegen::new();
+    codegen.set_file("src/index.jr");
+    let rust_code = codegen.generate(&program);
+    assert!(rust_code.contains(r#"let _frame = jrust_runtime::trace::enter("divide", "src/index.jr:2");"#));
+    assert!(rust_code.contains("fn main() {\n    jrust_runtime::init();\n"));
+
+    let program = Parser::new(Lexer::new("function main(): void {\n    print(1);\n}").tokenize().unwrap()).parse().unwrap();
+    let rust_code = Codegen::new().generate(&program);
+    assert!(!rust_code.contains("jrust_runtime"));
+    assert!(rust_code.contains("fn main() {\n    jrust_panic_hook();\n"));
+    assert!(rust_code.contains("eprintln!(\"jRust runtime error: {}\", message);"));
+    assert!(!Codegen::new_module().generate(&program).contains("jrust_panic_hook"));
+    let mut codegen = Codegen::new();
+    codegen.link_runtime();
+    let rust_code = codegen.generate(&program);
+    assert!(rust_code.contains(
+        "fn main() {\n    jrust_runtime::init();\n    let _frame = jrust_runtime::trace::enter(\"main\", \"line 1\");\n"
+    ));
+}
+
+#[test]
+fn codegen_env_args() {
+    let rust_code = transpile("let args = env.args();\nprint(args.length);");
+    assert!(rust_code.contains("let args = std::env::args().skip(1).collect::<Vec<String>>();"));
+}
+
+#[test]
+fn codegen_library_entry_has_no_main() {
+    let source = "import {double} from \"./utils/math\";\nexport function quadruple(x: number): number { return double(double(x)); }";
+    let tokens = Lexer::new(source).tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+    let rust_code = Codegen::new_library().generate(&program);
+    assert!(rust_code.contains("use utils::math::double;"));
+    assert!(rust_code.contains("pub fn quadruple(x: i32) -> i32"));
+    assert!(!rust_code.contains("fn main()"));
+}
+
+#[test]
+fn codegen_concatenates_typed_strings() {
+    let rust_code = transpile("let first: string = \"a\";\nlet second = first + first;\nprint(\"n\" + (1 + 2));\nlet part = [1, 2].slice(1);");
+    assert!(rust_code.contains("let second = format!(\"{}{}\", first, first);"));
+    assert!(rust_code.contains("format!(\"{}{}\", \"n\", 1 + 2)"));
+    assert!(rust_code.contains("vec![1, 2][1 as usize..].to_vec()"));
+}
+
+#[test]
+fn codegen_lowers_to_typed_ir() {
+    use jrust_transpiler_core::ir::{IrExprKind, IrStmtKind, IrType};
+
+    let tokens = Lexer::new("let name: string = \"x\";\nlet greeting = name + \"!\";").tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+    let ir = Codegen::new().lower(&program);
+    let main = ir.main.expect("expected a wrapped main");
+    match &main[2].kind {
+        IrStmtKind::Let { name, value, .. } => {
+            assert_eq!(name, "greeting");
+            assert_eq!(value.ty, IrType::Str);
+            assert!(matches!(&value.kind, IrExprKind::Format(parts) if parts.len() == 2));
+        }
+        other => panic!("expected a let, got {:?}", other),
+    }
+    assert_eq!(main[2].line, Some(2));
+}
+
+#[test]
+fn codegen_borrows_read_only_parameters() {
+    let rust_code = transpile("function shout(text: string): string { return text.toUpperCase(); }
+function total(values: number[]): number { return values.length; }
+function keep(text: string): string { return text; }
+let name = \"world\";
+print(shout(name));
+print(shout(\"hi\"));
+print(total([1, 2]));
+let kept = keep(name);
+print(keep(name));");
+    assert!(rust_code.contains("fn shout(text: &str) -> String"));
+    assert!(rust_code.contains("fn total(values: &[i32]) -> i32"));
+    assert!(rust_code.contains("fn keep(text: String) -> String"));
+    assert!(rust_code.contains("shout(&name)"));
+    assert!(rust_code.contains("shout(\"hi\")"));
+    assert!(rust_code.contains("total(&vec![1, 2])"));
+    assert!(rust_code.contains("let kept = keep(name.clone());"));
+    assert!(rust_code.contains("println!(\"{}\", keep(name));"));
+}
+
+#[test]
+fn codegen_borrows_iterated_exported_and_nested_parameters() {
+    let rust_code = transpile("function show(names: string[]): void { for n in names { print(n); } }
+export function total(nums: number[]): number { let sum = 0; for n in nums { sum = sum + n; } return sum; }
+function collect(names: string[]): string[] { let out: string[] = []; for n in names { out.push(n); } return out; }
+function outer(): void {
+    function greet(name: string): void { print(name); }
+    let who = \"bob\";
+    greet(who);
+    print(who);
+}
+let names: string[] = [\"a\"];
+show(names);
+print(total([1]));
+print(collect(names).length);
+print(names.length);");
+    assert!(rust_code.contains("fn show(names: &[String]) {\n        for n in names.iter().map(String::as_str) {"));
+    assert!(rust_code.contains("show(&names);"));
+    assert!(rust_code.contains("pub fn total(nums: impl AsRef<[i32]>) -> i32 {\n        let nums = nums.as_ref();"));
+    assert!(rust_code.contains("for n in nums.iter().copied() {"));
+    assert!(rust_code.contains("fn collect(names: Vec<String>) -> Vec<String>"));
+    assert!(rust_code.contains("fn greet(name: &str)"));
+    assert!(rust_code.contains("greet(&who);"));
+}
+
+#[test]
+fn codegen_clones_fields_read_out_of_elements() {
+    let rust_code = transpile("struct User { name: string, age: number }
+function first(users: User[]): string { return users[0].name; }
+let users: User[] = [User { name: \"a\", age: 1 }];
+let n = users[0].name;
+let u = users[0];
+let a = users[0].age;
+print(first(users));");
+    assert!(rust_code.contains("fn first(users: &[User]) -> String {\n        return users[jrust_index(0, users.len())].name.clone();"));
+    assert!(rust_code.contains("let n = users[jrust_index(0, users.len())].name.clone();"));
+    assert!(rust_code.contains("let u = users[jrust_index(0, users.len())].clone();"));
+    assert!(rust_code.contains("let a = users[jrust_index(0, users.len())].age;"));
+}
+
+#[test]
+fn codegen_clones_values_reused_in_loops() {
+    let rust_code = transpile("let label = \"x\";\nlet items: string[] = [];\nwhile items.length < 2 { items.push(label); }\nlet nums = [1];\nfor n in nums { print(n); }\nprint(nums.length);");
+    assert!(rust_code.contains("items.push(label.clone());"));
+    assert!(rust_code.contains("for n in nums.clone() {"));
+}
+
+#[test]
+fn codegen_infers_mutability() {
+    let rust_code = transpile("let mut unused = 1;\nlet count = 0;\ncount = count + 1;\nlet items: number[] = [];\nitems.push(1);\nprint(unused);");
+    assert!(rust_code.contains("let unused = 1;"));
+    assert!(rust_code.contains("let mut count = 0;"));
+    assert!(rust_code.contains("let mut items: Vec<i32> = vec![];"));
+}
+
+#[test]
+fn codegen_mutability_respects_shadowing() {
+    let rust_code = transpile("let x = 1;\nprint(x);\nfunction f(): void {\n  let x = 2;\n  x = 3;\n  print(x);\n}\nf();");
+    assert!(rust_code.contains("let x = 1;"));
+    assert!(rust_code.contains("let mut x = 2;"));
+}
+
+#[test]
+fn codegen_string_methods() {
+    let rust_code = transpile("let s = \"a-b\";\nlet prefix = \"a\";\nprint(s.replace(\"-\", \"+\"));\nprint(s.startsWith(prefix));\nprint(s.endsWith(\"b\"));\nprint(s.padStart(5, \"0\"));\nprint(s.repeat(2));\nprint(s.slice(1, 2));");
+    assert!(rust_code.contains("s.replacen(\"-\", \"+\", 1)"));
+    assert!(rust_code.contains("s.starts_with(&prefix)"));
+    assert!(rust_code.contains("s.ends_with(\"b\")"));
+    assert!(rust_code.contains("format!(\"{:0>1$}\", s, 5 as usize)"));
+    assert!(rust_code.contains("s.repeat(2 as usize)"));
+    assert!(rust_code.contains("s.chars().skip(1 as usize).take((2 - 1) as usize).collect::<String>()"));
+}
+
+#[test]
+fn codegen_array_methods_with_callbacks() {
+    let rust_code = transpile("let nums: number[] = [1, 2, 3];\nlet big = nums.filter(n => n > 1);\nlet total = nums.reduce((acc, n) => acc + n, 0);\nlet index = nums.findIndex(n => n == 2);\nprint(nums.some(n => n > 2));\nlet more = nums.concat([4]);\nnums.forEach(n => {\n    print(n);\n});");
+    assert!(rust_code.contains("nums.iter().filter(|&&n| n > 1).cloned().collect::<Vec<_>>()"));
+    assert!(rust_code.contains("nums.iter().fold(0, |acc, &n| acc + n)"));
+    assert!(rust_code.contains("nums.iter().position(|&n| n == 2).map(|i| i as i32).unwrap_or(-1)"));
+    assert!(rust_code.contains("nums.iter().any(|&n| n > 2)"));
+    assert!(rust_code.contains("nums.iter().chain(vec![4].iter()).cloned().collect::<Vec<_>>()"));
+    assert!(rust_code.contains("nums.iter().for_each(|&n| {\n        println!(\"{}\", n);\n    });"));
+}
+
+#[test]
+fn codegen_callbacks_borrow_owned_elements() {
+    let rust_code = transpile("function shout(names: string[]): string[] {\n    return names.map(n => n.toUpperCase());\n}\nprint(shout([\"a\"]).length);");
+    assert!(rust_code.contains("names.iter().map(|n| n.to_uppercase()).collect::<Vec<_>>()"));
+}
+
+#[test]
+fn codegen_sort_with_comparator_and_key() {
+    let rust_code = transpile("struct User { name: string, age: number }\nlet nums = [3, 1, 2];\nnums.sort((a, b) => b - a);\nlet users = [User { name: \"A\", age: 1 }];\nusers.sortBy(u => u.name);\nusers.sortBy(u => u.age);");
+    assert!(rust_code.contains("let mut nums = vec![3, 1, 2];"));
+    assert!(rust_code.contains("nums.sort_by(|&a, &b| (b - a).cmp(&0));"));
+    assert!(rust_code.contains("users.sort_by_key(|u| u.name.clone());"));
+    assert!(rust_code.contains("users.sort_by_key(|u| u.age);"));
+}
+
+#[test]
+fn codegen_negative_and_dynamic_indexes() {
+    let rust_code = transpile("let nums = [1, 2, 3];\nlet i = 1;\nprint(nums[0]);\nprint(nums[-1]);\nprint(nums[i + 1]);\nnums[-1] = 30;\nlet last = nums.at(-1);");
+    assert!(rust_code.contains("nums[jrust_index(0, nums.len())]"));
+    assert!(rust_code.contains("nums[jrust_index(-1, nums.len())]"));
+    assert!(rust_code.contains("nums[jrust_index(i + 1, nums.len())]"));
+    assert!(rust_code.contains("*jrust_slot(&mut nums, -1) = 30;"));
+    assert!(rust_code.contains("let last = jrust_at(&nums, -1);"));
+    assert!(rust_code.contains("fn jrust_index(index: i32, len: usize) -> usize"));
+    assert!(rust_code.contains("index {} is out of bounds for length {}"));
+}
+
+#[test]
+fn codegen_slice_syntax() {
+    let rust_code = transpile("let nums = [1, 2, 3];\nlet mid = nums[1..2];\nlet tail = nums[1..];\nlet word = \"hello\";\nlet part = word[1..3];");
+    assert!(rust_code.contains("let mid = nums[1 as usize..2 as usize].to_vec();"));
+    assert!(rust_code.contains("let tail = nums[1 as usize..].to_vec();"));
+    assert!(rust_code.contains("let part = word.chars().skip(1 as usize).take((3 - 1) as usize).collect::<String>();"));
+}
+
+#[test]
+fn codegen_nested_arrays() {
+    let rust_code = transpile("let grid: number[][] = [[1, 2], [3, 4]];\nlet board: number[2][2] = [[0, 0], [1, 1]];\nlet words: string[][] = [[\"a\"]];\nprint(grid[1][0]);");
+    assert!(rust_code.contains("let grid: Vec<Vec<i32>> = vec![vec![1, 2], vec![3, 4]];"));
+    assert!(rust_code.contains("let board: [[i32; 2]; 2] = [[0, 0], [1, 1]];"));
+    assert!(rust_code.contains("let words: Vec<Vec<String>> = vec![vec![\"a\".to_string()]];"));
+    assert!(rust_code.contains("grid[jrust_index(1, grid.len())][jrust_index(0, grid[jrust_index(1, grid.len())].len())]"));
+}
+
+#[test]
+fn codegen_rust_style_array_type() {
+    let rust_code = transpile("let grid: [[number; 2]; 2] = [[1, 2], [3, 4]];");
+    assert!(rust_code.contains("let grid: [[i32; 2]; 2] = [[1, 2], [3, 4]];"));
+}
+
+#[test]
+fn codegen_number_conversions() {
+    let rust_code = transpile("let age = parseInt(\"42\");\nlet price = parseFloat(\"1.5\");\nlet doubled = parseFloat(\"1.5\").unwrap() * 2;\nlet n = 7;\nlet label = n.toString();\nlet fixed = n.toFixed(2);");
+    assert!(rust_code.contains("let age = \"42\".trim().parse::<i32>().ok();"));
+    assert!(rust_code.contains("let price = \"1.5\".trim().parse::<f64>().ok();"));
+    assert!(rust_code.contains("let doubled = \"1.5\".trim().parse::<f64>().ok().unwrap() * (2 as f64);"));
+    assert!(rust_code.contains("let label = n.to_string();"));
+    assert!(rust_code.contains("let fixed = format!(\"{:.1$}\", f64::from(n), 2 as usize);"));
+}
+
+#[test]
+fn codegen_input_builtin() {
+    let rust_code = transpile("let name = input(\"Name: \");\nlet prompt = \"Age? \";\nlet age = input(prompt);\nlet line = input();");
+    assert!(rust_code.contains("let name = jrust_input(\"Name: \");"));
+    assert!(rust_code.contains("let age = jrust_input(&prompt);"));
+    assert!(rust_code.contains("let line = jrust_input(\"\");"));
+    assert!(rust_code.contains("fn jrust_input(prompt: &str) -> String {"));
+    assert!(rust_code.contains("line.trim().to_string()"));
+}
+
+#[test]
+fn codegen_std_imports_use_runtime() {
+    let rust_code = transpile("import {randomInt, uuid} from \"std/random\";\nlet roll = randomInt(1, 6);");
+    assert!(rust_code.contains("use jrust_runtime::random::{random_int, uuid};"));
+    assert!(rust_code.contains("let roll = random_int(1, 6);"));
+}
+
+#[test]
+fn codegen_std_fs_errors_throw() {
+    let rust_code = transpile(
+        r#"import {readFile, exists} from "std/fs";
+let text: string = readFile("notes.txt");
+let found: boolean = exists("notes.txt");
+try {
+    let again: string = readFile("notes.txt");
+} catch (e) {
+    print(e);
+}"#,
+    );
+    assert!(rust_code.contains(r#"let text: String = read_file("notes.txt".to_string()).unwrap_or_else(|err| panic!("{}", err));"#));
+    assert!(rust_code.contains(r#"let found: bool = exists("notes.txt".to_string());"#));
+    assert!(rust_code.contains(r#"let again: String = read_file("notes.txt".to_string())?;"#));
+}
+
+#[test]
+fn codegen_std_env_and_process_namespaces() {
+    let rust_code = transpile(
+        r#"import * as env from "std/env";
+import * as process from "std/process";
+let home = env.get("HOME");
+let listing = process.run("ls", ["-l"]);
+let none = process.run("true", []);
+process.exit(1);"#,
+    );
+    assert!(rust_code.contains("use jrust_runtime::env as env;"));
+    assert!(rust_code.contains(r#"let home = env::get("HOME".to_string());"#));
+    assert!(rust_code.contains(r#"process::run("ls".to_string(), vec!["-l".to_string()]).unwrap_or_else("#));
+    assert!(rust_code.contains(r#"process::run("true".to_string(), vec![]).unwrap_or_else("#));
+    assert!(rust_code.contains("process::exit(1);"));
+}
+
+#[test]
+fn codegen_std_time() {
+    let rust_code = transpile(
+        r#"import {now, since, formatDate, Duration} from "std/time";
+let start = now();
+let elapsed: Duration = since(start);
+print(elapsed.toMillis());
+print(formatDate(start, "%Y-%m-%d"));"#,
+    );
+    assert!(rust_code.contains("use jrust_runtime::time::{now, since, format_date, Duration};"));
+    assert!(rust_code.contains("let elapsed: Duration = since(start);"));
+    assert!(rust_code.contains("elapsed.to_millis()"));
+    assert!(rust_code.contains(r#"format_date(start, "%Y-%m-%d".to_string()).unwrap_or_else("#));
+}
+
+#[test]
+fn codegen_std_json_and_serializable() {
+    let rust_code = transpile(
+        r#"import * as json from "std/json";
+@serializable
+struct User {
+    name: string,
+    tags: string[]
+}
+let user = User { name: "Ada", tags: ["math"] };
+print(json.stringify(user));
+let data = json.parse("{\"name\": \"Grace\"}");
+let key: string = "name";
+print(data["name"]);
+print(data[key]);"#,
+    );
+    assert!(rust_code.contains("#[derive(Debug, Clone, jrust_runtime::serde::Serialize, jrust_runtime::serde::Deserialize)]"));
+    assert!(rust_code.contains(r#"#[serde(crate = "jrust_runtime::serde")]"#));
+    assert!(rust_code.contains(r#"User { name: "Ada".to_string(), tags: vec!["math".to_string()] }"#));
+    assert!(rust_code.contains("json::stringify(user).unwrap_or_else("));
+    assert!(rust_code.contains(r#"json::parse("{\"name\": \"Grace\"}".to_string()).unwrap_or_else("#));
+    assert!(rust_code.contains(r#"data["name"]"#));
+    assert!(rust_code.contains("data[&key]"));
+}
+
+#[test]
+fn codegen_std_csv() {
+    let rust_code = transpile(
+        r#"import * as csv from "std/csv";
+@serializable
+struct Sale {
+    region: string,
+    amount: number
+}
+let text = "region,amount\nnorth,120";
+let rows = csv.parse(text);
+print(rows.length);
+let sales: Sale[] = csv.parseRecords(text);
+print(csv.write(sales));"#,
+    );
+    assert!(rust_code.contains("use jrust_runtime::csv as csv;"));
+    assert!(rust_code.contains("let rows = csv::parse(text.clone()).unwrap_or_else("));
+    assert!(rust_code.contains("let sales: Vec<Sale> = csv::parse_records(text).unwrap_or_else("));
+    assert!(rust_code.contains("csv::write(sales).unwrap_or_else("));
+}
+
+#[test]
+fn codegen_std_log() {
+    let rust_code = transpile(
+        r#"import * as log from "std/log";
+import { warn } from "std/log";
+let workers = 4;
+log.info("starting " + workers + " workers");
+warn("disk almost full");
+log.error(workers);"#,
+    );
+    assert!(rust_code.contains("use jrust_runtime::log as log;"));
+    assert!(rust_code.contains("use jrust_runtime::log::warn;"));
+    assert!(rust_code.contains(r#"log::info(format!("#));
+    assert!(rust_code.contains(r#"warn("disk almost full".to_string());"#));
+    assert!(rust_code.contains("log::error(workers);"));
+}
+
+#[test]
+fn codegen_std_hashes_and_encodings() {
+    let rust_code = transpile(
+        r#"import { sha256 } from "std/crypto";
+import * as base64 from "std/base64";
+import * as url from "std/url";
+let token = base64.encode("user:secret");
+print(base64.decode(token));
+print(sha256("hello"));
+print(url.encode("a b"));"#,
+    );
+    assert!(rust_code.contains("use jrust_runtime::crypto::sha256;"));
+    assert!(rust_code.contains("use jrust_runtime::base64 as base64;"));
+    assert!(rust_code.contains(r#"let token = base64::encode("user:secret".to_string());"#));
+    assert!(rust_code.contains("base64::decode(token).unwrap_or_else("));
+    assert!(rust_code.contains(r#"sha256("hello".to_string())"#));
+    assert!(rust_code.contains(r#"url::encode("a b".to_string())"#));
+}
+
+#[test]
+fn codegen_std_http_fetch() {
+    let rust_code = transpile(
+        r#"import { fetch, FetchOptions } from "std/http";
+let res = fetch("https://example.com");
+print(res.status);
+let token: string = "abc";
+let saved = fetch("https://example.com/items", FetchOptions { method: "POST", headers: [["Authorization", token]] });
+print(saved.text());"#,
+    );
+    assert!(rust_code.contains("use jrust_runtime::http::{fetch, FetchOptions};"));
+    assert!(rust_code.contains(r#"let res = fetch("https://example.com".to_string(), Default::default()).unwrap_or_else("#));
+    assert!(rust_code.contains(
+        r#"FetchOptions { method: "POST".to_string(), headers: vec![vec!["Authorization".to_string(), token]], ..Default::default() }"#
+    ));
+    assert!(rust_code.contains("saved.text()"));
+}
+
+#[test]
+fn codegen_std_http_serve() {
+    let rust_code = transpile(
+        r#"import { serve, Request, Response } from "std/http";
+function handle(req: Request): Response {
+    if (req.path == "/hello") {
+        return Response { body: "hi" };
+    }
+    return Response { status: 404, body: "not found" };
+}
+serve(8080, handle);"#,
+    );
+    assert!(rust_code.contains("use jrust_runtime::http::{serve, Request, Response};"));
+    assert!(rust_code.contains("fn handle(req: Request) -> Response"));
+    assert!(rust_code.contains(r#"Response { body: "hi".to_string(), ..Default::default() }"#));
+    assert!(rust_code.contains(r#"Response { status: 404, body: "not found".to_string(), ..Default::default() }"#));
+    assert!(rust_code.contains("serve(8080, handle).unwrap_or_else("));
+}
+
+#[test]
+fn codegen_enum_named_fields_and_match() {
+    let rust_code = transpile("enum Shape { Circle { radius: number }, Rect { w: number, h: number }, Empty }
+function area(shape: Shape): number {
+    match (shape) {
+        Shape.Circle { radius } => { return radius; }
+        Shape.Rect { w } => return w;
+        _ => return 0;
+    }
+}
+let shape = Shape.Circle { radius: 2 };");
+    assert!(rust_code.contains("Circle { radius: i32 },"));
+    assert!(rust_code.contains("Rect { w: i32, h: i32 },"));
+    assert!(rust_code.contains("Shape::Circle { radius: 2 }"));
+    assert!(rust_code.contains("match shape {"));
+    assert!(rust_code.contains("Shape::Circle { radius } => {"));
+    assert!(rust_code.contains("Shape::Rect { w, .. } => {"));
+    assert!(rust_code.contains("_ => {"));
+}
+
+#[test]
+fn codegen_qualified_enum_variants() {
+    let rust_code = transpile("enum Status { Active, Pending(string), Retry(number, string) }
+let s = Status.Active;
+let p = Status.Pending(\"later\");
+let r: Status = Status.Retry(2, \"again\");
+print(s, s == Status.Active);");
+    assert!(rust_code.contains("let s = Status::Active;"));
+    assert!(rust_code.contains("Status::Pending(\"later\".to_string())"));
+    assert!(rust_code.contains("Status::Retry(2, \"again\".to_string())"));
+    assert!(rust_code.contains("println!(\"{:?} {}\", s, s == Status::Active);"));
+}
+
+#[test]
+fn codegen_comparison_annotations_derive_traits() {
+    let rust_code = transpile("@comparable struct Version { major: number }\n@hashable enum Kind { A, B }\n@equatable @derive(Hash) struct Tag { name: string }");
+    assert!(rust_code.contains("#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]\n    struct Version"));
+    assert!(rust_code.contains("#[derive(Debug, Clone, PartialEq, Eq, Hash)]\n    enum Kind"));
+    assert!(rust_code.contains("#[derive(Debug, Clone, Hash, PartialEq, Eq)]\n    struct Tag"));
+}
+
+#[test]
+fn codegen_concatenation_and_float_promotion() {
+    let rust_code = transpile("import {randomFloat} from \"std/random\";
+struct Point { x: number }
+function label(): string { return \"L\"; }
+function roll(): number { return randomFloat() * 6; }
+let p = Point { x: 1 };
+let text = \"at \" + p;
+let f = randomFloat();
+let bigger = f + 1 > 2;
+let whole: number = f * 10;");
+    assert!(rust_code.contains("return \"L\".to_string();"));
+    assert!(rust_code.contains("return ((random_float() * (6 as f64)) as i32);"));
+    assert!(rust_code.contains("format!(\"{}{:?}\", \"at \", p)"));
+    assert!(rust_code.contains("f + (1 as f64) > (2 as f64)"));
+    assert!(rust_code.contains("let whole: i32 = ((f * (10 as f64)) as i32);"));
+}
+
+#[test]
+fn codegen_negation() {
+    let rust_code = transpile("let raining = false;\nif (!raining) { print(\"dry\"); }");
+    assert!(rust_code.contains("if raining == false {"));
+}
+
+#[test]
+fn codegen_escapes_rust_keywords() {
+    let rust_code = transpile(
+        "import { loop } from \"./utils\";
+struct Job { ref: string, move: number }
+function match(self: number): number { return self + loop(); }
+function main(): void {
+    let crate = match(2);
+    let job = Job { ref: \"a\", move: crate };
+    print(job.move);
+}",
+    );
+    assert!(rust_code.contains("use utils::r#loop;"), "{}", rust_code);
+    assert!(rust_code.contains("r#ref: String,"));
+    assert!(rust_code.contains("fn r#match(self_: i32) -> i32"));
+    assert!(rust_code.contains("return self_ + r#loop();"));
+    assert!(rust_code.contains("let crate_ = r#match(2);"));
+    assert!(rust_code.contains("Job { r#ref: \"a\".to_string(), r#move: crate_ }"));
+    assert!(rust_code.contains("job.r#move"));
+}
+
+#[test]
+fn codegen_escapes_string_literals() {
+    let rust_code = transpile(r#"let s = "say \"hi\"\tC:\\temp\n{} {{x}} é";"#);
+    assert!(rust_code.contains(r#""say \"hi\"\tC:\\temp\n{} {{x}} é""#), "{}", rust_code);
+
+    let rust_code = transpile("print(\"{} items\");\nlet total = \"{\" + \"}\";\nprint(\"100% \" + total);");
+    assert!(rust_code.contains(r#"println!("{}", "{} items");"#), "{}", rust_code);
+    assert!(rust_code.contains(r#"let total = format!("{}{}", "{", "}");"#), "{}", rust_code);
+    assert!(rust_code.contains(r#"format!("{}{}", "100% ", total)"#), "{}", rust_code);
+}
+
+#[test]
+fn codegen_keeps_user_text_out_of_format_strings() {
+    let source = "throw \"bad {input} 100%\";\nprint(\"{}\", \"{:?}\");\nassert(1 < 2, \"{oops}\");\nlet s = \"{\" + 1 + \"}\";";
+    let program = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+    let mut codegen = Codegen::new();
+    codegen.set_file("src/{draft}.jr");
+    let rust_code = codegen.generate(&program);
+    assert!(rust_code.contains(r#"panic!("{}", "bad {input} 100%");"#), "{}", rust_code);
+    assert!(rust_code.contains(r#"println!("{} {}", "{}", "{:?}");"#), "{}", rust_code);
+    assert!(rust_code.contains(r#"assert!(1 < 2, "{} at src/{{draft}}.jr:3", "{oops}");"#), "{}", rust_code);
+    assert!(rust_code.contains(r#"format!("{}{}{}", "{", 1, "}")"#), "{}", rust_code);
+}
+
+#[test]
+fn codegen_maps_field_names_at_every_site() {
+    let rust_code = transpile(
+        "struct User { firstName: string, lastLogin?: number, loginCount: number = 0 }
+enum Shape { Ring { innerRadius: number } }
+function main(): void {
+    let user = User { firstName: \"Ada\" };
+    let users = [user];
+    users[0].loginCount = 2;
+    print(user.firstName);
+    let ring = Shape.Ring { innerRadius: 1 };
+    match (ring) {
+        Shape.Ring { innerRadius } => print(innerRadius);
+    }
+    user.firstName.toUpperCase();
+}",
+    );
+    for expected in [
+        "first_name: String,",
+        "last_login: Option<i32>,",
+        "login_count: i32,",
+        "Ring { inner_radius: i32 }",
+        "User { first_name: \"Ada\".to_string(), last_login: None, login_count: 0 }",
+        ".login_count = 2;",
+        "println!(\"{}\", user.first_name);",
+        "Shape::Ring { inner_radius: 1 }",
+        "Shape::Ring { inner_radius }",
+        "user.first_name.to_uppercase()",
+    ] {
+        assert!(rust_code.contains(expected), "missing {:?} in\n{}", expected, rust_code);
+    }
+}
+
+#[test]
+fn codegen_keeps_serialized_field_names() {
+    let rust_code = transpile("@serializable\nstruct Score { playerName: string, value: number }");
+    assert!(rust_code.contains("#[serde(rename = \"playerName\")]\n        player_name: String,"), "{}", rust_code);
+    assert!(!rust_code.contains("rename = \"value\""));
+}
+
+#[test]
+fn codegen_preserve_naming_keeps_names_as_written() {
+    let source = "import { randomInt, uuid as makeId } from \"std/random\";
+import { formatName } from \"./utils\";
+import * as process from \"std/process\";
+struct User { firstName: string }
+function greetUser(userName: string): string { return formatName(userName); }
+function main(): void {
+    let firstUser = User { firstName: greetUser(\"ada\") };
+    let roll = randomInt(1, 6);
+    print(firstUser.firstName, roll, makeId());
+    process.exit(0);
+}";
+    let program = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+    let mut codegen = Codegen::new();
+    codegen.set_naming(NamingPolicy::Preserve);
+    let rust_code = codegen.generate(&program);
+    for expected in [
+        "use jrust_runtime::random::{random_int as randomInt, uuid as makeId};",
+        "use utils::formatName;",
+        "#[allow(non_snake_case)]\nstruct User {\n    firstName: String,",
+        "#[allow(non_snake_case)]\nfn greetUser(userName: String) -> String {",
+        "return formatName(userName);",
+        "#[allow(non_snake_case)]\nfn main() {",
+        "let firstUser = User { firstName: greetUser(\"ada\".to_string()) };",
+        "randomInt(1, 6)",
+        "firstUser.firstName",
+        "makeId()",
+        "process::exit(0)",
+    ] {
+        assert!(rust_code.contains(expected), "missing {:?} in\n{}", expected, rust_code);
+    }
+
+    let rust_code = transpile(source);
+    assert!(rust_code.contains("use jrust_runtime::random::{random_int, uuid as make_id};"), "{}", rust_code);
+    assert!(rust_code.contains("fn greet_user(user_name: String) -> String {"));
+    assert!(!rust_code.contains("allow(non_snake_case)"));
+}
@@ -0,0 +1,138 @@
+use jrust_transpiler_core::ast::{FunctionDecl, Parameter};
+use jrust_transpiler_core::{compile, Cfg, CompileOptions, Compiler, OutputKind, Span, Stage, Type};
+
+#[test]
+fn test_compile_runs_the_full_pipeline() {
+    let rust_code = compile("let x: number = 42;\nprint(x);").unwrap();
+    assert!(rust_code.contains("fn main()"));
+    assert!(rust_code.contains("let x: i32 = 42;"));
+}
+
+#[test]
+fn test_compile_control_flow_from_source() {
+    let source = "let ready: boolean = false;\nlet items: any[] = [1, 2];\nfor item in items {\n    if item == 2 { break; } else { continue; }\n}\nwhile ready { ready = false; }";
+    let rust_code = compile(source).unwrap();
+    assert!(rust_code.contains("let mut ready: bool = false;"));
+    assert!(rust_code.contains("for item in items {"));
+    assert!(rust_code.contains("break;"));
+    assert!(rust_code.contains("continue;"));
+    assert!(rust_code.contains("while ready {"));
+}
+
+#[test]
+fn test_compiler_reports_the_failing_stage() {
+    let compiler = Compiler::new(CompileOptions::default());
+
+    let error = compiler.compile("let s = \"open;").unwrap_err();
+    assert_eq!(error.stage, Stage::Lex);
+
+    let error = compiler.compile("let x: number = ;").unwrap_err();
+    assert_eq!(error.stage, Stage::Parse);
+    assert!(error.diagnostic.line.is_some());
+
+    let error = compiler.compile("function one(): number {\n    return 1;\n}\nprint(one(2));").unwrap_err();
+    assert_eq!(error.stage, Stage::Check);
+    assert_eq!(error.stage.failure_message(), "Semantic check failed");
+}
+
+#[test]
+fn test_compiler_stops_after_requested_stage() {
+    let tokens_only = Compiler::new(CompileOptions { stop_after: Stage::Lex, ..CompileOptions::default() })
+        .compile("// note\nlet x = 1;")
+        .unwrap();
+    assert!(!tokens_only.tokens.is_empty());
+    assert_eq!(tokens_only.comments.len(), 1);
+    assert!(tokens_only.program.is_none());
+
+    let ast_only = Compiler::new(CompileOptions { stop_after: Stage::Parse, ..CompileOptions::default() })
+        .compile("function one(): number {\n    return 1;\n}\nprint(one(2));")
+        .unwrap();
+    assert!(ast_only.program.is_some());
+    assert!(ast_only.rust.is_none());
+}
+
+#[test]
+fn test_compiler_module_output_with_imports() {
+    let add = FunctionDecl {
+        name: "add".to_string(),
+        parameters: vec![
+            Parameter { name: "a".to_string(), param_type: Type::Number, type_span: Span::default(), span: Span::default() },
+            Parameter { name: "b".to_string(), param_type: Type::Number, type_span: Span::default(), span: Span::default() },
+        ],
+        return_type: Type::Number,
+        return_type_span: Span::default(),
+        body: Vec::new(),
+        attributes: Vec::new(),
+    };
+    let options = CompileOptions {
+        kind: OutputKind::Module,
+        imported_functions: vec![("add".to_string(), add)],
+        ..CompileOptions::default()
+    };
+
+    let error = Compiler::new(options.clone()).compile("export const X: number = add(1);").unwrap_err();
+    assert!(error.diagnostic.message.contains("expects 2 argument(s)"));
+
+    let compilation = Compiler::new(options)
+        .compile("export const X: number = 1;\nexport function double(n: number): number {\n    return n * 2;\n}\n")
+        .unwrap();
+    let rust_code = compilation.rust.unwrap();
+    assert!(!rust_code.contains("fn main()"));
+    assert!(rust_code.contains("pub fn double(n: i32) -> i32"));
+    assert!(!compilation.source_map.is_empty());
+}
+
+#[test]
+fn test_compiler_optimizes_when_enabled() {
+    let source = "function unused(): number { return helper(); }
+function helper(): number { return 1; }
+function logged(): number { return 4; }
+function early(): number {
+    return 2 * 3 + 1;
+    print(\"never\");
+}
+print(\"total: \" + (10 / 2) + \"!\");
+if 1 < 2 { print(early()); } else { print(\"no\"); }
+console.log(\"logged:\", logged());";
+    let optimized = Compiler::new(CompileOptions { optimize: true, ..CompileOptions::default() })
+        .compile(source)
+        .unwrap()
+        .rust
+        .unwrap();
+    assert!(optimized.contains("return 7;"));
+    assert!(!optimized.contains("never"));
+    assert!(optimized.contains("println!(\"{}\", \"total: 5!\".to_string());"));
+    assert!(!optimized.contains("fn unused"));
+    assert!(!optimized.contains("fn helper"));
+    assert!(!optimized.contains("\"no\""));
+    assert!(optimized.contains("fn early"));
+    assert!(optimized.contains("fn logged"));
+
+    let plain = compile(source).unwrap();
+    assert!(plain.contains("fn unused"));
+    assert!(plain.contains("return 2 * 3 + 1;"));
+}
+
+#[test]
+fn test_compiler_resolves_cfg_for_the_build() {
+    let source = "@cfg(windows)\nfunction sep(): string {\n    return \"\\\\\";\n}\n@cfg(unix)\nfunction sep(): string {\n    return \"/\";\n}\n@cfg(release) {\n    print(\"fast\");\n}\nprint(sep());";
+    let compile_for = |cfg: Cfg| {
+        Compiler::new(CompileOptions { cfg, ..CompileOptions::default() }).compile(source).unwrap().rust.unwrap()
+    };
+
+    let windows_debug = compile_for(Cfg::for_target(true, Some("x86_64-pc-windows-msvc")));
+    assert!(windows_debug.contains("return \"\\\\\".to_string();"));
+    assert!(!windows_debug.contains("return \"/\""));
+    assert!(!windows_debug.contains("fast"));
+    assert!(!windows_debug.contains("cfg"));
+
+    let mac_release = compile_for(Cfg::for_target(false, Some("aarch64-apple-darwin")));
+    assert!(mac_release.contains("return \"/\".to_string();"));
+    assert!(mac_release.contains("println!(\"{}\", \"fast\");"));
+}
+
+#[test]
+fn test_compile_raw_identifier() {
+    let rust_code = compile("let r#type: string = \"admin\";\nprint(r#type);").unwrap();
+    assert!(rust_code.contains("let r#type"), "{}", rust_code);
+}
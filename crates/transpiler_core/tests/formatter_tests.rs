@@ -0,0 +1,72 @@
+use jrust_transpiler_core::format_source;
+
+#[test]
+fn test_format_normalizes_spacing_and_indentation() {
+    let source = "function add(a:number,b:number):number{\nreturn a+b;\n}\n";
+    let expected = "function add(a: number, b: number): number {\n    return a + b;\n}\n";
+    assert_eq!(format_source(source).unwrap(), expected);
+}
+
+#[test]
+fn test_format_expands_blocks() {
+    let source = "if (x>5) { print(\"big\"); } else { print(\"small\"); }\n";
+    let expected = "if (x > 5) {\n    print(\"big\");\n} else {\n    print(\"small\");\n}\n";
+    assert_eq!(format_source(source).unwrap(), expected);
+}
+
+#[test]
+fn test_format_inserts_missing_semicolons() {
+    let source = "let x: number = 10\nprint(x)\n";
+    assert_eq!(format_source(source).unwrap(), "let x: number = 10;\nprint(x);\n");
+}
+
+#[test]
+fn test_format_preserves_comments() {
+    let source = "// Setup\nlet x = 1;   // first\n\n\n// Output\nprint(x);";
+    let expected = "// Setup\nlet x = 1;   // first\n\n// Output\nprint(x);\n";
+    assert_eq!(format_source(source).unwrap(), expected);
+}
+
+#[test]
+fn test_format_keeps_literals_and_generics() {
+    let source = "let [tx,rx] = channel<number>();\nlet p = Point{x:1,y:2};\nlet s = \"say \\\"hi\\\"\";\nlet flags: boolean[] = [!a, b];\n";
+    let expected = "let [tx, rx] = channel<number>();\nlet p = Point { x: 1, y: 2 };\nlet s = \"say \\\"hi\\\"\";\nlet flags: boolean[] = [!a, b];\n";
+    assert_eq!(format_source(source).unwrap(), expected);
+}
+
+#[test]
+fn test_format_keeps_array_types_on_one_line() {
+    let source = "let grid:[[number;2];2] = [[1,2],[3,4]];\nlet row: number[2] = grid[0];\n";
+    let expected = "let grid: [[number; 2]; 2] = [[1, 2], [3, 4]];\nlet row: number[2] = grid[0];\n";
+    assert_eq!(format_source(source).unwrap(), expected);
+}
+
+#[test]
+fn test_format_keeps_imports_and_attributes() {
+    let source = "import {add,sub} from \"./math\";\n\n@derive(Debug)\nstruct Point {\nx: number,\ny: number\n}\n";
+    let expected = "import {add, sub} from \"./math\";\n\n@derive(Debug)\nstruct Point {\n    x: number,\n    y: number\n}\n";
+    assert_eq!(format_source(source).unwrap(), expected);
+}
+
+#[test]
+fn test_format_is_idempotent() {
+    let source = "function main(): void {\n  let total = values[0] +\n  values[1];\n  lock (counter) { counter = counter + 1; }\n}\n";
+    let formatted = format_source(source).unwrap();
+    assert_eq!(
+        formatted,
+        "function main(): void {\n    let total = values[0] +\n        values[1];\n    lock (counter) {\n        counter = counter + 1;\n    }\n}\n"
+    );
+    assert_eq!(format_source(&formatted).unwrap(), formatted);
+}
+
+#[test]
+fn test_format_rejects_syntax_errors() {
+    let err = format_source("let x = ;").unwrap_err();
+    assert_eq!(err.code.as_deref(), Some("JR0101"));
+}
+
+#[test]
+fn test_format_keeps_raw_and_multiline_strings() {
+    let source = "let q = \"\"\"\n  SELECT *\n\"\"\";\nlet j = r#\"{\"a\": 1}\"#;\nprint(q);\n";
+    assert_eq!(format_source(source).unwrap(), source);
+}
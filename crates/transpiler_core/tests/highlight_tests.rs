@@ -0,0 +1,52 @@
+use jrust_transpiler_core::{highlight, HighlightKind};
+
+fn kinds(source: &str) -> Vec<(HighlightKind, usize, usize, usize)> {
+    highlight(source)
+        .unwrap()
+        .into_iter()
+        .map(|token| (token.kind, token.line, token.column, token.length))
+        .collect()
+}
+
+#[test]
+fn test_highlight_classifies_declarations() {
+    let tokens = kinds("// total\nlet total: number = add(1, 2);\n");
+    assert_eq!(
+        tokens,
+        vec![
+            (HighlightKind::Comment, 1, 1, 8),
+            (HighlightKind::Keyword, 2, 1, 3),
+            (HighlightKind::Variable, 2, 5, 5),
+            (HighlightKind::Type, 2, 12, 6),
+            (HighlightKind::Operator, 2, 19, 1),
+            (HighlightKind::Function, 2, 21, 3),
+            (HighlightKind::Number, 2, 25, 1),
+            (HighlightKind::Number, 2, 28, 1),
+        ]
+    );
+}
+
+#[test]
+fn test_highlight_uses_source_lengths_for_strings() {
+    let tokens = kinds("let s = \"a\\\"b\".toUpperCase();");
+    assert!(tokens.contains(&(HighlightKind::String, 1, 9, 6)));
+    assert!(tokens.contains(&(HighlightKind::Method, 1, 16, 11)));
+}
+
+#[test]
+fn test_highlight_contextual_keywords_and_types() {
+    let tokens = kinds("struct Point { x: number }\ntest \"origin\" {\n    let p = Point { x: 0 };\n    print(p.x);\n}\n");
+    assert!(tokens.contains(&(HighlightKind::Type, 1, 8, 5)));
+    assert!(tokens.contains(&(HighlightKind::Keyword, 2, 1, 4)));
+    assert!(tokens.contains(&(HighlightKind::Type, 3, 13, 5)));
+    assert!(tokens.contains(&(HighlightKind::Function, 4, 5, 5)));
+    assert!(tokens.contains(&(HighlightKind::Property, 4, 13, 1)));
+}
+
+#[test]
+fn test_highlight_splits_multiline_strings_by_line() {
+    let tokens = kinds("let q = \"\"\"\n  SELECT *\n\"\"\";");
+    assert!(tokens.contains(&(HighlightKind::String, 1, 9, 3)));
+    assert!(tokens.contains(&(HighlightKind::String, 2, 1, 10)));
+    assert!(tokens.contains(&(HighlightKind::String, 3, 1, 3)));
+}
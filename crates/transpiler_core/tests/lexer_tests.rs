@@ -1,4 +1,4 @@
-use jrust_transpiler_core::{Lexer, TokenKind};
+use jrust_transpiler_core::{FileId, Lexer, TokenKind};
 
 #[test]
 fn test_lexer_let_declaration() {
@@ -154,6 +154,17 @@ fn test_lexer_invalid_string() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_lexer_error_position() {
+    let mut lexer = Lexer::new("let a = 1;\nlet b = #;");
+    let err = lexer.tokenize().unwrap_err();
+
+    assert_eq!(err.message, "Unexpected character '#'");
+    assert_eq!(err.code.as_deref(), Some("JR0001"));
+    assert_eq!((err.line, err.column), (Some(2), Some(9)));
+    assert_eq!(err.to_string(), "Unexpected character '#' at line 2, column 9");
+}
+
 #[test]
 fn test_lexer_arithmetic_operators() {
     let mut lexer = Lexer::new("a + b - c * d / e % f");
@@ -211,6 +222,17 @@ fn test_lexer_array_brackets() {
     assert_eq!(tokens[9].kind, TokenKind::Comma);
 }
 
+#[test]
+fn test_lexer_boolean_literals() {
+    let mut lexer = Lexer::new("let done: boolean = true; let falsey = false;");
+    let tokens = lexer.tokenize().unwrap();
+
+    assert_eq!(tokens[3].kind, TokenKind::BooleanType);
+    assert_eq!(tokens[5].kind, TokenKind::BooleanLiteral(true));
+    assert!(matches!(tokens[8].kind, TokenKind::Identifier(ref s) if s == "falsey"));
+    assert_eq!(tokens[10].kind, TokenKind::BooleanLiteral(false));
+}
+
 #[test]
 fn test_lexer_any_type() {
     let mut lexer = Lexer::new("let value: any = 42;");
@@ -242,3 +264,92 @@ fn test_lexer_continue_keyword() {
     assert_eq!(tokens[1].kind, TokenKind::Semicolon);
     assert_eq!(tokens[2].kind, TokenKind::Eof);
 }
+
+#[test]
+fn test_lexer_readonly_and_mut_keywords() {
+    let mut lexer = Lexer::new("readonly mut");
+    let tokens = lexer.tokenize().unwrap();
+
+    assert_eq!(tokens[0].kind, TokenKind::Readonly);
+    assert_eq!(tokens[1].kind, TokenKind::Mut);
+}
+
+#[test]
+fn test_lexer_attribute_marker() {
+    let mut lexer = Lexer::new("@inline");
+    let tokens = lexer.tokenize().unwrap();
+
+    assert_eq!(tokens[0].kind, TokenKind::At);
+    assert_eq!(tokens[1].kind, TokenKind::Identifier("inline".to_string()));
+}
+
+#[test]
+fn test_lexer_records_comments() {
+    let mut lexer = Lexer::new("// header\nlet x = 1; // trailing\n");
+    lexer.tokenize().unwrap();
+
+    let comments = lexer.comments();
+    assert_eq!(comments.len(), 2);
+    assert_eq!(comments[0].text, "// header");
+    assert_eq!((comments[0].line, comments[0].column), (1, 1));
+    assert_eq!(comments[1].text, "// trailing");
+    assert_eq!((comments[1].line, comments[1].column), (2, 12));
+}
+
+#[test]
+fn test_lexer_records_byte_spans() {
+    let source = "print(\"héllo\");";
+    let tokens = Lexer::new(source).with_file(FileId(3)).tokenize().unwrap();
+
+    assert_eq!(tokens[2].span.text(source), "\"héllo\"");
+    assert_eq!(tokens[2].span.file, FileId(3));
+    assert_eq!((tokens[3].span.start, tokens[3].span.end), (14, 15));
+    assert!(tokens.last().unwrap().span.is_empty());
+}
+
+#[test]
+fn test_lexer_range_dots() {
+    let mut lexer = Lexer::new("nums[1..3].len");
+    let tokens = lexer.tokenize().unwrap();
+
+    assert_eq!(tokens[2].kind, TokenKind::NumberLiteral(1));
+    assert_eq!(tokens[3].kind, TokenKind::DotDot);
+    assert_eq!(tokens[4].kind, TokenKind::NumberLiteral(3));
+    assert_eq!(tokens[6].kind, TokenKind::Dot);
+}
+
+#[test]
+fn test_lexer_raw_identifiers() {
+    let tokens = Lexer::new("let r#type = r#for;").tokenize().unwrap();
+
+    assert_eq!(tokens[1].kind, TokenKind::Identifier("r#type".to_string()));
+    assert_eq!(tokens[3].kind, TokenKind::Identifier("r#for".to_string()));
+
+    let err = Lexer::new("let r#self = 1;").tokenize().unwrap_err();
+    assert_eq!(err.message, "'self' can't be a raw identifier");
+}
+
+#[test]
+fn test_lexer_raw_strings() {
+    let tokens = Lexer::new(r###"r"C:\temp" r#"{"a": "\n"}"# r##"say "#hi""##"###).tokenize().unwrap();
+
+    assert_eq!(tokens[0].kind, TokenKind::StringLiteral(r"C:\temp".to_string()));
+    assert_eq!(tokens[1].kind, TokenKind::StringLiteral(r#"{"a": "\n"}"#.to_string()));
+    assert_eq!(tokens[2].kind, TokenKind::StringLiteral(r##"say "#hi""##.to_string()));
+
+    let err = Lexer::new("let s = r#\"open\";").tokenize().unwrap_err();
+    assert_eq!(err.message, "Unterminated raw string");
+}
+
+#[test]
+fn test_lexer_multiline_strings() {
+    let source = "let q = \"\"\"\n  SELECT \"name\"\n  FROM users\\t\n\"\"\";\nprint(q);";
+    let tokens = Lexer::new(source).tokenize().unwrap();
+
+    assert_eq!(tokens[3].kind, TokenKind::StringLiteral("  SELECT \"name\"\n  FROM users\t\n".to_string()));
+    assert_eq!((tokens[4].line, tokens[4].column), (4, 4));
+    assert_eq!(tokens[5].line, 5);
+
+    let err = Lexer::new("let s = \"\"\"open\";").tokenize().unwrap_err();
+    assert_eq!(err.message, "Unterminated multi-line string");
+}
@@ -1,4 +1,4 @@
-use jrust_transpiler_core::{lexer::Lexer, parser::Parser, codegen::Codegen};
+use jrust_transpiler_core::{lexer::Lexer, parser::Parser, codegen::Codegen, ast::ImportSource, unused, Edit, Fix, StatementKind};
 
 #[test]
 fn test_single_import_std() {
@@ -196,7 +196,7 @@ export function processFile(path: string): void {
     let output = codegen.generate(&ast);
     
     assert!(output.contains("use std::io::{Read, Write};"));
-    assert!(output.contains("pub fn processFile"));
+    assert!(output.contains("pub fn process_file"));
 }
 
 #[test]
@@ -235,8 +235,330 @@ function main(): void {
     assert!(output.contains("use std::fs::{File, Read};"));
     assert!(output.contains("use std::collections::HashMap;"));
     assert!(output.contains("pub struct User"));
-    assert!(output.contains("pub fn createUser"));
+    assert!(output.contains("pub fn create_user"));
     assert!(output.contains("pub const MAX_USERS"));
     assert!(output.contains("fn main()"));
 }
 
+
+#[test]
+fn test_namespace_import_and_qualified_access() {
+    let input = r#"
+import * as math from "./utils/math";
+
+function main(): void {
+    let sum = math.add(1, 2);
+    print(math.PI);
+}
+"#;
+    
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let mut codegen = Codegen::new();
+    let output = codegen.generate(&ast);
+    
+    assert!(output.contains("use utils::math as math;"));
+    assert!(output.contains("let sum = math::add(1, 2);"));
+    assert!(output.contains("println!(\"{}\", math::PI);"));
+}
+
+#[test]
+fn test_wildcard_import_without_alias() {
+    let input = r#"
+import * from "std::collections";
+"#;
+    
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let mut codegen = Codegen::new();
+    let output = codegen.generate(&ast);
+    
+    assert!(output.contains("use std::collections::*;"));
+}
+
+#[test]
+fn test_namespace_import_in_module_file() {
+    let input = r#"
+import * as helpers from "./helpers";
+"#;
+    
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let mut codegen = Codegen::new_module();
+    let output = codegen.generate(&ast);
+    
+    assert!(output.contains("use super::helpers as helpers;"));
+}
+
+#[test]
+fn test_reexport_named_items() {
+    let input = r#"
+export {add, PI} from "./math";
+"#;
+    
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let mut codegen = Codegen::new_module();
+    let output = codegen.generate(&ast);
+    
+    assert!(output.contains("pub use super::math::{add, PI};"));
+}
+
+#[test]
+fn test_reexport_everything() {
+    let input = r#"
+export * from "./helpers";
+"#;
+    
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let mut codegen = Codegen::new_module();
+    let output = codegen.generate(&ast);
+    
+    assert!(output.contains("pub use super::helpers::*;"));
+}
+
+#[test]
+fn test_default_export_function() {
+    let input = r#"
+export default function greetUser(name: string): void {
+    print(name);
+}
+"#;
+    
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let mut codegen = Codegen::new_module();
+    let output = codegen.generate(&ast);
+    
+    assert!(output.contains("pub fn greet_user(name: impl AsRef<str>)"));
+    assert!(output.contains("pub use self::greet_user as default_export;"));
+}
+
+#[test]
+fn test_default_import_from_local_module() {
+    let input = r#"
+import sayHello from "./greeter";
+import File from "std::fs";
+"#;
+    
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let mut codegen = Codegen::new();
+    let output = codegen.generate(&ast);
+    
+    assert!(output.contains("use greeter::default_export as say_hello;"));
+    assert!(output.contains("use std::fs::File;"));
+}
+
+#[test]
+fn test_default_export_main_is_entry_point() {
+    let input = r#"
+export default function main(): void {
+    print("hi");
+}
+"#;
+    
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let mut codegen = Codegen::new();
+    let output = codegen.generate(&ast);
+    
+    assert_eq!(output.matches("fn main()").count(), 1);
+}
+
+#[test]
+fn test_exported_let_becomes_lazy_global() {
+    let input = r#"
+export let visits: number = 0;
+
+export function recordVisit(): void {
+    visits = visits + 1;
+}
+"#;
+    
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let mut codegen = Codegen::new_module();
+    let output = codegen.generate(&ast);
+    
+    assert!(!output.contains("static mut"));
+    assert!(output.contains("pub fn visits() -> &'static std::sync::Mutex<i32> {\n    static VALUE: std::sync::OnceLock<std::sync::Mutex<i32>> = std::sync::OnceLock::new();\n    VALUE.get_or_init(|| std::sync::Mutex::new(0))\n}"));
+    assert!(output.contains("*visits().lock().unwrap() = { let value = visits().lock().unwrap().clone(); value } + 1;"));
+}
+
+#[test]
+fn test_declared_global_from_import() {
+    let input = r#"
+import {visits} from "./state";
+
+print(visits);
+"#;
+    
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let mut codegen = Codegen::new();
+    codegen.declare_global("visits");
+    let output = codegen.generate(&ast);
+    
+    assert!(output.contains("{ let value = visits().lock().unwrap().clone(); value }"));
+}
+
+#[test]
+fn test_import_source_from_path() {
+    let input = r#"
+import {randomInt} from "std/random";
+import {File} from "std::fs";
+import {Serialize} from "serde";
+import {square} from "shapes/geometry";
+import {add} from "./utils/math";
+"#;
+
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let imports: Vec<(ImportSource, Option<&str>)> = ast
+        .statements
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StatementKind::ImportStmt(import_stmt) => Some((import_stmt.source, import_stmt.crate_name())),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        imports,
+        vec![
+            (ImportSource::Std, None),
+            (ImportSource::External, Some("std")),
+            (ImportSource::External, Some("serde")),
+            (ImportSource::External, Some("shapes")),
+            (ImportSource::Local, None),
+        ]
+    );
+}
+
+#[test]
+fn test_parent_relative_import() {
+    let input = r#"
+import {slugify} from "../../shared/helpers";
+import {clamp} from "./../limits";
+export {PI} from "../constants";
+"#;
+
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let mut codegen = Codegen::new_module();
+    let output = codegen.generate(&ast);
+
+    assert!(output.contains("use super::super::super::shared::helpers::slugify;"));
+    assert!(output.contains("use super::super::limits::clamp;"));
+    assert!(output.contains("pub use super::super::constants::PI;"));
+}
+
+#[test]
+fn test_visibility_modifiers() {
+    let input = r#"
+pub struct Point { x: number, y: number }
+pub const ORIGIN: number = 0;
+pub function describe(name: string): string {
+    return name;
+}
+internal function helper(): number {
+    return 1;
+}
+export function area(): number {
+    return helper();
+}
+"#;
+
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+    let mut codegen = Codegen::new_module();
+    let output = codegen.generate(&ast);
+
+    assert!(output.contains("pub(crate) struct Point {\n    pub(crate) x: i32,"));
+    assert!(output.contains("pub(crate) const ORIGIN: i32 = 0;"));
+    assert!(output.contains("pub(crate) fn describe(name: String) -> String {"));
+    assert!(output.contains("\nfn helper() -> i32 {"));
+    assert!(output.contains("pub fn area() -> i32 {"));
+}
+
+#[test]
+fn test_unused_imports() {
+    let input = r#"
+import {clamp, lerp as mix, Shape} from "./math";
+import * as colors from "./colors";
+import {slugify} from "./strings";
+import * as log from "std/log";
+
+function area(shape: Shape): number {
+    match (shape) {
+        Shape.Square(side) => { return clamp(side * side, 0, 100); }
+        _ => { return 0; }
+    }
+}
+"#;
+
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().unwrap();
+
+    let unused: Vec<&str> = unused::unused_imports(&ast).iter().map(|import| import.name).collect();
+    assert_eq!(unused, vec!["mix", "colors", "slugify", "log"]);
+
+    let warnings = unused::unused_import_warnings(&ast, input);
+    assert_eq!(warnings[0].message, "unused import: `mix`");
+    assert_eq!((warnings[0].line, warnings[0].column), (Some(2), Some(16)));
+    let fixes: Vec<&Fix> = warnings.iter().filter_map(|warning| warning.fix.as_deref()).collect();
+    let fixed = apply_fixes(input, &fixes);
+    assert!(fixed.starts_with("\nimport {clamp, Shape} from \"./math\";\n\nfunction area"));
+
+    let mut codegen = Codegen::new_module();
+    let output = codegen.generate(&ast);
+    assert!(output.contains("#[allow(unused_imports)]\nuse super::math::{clamp, lerp as mix, Shape};"));
+    assert!(output.contains("#[allow(unused_imports)]\nuse super::strings::slugify;"));
+}
+
+/// `source` with every `Replace` fix applied; the ones here don't overlap
+fn apply_fixes(source: &str, fixes: &[&Fix]) -> String {
+    let mut edits: Vec<(usize, usize, &str)> = fixes
+        .iter()
+        .filter_map(|fix| match &fix.edit {
+            Edit::Replace { start, end, text } => Some((*start, *end, text.as_str())),
+            Edit::Rename { .. } => None,
+        })
+        .collect();
+    edits.sort();
+    let mut fixed = source.to_string();
+    for (start, end, text) in edits.into_iter().rev() {
+        fixed.replace_range(start..end, text);
+    }
+    fixed
+}
@@ -1,4 +1,5 @@
-use jrust_transpiler_core::{Lexer, Parser};
+use jrust_transpiler_core::ast::{BinaryOp, Pattern, PatternBindings, Visibility};
+use jrust_transpiler_core::{ast_from_json, ast_to_json, Edit, ExpressionKind, Lexer, Parser, StatementKind, Type};
 
 #[test]
 fn parse_simple_variable_declaration() {
@@ -55,6 +56,23 @@ fn parse_print_with_concatenation() {
     assert_eq!(program.statements.len(), 1);
 }
 
+#[test]
+fn parse_print_with_several_arguments() {
+    let input = r#"print("sum:", 1 + 2, [3]);
+print();"#;
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().expect("Parser failed");
+
+    let StatementKind::PrintStmt(print_stmt) = &program.statements[0].kind else {
+        panic!("expected a print statement");
+    };
+    assert_eq!(print_stmt.arguments.len(), 3);
+    assert!(matches!(&print_stmt.arguments[1].kind, ExpressionKind::BinaryOp(_, BinaryOp::Add, _)));
+    assert!(matches!(&program.statements[1].kind, StatementKind::PrintStmt(print_stmt) if print_stmt.arguments.is_empty()));
+}
+
 #[test]
 fn parse_simple_function() {
     let input = "function greet(name: string): void { print(\"Hi\"); }";
@@ -164,6 +182,114 @@ fn parse_error_missing_semicolon() {
     assert!(result.is_err());
 }
 
+#[test]
+fn parse_error_reports_position() {
+    let input = "let x: number = 42;\nlet y = x +;";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    let err = parser.parse().expect_err("Incomplete expression should fail");
+
+    assert_eq!((err.line, err.column), (Some(2), Some(12)));
+    assert_eq!(
+        err.render("src/index.jr", input, false),
+        "error[JR0101]: Unexpected token in expression: Semicolon\n --> src/index.jr:2:12\n  |\n2 | let y = x +;\n  |            ^\n"
+    );
+}
+
+#[test]
+fn parse_error_missing_semicolon_code() {
+    let mut lexer = Lexer::new("let x: number = 42\nprint(x);");
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    let err = parser.parse().expect_err("Missing semicolon should fail");
+
+    assert_eq!(err.code.as_deref(), Some("JR0102"));
+    assert!(jrust_transpiler_core::error_codes::lookup("jr0102").is_some());
+}
+
+#[test]
+fn parse_error_missing_semicolon_has_fix() {
+    let input = "let x: number = 42\nprint(x);";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    let err = parser.parse().expect_err("Missing semicolon should fail");
+
+    let fix = err.fix.clone().expect("Missing semicolon should carry a fix");
+    assert_eq!(fix.edit, Edit::Replace { start: 18, end: 18, text: ";".to_string() });
+    assert!(err.render("src/index.jr", input, false).ends_with("  = help: insert ';'\n"));
+}
+
+#[test]
+fn parse_error_trailing_tokens() {
+    let input = "let total = (1 + 2)) extra;";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let err = Parser::new(tokens).parse().expect_err("Trailing tokens should fail");
+
+    assert_eq!(err.code.as_deref(), Some("JR0110"));
+    assert!(err.fix.is_none());
+    assert_eq!(
+        err.render("src/index.jr", input, false),
+        "error[JR0110]: Unexpected trailing tokens after variable declaration (found: RightParen)\n --> src/index.jr:1:20\n  |\n1 | let total = (1 + 2)) extra;\n  |                    ^^^^^^^\n"
+    );
+}
+
+#[test]
+fn parse_error_missing_semicolon_before_closing_brace() {
+    let mut lexer = Lexer::new("function f(): number { return 1 }");
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let err = Parser::new(tokens).parse().expect_err("Missing semicolon should fail");
+
+    assert_eq!(err.code.as_deref(), Some("JR0102"));
+    assert!(err.fix.is_some());
+}
+
+#[test]
+fn parse_error_stray_tokens() {
+    for (input, message) in [
+        ("let x = 1;;", "Unexpected ';' with no statement before it"),
+        ("struct A { x: number };", "Unexpected ';' with no statement before it"),
+        ("if true { } }", "Unexpected '}' with no matching '{'"),
+        ("print(1);\n)", "Unexpected ')' with no matching '('"),
+    ] {
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize().expect("Lexer failed");
+        let err = Parser::new(tokens).parse().expect_err("Stray token should fail");
+
+        assert_eq!(err.message, message, "for {:?}", input);
+        assert_eq!(err.code.as_deref(), Some("JR0110"));
+    }
+
+    let input = "let x = 1;;";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let err = Parser::new(tokens).parse().expect_err("Stray semicolon should fail");
+    assert_eq!(err.fix.map(|fix| fix.edit), Some(Edit::Replace { start: 10, end: 11, text: String::new() }));
+}
+
+#[test]
+fn parse_error_keyword_as_name() {
+    for (input, keyword) in [("let for = 3;", "for"), ("function type() { }", "type")] {
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize().expect("Lexer failed");
+        let err = Parser::new(tokens).parse().expect_err("Keyword name should fail");
+
+        assert_eq!(
+            err.message,
+            format!("'{}' is a reserved keyword and can't be used as a name (write r#{} to use it as one)", keyword, keyword),
+            "for {:?}",
+            input
+        );
+        assert_eq!(err.code.as_deref(), Some("JR0104"));
+    }
+
+    let mut lexer = Lexer::new("let r#type = 1;");
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    assert!(Parser::new(tokens).parse().is_ok());
+}
+
 #[test]
 fn parse_error_invalid_type() {
     let input = "let x: 123 = 42;";
@@ -328,3 +454,355 @@ fn parse_static_string_array() {
 
     assert_eq!(program.statements.len(), 1);
 }
+
+#[test]
+fn parse_struct_field_defaults_and_optional_fields() {
+    let input = "struct Config { retries: number = 3, verbose: boolean = false, label?: string }";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().expect("Parser failed");
+
+    match &program.statements[0].kind {
+        StatementKind::StructDecl(decl) => {
+            assert!(decl.fields[0].default.is_some());
+            assert!(decl.fields[1].default.is_some());
+            assert!(decl.fields[2].is_optional);
+            assert!(decl.fields[2].default.is_none());
+        }
+        other => panic!("Expected struct declaration, got {:?}", other),
+    }
+}
+
+#[test]
+fn parse_assignment_expression() {
+    let input = "x = 5; user.name = \"Bob\"; nums[0] = 1;";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().expect("Parser failed");
+
+    assert_eq!(program.statements.len(), 3);
+}
+
+#[test]
+fn parse_error_invalid_assignment_target() {
+    let input = "1 = 2;";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    assert!(parser.parse().is_err());
+}
+
+#[test]
+fn parse_nested_function_and_block() {
+    let input = "function outer(): void { function inner(): void { } { let y = 1; } }";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().expect("Parser failed");
+
+    match &program.statements[0].kind {
+        StatementKind::FunctionDecl(decl) => {
+            assert!(matches!(decl.body[0].kind, StatementKind::FunctionDecl(_)));
+            assert!(matches!(decl.body[1].kind, StatementKind::Block(_)));
+        }
+        other => panic!("Expected function declaration, got {:?}", other),
+    }
+}
+
+#[test]
+fn parse_attributes_on_exported_struct() {
+    let input = r#"@derive(Serialize) @serde(rename_all = "camelCase") export struct User { name: string }"#;
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().expect("Parser failed");
+
+    match &program.statements[0].kind {
+        StatementKind::ExportStmt(inner) => match &inner.kind {
+            StatementKind::StructDecl(decl) => {
+                assert_eq!(decl.attributes.len(), 2);
+                assert_eq!(decl.attributes[0].name, "derive");
+                assert_eq!(decl.attributes[1].arguments, vec!["rename_all = \"camelCase\"".to_string()]);
+            }
+            other => panic!("Expected struct declaration, got {:?}", other),
+        },
+        other => panic!("Expected export, got {:?}", other),
+    }
+}
+
+#[test]
+fn parse_error_attribute_on_variable() {
+    let input = "@inline let x = 1;";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    assert!(parser.parse().is_err());
+}
+
+#[test]
+fn parse_serializable_marks_program_as_using_runtime() {
+    let parse = |input: &str| {
+        let tokens = Lexer::new(input).tokenize().expect("Lexer failed");
+        Parser::new(tokens).parse()
+    };
+
+    let program = parse("@serializable export struct User { name: string }").expect("Parser failed");
+    assert!(program.uses_runtime());
+    assert!(!parse("struct User { name: string }").expect("Parser failed").uses_runtime());
+
+    let err = parse("@serializable function save(): void { }").expect_err("not a type");
+    assert!(err.message.contains("@serializable can only be applied to structs and enums"));
+}
+
+#[test]
+fn parse_error_destructuring_non_channel() {
+    let input = "let [a, b] = [1, 2];";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    assert!(parser.parse().is_err());
+}
+
+#[test]
+fn parse_test_block() {
+    let input = "test \"adds numbers\" { assertEq(1 + 1, 2); }";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().expect("Parser failed");
+
+    match &program.statements[0].kind {
+        StatementKind::TestBlock(block) => {
+            assert_eq!(block.name, "adds numbers");
+            assert_eq!(block.body.len(), 1);
+        }
+        other => panic!("Expected test block, got {:?}", other),
+    }
+}
+
+#[test]
+fn parse_test_as_identifier() {
+    let input = "let test = 1;\nprint(test);";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Lexer failed");
+    let mut parser = Parser::new(tokens);
+    assert!(parser.parse().is_ok());
+}
+
+#[test]
+fn parse_ast_json_round_trip() {
+    let tokens = Lexer::new("struct Point { x: number }\nlet p = Point { x: 1 };\nprint(p.x + 2);")
+        .tokenize()
+        .expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+    let json = ast_to_json(&program);
+    assert!(json.contains("\"StructDecl\""));
+    assert!(json.contains("\"name\": \"Point\""));
+
+    let restored = ast_from_json(&json).unwrap();
+    assert_eq!(format!("{:?}", restored), format!("{:?}", program));
+    assert!(ast_from_json("{\"statements\": 1}").is_err());
+}
+
+#[test]
+fn parse_records_spans_on_statements_and_expressions() {
+    let source = "let total = add(1, 2) * 3;\nfunction add(a: number, b: number): number {\n    return a + b;\n}";
+    let tokens = Lexer::new(source).tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+
+    assert_eq!(program.statements[0].span.text(source), "let total = add(1, 2) * 3;");
+    let StatementKind::VariableDecl(decl) = &program.statements[0].kind else {
+        panic!("Expected variable declaration");
+    };
+    assert_eq!(decl.value.span.text(source), "add(1, 2) * 3");
+    let ExpressionKind::BinaryOp(call, _, _) = &decl.value.kind else {
+        panic!("Expected binary operation");
+    };
+    assert_eq!(call.span.text(source), "add(1, 2)");
+
+    let StatementKind::FunctionDecl(func) = &program.statements[1].kind else {
+        panic!("Expected function declaration");
+    };
+    assert!(program.statements[1].span.text(source).starts_with("function add"));
+    assert!(program.statements[1].span.text(source).ends_with('}'));
+    assert_eq!(func.parameters[1].span.text(source), "b: number");
+    assert_eq!(func.body[0].span.text(source), "return a + b;");
+}
+
+#[test]
+fn parse_arrow_function_arguments() {
+    let source = "let sums = pairs.map((a, b: number) => a + b);\nitems.forEach(item => { print(item); });";
+    let tokens = Lexer::new(source).tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+
+    let StatementKind::VariableDecl(decl) = &program.statements[0].kind else {
+        panic!("Expected variable declaration");
+    };
+    let ExpressionKind::MethodCall { arguments, .. } = &decl.value.kind else {
+        panic!("Expected method call");
+    };
+    let ExpressionKind::Closure { parameters, body } = &arguments[0].kind else {
+        panic!("Expected closure");
+    };
+    assert_eq!(parameters.len(), 2);
+    assert!(matches!(body[0].kind, StatementKind::ReturnStmt(_)));
+
+    let StatementKind::ExpressionStmt(stmt) = &program.statements[1].kind else {
+        panic!("Expected expression statement");
+    };
+    let ExpressionKind::MethodCall { arguments, .. } = &stmt.expression.kind else {
+        panic!("Expected method call");
+    };
+    assert!(matches!(&arguments[0].kind, ExpressionKind::Closure { body, .. } if matches!(body[0].kind, StatementKind::PrintStmt(_))));
+}
+
+#[test]
+fn parse_unary_minus() {
+    let source = "let a = -5;\nlet b = -a;";
+    let tokens = Lexer::new(source).tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+
+    let StatementKind::VariableDecl(first) = &program.statements[0].kind else {
+        panic!("Expected variable declaration");
+    };
+    assert!(matches!(first.value.kind, ExpressionKind::NumberLiteral(-5)));
+
+    let StatementKind::VariableDecl(second) = &program.statements[1].kind else {
+        panic!("Expected variable declaration");
+    };
+    assert!(matches!(second.value.kind, ExpressionKind::BinaryOp(_, BinaryOp::Subtract, _)));
+}
+
+#[test]
+fn parse_slice_ranges() {
+    let source = "let a = nums[1..3];\nlet b = nums[2..];\nlet c = nums[..2];";
+    let tokens = Lexer::new(source).tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+
+    let bounds: Vec<(bool, bool)> = program
+        .statements
+        .iter()
+        .map(|stmt| match &stmt.kind {
+            StatementKind::VariableDecl(decl) => match &decl.value.kind {
+                ExpressionKind::SliceAccess { start, end, .. } => (start.is_some(), end.is_some()),
+                other => panic!("Expected slice, got {:?}", other),
+            },
+            _ => panic!("Expected variable declaration"),
+        })
+        .collect();
+    assert_eq!(bounds, vec![(true, true), (true, false), (false, true)]);
+}
+
+#[test]
+fn parse_nested_array_types() {
+    let source = "let grid: number[][] = [[1, 2], [3, 4]];\nlet board: number[3][2] = [[0, 0, 0], [1, 1, 1]];";
+    let tokens = Lexer::new(source).tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+
+    let types: Vec<Option<Type>> = program
+        .statements
+        .iter()
+        .map(|stmt| match &stmt.kind {
+            StatementKind::VariableDecl(decl) => decl.var_type.clone(),
+            _ => panic!("Expected variable declaration"),
+        })
+        .collect();
+    let array = |element_type: Type, size: Option<usize>| Type::Array { element_type: Box::new(element_type), size };
+    assert_eq!(types[0], Some(array(array(Type::Number, None), None)));
+    assert_eq!(types[1], Some(array(array(Type::Number, Some(3)), Some(2))));
+}
+
+#[test]
+fn parse_fixed_size_array_type_forms() {
+    let source = "let a: number[3] = [1, 2, 3];\nlet b: [number; 3] = [1, 2, 3];\nlet c: number[number, 3] = [1, 2, 3];";
+    let tokens = Lexer::new(source).tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+
+    let expected = Type::Array { element_type: Box::new(Type::Number), size: Some(3) };
+    for stmt in &program.statements {
+        let StatementKind::VariableDecl(decl) = &stmt.kind else {
+            panic!("Expected variable declaration");
+        };
+        assert_eq!(decl.var_type.as_ref(), Some(&expected));
+    }
+}
+
+#[test]
+fn parse_enum_named_fields_and_match() {
+    let source = "enum Shape { Circle { radius: number }, Empty }\nmatch (s) {\n    Shape.Circle { radius: r } => print(r);\n    _ => {}\n}";
+    let tokens = Lexer::new(source).tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+
+    let StatementKind::EnumDecl(decl) = &program.statements[0].kind else {
+        panic!("Expected enum declaration");
+    };
+    let fields = decl.variants[0].named_fields.as_ref().expect("named fields");
+    assert_eq!(fields[0].name, "radius");
+    assert!(decl.variants[1].named_fields.is_none());
+
+    let StatementKind::Match(stmt) = &program.statements[1].kind else {
+        panic!("Expected match statement");
+    };
+    assert_eq!(stmt.arms.len(), 2);
+    match &stmt.arms[0].pattern {
+        Pattern::Variant { enum_name, variant, bindings: PatternBindings::Named(fields) } => {
+            assert_eq!((enum_name.as_str(), variant.as_str()), ("Shape", "Circle"));
+            assert_eq!(fields, &vec![("radius".to_string(), "r".to_string())]);
+        }
+        other => panic!("Unexpected pattern {:?}", other),
+    }
+    assert!(matches!(stmt.arms[1].pattern, Pattern::Wildcard));
+}
+
+#[test]
+fn parse_rejects_comparison_annotation_on_function() {
+    let tokens = Lexer::new("@hashable function f(): void {}").tokenize().expect("Lexer failed");
+    let err = Parser::new(tokens).parse().expect_err("annotation on a function");
+    assert!(err.message.contains("@hashable can only be applied to structs and enums"));
+}
+
+#[test]
+fn parse_cfg_block_and_rejects_unknown_conditions() {
+    let tokens = Lexer::new("@cfg(debug, os = \"linux\") {\n    print(\"hi\");\n}").tokenize().expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+    let StatementKind::CfgBlock(block) = &program.statements[0].kind else {
+        panic!("Expected cfg block");
+    };
+    assert_eq!(block.conditions, vec!["debug".to_string(), "os = \"linux\"".to_string()]);
+    assert_eq!(block.body.len(), 1);
+
+    let tokens = Lexer::new("@cfg(linux) function f(): void {}").tokenize().expect("Lexer failed");
+    let err = Parser::new(tokens).parse().expect_err("unknown condition");
+    assert!(err.message.contains("Unknown condition 'linux' in attribute @cfg"));
+
+    let tokens = Lexer::new("@inline {\n}").tokenize().expect("Lexer failed");
+    let err = Parser::new(tokens).parse().expect_err("attribute on a block");
+    assert!(err.message.contains("@inline can't be applied to a block"));
+}
+
+#[test]
+fn parse_visibility_modifiers() {
+    let tokens = Lexer::new("pub function area(): number { return 1; }\n@inline internal function helper(): void {}\nlet pub = 1;\nprint(pub);")
+        .tokenize()
+        .expect("Lexer failed");
+    let program = Parser::new(tokens).parse().expect("Parser failed");
+    assert!(matches!(&program.statements[0].kind, StatementKind::VisibilityStmt(Visibility::Crate, _)));
+    let StatementKind::VisibilityStmt(Visibility::Internal, inner) = &program.statements[1].kind else {
+        panic!("Expected internal declaration");
+    };
+    let StatementKind::FunctionDecl(func_decl) = &inner.kind else {
+        panic!("Expected function");
+    };
+    assert_eq!(func_decl.attributes[0].name, "inline");
+    assert!(matches!(&program.statements[2].kind, StatementKind::VariableDecl(var_decl) if var_decl.name == "pub"));
+
+    let tokens = Lexer::new("pub let count = 0;").tokenize().expect("Lexer failed");
+    let err = Parser::new(tokens).parse().expect_err("pub on a variable");
+    assert!(err.message.contains("'pub' can't be applied to a variable"));
+
+    let tokens = Lexer::new("internal export function f(): void {}").tokenize().expect("Lexer failed");
+    let err = Parser::new(tokens).parse().expect_err("internal export");
+    assert!(err.message.contains("'internal' can't be combined with 'export'"));
+}
@@ -0,0 +1,60 @@
+use jrust_transpiler_core::visit::{walk_expression, walk_expression_mut};
+use jrust_transpiler_core::{Codegen, Expression, ExpressionKind, Lexer, Parser, Program, Type, Visitor, VisitorMut};
+
+fn parse(source: &str) -> Program {
+    let tokens = Lexer::new(source).tokenize().expect("Lexer failed");
+    Parser::new(tokens).parse().expect("Parser failed")
+}
+
+#[derive(Default)]
+struct Calls<'ast> {
+    names: Vec<&'ast str>,
+    types: usize,
+}
+
+impl<'ast> Visitor<'ast> for Calls<'ast> {
+    fn visit_expression(&mut self, expr: &'ast Expression) {
+        if let ExpressionKind::FunctionCall(name, _) = &expr.kind {
+            self.names.push(name);
+        }
+        walk_expression(self, expr);
+    }
+
+    fn visit_type(&mut self, _ty: &'ast Type) {
+        self.types += 1;
+    }
+}
+
+#[test]
+fn test_visitor_reaches_nested_statements_and_expressions() {
+    let program = parse(
+        "function twice(n: number): number {\n    return double(double(n));\n}\nfor i in [1, 2] {\n    if (i > 1) {\n        print(twice(i));\n    }\n}\n",
+    );
+    let mut calls = Calls::default();
+    calls.visit_program(&program);
+    assert_eq!(calls.names, vec!["double", "double", "twice"]);
+    assert_eq!(calls.types, 2);
+}
+
+struct Rename;
+
+impl VisitorMut for Rename {
+    fn visit_expression_mut(&mut self, expr: &mut Expression) {
+        if let ExpressionKind::Identifier(name) = &mut expr.kind {
+            if name == "old" {
+                *name = "renamed".to_string();
+            }
+        }
+        walk_expression_mut(self, expr);
+    }
+}
+
+#[test]
+fn test_visitor_mut_rewrites_the_ast() {
+    let mut program = parse("let renamed: number = 1;\nwhile (old < 3) {\n    print(old + 1);\n}\n");
+    Rename.visit_program_mut(&mut program);
+    let rust_code = Codegen::new().generate(&program);
+    assert!(rust_code.contains("while renamed < 3"));
+    assert!(rust_code.contains("renamed + 1"));
+    assert!(!rust_code.contains("old"));
+}